@@ -4,7 +4,8 @@ use clap::{Args, Parser, Subcommand};
 use mts::config::{Config, ExtensionConfig};
 use mts_mcp::mcp_server_runner::{serve, McpCommand};
 use mts_mcp::{
-    AutoVisualiserRouter, BrowserServer, ComputerControllerServer, DeveloperServer, MemoryServer, TutorialServer,
+    AutoVisualiserRouter, BrowserServer, ComputerControllerServer, DeveloperServer, MemoryServer,
+    TutorialServer,
 };
 
 use crate::commands::acp::run_acp_agent;
@@ -994,7 +995,35 @@ pub async fn cli() -> anyhow::Result<()> {
                     // Default to localhost:3000, can be configured via env var
                     let server_url = std::env::var("MTS_SERVER_URL")
                         .unwrap_or_else(|_| "http://localhost:3000".to_string());
-                    serve(BrowserServer::new(server_url)).await?
+                    let custom_instructions =
+                        std::env::var(mts::agents::extension::EXTENSION_INSTRUCTIONS_ENV).ok();
+                    let max_connections = std::env::var("MTS_BROWSER_MAX_CONNECTIONS")
+                        .ok()
+                        .and_then(|v| v.parse().ok());
+                    let timeout_overrides = std::env::var("MTS_BROWSER_TIMEOUTS").ok();
+                    let max_script_bytes = std::env::var("MTS_BROWSER_MAX_SCRIPT_BYTES")
+                        .ok()
+                        .and_then(|v| v.parse().ok());
+                    let connect_attempts = std::env::var("MTS_BROWSER_CONNECT_ATTEMPTS")
+                        .ok()
+                        .and_then(|v| v.parse().ok());
+                    let connect_backoff_ms = std::env::var("MTS_BROWSER_CONNECT_BACKOFF_MS")
+                        .ok()
+                        .and_then(|v| v.parse().ok());
+                    let keepalive_ms = std::env::var("MTS_BROWSER_KEEPALIVE_MS")
+                        .ok()
+                        .and_then(|v| v.parse().ok());
+                    serve(
+                        BrowserServer::new(server_url)
+                            .custom_instructions(custom_instructions)
+                            .max_connections(max_connections)
+                            .timeout_overrides(timeout_overrides)
+                            .max_script_bytes(max_script_bytes)
+                            .connect_attempts(connect_attempts)
+                            .connect_backoff_ms(connect_backoff_ms)
+                            .keepalive_interval_ms(keepalive_ms),
+                    )
+                    .await?
                 }
                 McpCommand::ComputerController => serve(ComputerControllerServer::new()).await?,
                 McpCommand::Memory => serve(MemoryServer::new()).await?,
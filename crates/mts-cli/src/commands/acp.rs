@@ -762,6 +762,11 @@ impl acp::Agent for MTSAcpAgent {
             schedule_id: None,
             max_turns: None,
             retry_config: None,
+            compaction: None,
+            fallback_providers: None,
+            tool_overrides: None,
+            max_total_tokens: None,
+            model_override: None,
         };
 
         let mut stream = self
@@ -764,6 +764,7 @@ pub fn configure_extensions_dialog() -> anyhow::Result<()> {
                     bundled: Some(true),
                     description,
                     available_tools: Vec::new(),
+                    instructions: None,
                 },
             });
 
@@ -1258,6 +1259,11 @@ pub fn configure_mts_mode_dialog() -> anyhow::Result<()> {
             "Chat Mode",
             "Engage with the selected provider without using tools, extensions, or file modification"
         )
+        .item(
+            MtsMode::Plan,
+            "Plan Mode",
+            "Propose the tool calls for a task without running any of them"
+        )
         .interact()?;
 
     config.set_mts_mode(mode)?;
@@ -1266,6 +1272,7 @@ pub fn configure_mts_mode_dialog() -> anyhow::Result<()> {
         MtsMode::Approve => "Set to Approve Mode - all tools and modifications require approval",
         MtsMode::SmartApprove => "Set to Smart Approve Mode - modifications require approval",
         MtsMode::Chat => "Set to Chat Mode - no tools or modifications enabled",
+        MtsMode::Plan => "Set to Plan Mode - tool calls are proposed but never run",
     };
     cliclack::outro(msg)?;
     Ok(())
@@ -1700,6 +1707,7 @@ pub async fn handle_openrouter_auth() -> anyhow::Result<()> {
                                 bundled: Some(true),
                                 description: "Developer extension".to_string(),
                                 available_tools: Vec::new(),
+                                instructions: None,
                             },
                         });
                         println!("✓ Developer extension enabled");
@@ -1776,6 +1784,7 @@ pub async fn handle_tetrate_auth() -> anyhow::Result<()> {
                                 bundled: Some(true),
                                 description: "Developer extension".to_string(),
                                 available_tools: Vec::new(),
+                                instructions: None,
                             },
                         });
                         println!("✓ Developer extension enabled");
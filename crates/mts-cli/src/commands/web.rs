@@ -514,6 +514,11 @@ async fn process_message_streaming(
         schedule_id: None,
         max_turns: None,
         retry_config: None,
+        compaction: None,
+        fallback_providers: None,
+        tool_overrides: None,
+        max_total_tokens: None,
+        model_override: None,
     };
 
     match agent.reply(user_message, session_config, None).await {
@@ -164,6 +164,7 @@ mod tests {
                     timeout: None,
                     bundled: None,
                     available_tools: Vec::new(),
+                    instructions: None,
                 },
             ]),
             settings: None,
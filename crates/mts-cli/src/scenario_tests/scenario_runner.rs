@@ -210,6 +210,7 @@ where
                 timeout: None,
                 bundled: None,
                 available_tools: vec![],
+                instructions: None,
             },
             Arc::new(Mutex::new(Box::new(mock_client))),
             None,
@@ -643,6 +643,11 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
         schedule_id: None,
         max_turns: None,
         retry_config: None,
+        compaction: None,
+        fallback_providers: None,
+        tool_overrides: None,
+        max_total_tokens: None,
+        model_override: None,
     };
 
     if let Err(e) = session
@@ -325,6 +325,7 @@ impl CliSession {
                     bundled: None,
                     description: name.to_string(),
                     available_tools: Vec::new(),
+                    instructions: None,
                 }
             };
             self.agent
@@ -815,6 +816,11 @@ impl CliSession {
             schedule_id: self.scheduled_job_id.clone(),
             max_turns: self.max_turns,
             retry_config: self.retry_config.clone(),
+            compaction: None,
+            fallback_providers: None,
+            tool_overrides: None,
+            max_total_tokens: None,
+            model_override: None,
         };
         let user_message = self
             .messages
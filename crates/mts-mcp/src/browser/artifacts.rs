@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Directory holding files produced by the browser tools.
+///
+/// Shared with mts-server, which serves these files from `GET /artifacts/{id}`.
+pub fn artifacts_dir() -> PathBuf {
+    std::env::temp_dir().join("mts-browser-artifacts")
+}
+
+/// Save bytes as a new artifact and return its id (the file name)
+pub async fn save_artifact(bytes: &[u8], extension: &str) -> std::io::Result<String> {
+    let dir = artifacts_dir();
+    tokio::fs::create_dir_all(&dir).await?;
+    let id = format!("{}.{}", Uuid::new_v4(), extension);
+    tokio::fs::write(dir.join(&id), bytes).await?;
+    Ok(id)
+}
+
+/// Resolve an artifact id to its path, rejecting ids that could escape the directory
+pub fn artifact_path(id: &str) -> Option<PathBuf> {
+    let is_valid = !id.is_empty()
+        && !id.starts_with('.')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+    is_valid.then(|| artifacts_dir().join(id))
+}
+
+/// MIME type for an artifact id based on its extension
+pub fn artifact_mime_type(id: &str) -> &'static str {
+    match id.rsplit('.').next() {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifact_path_rejects_traversal() {
+        assert!(artifact_path("abc-123.png").is_some());
+        assert!(artifact_path("../secret").is_none());
+        assert!(artifact_path("..").is_none());
+        assert!(artifact_path("a/b.png").is_none());
+        assert!(artifact_path("").is_none());
+    }
+}
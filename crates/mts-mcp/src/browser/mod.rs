@@ -1,4 +1,7 @@
+pub mod artifacts;
 pub mod rmcp_browser;
+pub mod structured_data;
+pub mod timeouts;
 pub mod types;
 
 pub use rmcp_browser::BrowserServer;
@@ -1,52 +1,468 @@
+use base64::Engine;
+use futures::stream::SplitSink;
+use futures::{SinkExt, StreamExt};
 use indoc::formatdoc;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use rmcp::{
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
     model::{
-        CallToolResult, Content, ErrorCode, ErrorData, Implementation, Role, ServerCapabilities, ServerInfo,
+        CallToolResult, Content, ErrorCode, ErrorData, Implementation, Meta, RawResource, Role,
+        ServerCapabilities, ServerInfo,
     },
-    tool, tool_router, ServerHandler,
+    tool, tool_handler, tool_router, ServerHandler,
 };
 use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{mpsc, RwLock};
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-use futures::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, Mutex, RwLock, Semaphore};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{
+    connect_async, tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream,
+};
 use uuid::Uuid;
 
-use super::types::{InteractionParams, NavigateParams, ScrapeParams};
+use super::artifacts::{artifacts_dir, save_artifact};
+use super::structured_data::extract_structured_data;
+use super::timeouts::TimeoutRegistry;
+use super::types::{
+    ConsoleLogsParams, DownloadParams, DragAndDropParams, EvaluateParams, GetAttributesParams,
+    GetComputedStyleParams, GetHistoryParams, InteractionParams, NavigateParams, OpenTabParams,
+    ScrapeDiffParams, ScrapeParams, ScreenshotFormat, ScreenshotParams, ScrollAndCollectParams,
+    SelectOptionParams, SetViewportParams, TabParams, WaitForAbsenceParams, WaitForSelectorParams,
+};
+
+/// Upper bound for the `timeout_ms` accepted by wait tools
+const MAX_WAIT_TIMEOUT_MS: u64 = 25_000;
+
+/// Upper bound for browser_scroll_and_collect's `max_iterations`, so a feed that
+/// never runs out still returns
+const MAX_SCROLL_ITERATIONS: u32 = 50;
+
+/// Upper bound for how long browser_scroll_and_collect waits for new items per scroll
+const MAX_SCROLL_WAIT_MS: u64 = 10_000;
+
+/// WebSocket route on the server, used when the server URL has no path
+const DEFAULT_WS_PATH: &str = "/ws/browser";
+
+/// Work out the WebSocket URL and the HTTP base URL (for artifact links) from the
+/// configured server address.
+///
+/// http(s) URLs and bare host:port addresses switch to ws(s). The path is kept when
+/// one is given, e.g. a reverse proxy route, and defaults to `/ws/browser` otherwise.
+/// The HTTP base of a ws(s) URL is its host, since the path names the socket route.
+fn server_endpoints(server_url: &str) -> (String, String) {
+    let server_url = server_url.trim_end_matches('/');
+    let (scheme, rest) = server_url.split_once("://").unwrap_or(("", server_url));
+    let (ws_scheme, http_scheme) = match scheme {
+        "https" | "wss" => ("wss", "https"),
+        _ => ("ws", "http"),
+    };
+    let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let ws_path = if path.is_empty() {
+        DEFAULT_WS_PATH
+    } else {
+        path
+    };
+
+    let ws_url = format!("{}://{}{}", ws_scheme, host, ws_path);
+    let http_url = match scheme {
+        "ws" | "wss" => format!("{}://{}", http_scheme, host),
+        _ => format!("{}://{}", http_scheme, rest),
+    };
+    (ws_url, http_url)
+}
+
+/// Range of viewport widths and heights accepted by browser_set_viewport, in CSS pixels
+const VIEWPORT_BOUNDS_PX: std::ops::RangeInclusive<u32> = 200..=4000;
+
+/// Viewport size reported by the backend after a SetViewport command
+#[derive(Debug, Deserialize)]
+struct Viewport {
+    width: u32,
+    height: u32,
+}
+
+/// Page summary reported by the backend after a PageInfo command
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PageInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    canonical_url: Option<String>,
+    /// OpenGraph properties without the `og:` prefix, e.g. "title" and "image"
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    open_graph: BTreeMap<String, String>,
+    /// Twitter card fields without the `twitter:` prefix, e.g. "card"
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    twitter: BTreeMap<String, String>,
+}
+
+/// Items gathered by a ScrollAndCollect command
+#[derive(Debug, Deserialize)]
+struct ScrollCollection {
+    /// outerHTML of each distinct item, in page order
+    items: Vec<String>,
+    /// Scrolls performed
+    iterations: u32,
+    /// Whether it stopped because a scroll added no items, rather than at the cap
+    exhausted: bool,
+}
+
+/// Console message or uncaught error reported by a GetConsoleLogs command
+#[derive(Debug, Serialize, Deserialize)]
+struct ConsoleEntry {
+    /// "log", "info", "warn", "error" or "debug"
+    level: String,
+    message: String,
+    /// Unix milliseconds
+    timestamp: u64,
+}
+
+/// File saved by the server after a Download command
+#[derive(Debug, Deserialize)]
+struct DownloadedFile {
+    path: String,
+    size: u64,
+    content_type: String,
+    url: String,
+}
+
+/// Properties reported by browser_get_computed_style when none are requested
+const DEFAULT_STYLE_PROPERTIES: &[&str] = &[
+    "display",
+    "visibility",
+    "color",
+    "font-size",
+    "width",
+    "height",
+];
+
+/// Default cap on commands in flight on the browser backend connection
+const DEFAULT_MAX_CONNECTIONS: usize = 4;
+
+/// Default size limit for browser_evaluate expressions
+const DEFAULT_MAX_SCRIPT_BYTES: usize = 16 * 1024;
+
+/// Default attempts at opening the backend WebSocket before a command fails
+const DEFAULT_CONNECT_ATTEMPTS: u32 = 3;
+
+/// Default wait before the first connect retry; it doubles with each later attempt
+const DEFAULT_CONNECT_BACKOFF: Duration = Duration::from_millis(200);
+
+/// Default time between keepalive pings on an idle backend connection; proxies
+/// commonly drop connections idle for 30-60 seconds
+const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Error given to commands still waiting when the backend connection closes
+const BACKEND_DISCONNECTED: &str = "Browser backend disconnected";
+
+/// Default cap on the content returned by browser_scrape
+const DEFAULT_MAX_CONTENT_BYTES: usize = 100_000;
+
+/// Snapshots browser_scrape_diff keeps before dropping the least recently stored key
+const MAX_SCRAPE_SNAPSHOTS: usize = 32;
+
+/// `_meta` key carrying the calling agent's session id
+const SESSION_ID_META_KEY: &str = "mts-session-id";
 
 // =============================================================================
 // WebSocket Message Types (matching server protocol)
 // =============================================================================
 
+/// Commands run in the tab named by `tab_id`, or the active tab when it is absent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum BrowserCommand {
     Navigate {
         url: String,
+        /// Agent session that issued the navigation, so the server can apply
+        /// per-session policy such as consent for scheduled runs
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
         command_id: String,
     },
     Click {
         selector: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
         command_id: String,
     },
     Type {
         selector: String,
         text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
         command_id: String,
     },
     ExtractDOM {
         selector: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
         command_id: String,
     },
+    /// Elements matching `hide_selectors` get `display: none` and `css` is injected
+    /// as a stylesheet while capturing; both are restored afterwards. Only the
+    /// element matching `selector` is captured, if given and found. The image is
+    /// encoded as `format`, at `quality` for lossy formats.
     Screenshot {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        selector: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        hide_selectors: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        css: Option<String>,
+        #[serde(default)]
+        format: ScreenshotFormat,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        quality: Option<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
         command_id: String,
     },
     ExecuteScript {
         script: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Resolves once an element matches `selector`, or fails after `timeout_ms`
+    WaitForSelector {
+        selector: String,
+        timeout_ms: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Resolves once no element matches `selector`, or fails after `timeout_ms`
+    WaitForAbsence {
+        selector: String,
+        timeout_ms: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Scrolls to the bottom of the page up to `max_iterations` times, waiting up to
+    /// `wait_ms` after each scroll for more elements to match `item_selector`, and
+    /// stops early once a scroll adds none. Reports JSON `{"items", "iterations",
+    /// "exhausted"}`: the outerHTML of every distinct item seen in page order (items
+    /// a virtualized list drops are kept), the scrolls made, and whether it stopped
+    /// because no new items appeared.
+    ScrollAndCollect {
+        item_selector: String,
+        max_iterations: u32,
+        wait_ms: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Reports computed values of `properties` for the first match of `selector`
+    GetComputedStyle {
+        selector: String,
+        properties: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Reports the values of `attributes` for the first match of `selector`
+    GetAttributes {
+        selector: String,
+        attributes: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Drags `source_selector` onto `target_selector` with HTML5 drag events
+    DragAndDrop {
+        source_selector: String,
+        target_selector: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Chooses an option of the `<select>` matching `selector` by value, or by
+    /// visible text when `by_label` is set, and reports the selected value as data
+    SelectOption {
+        selector: String,
+        value: String,
+        #[serde(default)]
+        by_label: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Resizes the viewport to `width` x `height` CSS pixels and reports the
+    /// applied size as JSON `{"width", "height"}`
+    SetViewport {
+        width: u32,
+        height: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Answered by the server with its recent command history as JSON
+    GetHistory {
+        limit: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Moves back in the page history; reports the resulting URL as data
+    GoBack {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Moves forward in the page history; reports the resulting URL as data
+    GoForward {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Reloads the current page; reports its URL as data
+    Reload {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Opens a blank tab with id `tab_id` and makes it the active tab
+    OpenTab {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
         command_id: String,
     },
+    /// Closes `tab_id`, or the active tab when absent
+    CloseTab {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Reports the page's title, description, canonical URL and OpenGraph and
+    /// Twitter card fields as JSON `{"title", "description", "canonical_url",
+    /// "open_graph", "twitter"}`, with the `og:` and `twitter:` prefixes dropped
+    PageInfo {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Reports the tab's buffered `console.*` output and uncaught errors logged
+    /// after `since` (Unix milliseconds) as a JSON list of `{"level", "message",
+    /// "timestamp"}`, oldest first. Uncaught errors and unhandled rejections have
+    /// level "error". The backend keeps the most recent 500 entries per tab,
+    /// across navigations within it, and drops them when the tab closes.
+    GetConsoleLogs {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        since: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Downloads `url` to disk; handled by the server, which reports the saved
+    /// file as JSON `{"path", "size", "content_type", "url"}`
+    Download { url: String, command_id: String },
+}
+
+impl BrowserCommand {
+    pub fn command_id(&self) -> &str {
+        match self {
+            BrowserCommand::Navigate { command_id, .. } => command_id,
+            BrowserCommand::Click { command_id, .. } => command_id,
+            BrowserCommand::Type { command_id, .. } => command_id,
+            BrowserCommand::ExtractDOM { command_id, .. } => command_id,
+            BrowserCommand::Screenshot { command_id, .. } => command_id,
+            BrowserCommand::ExecuteScript { command_id, .. } => command_id,
+            BrowserCommand::WaitForSelector { command_id, .. } => command_id,
+            BrowserCommand::WaitForAbsence { command_id, .. } => command_id,
+            BrowserCommand::ScrollAndCollect { command_id, .. } => command_id,
+            BrowserCommand::GetComputedStyle { command_id, .. } => command_id,
+            BrowserCommand::GetAttributes { command_id, .. } => command_id,
+            BrowserCommand::DragAndDrop { command_id, .. } => command_id,
+            BrowserCommand::SelectOption { command_id, .. } => command_id,
+            BrowserCommand::SetViewport { command_id, .. } => command_id,
+            BrowserCommand::GetHistory { command_id, .. } => command_id,
+            BrowserCommand::GoBack { command_id, .. } => command_id,
+            BrowserCommand::GoForward { command_id, .. } => command_id,
+            BrowserCommand::Reload { command_id, .. } => command_id,
+            BrowserCommand::OpenTab { command_id, .. } => command_id,
+            BrowserCommand::CloseTab { command_id, .. } => command_id,
+            BrowserCommand::PageInfo { command_id, .. } => command_id,
+            BrowserCommand::GetConsoleLogs { command_id, .. } => command_id,
+            BrowserCommand::Download { command_id, .. } => command_id,
+        }
+    }
+
+    /// Command type name, used to look up its timeout
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            BrowserCommand::Navigate { .. } => "navigate",
+            BrowserCommand::Click { .. } => "click",
+            BrowserCommand::Type { .. } => "type",
+            BrowserCommand::ExtractDOM { .. } => "extract_dom",
+            BrowserCommand::Screenshot { .. } => "screenshot",
+            BrowserCommand::ExecuteScript { .. } => "execute_script",
+            BrowserCommand::WaitForSelector { .. } => "wait_for_selector",
+            BrowserCommand::WaitForAbsence { .. } => "wait_for_absence",
+            BrowserCommand::ScrollAndCollect { .. } => "scroll_and_collect",
+            BrowserCommand::GetComputedStyle { .. } => "get_computed_style",
+            BrowserCommand::GetAttributes { .. } => "get_attributes",
+            BrowserCommand::DragAndDrop { .. } => "drag_and_drop",
+            BrowserCommand::SelectOption { .. } => "select_option",
+            BrowserCommand::SetViewport { .. } => "set_viewport",
+            BrowserCommand::GetHistory { .. } => "get_history",
+            BrowserCommand::GoBack { .. } => "go_back",
+            BrowserCommand::GoForward { .. } => "go_forward",
+            BrowserCommand::Reload { .. } => "reload",
+            BrowserCommand::OpenTab { .. } => "open_tab",
+            BrowserCommand::CloseTab { .. } => "close_tab",
+            BrowserCommand::PageInfo { .. } => "page_info",
+            BrowserCommand::GetConsoleLogs { .. } => "get_console_logs",
+            BrowserCommand::Download { .. } => "download",
+        }
+    }
+
+    /// Tab the command targets; None means the active tab
+    pub fn tab_id(&self) -> Option<&str> {
+        match self {
+            BrowserCommand::Navigate { tab_id, .. }
+            | BrowserCommand::Click { tab_id, .. }
+            | BrowserCommand::Type { tab_id, .. }
+            | BrowserCommand::ExtractDOM { tab_id, .. }
+            | BrowserCommand::Screenshot { tab_id, .. }
+            | BrowserCommand::ExecuteScript { tab_id, .. }
+            | BrowserCommand::WaitForSelector { tab_id, .. }
+            | BrowserCommand::WaitForAbsence { tab_id, .. }
+            | BrowserCommand::ScrollAndCollect { tab_id, .. }
+            | BrowserCommand::GetComputedStyle { tab_id, .. }
+            | BrowserCommand::GetAttributes { tab_id, .. }
+            | BrowserCommand::DragAndDrop { tab_id, .. }
+            | BrowserCommand::SelectOption { tab_id, .. }
+            | BrowserCommand::SetViewport { tab_id, .. }
+            | BrowserCommand::GetHistory { tab_id, .. }
+            | BrowserCommand::GoBack { tab_id, .. }
+            | BrowserCommand::GoForward { tab_id, .. }
+            | BrowserCommand::Reload { tab_id, .. }
+            | BrowserCommand::OpenTab { tab_id, .. }
+            | BrowserCommand::CloseTab { tab_id, .. }
+            | BrowserCommand::PageInfo { tab_id, .. }
+            | BrowserCommand::GetConsoleLogs { tab_id, .. } => tab_id.as_deref(),
+            BrowserCommand::Download { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BrowserErrorCode {
+    NoPageLoaded,
+    ElementNotFound,
+    RateLimited,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,12 +473,18 @@ pub enum BrowserEvent {
         success: bool,
         data: Option<String>,
         error: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_code: Option<BrowserErrorCode>,
     },
     NavigationComplete {
         url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
     },
     LoadingStateChanged {
         loading: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
     },
     ConsentRequired {
         allowed_domains: Vec<String>,
@@ -76,44 +498,206 @@ pub enum BrowserEvent {
 // Browser Server
 // =============================================================================
 
+type WsSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+
+/// Waiters for command results by command id; an `Err` means the command can't complete
+type ResponseChannels = Arc<
+    RwLock<std::collections::HashMap<String, mpsc::UnboundedSender<Result<BrowserEvent, String>>>>,
+>;
+
+/// Long-lived WebSocket to the browser backend, shared by all commands
+struct BackendConnection {
+    /// Shared with the reader task, which sends the keepalive pings
+    sink: Arc<Mutex<WsSink>>,
+    /// Set by the reader task once the socket closes or stops answering pings
+    closed: Arc<AtomicBool>,
+    /// Dispatches `CommandResult` events to the per-command channels
+    reader: JoinHandle<()>,
+}
+
+impl Drop for BackendConnection {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
 /// BrowserServer provides browser automation tools to the AI agent
 pub struct BrowserServer {
     tool_router: ToolRouter<Self>,
     /// WebSocket URL for the browser backend
     ws_url: String,
+    /// HTTP URL of the server, used to link to saved artifacts
+    http_url: String,
     /// Response channels for pending commands
-    response_channels: Arc<RwLock<std::collections::HashMap<String, mpsc::UnboundedSender<BrowserEvent>>>>,
+    response_channels: ResponseChannels,
+    /// Session-specific guidance appended to the tool instructions
+    custom_instructions: Option<String>,
+    /// Backend connection, opened on first use and reopened after it drops
+    connection: Arc<RwLock<Option<BackendConnection>>>,
+    /// Limits commands in flight on the backend connection; sends wait for a free slot
+    connection_slots: Arc<Semaphore>,
+    /// Per-command-type timeouts for backend responses
+    timeouts: TimeoutRegistry,
+    /// Largest expression browser_evaluate accepts, in bytes
+    max_script_bytes: usize,
+    /// Attempts at opening the backend WebSocket, including the first
+    connect_attempts: u32,
+    /// Wait before the first connect retry, doubled for each later one
+    connect_backoff: Duration,
+    /// Time between keepalive pings; None disables them
+    keepalive_interval: Option<Duration>,
+    /// Last page content seen by browser_scrape_diff, per caller-supplied key
+    scrape_snapshots: Arc<Mutex<ScrapeSnapshots>>,
 }
 
 impl BrowserServer {
     pub fn new(server_url: String) -> Self {
-        // Construct WebSocket URL from HTTP server URL
-        let ws_url = if server_url.starts_with("http://") {
-            server_url.replace("http://", "ws://")
-        } else if server_url.starts_with("https://") {
-            server_url.replace("https://", "wss://")
-        } else {
-            format!("ws://{}", server_url)
-        };
-        let ws_url = format!("{}/ws/browser", ws_url.trim_end_matches('/'));
+        let (ws_url, http_url) = server_endpoints(&server_url);
 
         Self {
             tool_router: Self::tool_router(),
             ws_url,
+            http_url,
+            custom_instructions: None,
             response_channels: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            connection: Arc::new(RwLock::new(None)),
+            connection_slots: Arc::new(Semaphore::new(DEFAULT_MAX_CONNECTIONS)),
+            timeouts: TimeoutRegistry::default(),
+            max_script_bytes: DEFAULT_MAX_SCRIPT_BYTES,
+            connect_attempts: DEFAULT_CONNECT_ATTEMPTS,
+            connect_backoff: DEFAULT_CONNECT_BACKOFF,
+            keepalive_interval: Some(DEFAULT_KEEPALIVE_INTERVAL),
+            scrape_snapshots: Arc::new(Mutex::new(ScrapeSnapshots::default())),
         }
     }
 
-    /// Send a command to the browser and wait for the result
-    async fn send_command(&self, command: BrowserCommand) -> Result<BrowserEvent, String> {
-        let command_id = match &command {
-            BrowserCommand::Navigate { command_id, .. } => command_id.clone(),
-            BrowserCommand::Click { command_id, .. } => command_id.clone(),
-            BrowserCommand::Type { command_id, .. } => command_id.clone(),
-            BrowserCommand::ExtractDOM { command_id, .. } => command_id.clone(),
-            BrowserCommand::Screenshot { command_id } => command_id.clone(),
-            BrowserCommand::ExecuteScript { command_id, .. } => command_id.clone(),
-        };
+    pub fn custom_instructions(mut self, value: Option<String>) -> Self {
+        self.custom_instructions = value.filter(|v| !v.trim().is_empty());
+        self
+    }
+
+    /// Cap commands in flight on the backend connection, keeping the default when `None`
+    pub fn max_connections(mut self, value: Option<usize>) -> Self {
+        if let Some(max) = value {
+            self.connection_slots = Arc::new(Semaphore::new(max.max(1)));
+        }
+        self
+    }
+
+    /// Limit the size of browser_evaluate expressions, keeping the default when `None`
+    pub fn max_script_bytes(mut self, value: Option<usize>) -> Self {
+        if let Some(max) = value {
+            self.max_script_bytes = max;
+        }
+        self
+    }
+
+    /// Set how many times to try opening the backend WebSocket, keeping the default
+    /// when `None`
+    pub fn connect_attempts(mut self, value: Option<u32>) -> Self {
+        if let Some(attempts) = value {
+            self.connect_attempts = attempts.max(1);
+        }
+        self
+    }
+
+    /// Set the wait before the first connect retry, keeping the default when `None`
+    pub fn connect_backoff_ms(mut self, value: Option<u64>) -> Self {
+        if let Some(ms) = value {
+            self.connect_backoff = Duration::from_millis(ms);
+        }
+        self
+    }
+
+    /// Set the time between keepalive pings, keeping the default when `None`; 0
+    /// disables them. A ping still unanswered at the next one drops the connection,
+    /// so the following command reconnects.
+    pub fn keepalive_interval_ms(mut self, value: Option<u64>) -> Self {
+        if let Some(ms) = value {
+            self.keepalive_interval = (ms > 0).then(|| Duration::from_millis(ms));
+        }
+        self
+    }
+
+    /// Override command timeouts from a spec like `default=45000,navigate=60000`
+    pub fn timeout_overrides(mut self, spec: Option<String>) -> Self {
+        if let Some(spec) = spec {
+            self.timeouts = self.timeouts.with_overrides(&spec);
+        }
+        self
+    }
+
+    /// Send a command and return its result data, mapping failures to tool errors
+    async fn execute(
+        &self,
+        command: BrowserCommand,
+        meta: &Meta,
+        action: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Option<String>, ErrorData> {
+        match self.send_command(command, meta, timeout).await {
+            Ok(BrowserEvent::CommandResult {
+                success: true,
+                data,
+                ..
+            }) => Ok(data),
+            Ok(BrowserEvent::CommandResult {
+                error, error_code, ..
+            }) => Err(command_failed(action, error, error_code)),
+            Ok(event) => Err(ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!("Unexpected event: {:?}", event),
+                None,
+            )),
+            Err(e) => Err(ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!("Command failed: {}", e),
+                None,
+            )),
+        }
+    }
+
+    /// Run a back/forward/reload command and report the URL it landed on
+    async fn move_in_history(
+        &self,
+        command: BrowserCommand,
+        meta: &Meta,
+        action: &str,
+    ) -> Result<CallToolResult, ErrorData> {
+        tracing::info!("[BrowserMCP] {}", action);
+
+        let url = self.execute(command, meta, action, None).await?;
+        Ok(text_result(format!(
+            "✓ {}: now at {}",
+            action,
+            url.as_deref().unwrap_or("unknown URL")
+        )))
+    }
+
+    /// Link to a saved screenshot artifact
+    fn screenshot_link(&self, screenshot: &SavedScreenshot) -> Content {
+        let mut resource = RawResource::new(
+            format!("{}/artifacts/{}", self.http_url, screenshot.id),
+            screenshot.id.clone(),
+        );
+        resource.mime_type = Some(screenshot.format.mime_type().to_string());
+        resource.size = u32::try_from(screenshot.size).ok();
+        Content::resource_link(resource)
+    }
+
+    /// Send a command to the browser and wait for the result.
+    ///
+    /// The session id in `meta` goes along with the command so the backend can
+    /// keep each session's history apart. `timeout` overrides the configured
+    /// timeout for the command's type.
+    async fn send_command(
+        &self,
+        command: BrowserCommand,
+        meta: &Meta,
+        timeout: Option<Duration>,
+    ) -> Result<BrowserEvent, String> {
+        let timeout = self.timeouts.resolve(command.type_name(), timeout);
+        let command_id = command.command_id().to_string();
 
         // Create response channel
         let (tx, mut rx) = mpsc::unbounded_channel();
@@ -122,25 +706,148 @@ impl BrowserServer {
             channels.insert(command_id.clone(), tx);
         }
 
-        // Connect to WebSocket
-        let (ws_stream, _) = connect_async(&self.ws_url)
+        // Wait for a free slot; held until this command completes
+        let _slot = self
+            .connection_slots
+            .acquire()
             .await
-            .map_err(|e| format!("Failed to connect to browser WebSocket: {}", e))?;
+            .map_err(|_| "Connection pool closed".to_string())?;
+
+        let result = async {
+            let mut command_json = serde_json::to_value(&command)
+                .map_err(|e| format!("Failed to serialize command: {}", e))?;
+            if let (Some(session_id), Some(fields)) =
+                (session_id_from_meta(meta), command_json.as_object_mut())
+            {
+                fields
+                    .entry("session_id")
+                    .or_insert(serde_json::Value::String(session_id));
+            }
+            self.send_text(command_json.to_string()).await?;
+
+            // Wait for response with timeout
+            tokio::time::timeout(timeout, rx.recv())
+                .await
+                .map_err(|_| format!("Command timeout ({}ms)", timeout.as_millis()))?
+                .unwrap_or_else(|| Err("Response channel closed".to_string()))
+        }
+        .await;
+
+        // Cleanup
+        {
+            let mut channels = self.response_channels.write().await;
+            channels.remove(&command_id);
+        }
+
+        result
+    }
 
-        let (mut write, mut read) = ws_stream.split();
+    /// Send text over the shared backend connection, reconnecting if it has dropped
+    async fn send_text(&self, text: String) -> Result<(), String> {
+        let mut connection = self.connection.write().await;
+
+        if let Some(open) = connection
+            .as_mut()
+            .filter(|c| !c.closed.load(Ordering::SeqCst))
+        {
+            let sent = open
+                .sink
+                .lock()
+                .await
+                .send(Message::Text(text.clone().into()))
+                .await;
+            match sent {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    tracing::warn!(
+                        "[BrowserMCP] Browser WebSocket dropped ({}), reconnecting",
+                        e
+                    )
+                }
+            }
+        }
 
-        // Send command
-        let command_json = serde_json::to_string(&command)
-            .map_err(|e| format!("Failed to serialize command: {}", e))?;
-        write
-            .send(Message::Text(command_json.into()))
+        let fresh = self.connect().await?;
+        fresh
+            .sink
+            .lock()
+            .await
+            .send(Message::Text(text.into()))
             .await
             .map_err(|e| format!("Failed to send command: {}", e))?;
+        *connection = Some(fresh);
+        Ok(())
+    }
+
+    /// Open the backend WebSocket and spawn the task that reads its responses and
+    /// keeps the connection alive. Connection and handshake failures are retried
+    /// with exponential backoff.
+    async fn connect(&self) -> Result<BackendConnection, String> {
+        let mut attempt = 1;
+        let ws_stream = loop {
+            match connect_async(&self.ws_url).await {
+                Ok((ws_stream, _)) => break ws_stream,
+                Err(e) if attempt < self.connect_attempts => {
+                    let delay = self
+                        .connect_backoff
+                        .saturating_mul(2u32.saturating_pow(attempt - 1));
+                    tracing::debug!(
+                        "[BrowserMCP] Browser WebSocket connect attempt {}/{} failed ({}), retrying in {}ms",
+                        attempt,
+                        self.connect_attempts,
+                        e,
+                        delay.as_millis()
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    return Err(format!(
+                        "Failed to connect to browser WebSocket after {} attempt(s): {}",
+                        attempt, e
+                    ))
+                }
+            }
+        };
+
+        let (sink, mut read) = ws_stream.split();
+        let sink = Arc::new(Mutex::new(sink));
+        let closed = Arc::new(AtomicBool::new(false));
 
-        // Spawn task to read responses
         let response_channels = self.response_channels.clone();
-        let read_task = tokio::spawn(async move {
-            while let Some(msg) = read.next().await {
+        let reader_closed = closed.clone();
+        let ping_sink = sink.clone();
+        let keepalive_interval = self.keepalive_interval;
+        let reader = tokio::spawn(async move {
+            let period = keepalive_interval.unwrap_or(DEFAULT_KEEPALIVE_INTERVAL);
+            let mut keepalive =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            keepalive.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // Any frame from the backend shows the connection is still up
+            let mut awaiting_pong = false;
+
+            loop {
+                let msg = tokio::select! {
+                    msg = read.next() => match msg {
+                        Some(msg) => msg,
+                        None => break,
+                    },
+                    _ = keepalive.tick(), if keepalive_interval.is_some() => {
+                        if awaiting_pong {
+                            tracing::warn!(
+                                "[BrowserMCP] Browser WebSocket missed a keepalive pong, reconnecting on next command"
+                            );
+                            break;
+                        }
+                        let ping = Message::Ping(Default::default());
+                        if ping_sink.lock().await.send(ping).await.is_err() {
+                            break;
+                        }
+                        awaiting_pong = true;
+                        continue;
+                    }
+                };
+                awaiting_pong = false;
                 match msg {
                     Ok(Message::Text(text)) => {
                         if let Ok(event) = serde_json::from_str::<BrowserEvent>(&text) {
@@ -148,7 +855,7 @@ impl BrowserServer {
                             if let BrowserEvent::CommandResult { command_id, .. } = &event {
                                 let channels = response_channels.read().await;
                                 if let Some(tx) = channels.get(command_id) {
-                                    let _ = tx.send(event);
+                                    let _ = tx.send(Ok(event));
                                 }
                             }
                         }
@@ -161,23 +868,443 @@ impl BrowserServer {
                     _ => {}
                 }
             }
+            // Mark closed first so new commands reconnect, then fail everything still
+            // waiting here rather than leaving it to run into its timeout
+            reader_closed.store(true, Ordering::SeqCst);
+            for (_, tx) in response_channels.write().await.drain() {
+                let _ = tx.send(Err(BACKEND_DISCONNECTED.to_string()));
+            }
         });
 
-        // Wait for response with timeout
-        let result = tokio::time::timeout(Duration::from_secs(30), rx.recv())
-            .await
-            .map_err(|_| "Command timeout (30s)".to_string())?
-            .ok_or_else(|| "Response channel closed".to_string())?;
+        Ok(BackendConnection {
+            sink,
+            closed,
+            reader,
+        })
+    }
+}
 
-        // Cleanup
-        {
-            let mut channels = self.response_channels.write().await;
-            channels.remove(&command_id);
+/// Build the tool error for a failed command, keeping "no page loaded" distinguishable
+/// so the agent knows to navigate first rather than retry
+fn command_failed(
+    action: &str,
+    error: Option<String>,
+    error_code: Option<BrowserErrorCode>,
+) -> ErrorData {
+    let error_msg = error.unwrap_or_else(|| "Unknown error".to_string());
+    let code = match error_code {
+        Some(BrowserErrorCode::NoPageLoaded) => ErrorCode::INVALID_REQUEST,
+        Some(BrowserErrorCode::ElementNotFound) => ErrorCode::INVALID_PARAMS,
+        Some(BrowserErrorCode::RateLimited) => ErrorCode::INVALID_REQUEST,
+        None => ErrorCode::INTERNAL_ERROR,
+    };
+    ErrorData::new(code, format!("{} failed: {}", action, error_msg), None)
+}
+
+/// Session id the agent attaches to every tool call's `_meta`
+fn session_id_from_meta(meta: &Meta) -> Option<String> {
+    meta.0
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(SESSION_ID_META_KEY))
+        .and_then(|(_, value)| value.as_str())
+        .map(str::to_string)
+}
+
+/// Text result shown in full to the assistant and at low priority to the user
+fn text_result(text: String) -> CallToolResult {
+    CallToolResult::success(vec![
+        Content::text(text.clone()).with_audience(vec![Role::Assistant]),
+        Content::text(text)
+            .with_audience(vec![Role::User])
+            .with_priority(0.0),
+    ])
+}
+
+/// Transcode a page reported as raw bytes into UTF-8.
+///
+/// When a page isn't UTF-8, the browser reports its bytes as a Latin-1 string (one
+/// char per byte). The encoding comes from `override_label` or the page's declared
+/// meta charset; content that already holds wider chars was decoded by the browser
+/// and is returned unchanged.
+fn transcode_page(content: String, override_label: Option<&str>) -> Result<String, String> {
+    let encoding = match override_label {
+        Some(label) => Some(
+            encoding_rs::Encoding::for_label(label.trim().as_bytes())
+                .ok_or_else(|| format!("Unknown encoding: {}", label))?,
+        ),
+        None => declared_charset(&content)
+            .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes())),
+    };
+
+    let Some(encoding) = encoding.filter(|e| *e != encoding_rs::UTF_8) else {
+        return Ok(content);
+    };
+    let Some(bytes) = content
+        .chars()
+        .map(|c| u8::try_from(c).ok())
+        .collect::<Option<Vec<u8>>>()
+    else {
+        return Ok(content);
+    };
+
+    Ok(encoding.decode(&bytes).0.into_owned())
+}
+
+/// Charset declared by a `<meta charset>` or `<meta http-equiv="Content-Type">` tag
+fn declared_charset(html: &str) -> Option<String> {
+    static CHARSET: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r#"(?i)<meta[^>]+charset\s*=\s*["']?\s*([A-Za-z0-9_:.\-]+)"#).unwrap()
+    });
+    CHARSET
+        .captures(html)
+        .map(|captures| captures[1].to_string())
+}
+
+/// Script that focuses or blurs the element matching `selector`.
+///
+/// Fails if nothing matches or the element can't take focus. Native focus()/blur()
+/// only fire events while the document has focus, so they're dispatched explicitly
+/// otherwise.
+fn focus_script(selector: &str, focus: bool) -> String {
+    let selector = serde_json::to_string(selector).unwrap_or_default();
+    let (method, event, bubbling_event) = if focus {
+        ("focus", "focus", "focusin")
+    } else {
+        ("blur", "blur", "focusout")
+    };
+    formatdoc! {r#"
+        (() => {{
+          const el = document.querySelector({selector});
+          if (!el) throw new Error('No element matches ' + {selector});
+          el.{method}();
+          const focused = document.activeElement === el;
+          if ({focus} && !focused) throw new Error('Element is not focusable: ' + {selector});
+          if (!document.hasFocus()) {{
+            el.dispatchEvent(new FocusEvent('{event}'));
+            el.dispatchEvent(new FocusEvent('{bubbling_event}', {{ bubbles: true }}));
+          }}
+        }})()
+    "#}
+}
+
+/// Script that hovers the element matching `selector` with pointer and mouse events.
+///
+/// Reveals menus driven by mouseover/mouseenter listeners; CSS `:hover` rules can't
+/// be triggered from script.
+fn hover_script(selector: &str) -> String {
+    let selector = serde_json::to_string(selector).unwrap_or_default();
+    formatdoc! {r#"
+        (() => {{
+          const el = document.querySelector({selector});
+          if (!el) throw new Error('No element matches ' + {selector});
+          el.scrollIntoView({{ block: 'center' }});
+          const rect = el.getBoundingClientRect();
+          const init = {{
+            bubbles: true,
+            cancelable: true,
+            composed: true,
+            view: window,
+            clientX: rect.left + rect.width / 2,
+            clientY: rect.top + rect.height / 2,
+          }};
+          el.dispatchEvent(new PointerEvent('pointerover', init));
+          el.dispatchEvent(new PointerEvent('pointerenter', {{ ...init, bubbles: false }}));
+          el.dispatchEvent(new MouseEvent('mouseover', init));
+          el.dispatchEvent(new MouseEvent('mouseenter', {{ ...init, bubbles: false }}));
+          el.dispatchEvent(new PointerEvent('pointermove', init));
+          el.dispatchEvent(new MouseEvent('mousemove', init));
+        }})()
+    "#}
+}
+
+/// Script that scrolls for the scroll action and returns the window's resulting
+/// vertical position.
+///
+/// Without a `position`, the element matching `selector` is scrolled into view.
+/// Otherwise the window scrolls: 'top' and 'bottom' jump to either end, a signed
+/// number like '+800' or '-400' scrolls by that many pixels, and an unsigned
+/// number scrolls to that position. Returns None for any other position.
+fn scroll_script(selector: &str, position: Option<&str>) -> Option<String> {
+    let scroll = match position.map(str::trim) {
+        None => {
+            let selector = serde_json::to_string(selector).unwrap_or_default();
+            formatdoc! {r#"
+                const el = document.querySelector({selector});
+                if (!el) throw new Error('No element matches ' + {selector});
+                el.scrollIntoView({{ block: 'center' }});"#}
         }
-        read_task.abort();
+        Some("top") => "window.scrollTo({ top: 0 });".to_string(),
+        Some("bottom") => {
+            "window.scrollTo({ top: document.documentElement.scrollHeight });".to_string()
+        }
+        Some(offset) if offset.starts_with(['+', '-']) => {
+            let pixels: f64 = offset.parse().ok().filter(|p: &f64| p.is_finite())?;
+            format!("window.scrollBy({{ top: {} }});", pixels)
+        }
+        Some(top) => {
+            let pixels: f64 = top.parse().ok().filter(|p: &f64| p.is_finite())?;
+            format!("window.scrollTo({{ top: {} }});", pixels)
+        }
+    };
+    Some(format!(
+        "(() => {{
+{}
+return window.scrollY;
+}})()",
+        scroll
+    ))
+}
+
+/// Named keys accepted by the press_key action, with their `code` and legacy `keyCode`
+const NAMED_KEYS: &[(&str, &str, u32)] = &[
+    ("Enter", "Enter", 13),
+    ("Escape", "Escape", 27),
+    ("Tab", "Tab", 9),
+    ("Backspace", "Backspace", 8),
+    ("Delete", "Delete", 46),
+    (" ", "Space", 32),
+    ("ArrowUp", "ArrowUp", 38),
+    ("ArrowDown", "ArrowDown", 40),
+    ("ArrowLeft", "ArrowLeft", 37),
+    ("ArrowRight", "ArrowRight", 39),
+    ("Home", "Home", 36),
+    ("End", "End", 35),
+    ("PageUp", "PageUp", 33),
+    ("PageDown", "PageDown", 34),
+];
+
+/// A key the press_key action can send: its `key`, `code` and legacy `keyCode`
+#[derive(Debug, PartialEq, Eq)]
+struct KeyPress {
+    key: String,
+    code: String,
+    key_code: u32,
+}
+
+/// Resolve a key name from NAMED_KEYS ("Space" also names " "), or a single
+/// printable character
+fn resolve_key(name: &str) -> Option<KeyPress> {
+    let name = if name == "Space" { " " } else { name };
+    if let Some((key, code, key_code)) = NAMED_KEYS.iter().find(|(key, ..)| *key == name) {
+        return Some(KeyPress {
+            key: key.to_string(),
+            code: code.to_string(),
+            key_code: *key_code,
+        });
+    }
+
+    let mut chars = name.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        return None;
+    };
+    if c.is_control() {
+        return None;
+    }
+    let (code, key_code) = if c.is_ascii_alphabetic() {
+        let upper = c.to_ascii_uppercase();
+        (format!("Key{}", upper), upper as u32)
+    } else if c.is_ascii_digit() {
+        (format!("Digit{}", c), c as u32)
+    } else {
+        (String::new(), 0)
+    };
+    Some(KeyPress {
+        key: c.to_string(),
+        code,
+        key_code,
+    })
+}
+
+/// Script that focuses the element matching `selector` and presses `key` on it.
+///
+/// Dispatches keydown, keypress (for characters and Enter) and keyup. Synthetic
+/// events have no default action, so Enter in a form input submits the form
+/// explicitly unless keydown was cancelled.
+fn press_key_script(selector: &str, key: &KeyPress) -> String {
+    let selector = serde_json::to_string(selector).unwrap_or_default();
+    let key_name = serde_json::to_string(&key.key).unwrap_or_default();
+    let code = serde_json::to_string(&key.code).unwrap_or_default();
+    let key_code = key.key_code;
+    formatdoc! {r#"
+        (() => {{
+          const el = document.querySelector({selector});
+          if (!el) throw new Error('No element matches ' + {selector});
+          if (typeof el.focus === 'function') el.focus();
+          const key = {key_name};
+          const init = {{
+            key,
+            code: {code},
+            keyCode: {key_code},
+            which: {key_code},
+            bubbles: true,
+            cancelable: true,
+            composed: true,
+          }};
+          const proceed = el.dispatchEvent(new KeyboardEvent('keydown', init));
+          if (proceed && (key.length === 1 || key === 'Enter')) {{
+            el.dispatchEvent(new KeyboardEvent('keypress', init));
+          }}
+          if (proceed && key === 'Enter' && el.tagName === 'INPUT' && el.form) {{
+            el.form.requestSubmit();
+          }}
+          el.dispatchEvent(new KeyboardEvent('keyup', init));
+        }})()
+    "#}
+}
+
+/// Wrap an expression so the page returns its awaited value as JSON
+fn evaluate_script(expression: &str) -> String {
+    let expression = serde_json::to_string(expression).unwrap_or_default();
+    formatdoc! {r#"
+        (async () => {{
+          const value = await (0, eval)({expression});
+          return JSON.stringify(value === undefined ? null : value) ?? 'null';
+        }})()
+    "#}
+}
+
+/// Convert HTML to text, reporting malformed input instead of panicking
+fn html_to_text(html: &str, width: usize) -> Result<String, String> {
+    let converted = std::panic::catch_unwind(|| {
+        html2text::config::plain().string_from_read(html.as_bytes(), width)
+    });
+    let reason = match converted {
+        Ok(Ok(text)) => return Ok(text),
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => "converter panicked".to_string(),
+    };
+    Err(format!(
+        "Failed to convert content ({} bytes of HTML): {}",
+        html.len(),
+        reason
+    ))
+}
+
+/// A screenshot written to the artifacts directory
+struct SavedScreenshot {
+    id: String,
+    size: usize,
+    format: ScreenshotFormat,
+}
+
+/// Decode a base64 screenshot and save it as an artifact
+async fn save_screenshot(
+    base64_image: &str,
+    format: ScreenshotFormat,
+) -> Result<SavedScreenshot, ErrorData> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(base64_image)
+        .map_err(|e| {
+            ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!("Screenshot data is not valid base64: {}", e),
+                None,
+            )
+        })?;
+    let id = save_artifact(&bytes, format.extension())
+        .await
+        .map_err(|e| {
+            ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!("Failed to save screenshot: {}", e),
+                None,
+            )
+        })?;
+    Ok(SavedScreenshot {
+        id,
+        size: bytes.len(),
+        format,
+    })
+}
+
+/// Convert HTML to Markdown, keeping headings, links, lists and emphasis
+fn html_to_markdown(html: &str) -> Result<String, String> {
+    let converted = std::panic::catch_unwind(|| htmd::convert(html));
+    let reason = match converted {
+        Ok(Ok(markdown)) => return Ok(markdown),
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => "converter panicked".to_string(),
+    };
+    Err(format!(
+        "Failed to convert content ({} bytes of HTML): {}",
+        html.len(),
+        reason
+    ))
+}
+
+/// Page snapshots kept by browser_scrape_diff
+#[derive(Default)]
+struct ScrapeSnapshots {
+    entries: std::collections::HashMap<String, (u64, String)>,
+    next_seq: u64,
+}
+
+impl ScrapeSnapshots {
+    /// Store `content` under `key` and return the snapshot it replaces. When full,
+    /// the least recently stored key is dropped to make room.
+    fn replace(&mut self, key: String, content: String) -> Option<String> {
+        if !self.entries.contains_key(&key) && self.entries.len() >= MAX_SCRAPE_SNAPSHOTS {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (seq, _))| *seq)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.next_seq += 1;
+        self.entries
+            .insert(key, (self.next_seq, content))
+            .map(|(_, previous)| previous)
+    }
+}
+
+/// Non-blank lines that differ between two scrapes of a page
+#[derive(Debug, Serialize)]
+struct ScrapeDiff {
+    key: String,
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+impl ScrapeDiff {
+    fn between(key: &str, before: &str, after: &str) -> Self {
+        // A last line without a newline would otherwise differ from the same line
+        // followed by more content
+        let before = format!("{}\n", before.trim_end_matches('\n'));
+        let after = format!("{}\n", after.trim_end_matches('\n'));
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        for change in TextDiff::from_lines(&before, &after).iter_all_changes() {
+            let line = change.value().trim_end_matches(['\r', '\n']);
+            if line.trim().is_empty() {
+                continue;
+            }
+            match change.tag() {
+                ChangeTag::Insert => added.push(line.to_string()),
+                ChangeTag::Delete => removed.push(line.to_string()),
+                ChangeTag::Equal => {}
+            }
+        }
+        Self {
+            key: key.to_string(),
+            added,
+            removed,
+        }
+    }
+}
 
-        Ok(result)
+/// The longest prefix of `text` that fits in `max_bytes` without splitting a character
+fn truncate_at_char_boundary(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
     }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.get(..end).unwrap_or(text)
 }
 
 impl Default for BrowserServer {
@@ -200,7 +1327,11 @@ impl BrowserServer {
         name = "browser_navigate",
         description = "Navigate the browser to a URL. Waits for page load. Returns final URL after redirects and page title. URLs must be http:// or https://. Subject to domain allowlist for security."
     )]
-    pub async fn navigate(&self, params: Parameters<NavigateParams>) -> Result<CallToolResult, ErrorData> {
+    pub async fn navigate(
+        &self,
+        params: Parameters<NavigateParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
         let params = params.0;
         let command_id = Uuid::new_v4().to_string();
 
@@ -208,19 +1339,28 @@ impl BrowserServer {
 
         let command = BrowserCommand::Navigate {
             url: params.url.clone(),
+            session_id: session_id_from_meta(&meta),
+            tab_id: params.tab_id.clone(),
             command_id,
         };
 
-        match self.send_command(command).await {
+        match self
+            .send_command(command, &meta, params.timeout_ms.map(Duration::from_millis))
+            .await
+        {
             Ok(BrowserEvent::CommandResult {
                 success,
                 data,
                 error,
+                error_code,
                 ..
             }) => {
                 if success {
                     let result_text = if let Some(data) = data {
-                        format!("✓ Successfully navigated to: {}\n\nPage content preview:\n{}", params.url, data)
+                        format!(
+                            "✓ Successfully navigated to: {}\n\nPage content preview:\n{}",
+                            params.url, data
+                        )
                     } else {
                         format!("✓ Successfully navigated to: {}", params.url)
                     };
@@ -232,12 +1372,7 @@ impl BrowserServer {
                             .with_priority(0.0),
                     ]))
                 } else {
-                    let error_msg = error.unwrap_or_else(|| "Unknown error".to_string());
-                    Err(ErrorData::new(
-                        ErrorCode::INTERNAL_ERROR,
-                        format!("Navigation failed: {}", error_msg),
-                        None,
-                    ))
+                    Err(command_failed("Navigation", error, error_code))
                 }
             }
             Ok(event) => Err(ErrorData::new(
@@ -258,14 +1393,23 @@ impl BrowserServer {
     /// This tool allows you to interact with elements on the current page:
     /// - 'click': Click a button or link
     /// - 'type': Fill in an input field or textarea
-    /// - 'scroll': Scroll to an element
+    /// - 'scroll': Scroll to an element, or scroll the window with a `value` of
+    ///   'top', 'bottom', a pixel offset like '+800' or '-400', or a pixel position
+    ///   like '1200', e.g. to trigger infinite scroll
+    /// - 'focus' / 'blur': Focus or blur an element, firing its focus events
+    /// - 'hover': Move the pointer over an element, e.g. to open a menu
+    /// - 'press_key': Press the key named in `value` on an element
     ///
     /// Use CSS selectors to target elements (e.g., "#submit-button", ".input-field", "button[type='submit']")
     #[tool(
         name = "browser_interaction",
-        description = "Interact with page elements: click buttons, fill inputs, or scroll. Actions: 'click', 'type', 'scroll'. Use CSS selectors to target elements."
+        description = "Interact with page elements: click buttons, fill inputs, scroll, focus/blur, hover, or press keys. Actions: 'click', 'type', 'scroll', 'focus', 'blur', 'hover', 'press_key'. For 'scroll', either give a selector to scroll into view, or a value of 'top', 'bottom', a pixel offset like '+800' / '-400', or a pixel position like '1200' to scroll the window (no selector needed). For 'press_key', value is a key name (e.g. 'Enter', 'Escape', 'Tab', 'ArrowDown') or a single character. Use CSS selectors to target elements."
     )]
-    pub async fn interact(&self, params: Parameters<InteractionParams>) -> Result<CallToolResult, ErrorData> {
+    pub async fn interact(
+        &self,
+        params: Parameters<InteractionParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
         let params = params.0;
         let command_id = Uuid::new_v4().to_string();
 
@@ -275,9 +1419,19 @@ impl BrowserServer {
             params.selector
         );
 
+        let scrolls_window = params.action == "scroll" && params.value.is_some();
+        if params.selector.is_empty() && !scrolls_window {
+            return Err(ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("The '{}' action requires a 'selector'", params.action),
+                None,
+            ));
+        }
+
         let command = match params.action.as_str() {
             "click" => BrowserCommand::Click {
                 selector: params.selector.clone(),
+                tab_id: params.tab_id.clone(),
                 command_id,
             },
             "type" => {
@@ -291,39 +1445,109 @@ impl BrowserServer {
                 BrowserCommand::Type {
                     selector: params.selector.clone(),
                     text,
+                    tab_id: params.tab_id.clone(),
                     command_id,
                 }
             }
             "scroll" => {
-                // Implement scroll as execute script
-                let script = format!(
-                    "document.querySelector('{}').scrollIntoView({{ behavior: 'smooth' }})",
-                    params.selector.replace('\'', "\\'")
-                );
-                BrowserCommand::ExecuteScript { script, command_id }
+                let script = scroll_script(&params.selector, params.value.as_deref())
+                    .ok_or_else(|| {
+                        ErrorData::new(
+                            ErrorCode::INVALID_PARAMS,
+                            format!(
+                                "Invalid scroll value: '{}'. Use 'top', 'bottom', a pixel offset like '+800' or '-400', or a pixel position like '1200'",
+                                params.value.as_deref().unwrap_or_default()
+                            ),
+                            None,
+                        )
+                    })?;
+                BrowserCommand::ExecuteScript {
+                    script,
+                    tab_id: params.tab_id.clone(),
+                    command_id,
+                }
             }
-            _ => {
-                return Err(ErrorData::new(
-                    ErrorCode::INVALID_PARAMS,
-                    format!("Invalid action: '{}'. Must be 'click', 'type', or 'scroll'", params.action),
-                    None,
-                ));
+            "focus" => BrowserCommand::ExecuteScript {
+                script: focus_script(&params.selector, true),
+                tab_id: params.tab_id.clone(),
+                command_id,
+            },
+            "blur" => BrowserCommand::ExecuteScript {
+                script: focus_script(&params.selector, false),
+                tab_id: params.tab_id.clone(),
+                command_id,
+            },
+            "hover" => BrowserCommand::ExecuteScript {
+                script: hover_script(&params.selector),
+                tab_id: params.tab_id.clone(),
+                command_id,
+            },
+            "press_key" => {
+                let name = params.value.as_deref().ok_or_else(|| {
+                    ErrorData::new(
+                        ErrorCode::INVALID_PARAMS,
+                        "The 'press_key' action requires a 'value' parameter naming the key"
+                            .to_string(),
+                        None,
+                    )
+                })?;
+                let key = resolve_key(name).ok_or_else(|| {
+                    ErrorData::new(
+                        ErrorCode::INVALID_PARAMS,
+                        format!(
+                            "Unknown key: '{}'. Use a single character or one of: {}",
+                            name,
+                            // Codes match the key names, except that they spell out Space
+                            NAMED_KEYS
+                                .iter()
+                                .map(|(_, code, _)| *code)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                        None,
+                    )
+                })?;
+                BrowserCommand::ExecuteScript {
+                    script: press_key_script(&params.selector, &key),
+                    tab_id: params.tab_id.clone(),
+                    command_id,
+                }
+            }
+            _ => {
+                return Err(ErrorData::new(
+                    ErrorCode::INVALID_PARAMS,
+                    format!(
+                        "Invalid action: '{}'. Must be 'click', 'type', 'scroll', 'focus', 'blur', 'hover', or 'press_key'",
+                        params.action
+                    ),
+                    None,
+                ));
             }
         };
 
-        match self.send_command(command).await {
+        match self
+            .send_command(command, &meta, params.timeout_ms.map(Duration::from_millis))
+            .await
+        {
             Ok(BrowserEvent::CommandResult {
                 success,
                 data,
                 error,
+                error_code,
                 ..
             }) => {
                 if success {
+                    let target = if scrolls_window {
+                        "window"
+                    } else {
+                        params.selector.as_str()
+                    };
                     let result_text = format!(
                         "✓ Successfully performed '{}' on '{}'{}",
                         params.action,
-                        params.selector,
-                        data.map(|d| format!("\n\nResult: {}", d)).unwrap_or_default()
+                        target,
+                        data.map(|d| format!("\n\nResult: {}", d))
+                            .unwrap_or_default()
                     );
 
                     Ok(CallToolResult::success(vec![
@@ -333,12 +1557,7 @@ impl BrowserServer {
                             .with_priority(0.0),
                     ]))
                 } else {
-                    let error_msg = error.unwrap_or_else(|| "Unknown error".to_string());
-                    Err(ErrorData::new(
-                        ErrorCode::INTERNAL_ERROR,
-                        format!("Interaction failed: {}", error_msg),
-                        None,
-                    ))
+                    Err(command_failed("Interaction", error, error_code))
                 }
             }
             Ok(event) => Err(ErrorData::new(
@@ -361,84 +1580,185 @@ impl BrowserServer {
     /// - Extract a specific element (with CSS selector)
     /// - Choose output format: 'text' (default), 'html', or 'markdown'
     ///
-    /// The content is automatically truncated if it exceeds 100KB.
+    /// The content is truncated at `max_content_bytes` (default 100KB).
     #[tool(
         name = "browser_scrape",
-        description = "Extract content from the current page. Returns simplified HTML or text. Optional CSS selector to extract specific elements. Supports 'text', 'html', or 'markdown' format."
+        annotations(read_only_hint = true),
+        description = "Extract content from the current page. Returns simplified HTML or text. Optional CSS selector to extract specific elements. Supports 'text', 'html', or 'markdown' format. Pass encoding (e.g. 'shift_jis') if a legacy-encoded page comes back garbled. Output is truncated at max_content_bytes (default 100KB)."
+    )]
+    pub async fn scrape(
+        &self,
+        params: Parameters<ScrapeParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        let formatted_content = self
+            .extract_content(
+                &meta,
+                params.selector.clone(),
+                &params.format,
+                params.encoding.as_deref(),
+                params.tab_id.clone(),
+                params.timeout_ms,
+            )
+            .await?;
+
+        // Truncate if too large
+        let max_size = params
+            .max_content_bytes
+            .unwrap_or(DEFAULT_MAX_CONTENT_BYTES);
+        let final_content = if formatted_content.len() > max_size {
+            format!(
+                "{}...\n\n[Content truncated at {} bytes]",
+                truncate_at_char_boundary(&formatted_content, max_size),
+                max_size
+            )
+        } else {
+            formatted_content
+        };
+
+        let result_text = format!(
+            "✓ Successfully extracted content{}\n\n{}",
+            params
+                .selector
+                .as_ref()
+                .map(|s| format!(" from '{}'", s))
+                .unwrap_or_default(),
+            final_content
+        );
+
+        Ok(CallToolResult::success(vec![
+            Content::text(result_text.clone()).with_audience(vec![Role::Assistant]),
+            Content::text(result_text)
+                .with_audience(vec![Role::User])
+                .with_priority(0.0),
+        ]))
+    }
+
+    /// Scrape the page and compare it with the snapshot last stored under the same key
+    ///
+    /// The first call for a key only records a baseline. Later calls report the
+    /// lines added and removed since the previous call and replace the snapshot.
+    #[tool(
+        name = "browser_scrape_diff",
+        annotations(read_only_hint = true),
+        description = "Scrape the current page and report lines added and removed since the last browser_scrape_diff call with the same key. The first call for a key records a baseline. Much cheaper than comparing two full scrapes when watching a page for changes."
     )]
-    pub async fn scrape(&self, params: Parameters<ScrapeParams>) -> Result<CallToolResult, ErrorData> {
+    pub async fn scrape_diff(
+        &self,
+        params: Parameters<ScrapeDiffParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
         let params = params.0;
+        if params.key.trim().is_empty() {
+            return Err(ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                "key must not be empty",
+                None,
+            ));
+        }
+
+        let content = self
+            .extract_content(
+                &meta,
+                params.selector,
+                &params.format,
+                params.encoding.as_deref(),
+                params.tab_id,
+                params.timeout_ms,
+            )
+            .await?;
+
+        let previous = self
+            .scrape_snapshots
+            .lock()
+            .await
+            .replace(params.key.clone(), content.clone());
+        let Some(previous) = previous else {
+            return Ok(text_result(format!(
+                "Captured baseline for '{}' ({} lines). Call again with the same key to see what changed.",
+                params.key,
+                content.lines().count()
+            )));
+        };
+
+        let diff = ScrapeDiff::between(&params.key, &previous, &content);
+        if diff.added.is_empty() && diff.removed.is_empty() {
+            return Ok(text_result(format!(
+                "No changes since the last snapshot for '{}'",
+                params.key
+            )));
+        }
+
+        let max_size = params
+            .max_content_bytes
+            .unwrap_or(DEFAULT_MAX_CONTENT_BYTES);
+        let json = serde_json::to_string_pretty(&diff).unwrap_or_default();
+        let json = if json.len() > max_size {
+            format!(
+                "{}...\n\n[Diff truncated at {} bytes]",
+                truncate_at_char_boundary(&json, max_size),
+                max_size
+            )
+        } else {
+            json
+        };
+        Ok(text_result(json))
+    }
+
+    /// Fetch the page (or a selector within it) and convert it to `format`
+    async fn extract_content(
+        &self,
+        meta: &Meta,
+        selector: Option<String>,
+        format: &str,
+        encoding: Option<&str>,
+        tab_id: Option<String>,
+        timeout_ms: Option<u64>,
+    ) -> Result<String, ErrorData> {
         let command_id = Uuid::new_v4().to_string();
 
         tracing::info!(
             "[BrowserMCP] Scraping page (selector: {:?}, format: {})",
-            params.selector,
-            params.format
+            selector,
+            format
         );
 
         let command = BrowserCommand::ExtractDOM {
-            selector: params.selector.clone(),
+            selector,
+            tab_id,
             command_id,
         };
 
-        match self.send_command(command).await {
+        match self
+            .send_command(command, meta, timeout_ms.map(Duration::from_millis))
+            .await
+        {
             Ok(BrowserEvent::CommandResult {
                 success,
                 data,
                 error,
+                error_code,
                 ..
             }) => {
                 if success {
                     let content = data.unwrap_or_else(|| "No content extracted".to_string());
+                    let content = transcode_page(content, encoding)
+                        .map_err(|e| ErrorData::new(ErrorCode::INVALID_PARAMS, e, None))?;
 
                     // Convert format if needed
-                    let formatted_content = match params.format.as_str() {
+                    match format {
                         "text" => {
                             // Simple HTML to text conversion
-                            html2text::from_read(content.as_bytes(), 120)
-                        }
-                        "markdown" => {
-                            // Convert HTML to Markdown
-                            html2text::from_read(content.as_bytes(), 120)
+                            html_to_text(&content, 120)
                         }
-                        "html" => content,
-                        _ => content,
-                    };
-
-                    // Truncate if too large
-                    let max_size = 100_000; // 100KB
-                    let final_content = if formatted_content.len() > max_size {
-                        format!(
-                            "{}...\n\n[Content truncated at 100KB]",
-                            &formatted_content[..max_size]
-                        )
-                    } else {
-                        formatted_content
-                    };
-
-                    let result_text = format!(
-                        "✓ Successfully extracted content{}\n\n{}",
-                        params
-                            .selector
-                            .as_ref()
-                            .map(|s| format!(" from '{}'", s))
-                            .unwrap_or_default(),
-                        final_content
-                    );
-
-                    Ok(CallToolResult::success(vec![
-                        Content::text(result_text.clone()).with_audience(vec![Role::Assistant]),
-                        Content::text(result_text)
-                            .with_audience(vec![Role::User])
-                            .with_priority(0.0),
-                    ]))
+                        "markdown" => html_to_markdown(&content),
+                        "html" => Ok(content),
+                        _ => Ok(content),
+                    }
+                    .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e, None))
                 } else {
-                    let error_msg = error.unwrap_or_else(|| "Unknown error".to_string());
-                    Err(ErrorData::new(
-                        ErrorCode::INTERNAL_ERROR,
-                        format!("Scrape failed: {}", error_msg),
-                        None,
-                    ))
+                    Err(command_failed("Scrape", error, error_code))
                 }
             }
             Ok(event) => Err(ErrorData::new(
@@ -457,49 +1777,119 @@ impl BrowserServer {
     /// Capture a screenshot of the current page
     ///
     /// This tool captures a screenshot of the currently loaded page.
-    /// The screenshot is returned as a base64-encoded PNG image and is also
-    /// saved to a temporary file for later reference.
+    /// The screenshot is returned as a base64-encoded image, PNG unless `format` asks
+    /// for JPEG or WebP; those are much smaller on photo-heavy pages, at `quality`
+    /// when given, which keeps vision-model token use down. With `save_to_disk`,
+    /// it is also written to the artifacts directory and the absolute path is
+    /// included in the result so other tools can read the file.
+    ///
+    /// With `as_resource_link`, the image is saved on the server and a link to
+    /// `GET /artifacts/{id}` is returned instead of the inline image, which keeps
+    /// large images out of the context for clients that can fetch resources.
+    ///
+    /// Elements matching `hide_selectors` (cookie banners, ads, fixed headers) are
+    /// hidden and `css` is injected only for the capture; the page is restored afterwards.
+    ///
+    /// With a `selector`, only that element is captured. If the selector is invalid or
+    /// matches nothing, the full page is captured and the result says so.
     ///
     /// The image can be used for visual debugging or documentation.
     #[tool(
         name = "browser_screenshot",
-        description = "Capture a screenshot of the current page. Returns base64-encoded PNG image. Useful for visual debugging and documentation."
+        description = "Capture a screenshot of the current page. Returns a base64-encoded image (PNG by default; format jpeg or webp with an optional quality of 1-100 is much smaller for photo-heavy pages and cheaper for vision models), or a resource link to fetch it when as_resource_link is true. Optional selector captures just that element; if it matches nothing, the full page is captured and a warning is included. Set save_to_disk to also write the image to a temporary file and get its absolute path. Optional hide_selectors and css are applied only while capturing. Useful for visual debugging and documentation."
     )]
-    pub async fn screenshot(&self) -> Result<CallToolResult, ErrorData> {
+    pub async fn screenshot(
+        &self,
+        params: Parameters<ScreenshotParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
         let command_id = Uuid::new_v4().to_string();
 
-        tracing::info!("[BrowserMCP] Taking screenshot");
+        if let Some(quality) = params.quality {
+            if params.format == ScreenshotFormat::Png {
+                return Err(ErrorData::new(
+                    ErrorCode::INVALID_PARAMS,
+                    "quality applies only to the jpeg and webp formats".to_string(),
+                    None,
+                ));
+            }
+            if !(1..=100).contains(&quality) {
+                return Err(ErrorData::new(
+                    ErrorCode::INVALID_PARAMS,
+                    format!("quality must be between 1 and 100, got {}", quality),
+                    None,
+                ));
+            }
+        }
+
+        tracing::info!(
+            "[BrowserMCP] Taking screenshot (selector: {:?}, hiding {} selectors)",
+            params.selector,
+            params.hide_selectors.len()
+        );
 
-        let command = BrowserCommand::Screenshot { command_id };
+        let command = BrowserCommand::Screenshot {
+            selector: params.selector.clone(),
+            hide_selectors: params.hide_selectors.clone(),
+            css: params.css.clone(),
+            format: params.format,
+            quality: params.quality,
+            tab_id: params.tab_id.clone(),
+            command_id,
+        };
 
-        match self.send_command(command).await {
+        match self.send_command(command, &meta, None).await {
             Ok(BrowserEvent::CommandResult {
                 success,
                 data,
                 error,
+                error_code,
                 ..
             }) => {
                 if success {
                     let base64_image = data.unwrap_or_default();
+                    // The backend captures the full page when the selector matches nothing
+                    let warning = params
+                        .selector
+                        .as_ref()
+                        .filter(|_| error_code == Some(BrowserErrorCode::ElementNotFound))
+                        .map(|selector| {
+                            format!(
+                                "⚠ No element matches '{}'; captured the full page instead",
+                                selector
+                            )
+                        });
 
-                    // Create image content for display
-                    let image_content = Content::image(base64_image, "image/png");
-
-                    let text_content = Content::text("✓ Screenshot captured successfully")
-                        .with_audience(vec![Role::User])
-                        .with_priority(0.0);
+                    let saved = if params.as_resource_link || params.save_to_disk {
+                        Some(save_screenshot(&base64_image, params.format).await?)
+                    } else {
+                        None
+                    };
 
-                    Ok(CallToolResult::success(vec![
-                        image_content.with_audience(vec![Role::Assistant]),
-                        text_content,
-                    ]))
+                    let mut content = match &saved {
+                        Some(saved) if params.as_resource_link => {
+                            vec![self.screenshot_link(saved)]
+                        }
+                        // Create image content for display
+                        _ => vec![Content::image(base64_image, params.format.mime_type())
+                            .with_audience(vec![Role::Assistant])],
+                    };
+                    content.push(
+                        Content::text("✓ Screenshot captured successfully")
+                            .with_audience(vec![Role::User])
+                            .with_priority(0.0),
+                    );
+                    if let Some(saved) = saved.filter(|_| params.save_to_disk) {
+                        content.push(Content::text(format!(
+                            "Saved to {}",
+                            artifacts_dir().join(saved.id).display()
+                        )));
+                    }
+                    content.extend(warning.map(Content::text));
+                    Ok(CallToolResult::success(content))
                 } else {
-                    let error_msg = error.unwrap_or_else(|| "Unknown error".to_string());
-                    Err(ErrorData::new(
-                        ErrorCode::INTERNAL_ERROR,
-                        format!("Screenshot failed: {}", error_msg),
-                        None,
-                    ))
+                    Err(command_failed("Screenshot", error, error_code))
                 }
             }
             Ok(event) => Err(ErrorData::new(
@@ -514,80 +1904,2511 @@ impl BrowserServer {
             )),
         }
     }
-}
 
-impl ServerHandler for BrowserServer {
-    fn get_info(&self) -> ServerInfo {
-        let instructions = formatdoc! {r#"
-            Browser Automation Tools
+    /// Wait for an element to appear
+    ///
+    /// Resolves once an element matches the selector, so pages that render
+    /// asynchronously are ready before the next interaction. Resolves immediately
+    /// if something already matches.
+    #[tool(
+        name = "browser_wait_for_selector",
+        annotations(read_only_hint = true),
+        description = "Wait until an element matching a CSS selector exists. Call this after browser_navigate and before browser_interaction when the target may not have rendered yet. Resolves immediately if already present; fails after timeout_ms (default 10000, max 25000)."
+    )]
+    pub async fn wait_for_selector(
+        &self,
+        params: Parameters<WaitForSelectorParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        let timeout_ms = params.timeout_ms.min(MAX_WAIT_TIMEOUT_MS);
 
-            You have access to browser automation capabilities through four tools:
+        tracing::info!(
+            "[BrowserMCP] Waiting for selector {} ({}ms)",
+            params.selector,
+            timeout_ms
+        );
 
-            1. **browser_navigate**: Navigate to URLs and wait for page load
-               - URLs must be http:// or https://
-               - Returns the final URL after redirects
-               - Subject to domain allowlist for security
+        let command = BrowserCommand::WaitForSelector {
+            selector: params.selector.clone(),
+            timeout_ms,
+            tab_id: params.tab_id.clone(),
+            command_id: Uuid::new_v4().to_string(),
+        };
+        self.execute(
+            command,
+            &meta,
+            "Wait for selector",
+            // Leave the backend time to report its own timeout first
+            Some(Duration::from_millis(timeout_ms + 5_000)),
+        )
+        .await?;
 
-            2. **browser_interaction**: Interact with page elements
-               - Actions: 'click', 'type', 'scroll'
-               - Use CSS selectors to target elements
-               - Examples: #button-id, .class-name, input[name=email]
+        Ok(text_result(format!(
+            "✓ Found element matching '{}'",
+            params.selector
+        )))
+    }
 
-            3. **browser_scrape**: Extract page content
-               - Can extract entire page or specific elements
-               - Supports text, HTML, or markdown format
-               - Automatically truncates large content
+    /// Extract structured data from the current page
+    ///
+    /// Collects JSON-LD blocks and microdata items from the whole document, which
+    /// usually describe products, articles and events more reliably than scraped text.
+    #[tool(
+        name = "browser_extract_structured_data",
+        annotations(read_only_hint = true),
+        description = "Extract the current page's structured data as a JSON array: every <script type=\"application/ld+json\"> block (parsed) followed by each microdata itemscope as an object with @type and its itemprop values. Returns [] when the page has none."
+    )]
+    pub async fn extract_structured_data(
+        &self,
+        params: Parameters<TabParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        tracing::info!("[BrowserMCP] Extracting structured data");
 
-            4. **browser_screenshot**: Capture page screenshots
-               - Returns base64-encoded PNG images
-               - Useful for visual debugging
+        let command = BrowserCommand::ExtractDOM {
+            // JSON-LD usually lives in <head>, so take the whole document
+            selector: Some("html".to_string()),
+            tab_id: params.0.tab_id,
+            command_id: Uuid::new_v4().to_string(),
+        };
+        let html = self
+            .execute(command, &meta, "Extract structured data", None)
+            .await?
+            .unwrap_or_default();
+        let html = transcode_page(html, None)
+            .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e, None))?;
 
-            **Important Notes:**
-            - The browser is persistent across the session
-            - Navigation is subject to URL allowlist (GitHub, StackOverflow, Wikipedia, localhost by default)
-            - All actions are audited for security
-            - Commands timeout after 30 seconds
+        let items = extract_structured_data(&html);
+        Ok(text_result(
+            serde_json::to_string_pretty(&items).unwrap_or_default(),
+        ))
+    }
 
-            **Common Workflows:**
-            1. Navigate to a page → Scrape content → Process data
-            2. Navigate → Interact (fill form) → Interact (submit) → Scrape result
-            3. Navigate → Screenshot → Analyze visually
+    /// Summarize the current page
+    ///
+    /// Reads the title, meta description, canonical link and OpenGraph and Twitter
+    /// card tags, which is far cheaper than scraping the page to find them.
+    #[tool(
+        name = "browser_page_info",
+        annotations(read_only_hint = true),
+        description = "Get a compact JSON summary of the current page: title, meta description, canonical_url, open_graph (og:* tags) and twitter (twitter:* tags). Use instead of scraping when you only need to know what a page is about. Fields the page lacks are omitted."
+    )]
+    pub async fn page_info(
+        &self,
+        params: Parameters<TabParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        tracing::info!("[BrowserMCP] Getting page info");
 
-            Browser URL: {}
-            "#,
-            self.ws_url
+        let command = BrowserCommand::PageInfo {
+            tab_id: params.0.tab_id,
+            command_id: Uuid::new_v4().to_string(),
+        };
+        let info: PageInfo = match self.execute(command, &meta, "Get page info", None).await? {
+            Some(data) => serde_json::from_str(&data).map_err(|e| {
+                ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!("Invalid page info: {}", e),
+                    None,
+                )
+            })?,
+            None => PageInfo::default(),
         };
 
-        ServerInfo {
-            server_info: Implementation {
-                name: "mts-browser".to_string(),
-                version: env!("CARGO_PKG_VERSION").to_owned(),
-                title: None,
-                icons: None,
-                website_url: None,
-            },
-            capabilities: ServerCapabilities::builder()
-                .enable_tools()
-                .build(),
-            instructions: Some(instructions),
-            ..Default::default()
+        Ok(text_result(
+            serde_json::to_string_pretty(&info).unwrap_or_default(),
+        ))
+    }
+
+    /// Read the page's console output
+    ///
+    /// Returns buffered `console.*` messages and uncaught errors, so a failed
+    /// interaction can be traced back to the script error behind it.
+    #[tool(
+        name = "browser_console_logs",
+        annotations(read_only_hint = true),
+        description = "Get the page's console output and uncaught JavaScript errors as JSON entries {level, message, timestamp}, oldest first. Use to diagnose why an interaction or page load failed. Pass since (a previous entry's timestamp, in Unix milliseconds) to get only newer entries. The browser keeps the latest 500 entries per tab."
+    )]
+    pub async fn console_logs(
+        &self,
+        params: Parameters<ConsoleLogsParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        tracing::info!("[BrowserMCP] Getting console logs since {:?}", params.since);
+
+        let command = BrowserCommand::GetConsoleLogs {
+            since: params.since,
+            tab_id: params.tab_id,
+            command_id: Uuid::new_v4().to_string(),
+        };
+        let entries: Vec<ConsoleEntry> = match self
+            .execute(command, &meta, "Get console logs", None)
+            .await?
+        {
+            Some(data) => serde_json::from_str(&data).map_err(|e| {
+                ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!("Invalid console logs: {}", e),
+                    None,
+                )
+            })?,
+            None => Vec::new(),
+        };
+
+        if entries.is_empty() {
+            return Ok(text_result("No console output".to_string()));
         }
+        Ok(text_result(
+            serde_json::to_string_pretty(&entries).unwrap_or_default(),
+        ))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Wait for an element to disappear
+    ///
+    /// Resolves once no element matches the selector, e.g. after a loading spinner
+    /// is removed. Resolves immediately if nothing matches when called.
+    #[tool(
+        name = "browser_wait_for_absence",
+        annotations(read_only_hint = true),
+        description = "Wait until no element matches a CSS selector (e.g. a loading spinner). Resolves immediately if already absent; fails after timeout_ms (default 10000, max 25000)."
+    )]
+    pub async fn wait_for_absence(
+        &self,
+        params: Parameters<WaitForAbsenceParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        let timeout_ms = params.timeout_ms.min(MAX_WAIT_TIMEOUT_MS);
 
-    #[test]
-    fn test_browser_server_creation() {
-        let server = BrowserServer::new("http://localhost:3000".to_string());
-        assert_eq!(server.ws_url, "ws://localhost:3000/ws/browser");
+        tracing::info!(
+            "[BrowserMCP] Waiting for absence of {} ({}ms)",
+            params.selector,
+            timeout_ms
+        );
+
+        let command = BrowserCommand::WaitForAbsence {
+            selector: params.selector.clone(),
+            timeout_ms,
+            tab_id: params.tab_id.clone(),
+            command_id: Uuid::new_v4().to_string(),
+        };
+        self.execute(
+            command,
+            &meta,
+            "Wait for absence",
+            // Leave the backend time to report its own timeout first
+            Some(Duration::from_millis(timeout_ms + 5_000)),
+        )
+        .await?;
+
+        Ok(text_result(format!(
+            "✓ No elements match '{}'",
+            params.selector
+        )))
     }
 
-    #[test]
-    fn test_https_to_wss_conversion() {
-        let server = BrowserServer::new("https://example.com:8443".to_string());
-        assert_eq!(server.ws_url, "wss://example.com:8443/ws/browser");
+    /// Scroll an infinite feed and collect its items
+    ///
+    /// The backend repeats scroll-to-bottom until no new items load or the iteration
+    /// cap is hit, replacing a scroll/scrape round trip per page of results.
+    #[tool(
+        name = "browser_scroll_and_collect",
+        annotations(read_only_hint = true),
+        description = "Collect the items of an infinite-scroll page in one call. Scrolls to the bottom, waits up to wait_ms (default 2000, max 10000) for more elements matching item_selector, and repeats until a scroll adds no new items or max_iterations (default 10, max 50) is reached. Returns every distinct item in page order as 'text' (default), 'html' or 'markdown', truncated at max_content_bytes (default 100KB)."
+    )]
+    pub async fn scroll_and_collect(
+        &self,
+        params: Parameters<ScrollAndCollectParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        let max_iterations = params.max_iterations.clamp(1, MAX_SCROLL_ITERATIONS);
+        let wait_ms = params.wait_ms.min(MAX_SCROLL_WAIT_MS);
+
+        tracing::info!(
+            "[BrowserMCP] Scrolling to collect {} (up to {} scrolls, {}ms each)",
+            params.item_selector,
+            max_iterations,
+            wait_ms
+        );
+
+        let command = BrowserCommand::ScrollAndCollect {
+            item_selector: params.item_selector.clone(),
+            max_iterations,
+            wait_ms,
+            tab_id: params.tab_id.clone(),
+            command_id: Uuid::new_v4().to_string(),
+        };
+        // Every scroll may use its full wait; leave the backend time to report on top
+        let timeout = Duration::from_millis(max_iterations as u64 * (wait_ms + 1_000) + 5_000);
+        let data = self
+            .execute(command, &meta, "Scroll and collect", Some(timeout))
+            .await?
+            .unwrap_or_default();
+        let collection: ScrollCollection = serde_json::from_str(&data).map_err(|e| {
+            ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!("Invalid scroll results: {}", e),
+                None,
+            )
+        })?;
+
+        let items = collection
+            .items
+            .iter()
+            .map(|html| match params.format.as_str() {
+                "text" => html_to_text(html, 120).map(|text| text.trim().to_string()),
+                "markdown" => html_to_markdown(html),
+                _ => Ok(html.clone()),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e, None))?;
+
+        let stopped = if collection.exhausted {
+            "no new items appeared".to_string()
+        } else {
+            format!("reached the limit of {} scrolls", max_iterations)
+        };
+        let content = items.join("\n\n---\n\n");
+        let max_size = params
+            .max_content_bytes
+            .unwrap_or(DEFAULT_MAX_CONTENT_BYTES);
+        let content = if content.len() > max_size {
+            format!(
+                "{}...\n\n[Content truncated at {} bytes]",
+                truncate_at_char_boundary(&content, max_size),
+                max_size
+            )
+        } else {
+            content
+        };
+
+        Ok(text_result(format!(
+            "✓ Collected {} items matching '{}' after {} scrolls ({})\n\n{}",
+            items.len(),
+            params.item_selector,
+            collection.iterations,
+            stopped,
+            content
+        )))
+    }
+
+    /// Drag one element onto another
+    ///
+    /// Dispatches the HTML5 drag sequence (dragstart, dragover, drop, dragend), which
+    /// drives kanban boards, sortable lists and drop zones that ignore plain clicks.
+    #[tool(
+        name = "browser_drag_and_drop",
+        description = "Drag the element matching source_selector onto the element matching target_selector using HTML5 drag events. Use for kanban boards, sortable lists and drop zones. Fails if either selector matches nothing."
+    )]
+    pub async fn drag_and_drop(
+        &self,
+        params: Parameters<DragAndDropParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        tracing::info!(
+            "[BrowserMCP] Dragging {} onto {}",
+            params.source_selector,
+            params.target_selector
+        );
+
+        let command = BrowserCommand::DragAndDrop {
+            source_selector: params.source_selector.clone(),
+            target_selector: params.target_selector.clone(),
+            tab_id: params.tab_id.clone(),
+            command_id: Uuid::new_v4().to_string(),
+        };
+        self.execute(command, &meta, "Drag and drop", None).await?;
+
+        Ok(text_result(format!(
+            "✓ Dragged '{}' onto '{}'",
+            params.source_selector, params.target_selector
+        )))
+    }
+
+    /// Choose an option in a dropdown
+    ///
+    /// Typing into a `<select>` does nothing, so this picks the option directly and
+    /// fires `change` for forms bound by a framework.
+    #[tool(
+        name = "browser_select",
+        description = "Choose an option of the <select> element matching selector. value matches the option's value attribute, or its visible text when by_label is true. Fires a change event so forms react. Returns the selected value."
+    )]
+    pub async fn select_option(
+        &self,
+        params: Parameters<SelectOptionParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        tracing::info!(
+            "[BrowserMCP] Selecting {} in {}",
+            params.value,
+            params.selector
+        );
+
+        let command = BrowserCommand::SelectOption {
+            selector: params.selector.clone(),
+            value: params.value.clone(),
+            by_label: params.by_label,
+            tab_id: params.tab_id.clone(),
+            command_id: Uuid::new_v4().to_string(),
+        };
+        let data = self.execute(command, &meta, "Select option", None).await?;
+        // Older backends don't report the value; by value, it is the one requested
+        let selected = data.unwrap_or(params.value);
+
+        Ok(text_result(format!(
+            "✓ Selected '{}' in '{}'",
+            selected, params.selector
+        )))
+    }
+
+    /// Resize the browser viewport
+    ///
+    /// Layout, scraped content and screenshots follow the viewport, so shrinking it
+    /// triggers mobile breakpoints.
+    #[tool(
+        name = "browser_set_viewport",
+        description = "Set the browser viewport to width x height CSS pixels (each 200-4000), e.g. 390x844 for a phone or 1440x900 for a laptop. Later screenshots and scrapes use this layout. Returns the applied size."
+    )]
+    pub async fn set_viewport(
+        &self,
+        params: Parameters<SetViewportParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        for (name, value) in [("width", params.width), ("height", params.height)] {
+            if !VIEWPORT_BOUNDS_PX.contains(&value) {
+                return Err(ErrorData::new(
+                    ErrorCode::INVALID_PARAMS,
+                    format!(
+                        "Viewport {} {}px is out of range; use {}-{}px",
+                        name,
+                        value,
+                        VIEWPORT_BOUNDS_PX.start(),
+                        VIEWPORT_BOUNDS_PX.end()
+                    ),
+                    None,
+                ));
+            }
+        }
+
+        tracing::info!(
+            "[BrowserMCP] Setting viewport to {}x{}",
+            params.width,
+            params.height
+        );
+
+        let command = BrowserCommand::SetViewport {
+            width: params.width,
+            height: params.height,
+            tab_id: params.tab_id,
+            command_id: Uuid::new_v4().to_string(),
+        };
+        let data = self.execute(command, &meta, "Set viewport", None).await?;
+        let applied = match data {
+            Some(data) => serde_json::from_str(&data).map_err(|e| {
+                ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!("Invalid viewport data: {}", e),
+                    None,
+                )
+            })?,
+            None => Viewport {
+                width: params.width,
+                height: params.height,
+            },
+        };
+
+        Ok(text_result(format!(
+            "✓ Viewport set to {}x{}",
+            applied.width, applied.height
+        )))
+    }
+
+    /// Download a file to disk
+    ///
+    /// The server fetches the file itself, so the page is left untouched. The URL is
+    /// checked against the same allowlist as navigation, and files over the configured
+    /// maximum size are rejected.
+    #[tool(
+        name = "browser_download",
+        description = "Download a file from a URL and save it to disk. Returns the saved file path, its size in bytes and its content type. URLs must be http:// or https://. Subject to the domain allowlist and a maximum file size."
+    )]
+    pub async fn download(
+        &self,
+        params: Parameters<DownloadParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        tracing::info!("[BrowserMCP] Downloading: {}", params.url);
+
+        let command = BrowserCommand::Download {
+            url: params.url.clone(),
+            command_id: Uuid::new_v4().to_string(),
+        };
+        let data = self
+            .execute(
+                command,
+                &meta,
+                "Download",
+                params.timeout_ms.map(Duration::from_millis),
+            )
+            .await?
+            .ok_or_else(|| {
+                ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    "Download finished without reporting the saved file".to_string(),
+                    None,
+                )
+            })?;
+        let file: DownloadedFile = serde_json::from_str(&data).map_err(|e| {
+            ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!("Invalid download data: {}", e),
+                None,
+            )
+        })?;
+
+        Ok(text_result(format!(
+            "✓ Downloaded {} to {} ({} bytes, {})",
+            file.url, file.path, file.size, file.content_type
+        )))
+    }
+
+    /// Go back to the previous page
+    ///
+    /// Moves within pages already visited, so it is not subject to the URL allowlist.
+    #[tool(
+        name = "browser_go_back",
+        description = "Go back one page in the browser history, like the browser's back button. Returns the URL of the page now shown."
+    )]
+    pub async fn go_back(
+        &self,
+        params: Parameters<TabParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let command = BrowserCommand::GoBack {
+            tab_id: params.0.tab_id,
+            command_id: Uuid::new_v4().to_string(),
+        };
+        self.move_in_history(command, &meta, "Go back").await
+    }
+
+    /// Go forward to the next page
+    ///
+    /// Only has an effect after going back.
+    #[tool(
+        name = "browser_go_forward",
+        description = "Go forward one page in the browser history after going back. Returns the URL of the page now shown."
+    )]
+    pub async fn go_forward(
+        &self,
+        params: Parameters<TabParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let command = BrowserCommand::GoForward {
+            tab_id: params.0.tab_id,
+            command_id: Uuid::new_v4().to_string(),
+        };
+        self.move_in_history(command, &meta, "Go forward").await
+    }
+
+    /// Reload the current page
+    #[tool(
+        name = "browser_reload",
+        description = "Reload the current page, e.g. to reset a form or pick up changed content. Returns the page URL."
+    )]
+    pub async fn reload(
+        &self,
+        params: Parameters<TabParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let command = BrowserCommand::Reload {
+            tab_id: params.0.tab_id,
+            command_id: Uuid::new_v4().to_string(),
+        };
+        self.move_in_history(command, &meta, "Reload").await
+    }
+
+    /// Open a new browser tab
+    ///
+    /// The new tab starts blank and becomes the active tab, so later commands
+    /// without a tab_id run in it.
+    #[tool(
+        name = "browser_open_tab",
+        description = "Open a new blank browser tab and make it the active tab. Returns its tab_id; pass tab_id to other browser tools to work in a specific tab, e.g. keep reference docs open in one tab while filling a form in another. Optional tab_id names the tab (e.g. 'docs')."
+    )]
+    pub async fn open_tab(
+        &self,
+        params: Parameters<OpenTabParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let tab_id = params.0.tab_id.unwrap_or_else(|| {
+            let suffix: String = Uuid::new_v4()
+                .simple()
+                .to_string()
+                .chars()
+                .take(8)
+                .collect();
+            format!("tab-{}", suffix)
+        });
+
+        tracing::info!("[BrowserMCP] Opening tab {}", tab_id);
+
+        let command = BrowserCommand::OpenTab {
+            tab_id: Some(tab_id.clone()),
+            command_id: Uuid::new_v4().to_string(),
+        };
+        self.execute(command, &meta, "Open tab", None).await?;
+
+        Ok(text_result(format!(
+            "✓ Opened tab '{}' (now active)",
+            tab_id
+        )))
+    }
+
+    /// Close a browser tab
+    #[tool(
+        name = "browser_close_tab",
+        description = "Close a browser tab by tab_id, or the active tab if none is given. If the active tab closes, another open tab becomes active. The last open tab can't be closed."
+    )]
+    pub async fn close_tab(
+        &self,
+        params: Parameters<TabParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let tab_id = params.0.tab_id;
+
+        tracing::info!("[BrowserMCP] Closing tab {:?}", tab_id);
+
+        let command = BrowserCommand::CloseTab {
+            tab_id: tab_id.clone(),
+            command_id: Uuid::new_v4().to_string(),
+        };
+        self.execute(command, &meta, "Close tab", None).await?;
+
+        Ok(text_result(match tab_id {
+            Some(tab_id) => format!("✓ Closed tab '{}'", tab_id),
+            None => "✓ Closed the active tab".to_string(),
+        }))
+    }
+
+    /// Get recent browser commands and their outcomes
+    ///
+    /// Lets the agent check what it already tried, e.g. to avoid repeating a
+    /// navigation that was blocked or a click that failed.
+    #[tool(
+        name = "browser_get_history",
+        annotations(read_only_hint = true),
+        description = "List the most recent browser commands of this session with their outcomes (blocked, queued, forwarded, succeeded, failed). Optional limit (default 10)."
+    )]
+    pub async fn get_history(
+        &self,
+        params: Parameters<GetHistoryParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let command = BrowserCommand::GetHistory {
+            limit: params.0.limit,
+            tab_id: None,
+            command_id: Uuid::new_v4().to_string(),
+        };
+        let data = self.execute(command, &meta, "Get history", None).await?;
+
+        let history: Vec<serde_json::Value> = data
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(|e| {
+                ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!("Invalid history data: {}", e),
+                    None,
+                )
+            })?
+            .unwrap_or_default();
+
+        if history.is_empty() {
+            return Ok(text_result("No browser commands yet".to_string()));
+        }
+        Ok(text_result(
+            serde_json::to_string_pretty(&history).unwrap_or_default(),
+        ))
+    }
+
+    /// Evaluate JavaScript in the page and return the result
+    ///
+    /// Runs with the page's own privileges, so it can read the DOM, `window.location`,
+    /// cookies and storage. The result is returned as JSON; promises are awaited.
+    #[tool(
+        name = "browser_evaluate",
+        description = "Evaluate a JavaScript expression in the page context (the page's window, DOM, cookies and localStorage) and return its result as JSON, e.g. document.querySelectorAll('li').length or window.location.href. Promises are awaited; values that can't be serialized come back as null. Expressions over the configured size limit (default 16KB) are rejected. Avoid side effects unless intended."
+    )]
+    pub async fn evaluate(
+        &self,
+        params: Parameters<EvaluateParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        if params.expression.len() > self.max_script_bytes {
+            return Err(ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!(
+                    "Expression is {} bytes, over the {} byte limit",
+                    params.expression.len(),
+                    self.max_script_bytes
+                ),
+                None,
+            ));
+        }
+
+        tracing::info!(
+            "[BrowserMCP] Evaluating {} byte expression",
+            params.expression.len()
+        );
+
+        let command = BrowserCommand::ExecuteScript {
+            script: evaluate_script(&params.expression),
+            tab_id: params.tab_id.clone(),
+            command_id: Uuid::new_v4().to_string(),
+        };
+        let data = self
+            .execute(
+                command,
+                &meta,
+                "Evaluate",
+                params.timeout_ms.map(Duration::from_millis),
+            )
+            .await?;
+
+        // Fall back to a plain string if the backend didn't return JSON
+        let value = match data {
+            Some(data) => serde_json::from_str(&data).unwrap_or(serde_json::Value::String(data)),
+            None => serde_json::Value::Null,
+        };
+        Ok(text_result(
+            serde_json::to_string_pretty(&value).unwrap_or_default(),
+        ))
+    }
+
+    /// Get computed CSS styles for an element
+    ///
+    /// Returns a JSON object of property name to computed value for the first element
+    /// matching the selector. Properties the browser doesn't report are null.
+    #[tool(
+        name = "browser_get_computed_style",
+        annotations(read_only_hint = true),
+        description = "Get computed CSS styles of the first element matching a selector, as JSON. Optional properties list (e.g. ['display', 'margin-top']); defaults to display, visibility, color, font-size, width and height. Fails if nothing matches."
+    )]
+    pub async fn get_computed_style(
+        &self,
+        params: Parameters<GetComputedStyleParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        let properties = params
+            .properties
+            .filter(|p| !p.is_empty())
+            .unwrap_or_else(|| {
+                DEFAULT_STYLE_PROPERTIES
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect()
+            });
+
+        tracing::info!(
+            "[BrowserMCP] Getting computed style of {} ({:?})",
+            params.selector,
+            properties
+        );
+
+        let command = BrowserCommand::GetComputedStyle {
+            selector: params.selector.clone(),
+            properties: properties.clone(),
+            tab_id: params.tab_id.clone(),
+            command_id: Uuid::new_v4().to_string(),
+        };
+        let data = self
+            .execute(command, &meta, "Get computed style", None)
+            .await?;
+
+        let reported: serde_json::Map<String, serde_json::Value> = data
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(|e| {
+                ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!("Invalid computed style data: {}", e),
+                    None,
+                )
+            })?
+            .ok_or_else(|| {
+                command_failed(
+                    "Get computed style",
+                    Some(format!("No element matches '{}'", params.selector)),
+                    Some(BrowserErrorCode::ElementNotFound),
+                )
+            })?;
+
+        let styles: serde_json::Map<String, serde_json::Value> = properties
+            .into_iter()
+            .map(|p| {
+                let value = reported.get(&p).cloned().unwrap_or_default();
+                (p, value)
+            })
+            .collect();
+
+        Ok(text_result(
+            serde_json::to_string_pretty(&styles).unwrap_or_default(),
+        ))
+    }
+
+    /// Read attributes of an element
+    ///
+    /// Returns a JSON object of attribute name to value for the first element
+    /// matching the selector. Attributes the element doesn't have are null.
+    #[tool(
+        name = "browser_get_attributes",
+        annotations(read_only_hint = true),
+        description = "Read attributes of the first element matching a selector, as JSON, e.g. ['href'] for a link or ['value'] for an input. Attributes the element doesn't have are null. Prefer this over scraping HTML when you need a link target or form value. Fails if nothing matches."
+    )]
+    pub async fn get_attributes(
+        &self,
+        params: Parameters<GetAttributesParams>,
+        meta: Meta,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        if params.attributes.is_empty() {
+            return Err(ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                "At least one attribute name is required".to_string(),
+                None,
+            ));
+        }
+
+        tracing::info!(
+            "[BrowserMCP] Getting attributes of {} ({:?})",
+            params.selector,
+            params.attributes
+        );
+
+        let command = BrowserCommand::GetAttributes {
+            selector: params.selector.clone(),
+            attributes: params.attributes.clone(),
+            tab_id: params.tab_id.clone(),
+            command_id: Uuid::new_v4().to_string(),
+        };
+        let data = self.execute(command, &meta, "Get attributes", None).await?;
+
+        let reported: serde_json::Map<String, serde_json::Value> = data
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(|e| {
+                ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!("Invalid attribute data: {}", e),
+                    None,
+                )
+            })?
+            .ok_or_else(|| {
+                command_failed(
+                    "Get attributes",
+                    Some(format!("No element matches '{}'", params.selector)),
+                    Some(BrowserErrorCode::ElementNotFound),
+                )
+            })?;
+
+        let attributes: serde_json::Map<String, serde_json::Value> = params
+            .attributes
+            .into_iter()
+            .map(|name| {
+                let value = reported.get(&name).cloned().unwrap_or_default();
+                (name, value)
+            })
+            .collect();
+
+        Ok(text_result(
+            serde_json::to_string_pretty(&attributes).unwrap_or_default(),
+        ))
+    }
+}
+
+#[tool_handler(router = self.tool_router)]
+impl ServerHandler for BrowserServer {
+    fn get_info(&self) -> ServerInfo {
+        let mut instructions = formatdoc! {r#"
+            Browser Automation Tools
+
+            You have access to browser automation capabilities through the following tools:
+
+            1. **browser_navigate**: Navigate to URLs and wait for page load
+               - URLs must be http:// or https://
+               - Returns the final URL after redirects
+               - Subject to domain allowlist for security
+
+            2. **browser_interaction**: Interact with page elements
+               - Actions: 'click', 'type', 'scroll', 'focus', 'blur', 'hover', 'press_key'
+               - Use focus/blur for widgets that react to focus (date pickers, validation)
+               - Use hover to open menus that appear on mouseover
+               - Use press_key with value 'Enter' to submit, 'Escape' to close modals
+               - Use scroll with value 'bottom' or '+800' to load more of an infinite feed
+               - Use CSS selectors to target elements
+               - Examples: #button-id, .class-name, input[name=email]
+
+            3. **browser_scrape**: Extract page content
+               - Can extract entire page or specific elements
+               - Supports text, HTML, or markdown format
+               - Automatically truncates large content
+               - Legacy encodings are detected from the page's meta charset, or set via encoding
+
+            4. **browser_screenshot**: Capture page screenshots
+               - Returns base64-encoded PNG images
+               - Set as_resource_link to get a link to the saved image instead
+               - Set save_to_disk to get the absolute path of a saved PNG file
+               - Useful for visual debugging
+
+            5. **browser_wait_for_absence**: Wait for an element to disappear
+               - Use after actions that show a loading spinner or overlay
+               - Resolves immediately if nothing matches
+
+            6. **browser_get_computed_style**: Read an element's computed CSS
+               - Returns property values as JSON for layout and visual debugging
+               - Defaults to display, visibility, color, font-size and box dimensions
+
+            7. **browser_get_history**: Review recent browser commands and outcomes
+               - Check before retrying so blocked or failed actions aren't repeated
+
+            8. **browser_drag_and_drop**: Drag one element onto another
+               - For kanban boards, sortable lists and drop zones
+
+            9. **browser_wait_for_selector**: Wait for an element to appear
+               - Call between navigation and interaction when content renders late
+               - Resolves immediately if the element already exists
+
+            10. **browser_extract_structured_data**: Read JSON-LD and microdata
+               - Returns products, articles, events etc. as parsed JSON
+               - Prefer over scraping when the page publishes structured data
+
+            11. **browser_go_back** / **browser_go_forward** / **browser_reload**: Move through history
+               - Back up after submitting a form to retry with different input
+               - Each returns the URL of the page now shown
+
+            12. **browser_evaluate**: Run a JavaScript expression in the page
+               - Read state that is hard to scrape: element counts, window.location, localStorage
+               - Returns the value as JSON
+
+            13. **browser_get_attributes**: Read element attributes
+               - Use for link targets (href), form values (value) and other properties
+               - More reliable than parsing scraped HTML
+
+            14. **browser_open_tab** / **browser_close_tab**: Work in several tabs
+               - Opening a tab makes it active; commands without tab_id run in the active tab
+               - Pass tab_id to any browser tool to target a specific tab
+
+            15. **browser_select**: Choose an option in a <select> dropdown
+               - Use instead of typing into dropdowns
+               - Match by option value, or by visible text with by_label
+
+            16. **browser_set_viewport**: Resize the viewport
+               - Use before screenshots or scrapes to trigger mobile or desktop layouts
+
+            17. **browser_page_info**: Summarize the page
+               - Title, description, canonical URL, OpenGraph and Twitter card fields
+               - Much smaller than a scrape when you only need to know what a page is
+
+            18. **browser_console_logs**: Read console output and uncaught errors
+               - Check here first when an interaction or page load fails unexpectedly
+               - Pass the last timestamp as since to see only new entries
+
+            19. **browser_download**: Save a file from a URL to disk
+               - Returns the saved path, size and content type
+               - Subject to the same allowlist as navigation, with a maximum file size
+
+            20. **browser_scroll_and_collect**: Gather items from an infinite-scroll feed
+               - Pass a selector matching one item; scrolling stops when no new items load
+               - Capped at max_iterations scrolls, so raise it only for long feeds
+
+            21. **browser_scrape_diff**: See what changed on a page
+               - The first call with a key records a baseline; later calls return added and removed lines
+               - Use to watch a page across actions instead of comparing full scrapes
+
+            **Important Notes:**
+            - The browser is persistent across the session
+            - Navigation is subject to URL allowlist (GitHub, StackOverflow, Wikipedia, localhost by default)
+            - All actions are audited for security
+            - Commands timeout after 30 seconds
+
+            **Common Workflows:**
+            1. Navigate to a page → Scrape content → Process data
+            2. Navigate → Wait for selector → Interact (fill form) → Interact (submit) → Scrape result
+            3. Navigate → Screenshot → Analyze visually
+
+            Browser URL: {}
+            "#,
+            self.ws_url
+        };
+
+        if let Some(custom) = &self.custom_instructions {
+            instructions.push_str(&format!("\n**Session Instructions:**\n{}\n", custom.trim()));
+        }
+
+        ServerInfo {
+            server_info: Implementation {
+                name: "mts-browser".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_owned(),
+                title: None,
+                icons: None,
+                website_url: None,
+            },
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            instructions: Some(instructions),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use tokio::net::TcpListener;
+
+    /// Minimal stand-in for the browser backend that answers every command with `respond`
+    async fn spawn_mock_backend<F, Fut>(respond: F) -> String
+    where
+        F: Fn(BrowserCommand) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = BrowserEvent> + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let respond = Arc::new(respond);
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let respond = respond.clone();
+                tokio::spawn(async move {
+                    let ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    let (mut write, mut read) = ws.split();
+                    while let Some(Ok(Message::Text(text))) = read.next().await {
+                        let command: BrowserCommand = serde_json::from_str(&text).unwrap();
+                        let event = respond(command).await;
+                        let json = serde_json::to_string(&event).unwrap();
+                        if write.send(Message::Text(json.into())).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        format!("http://{}", addr)
+    }
+
+    fn command_result(command: &BrowserCommand, success: bool) -> BrowserEvent {
+        BrowserEvent::CommandResult {
+            command_id: command.command_id().to_string(),
+            success,
+            data: None,
+            error: (!success).then(|| "Timed out".to_string()),
+            error_code: None,
+        }
+    }
+
+    /// Backend that resolves WaitForAbsence once `present` is cleared and
+    /// WaitForSelector once it is set
+    async fn spawn_wait_backend(present: Arc<AtomicBool>) -> String {
+        spawn_mock_backend(move |command| {
+            let present = present.clone();
+            async move {
+                let (timeout_ms, want_present) = match &command {
+                    BrowserCommand::WaitForAbsence { timeout_ms, .. } => (*timeout_ms, false),
+                    BrowserCommand::WaitForSelector { timeout_ms, .. } => (*timeout_ms, true),
+                    _ => panic!("Unexpected command: {:?}", command),
+                };
+                let deadline = tokio::time::Instant::now() + Duration::from_millis(timeout_ms);
+                while present.load(Ordering::SeqCst) != want_present {
+                    if tokio::time::Instant::now() >= deadline {
+                        return command_result(&command, false);
+                    }
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+                command_result(&command, true)
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_sends_beyond_connection_limit_are_serialized() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let url = {
+            let (active, peak) = (active.clone(), peak.clone());
+            spawn_mock_backend(move |command| {
+                let (active, peak) = (active.clone(), peak.clone());
+                async move {
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    command_result(&command, true)
+                }
+            })
+            .await
+        };
+
+        let server = Arc::new(BrowserServer::new(url).max_connections(Some(1)));
+        let sends = (0..3).map(|_| {
+            let server = server.clone();
+            tokio::spawn(async move {
+                let command = BrowserCommand::Screenshot {
+                    selector: None,
+                    hide_selectors: Vec::new(),
+                    css: None,
+                    format: ScreenshotFormat::Png,
+                    quality: None,
+                    tab_id: None,
+                    command_id: Uuid::new_v4().to_string(),
+                };
+                server.send_command(command, &Meta::new(), None).await
+            })
+        });
+        for result in futures::future::join_all(sends).await {
+            assert!(result.unwrap().is_ok());
+        }
+
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+    }
+
+    /// What a counting backend connection does after its first reply
+    #[derive(Clone, Copy)]
+    enum AfterReply {
+        KeepServing,
+        Close,
+        /// Keep the socket open but stop reading, so pings go unanswered
+        GoSilent,
+    }
+
+    /// Backend that counts accepted connections, dropping the first `refuse_first`
+    /// before the handshake
+    async fn spawn_counting_backend(
+        refuse_first: usize,
+        after_reply: AfterReply,
+    ) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let accepted = connections.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                if accepted.fetch_add(1, Ordering::SeqCst) < refuse_first {
+                    drop(stream);
+                    continue;
+                }
+                tokio::spawn(async move {
+                    let ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    let (mut write, mut read) = ws.split();
+                    while let Some(Ok(message)) = read.next().await {
+                        // Pings are answered by the library while reading
+                        let Message::Text(text) = message else {
+                            continue;
+                        };
+                        let command: BrowserCommand = serde_json::from_str(&text).unwrap();
+                        let json = serde_json::to_string(&command_result(&command, true)).unwrap();
+                        if write.send(Message::Text(json.into())).await.is_err() {
+                            break;
+                        }
+                        match after_reply {
+                            AfterReply::KeepServing => {}
+                            AfterReply::Close => {
+                                let _ = write.close().await;
+                                break;
+                            }
+                            AfterReply::GoSilent => {
+                                std::future::pending::<()>().await;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        (format!("http://{}", addr), connections)
+    }
+
+    fn history_command() -> BrowserCommand {
+        BrowserCommand::GetHistory {
+            limit: 1,
+            tab_id: None,
+            command_id: Uuid::new_v4().to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commands_reuse_one_connection() {
+        let (url, connections) = spawn_counting_backend(0, AfterReply::KeepServing).await;
+        let server = BrowserServer::new(url);
+
+        for _ in 0..3 {
+            assert!(server
+                .send_command(history_command(), &Meta::new(), None)
+                .await
+                .is_ok());
+        }
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_after_connection_drops() {
+        let (url, connections) = spawn_counting_backend(0, AfterReply::Close).await;
+        let server = BrowserServer::new(url);
+
+        assert!(server
+            .send_command(history_command(), &Meta::new(), None)
+            .await
+            .is_ok());
+        // Let the reader observe the close before the next command
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(server
+            .send_command(history_command(), &Meta::new(), None)
+            .await
+            .is_ok());
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_fails_pending_commands() {
+        // Backend that takes one command and hangs up without answering it
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let _ = ws.next().await;
+            let _ = ws.close(None).await;
+        });
+        let server = BrowserServer::new(format!("http://{}", addr));
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            server.send_command(
+                history_command(),
+                &Meta::new(),
+                Some(Duration::from_secs(60)),
+            ),
+        )
+        .await
+        .expect("pending command should fail as soon as the backend disconnects");
+        assert_eq!(result.err().as_deref(), Some(BACKEND_DISCONNECTED));
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_keeps_answering_connection() {
+        let (url, connections) = spawn_counting_backend(0, AfterReply::KeepServing).await;
+        let server = BrowserServer::new(url).keepalive_interval_ms(Some(20));
+
+        assert!(server
+            .send_command(history_command(), &Meta::new(), None)
+            .await
+            .is_ok());
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(server
+            .send_command(history_command(), &Meta::new(), None)
+            .await
+            .is_ok());
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_missed_pong_reconnects() {
+        let (url, connections) = spawn_counting_backend(0, AfterReply::GoSilent).await;
+        let server = BrowserServer::new(url).keepalive_interval_ms(Some(20));
+
+        assert!(server
+            .send_command(history_command(), &Meta::new(), None)
+            .await
+            .is_ok());
+        // Two intervals: one to ping, one to notice the missing pong
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(server
+            .send_command(history_command(), &Meta::new(), None)
+            .await
+            .is_ok());
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_connect_retries_failed_handshakes() {
+        let (url, connections) = spawn_counting_backend(2, AfterReply::KeepServing).await;
+        let server = BrowserServer::new(url)
+            .connect_attempts(Some(3))
+            .connect_backoff_ms(Some(1));
+
+        assert!(server
+            .send_command(history_command(), &Meta::new(), None)
+            .await
+            .is_ok());
+        assert_eq!(connections.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_connect_gives_up_after_configured_attempts() {
+        let (url, connections) = spawn_counting_backend(usize::MAX, AfterReply::KeepServing).await;
+        let server = BrowserServer::new(url)
+            .connect_attempts(Some(2))
+            .connect_backoff_ms(Some(1));
+
+        let error = server
+            .send_command(history_command(), &Meta::new(), None)
+            .await
+            .unwrap_err();
+        assert!(error.contains("after 2 attempt(s)"), "{}", error);
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+    }
+
+    fn byte_string(bytes: &[u8]) -> String {
+        bytes.iter().map(|&b| char::from(b)).collect()
+    }
+
+    #[test]
+    fn test_transcode_page_with_encoding_override() {
+        // "Привет" in Windows-1251
+        let raw = byte_string(&[0xCF, 0xF0, 0xE8, 0xE2, 0xE5, 0xF2]);
+        assert_eq!(
+            transcode_page(raw.clone(), Some("windows-1251")).unwrap(),
+            "Привет"
+        );
+        assert!(transcode_page(raw, Some("not-an-encoding")).is_err());
+
+        // Already decoded by the browser, so the override is a no-op
+        assert_eq!(
+            transcode_page("Привет".to_string(), Some("windows-1251")).unwrap(),
+            "Привет"
+        );
+    }
+
+    #[test]
+    fn test_transcode_page_uses_declared_charset() {
+        // "日本" in Shift_JIS
+        let mut raw = byte_string(b"<meta charset=\"Shift_JIS\"><p>");
+        raw.push_str(&byte_string(&[0x93, 0xFA, 0x96, 0x7B]));
+        let page = transcode_page(raw, None).unwrap();
+        assert!(page.ends_with("<p>日本"));
+    }
+
+    #[test]
+    fn test_html_to_text_degrades_on_broken_html() {
+        let text = html_to_text("<p>Hello <b>world</p><div><span>", 120).unwrap();
+        assert!(text.contains("Hello"));
+
+        let nested = "<blockquote>".repeat(5_000) + "deep";
+        let err = html_to_text(&nested, 120).unwrap_err();
+        assert!(err.starts_with("Failed to convert content"));
+        assert!(err.contains(&format!("{} bytes", nested.len())));
+    }
+
+    #[test]
+    fn test_html_to_markdown_keeps_structure() {
+        let html = r#"<h1>Title</h1><h2>Usage</h2><p>See <a href="https://example.com">the docs</a>.</p><ul><li>One</li><li>Two</li></ul>"#;
+        let markdown = html_to_markdown(html).unwrap();
+        assert!(markdown.starts_with("# Title\n"));
+        assert!(markdown.contains("## Usage"));
+        assert!(markdown.contains("[the docs](https://example.com)"));
+        assert!(markdown.contains("*   One\n*   Two"));
+        assert_ne!(markdown, html_to_text(html, 120).unwrap());
+    }
+
+    #[test]
+    fn test_truncate_at_char_boundary() {
+        // "é" is 2 bytes and "日" is 3, so most cut points land mid-character
+        let text = "é日".repeat(50_000);
+        for max_bytes in 99_995..=100_005 {
+            let truncated = truncate_at_char_boundary(&text, max_bytes);
+            assert!(truncated.len() <= max_bytes);
+            assert!(max_bytes - truncated.len() < 3);
+        }
+        assert_eq!(truncate_at_char_boundary("日本", 4), "日");
+        assert_eq!(truncate_at_char_boundary("日本", 2), "");
+        assert_eq!(truncate_at_char_boundary("abc", 10), "abc");
+    }
+
+    #[test]
+    fn test_browser_server_creation() {
+        let server = BrowserServer::new("http://localhost:3000".to_string());
+        assert_eq!(server.ws_url, "ws://localhost:3000/ws/browser");
+    }
+
+    #[test]
+    fn test_https_to_wss_conversion() {
+        let server = BrowserServer::new("https://example.com:8443".to_string());
+        assert_eq!(server.ws_url, "wss://example.com:8443/ws/browser");
+    }
+
+    #[test]
+    fn test_websocket_urls_keep_their_path() {
+        let server = BrowserServer::new("ws://proxy.internal/custom/path".to_string());
+        assert_eq!(server.ws_url, "ws://proxy.internal/custom/path");
+        assert_eq!(server.http_url, "http://proxy.internal");
+
+        let server = BrowserServer::new("wss://example.com:8443/mts/socket/".to_string());
+        assert_eq!(server.ws_url, "wss://example.com:8443/mts/socket");
+        assert_eq!(server.http_url, "https://example.com:8443");
+
+        let server = BrowserServer::new("wss://example.com".to_string());
+        assert_eq!(server.ws_url, "wss://example.com/ws/browser");
+
+        let server = BrowserServer::new("http://example.com/mts".to_string());
+        assert_eq!(server.ws_url, "ws://example.com/mts");
+        assert_eq!(server.http_url, "http://example.com/mts");
+    }
+
+    #[test]
+    fn test_bare_host_defaults_to_ws() {
+        let server = BrowserServer::new("localhost:3000/".to_string());
+        assert_eq!(server.ws_url, "ws://localhost:3000/ws/browser");
+        assert_eq!(server.http_url, "http://localhost:3000");
+    }
+
+    #[test]
+    fn test_custom_instructions_are_appended() {
+        let server = BrowserServer::new("http://localhost:3000".to_string())
+            .custom_instructions(Some("Only scrape pages, never interact.".to_string()));
+        let instructions = server.get_info().instructions.unwrap();
+        assert!(instructions.contains("Browser Automation Tools"));
+        assert!(instructions.contains("Only scrape pages, never interact."));
+
+        let server = BrowserServer::new("http://localhost:3000".to_string());
+        let instructions = server.get_info().instructions.unwrap();
+        assert!(!instructions.contains("Session Instructions"));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_absence_resolves_after_element_removed() {
+        let present = Arc::new(AtomicBool::new(true));
+        let server = BrowserServer::new(spawn_wait_backend(present.clone()).await);
+
+        let remover = present.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            remover.store(false, Ordering::SeqCst);
+        });
+
+        let result = server
+            .wait_for_absence(
+                Parameters(WaitForAbsenceParams {
+                    selector: ".spinner".to_string(),
+                    timeout_ms: 2_000,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await;
+        assert!(result.is_ok());
+        assert!(!present.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_focus_action_runs_focus_script() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::ExecuteScript { script, .. } = &command else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            assert!(script.contains(r#"document.querySelector("input[name=\"date\"]")"#));
+            assert!(script.contains("el.focus();"));
+            assert!(script.contains("new FocusEvent('focus')"));
+            assert!(script.contains("new FocusEvent('focusin', { bubbles: true })"));
+            command_result(&command, true)
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .interact(
+                Parameters(InteractionParams {
+                    action: "focus".to_string(),
+                    selector: r#"input[name="date"]"#.to_string(),
+                    value: None,
+                    timeout_ms: None,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_screenshot_carries_hidden_selectors() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::Screenshot {
+                hide_selectors,
+                css,
+                ..
+            } = &command
+            else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            assert_eq!(hide_selectors, &vec!["#cookie-banner", ".ad"]);
+            assert_eq!(css.as_deref(), Some("header { position: static; }"));
+            command_result(&command, true)
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .screenshot(
+                Parameters(ScreenshotParams {
+                    hide_selectors: vec!["#cookie-banner".to_string(), ".ad".to_string()],
+                    css: Some("header { position: static; }".to_string()),
+                    ..Default::default()
+                }),
+                Meta::new(),
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_screenshot_format_sets_mime_type() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::Screenshot {
+                format, quality, ..
+            } = &command
+            else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            assert_eq!(*format, ScreenshotFormat::Webp);
+            assert_eq!(*quality, Some(70));
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some("UklGRg==".to_string()),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .screenshot(
+                Parameters(ScreenshotParams {
+                    format: ScreenshotFormat::Webp,
+                    quality: Some(70),
+                    as_resource_link: true,
+                    ..Default::default()
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+        let link = result.content[0].as_resource_link().unwrap();
+        assert_eq!(link.mime_type.as_deref(), Some("image/webp"));
+        assert!(link.name.ends_with(".webp"));
+        std::fs::remove_file(artifacts_dir().join(&link.name)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_screenshot_rejects_invalid_quality() {
+        let server = BrowserServer::new("ws://127.0.0.1:1".to_string());
+
+        for (format, quality) in [
+            (ScreenshotFormat::Png, 80),
+            (ScreenshotFormat::Jpeg, 0),
+            (ScreenshotFormat::Jpeg, 101),
+        ] {
+            let error = server
+                .screenshot(
+                    Parameters(ScreenshotParams {
+                        format,
+                        quality: Some(quality),
+                        ..Default::default()
+                    }),
+                    Meta::new(),
+                )
+                .await
+                .unwrap_err();
+            assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_element_screenshot_warns_on_full_page_fallback() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::Screenshot { selector, .. } = &command else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            let found = selector.as_deref() == Some("#chart");
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some("iVBORw0KGgo=".to_string()),
+                error: None,
+                error_code: (!found).then_some(BrowserErrorCode::ElementNotFound),
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let cropped = server
+            .screenshot(
+                Parameters(ScreenshotParams {
+                    selector: Some("#chart".to_string()),
+                    ..Default::default()
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(cropped.content.len(), 2);
+        assert!(cropped.content[0].as_image().is_some());
+
+        let fallback = server
+            .screenshot(
+                Parameters(ScreenshotParams {
+                    selector: Some("#missing".to_string()),
+                    ..Default::default()
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+        assert!(fallback.content[0].as_image().is_some());
+        assert_eq!(
+            fallback.content[2].as_text().unwrap().text,
+            "⚠ No element matches '#missing'; captured the full page instead"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_screenshot_saved_to_disk_reports_path() {
+        let url = spawn_mock_backend(|command| async move {
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some("iVBORw0KGgo=".to_string()),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .screenshot(
+                Parameters(ScreenshotParams {
+                    save_to_disk: true,
+                    ..Default::default()
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+        assert!(result.content[0].as_image().is_some());
+        let text = &result.content[2].as_text().unwrap().text;
+        let path = std::path::PathBuf::from(text.strip_prefix("Saved to ").unwrap());
+        assert!(path.is_absolute());
+        assert_eq!(std::fs::read(&path).unwrap(), b"\x89PNG\r\n\x1a\n".to_vec());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_drag_and_drop_sends_both_selectors() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::DragAndDrop {
+                source_selector,
+                target_selector,
+                ..
+            } = &command
+            else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            assert_eq!(source_selector, "#card-1");
+            assert_eq!(target_selector, "#column-done");
+            command_result(&command, true)
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .drag_and_drop(
+                Parameters(DragAndDropParams {
+                    source_selector: "#card-1".to_string(),
+                    target_selector: "#column-done".to_string(),
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_select_option_reports_selected_value() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::SelectOption {
+                selector,
+                value,
+                by_label,
+                ..
+            } = &command
+            else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            assert_eq!(selector, "#country");
+            assert_eq!(value, "Canada");
+            assert!(*by_label);
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some("ca".to_string()),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .select_option(
+                Parameters(SelectOptionParams {
+                    selector: "#country".to_string(),
+                    value: "Canada".to_string(),
+                    by_label: true,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content[0].as_text().unwrap().text,
+            "✓ Selected 'ca' in '#country'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_viewport_reports_applied_size() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::SetViewport { width, height, .. } = &command else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            assert_eq!((*width, *height), (390, 844));
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some(r#"{"width":390,"height":800}"#.to_string()),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .set_viewport(
+                Parameters(SetViewportParams {
+                    width: 390,
+                    height: 844,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content[0].as_text().unwrap().text,
+            "✓ Viewport set to 390x800"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_page_info_keeps_reported_fields() {
+        let url = spawn_mock_backend(|command| async move {
+            assert!(matches!(command, BrowserCommand::PageInfo { .. }));
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some(
+                    r#"{"title":"Rust","canonical_url":"https://www.rust-lang.org/","open_graph":{"type":"website"},"twitter":{}}"#
+                        .to_string(),
+                ),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .page_info(Parameters(TabParams::default()), Meta::new())
+            .await
+            .unwrap();
+        let info: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(
+            info,
+            serde_json::json!({
+                "title": "Rust",
+                "canonical_url": "https://www.rust-lang.org/",
+                "open_graph": {"type": "website"}
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scroll_and_collect_caps_iterations_and_formats_items() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::ScrollAndCollect {
+                max_iterations,
+                wait_ms,
+                ..
+            } = &command
+            else {
+                panic!("expected ScrollAndCollect, got {:?}", command);
+            };
+            assert_eq!(*max_iterations, MAX_SCROLL_ITERATIONS);
+            assert_eq!(*wait_ms, MAX_SCROLL_WAIT_MS);
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some(
+                    serde_json::json!({
+                        "items": ["<li>First post</li>", "<li>Second post</li>"],
+                        "iterations": 3,
+                        "exhausted": true,
+                    })
+                    .to_string(),
+                ),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .scroll_and_collect(
+                Parameters(ScrollAndCollectParams {
+                    item_selector: "li".to_string(),
+                    max_iterations: 500,
+                    wait_ms: 60_000,
+                    format: "text".to_string(),
+                    max_content_bytes: None,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content[0].as_text().unwrap().text,
+            "✓ Collected 2 items matching 'li' after 3 scrolls (no new items appeared)\n\nFirst post\n\n---\n\nSecond post"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_console_logs_pass_since_and_report_entries() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::GetConsoleLogs { since, .. } = &command else {
+                panic!("expected GetConsoleLogs, got {:?}", command);
+            };
+            let data = match since {
+                Some(_) => "[]".to_string(),
+                None => r#"[{"level":"error","message":"Uncaught TypeError: x is undefined","timestamp":1700000000000}]"#
+                    .to_string(),
+            };
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some(data),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .console_logs(Parameters(ConsoleLogsParams::default()), Meta::new())
+            .await
+            .unwrap();
+        let entries: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(entries[0]["level"], "error");
+        assert_eq!(entries[0]["timestamp"], 1_700_000_000_000u64);
+
+        let result = server
+            .console_logs(
+                Parameters(ConsoleLogsParams {
+                    since: Some(1_700_000_000_000),
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content[0].as_text().unwrap().text,
+            "No console output"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_reports_saved_file() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::Download { url, .. } = &command else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            assert_eq!(url, "https://example.com/report.pdf");
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some(
+                    r#"{"path":"/tmp/a.pdf","size":42,"content_type":"application/pdf","url":"https://example.com/report.pdf"}"#
+                        .to_string(),
+                ),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .download(
+                Parameters(DownloadParams {
+                    url: "https://example.com/report.pdf".to_string(),
+                    timeout_ms: None,
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content[0].as_text().unwrap().text,
+            "✓ Downloaded https://example.com/report.pdf to /tmp/a.pdf (42 bytes, application/pdf)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_viewport_rejects_out_of_range_size() {
+        let server = BrowserServer::new("http://127.0.0.1:9".to_string());
+
+        let err = server
+            .set_viewport(
+                Parameters(SetViewportParams {
+                    width: 1280,
+                    height: 50,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("height 50px is out of range"));
+    }
+
+    #[tokio::test]
+    async fn test_get_history_lists_entries() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::GetHistory { limit, .. } = &command else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            assert_eq!(*limit, 10);
+            let history = serde_json::json!([
+                {"command_id": "a", "command_type": "navigate", "outcome": "blocked"}
+            ]);
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some(history.to_string()),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .get_history(Parameters(GetHistoryParams::default()), Meta::new())
+            .await
+            .unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("\"outcome\": \"blocked\""));
+    }
+
+    #[tokio::test]
+    async fn test_history_moves_report_resulting_url() {
+        let url = spawn_mock_backend(|command| async move {
+            let page = match &command {
+                BrowserCommand::GoBack { .. } => "https://example.com/form",
+                BrowserCommand::GoForward { .. } => "https://example.com/submitted",
+                BrowserCommand::Reload { .. } => "https://example.com/form",
+                _ => panic!("Unexpected command: {:?}", command),
+            };
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some(page.to_string()),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let text = |result: CallToolResult| result.content[0].as_text().unwrap().text.clone();
+        assert_eq!(
+            text(
+                server
+                    .go_back(Parameters(TabParams::default()), Meta::new())
+                    .await
+                    .unwrap()
+            ),
+            "✓ Go back: now at https://example.com/form"
+        );
+        assert!(text(
+            server
+                .go_forward(Parameters(TabParams::default()), Meta::new())
+                .await
+                .unwrap()
+        )
+        .ends_with("/submitted"));
+        assert!(text(
+            server
+                .reload(Parameters(TabParams::default()), Meta::new())
+                .await
+                .unwrap()
+        )
+        .ends_with("/form"));
+    }
+
+    #[test]
+    fn test_session_id_from_meta() {
+        let mut meta = Meta::new();
+        assert_eq!(session_id_from_meta(&meta), None);
+
+        meta.0
+            .insert("MTS-Session-Id".to_string(), "session-1".into());
+        assert_eq!(session_id_from_meta(&meta).as_deref(), Some("session-1"));
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_returns_json_value() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::ExecuteScript { script, .. } = &command else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            assert!(script.contains(r#"(0, eval)("document.querySelectorAll('li').length")"#));
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some(r#"{"count":3}"#.to_string()),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .evaluate(
+                Parameters(EvaluateParams {
+                    expression: "document.querySelectorAll('li').length".to_string(),
+                    timeout_ms: None,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert_eq!(text, "{\n  \"count\": 3\n}");
+    }
+
+    #[tokio::test]
+    async fn test_scrape_truncates_multibyte_content() {
+        let url = spawn_mock_backend(|command| async move {
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some("日本語".repeat(10)),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .scrape(
+                Parameters(ScrapeParams {
+                    selector: None,
+                    format: "html".to_string(),
+                    encoding: None,
+                    max_content_bytes: Some(16),
+                    timeout_ms: None,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.ends_with("日本語日本...\n\n[Content truncated at 16 bytes]"));
+    }
+
+    #[tokio::test]
+    async fn test_scrape_diff_reports_changes_since_baseline() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let url = spawn_mock_backend(move |command| {
+            let page = match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => "Inbox\nfirst message\nsecond message",
+                _ => "Inbox\nsecond message\nthird message",
+            };
+            async move {
+                BrowserEvent::CommandResult {
+                    command_id: command.command_id().to_string(),
+                    success: true,
+                    data: Some(page.to_string()),
+                    error: None,
+                    error_code: None,
+                }
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+        let params = || ScrapeDiffParams {
+            key: "inbox".to_string(),
+            selector: None,
+            format: "html".to_string(),
+            encoding: None,
+            max_content_bytes: None,
+            tab_id: None,
+            timeout_ms: None,
+        };
+
+        let result = server
+            .scrape_diff(Parameters(params()), Meta::new())
+            .await
+            .unwrap();
+        assert!(result.content[0]
+            .as_text()
+            .unwrap()
+            .text
+            .starts_with("Captured baseline for 'inbox' (3 lines)"));
+
+        let result = server
+            .scrape_diff(Parameters(params()), Meta::new())
+            .await
+            .unwrap();
+        let diff: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(diff["added"], serde_json::json!(["third message"]));
+        assert_eq!(diff["removed"], serde_json::json!(["first message"]));
+
+        let result = server
+            .scrape_diff(Parameters(params()), Meta::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content[0].as_text().unwrap().text,
+            "No changes since the last snapshot for 'inbox'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_rejects_oversized_expression() {
+        let server = BrowserServer::new("http://127.0.0.1:9".to_string()).max_script_bytes(Some(8));
+
+        let err = server
+            .evaluate(
+                Parameters(EvaluateParams {
+                    expression: "window.location.href".to_string(),
+                    timeout_ms: None,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("over the 8 byte limit"));
+    }
+
+    #[tokio::test]
+    async fn test_press_key_action_dispatches_keyboard_events() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::ExecuteScript { script, .. } = &command else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            assert!(script.contains(r##"document.querySelector("#search")"##));
+            assert!(script.contains(r#"const key = "Enter";"#));
+            assert!(script.contains("keyCode: 13,"));
+            assert!(script.contains("new KeyboardEvent('keydown', init)"));
+            assert!(script.contains("new KeyboardEvent('keyup', init)"));
+            command_result(&command, true)
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .interact(
+                Parameters(InteractionParams {
+                    action: "press_key".to_string(),
+                    selector: "#search".to_string(),
+                    value: Some("Enter".to_string()),
+                    timeout_ms: None,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_press_key_rejects_unknown_key() {
+        let server = BrowserServer::new("http://127.0.0.1:9".to_string());
+
+        let err = server
+            .interact(
+                Parameters(InteractionParams {
+                    action: "press_key".to_string(),
+                    selector: "#search".to_string(),
+                    value: Some("Return".to_string()),
+                    timeout_ms: None,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("Unknown key: 'Return'"));
+        assert!(err.message.contains("Enter, Escape, Tab"));
+    }
+
+    #[test]
+    fn test_resolve_key() {
+        assert_eq!(
+            resolve_key("Space"),
+            Some(KeyPress {
+                key: " ".to_string(),
+                code: "Space".to_string(),
+                key_code: 32,
+            })
+        );
+        assert_eq!(
+            resolve_key("a"),
+            Some(KeyPress {
+                key: "a".to_string(),
+                code: "KeyA".to_string(),
+                key_code: 65,
+            })
+        );
+        assert_eq!(resolve_key("7").unwrap().code, "Digit7");
+        assert_eq!(resolve_key("/").unwrap().key_code, 0);
+        assert_eq!(resolve_key("escape"), None);
+        assert_eq!(resolve_key("\n"), None);
+        assert_eq!(resolve_key(""), None);
+    }
+
+    #[test]
+    fn test_hover_script_fires_mouse_events() {
+        let script = hover_script("nav .menu");
+        assert!(script.contains(r#"document.querySelector("nav .menu")"#));
+        assert!(script.contains("new MouseEvent('mouseover', init)"));
+        assert!(script.contains("new MouseEvent('mouseenter', { ...init, bubbles: false })"));
+    }
+
+    #[test]
+    fn test_scroll_script_forms() {
+        let into_view = scroll_script("#comments", None).unwrap();
+        assert!(into_view.contains(r##"document.querySelector("#comments")"##));
+        assert!(into_view.contains("el.scrollIntoView({ block: 'center' });"));
+
+        let script = |value: &str| scroll_script("", Some(value));
+        assert!(script("top")
+            .unwrap()
+            .contains("window.scrollTo({ top: 0 });"));
+        assert!(script("bottom")
+            .unwrap()
+            .contains("window.scrollTo({ top: document.documentElement.scrollHeight });"));
+        assert!(script("+800")
+            .unwrap()
+            .contains("window.scrollBy({ top: 800 });"));
+        assert!(script("-400")
+            .unwrap()
+            .contains("window.scrollBy({ top: -400 });"));
+        assert!(script("1200")
+            .unwrap()
+            .contains("window.scrollTo({ top: 1200 });"));
+        assert!(script("1200").unwrap().contains("return window.scrollY;"));
+        assert!(script("middle").is_none());
+        assert!(script("+inf").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_scroll_value_needs_no_selector() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::ExecuteScript { script, .. } = &command else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            assert!(
+                script.contains("window.scrollTo({ top: document.documentElement.scrollHeight });")
+            );
+            command_result(&command, true)
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let scroll = |selector: &str, value: Option<&str>| InteractionParams {
+            action: "scroll".to_string(),
+            selector: selector.to_string(),
+            value: value.map(str::to_string),
+            timeout_ms: None,
+            tab_id: None,
+        };
+        assert!(server
+            .interact(Parameters(scroll("", Some("bottom"))), Meta::new())
+            .await
+            .is_ok());
+
+        let err = server
+            .interact(Parameters(scroll("", None)), Meta::new())
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+        let err = server
+            .interact(Parameters(scroll("", Some("sideways"))), Meta::new())
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Invalid scroll value: 'sideways'"));
+    }
+
+    #[test]
+    fn test_blur_script_fires_blur_events() {
+        let script = focus_script("#email", false);
+        assert!(script.contains("el.blur();"));
+        assert!(script.contains("new FocusEvent('focusout', { bubbles: true })"));
+        assert!(!script.contains("new FocusEvent('focus')"));
+    }
+
+    #[tokio::test]
+    async fn test_get_computed_style_returns_requested_properties() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::GetComputedStyle { properties, .. } = &command else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            let styles: serde_json::Map<String, serde_json::Value> = properties
+                .iter()
+                .filter(|p| p.as_str() != "z-index")
+                .map(|p| (p.clone(), format!("{}-value", p).into()))
+                .collect();
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some(serde_json::to_string(&styles).unwrap()),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .get_computed_style(
+                Parameters(GetComputedStyleParams {
+                    selector: "#header".to_string(),
+                    properties: Some(vec!["display".to_string(), "z-index".to_string()]),
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+
+        let text = result.content[0].as_text().unwrap().text.clone();
+        let styles: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            styles,
+            serde_json::json!({"display": "display-value", "z-index": null})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tab_tools_send_tab_ids() {
+        let commands = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = commands.clone();
+        let url = spawn_mock_backend(move |command| {
+            seen.lock().unwrap().push(command.clone());
+            async move { command_result(&command, true) }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .open_tab(
+                Parameters(OpenTabParams {
+                    tab_id: Some("docs".to_string()),
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content[0].as_text().unwrap().text,
+            "✓ Opened tab 'docs' (now active)"
+        );
+        server
+            .reload(
+                Parameters(TabParams {
+                    tab_id: Some("docs".to_string()),
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+        server
+            .close_tab(Parameters(TabParams::default()), Meta::new())
+            .await
+            .unwrap();
+        let generated = server
+            .open_tab(Parameters(OpenTabParams::default()), Meta::new())
+            .await
+            .unwrap();
+        assert!(generated.content[0]
+            .as_text()
+            .unwrap()
+            .text
+            .starts_with("✓ Opened tab 'tab-"));
+
+        let commands = commands.lock().unwrap();
+        let tabs: Vec<_> = commands
+            .iter()
+            .map(|command| (command.type_name(), command.tab_id()))
+            .collect();
+        assert_eq!(
+            &tabs[..3],
+            &[
+                ("open_tab", Some("docs")),
+                ("reload", Some("docs")),
+                ("close_tab", None),
+            ]
+        );
+        assert!(tabs[3].1.is_some_and(|id| id.starts_with("tab-")));
+    }
+
+    #[tokio::test]
+    async fn test_get_attributes_reports_missing_as_null() {
+        let url = spawn_mock_backend(|command| async move {
+            let BrowserCommand::GetAttributes {
+                selector,
+                attributes,
+                ..
+            } = &command
+            else {
+                panic!("Unexpected command: {:?}", command);
+            };
+            assert_eq!(selector, "a.next");
+            assert_eq!(attributes, &["href", "target"]);
+            BrowserEvent::CommandResult {
+                command_id: command.command_id().to_string(),
+                success: true,
+                data: Some(r#"{"href": "/page/2", "target": null}"#.to_string()),
+                error: None,
+                error_code: None,
+            }
+        })
+        .await;
+        let server = BrowserServer::new(url);
+
+        let result = server
+            .get_attributes(
+                Parameters(GetAttributesParams {
+                    selector: "a.next".to_string(),
+                    attributes: vec!["href".to_string(), "target".to_string()],
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await
+            .unwrap();
+
+        let text = result.content[0].as_text().unwrap().text.clone();
+        let attributes: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            attributes,
+            serde_json::json!({"href": "/page/2", "target": null})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_absence_times_out_while_present() {
+        let present = Arc::new(AtomicBool::new(true));
+        let server = BrowserServer::new(spawn_wait_backend(present).await);
+
+        let result = server
+            .wait_for_absence(
+                Parameters(WaitForAbsenceParams {
+                    selector: ".spinner".to_string(),
+                    timeout_ms: 50,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_selector_resolves_after_element_renders() {
+        let present = Arc::new(AtomicBool::new(false));
+        let server = BrowserServer::new(spawn_wait_backend(present.clone()).await);
+
+        let renderer = present.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            renderer.store(true, Ordering::SeqCst);
+        });
+
+        let result = server
+            .wait_for_selector(
+                Parameters(WaitForSelectorParams {
+                    selector: "#submit".to_string(),
+                    timeout_ms: 2_000,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await;
+        assert!(result.is_ok());
+
+        let missing =
+            BrowserServer::new(spawn_wait_backend(Arc::new(AtomicBool::new(false))).await);
+        let result = missing
+            .wait_for_selector(
+                Parameters(WaitForSelectorParams {
+                    selector: "#never".to_string(),
+                    timeout_ms: 50,
+                    tab_id: None,
+                }),
+                Meta::new(),
+            )
+            .await;
+        assert!(result.is_err());
     }
 }
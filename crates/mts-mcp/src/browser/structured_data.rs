@@ -0,0 +1,294 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Elements that never have a closing tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+static JSON_LD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?is)<script\b[^>]*\btype\s*=\s*["']?application/ld\+json["']?[^>]*>(.*?)</script\s*>"#,
+    )
+    .unwrap()
+});
+
+/// Script and style bodies are skipped when building the microdata tree
+static RAW_TEXT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)<script\b.*?</script\s*>|<style\b.*?</style\s*>|<!--.*?-->").unwrap()
+});
+
+static TOKEN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"<(/?)([a-zA-Z][a-zA-Z0-9-]*)((?:[^>"']|"[^"]*"|'[^']*')*)>|([^<]+)|<"#).unwrap()
+});
+
+static ATTRIBUTE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"([^\s"'<>/=]+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+)))?"#).unwrap()
+});
+
+/// Collect the structured data embedded in a page.
+///
+/// Returns every JSON-LD object (top-level arrays are flattened) followed by one
+/// object per top-level microdata item, written in JSON-LD style with `@type`,
+/// `@id` and one key per `itemprop`. Invalid JSON-LD blocks are skipped.
+pub fn extract_structured_data(html: &str) -> Vec<Value> {
+    let mut items = extract_json_ld(html);
+    let root = parse_tree(&RAW_TEXT.replace_all(html, ""));
+    collect_microdata_items(&root, &mut items);
+    items
+}
+
+fn extract_json_ld(html: &str) -> Vec<Value> {
+    let mut items = Vec::new();
+    for captures in JSON_LD.captures_iter(html) {
+        match serde_json::from_str::<Value>(captures[1].trim()) {
+            Ok(Value::Array(values)) => items.extend(values),
+            Ok(value) => items.push(value),
+            Err(e) => tracing::warn!("[BrowserMCP] Skipping invalid JSON-LD block: {}", e),
+        }
+    }
+    items
+}
+
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+struct Element {
+    tag: String,
+    attrs: HashMap<String, String>,
+    children: Vec<Node>,
+}
+
+impl Element {
+    fn new(tag: String, attrs: HashMap<String, String>) -> Self {
+        Self {
+            tag,
+            attrs,
+            children: Vec::new(),
+        }
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.get(name).map(String::as_str)
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    fn text_content(&self, out: &mut String) {
+        for child in &self.children {
+            match child {
+                Node::Element(element) => element.text_content(out),
+                Node::Text(text) => out.push_str(text),
+            }
+        }
+    }
+}
+
+/// Build a forgiving element tree: unmatched closing tags are ignored and unclosed
+/// elements are closed by their parent's closing tag.
+fn parse_tree(html: &str) -> Element {
+    let mut stack = vec![Element::new(String::new(), HashMap::new())];
+
+    for token in TOKEN.captures_iter(html) {
+        if let Some(text) = token.get(4) {
+            let text = decode_entities(text.as_str());
+            attach(&mut stack, Node::Text(text));
+            continue;
+        }
+        let Some(tag) = token.get(2) else {
+            continue;
+        };
+        let tag = tag.as_str().to_ascii_lowercase();
+
+        if !token[1].is_empty() {
+            if let Some(position) = stack.iter().rposition(|element| element.tag == tag) {
+                while stack.len() > position {
+                    let element = stack.pop().unwrap();
+                    attach(&mut stack, Node::Element(element));
+                }
+            }
+            continue;
+        }
+
+        let raw_attrs = &token[3];
+        let element = Element::new(tag, parse_attributes(raw_attrs));
+        if VOID_ELEMENTS.contains(&element.tag.as_str()) || raw_attrs.trim_end().ends_with('/') {
+            attach(&mut stack, Node::Element(element));
+        } else {
+            stack.push(element);
+        }
+    }
+
+    while stack.len() > 1 {
+        let element = stack.pop().unwrap();
+        attach(&mut stack, Node::Element(element));
+    }
+    stack.pop().unwrap()
+}
+
+fn attach(stack: &mut [Element], node: Node) {
+    stack.last_mut().unwrap().children.push(node);
+}
+
+fn parse_attributes(raw: &str) -> HashMap<String, String> {
+    ATTRIBUTE
+        .captures_iter(raw)
+        .map(|captures| {
+            let value = (2..=4)
+                .find_map(|group| captures.get(group))
+                .map(|value| decode_entities(value.as_str()))
+                .unwrap_or_default();
+            (captures[1].to_ascii_lowercase(), value)
+        })
+        .collect()
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn collect_microdata_items(element: &Element, items: &mut Vec<Value>) {
+    for child in element.elements() {
+        if child.attrs.contains_key("itemscope") && !child.attrs.contains_key("itemprop") {
+            items.push(microdata_item(child));
+        } else {
+            collect_microdata_items(child, items);
+        }
+    }
+}
+
+fn microdata_item(element: &Element) -> Value {
+    let mut item = Map::new();
+    if let Some(item_type) = element.attr("itemtype") {
+        let item_type = item_type
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches("https://schema.org/")
+            .trim_start_matches("http://schema.org/");
+        item.insert("@type".to_string(), Value::String(item_type.to_string()));
+    }
+    if let Some(id) = element.attr("itemid") {
+        item.insert("@id".to_string(), Value::String(id.to_string()));
+    }
+    collect_properties(element, &mut item);
+    Value::Object(item)
+}
+
+fn collect_properties(element: &Element, item: &mut Map<String, Value>) {
+    for child in element.elements() {
+        let is_scope = child.attrs.contains_key("itemscope");
+        if let Some(names) = child.attr("itemprop") {
+            let value = if is_scope {
+                microdata_item(child)
+            } else {
+                Value::String(property_value(child))
+            };
+            for name in names.split_whitespace() {
+                insert_property(item, name, value.clone());
+            }
+        }
+        if !is_scope {
+            collect_properties(child, item);
+        }
+    }
+}
+
+/// The value of a non-item property, following the microdata spec's per-element rules
+fn property_value(element: &Element) -> String {
+    let attribute = match element.tag.as_str() {
+        "meta" => Some("content"),
+        "a" | "area" | "link" => Some("href"),
+        "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => Some("src"),
+        "object" => Some("data"),
+        "data" | "meter" => Some("value"),
+        "time" => Some("datetime"),
+        _ => None,
+    };
+    if let Some(value) = attribute.and_then(|name| element.attr(name)) {
+        return value.to_string();
+    }
+    let mut text = String::new();
+    element.text_content(&mut text);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Repeated properties are collected into an array
+fn insert_property(item: &mut Map<String, Value>, name: &str, value: Value) {
+    match item.get_mut(name) {
+        Some(Value::Array(values)) => values.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            item.insert(name.to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_extracts_json_ld_and_microdata() {
+        let html = r#"<html><head>
+            <script type="application/ld+json">
+              {"@context": "https://schema.org", "@type": "Article", "headline": "Hello &amp; welcome"}
+            </script>
+            <script type="application/ld+json">not json</script>
+            </head><body>
+            <div itemscope itemtype="https://schema.org/Product">
+              <span itemprop="name">Widget &amp; Co</span>
+              <meta itemprop="sku" content="W-1">
+              <a itemprop="url" href="/widget">Details</a>
+              <div itemprop="offers" itemscope itemtype="https://schema.org/Offer">
+                <span itemprop="price">9.99</span>
+              </div>
+              <span itemprop="color">red</span><span itemprop="color">blue</span>
+            </div>
+            </body></html>"#;
+
+        let items = extract_structured_data(html);
+        assert_eq!(
+            items,
+            vec![
+                json!({
+                    "@context": "https://schema.org",
+                    "@type": "Article",
+                    "headline": "Hello &amp; welcome",
+                }),
+                json!({
+                    "@type": "Product",
+                    "name": "Widget & Co",
+                    "sku": "W-1",
+                    "url": "/widget",
+                    "offers": {"@type": "Offer", "price": "9.99"},
+                    "color": ["red", "blue"],
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_page_without_structured_data() {
+        assert!(extract_structured_data("<html><body><p>Plain</p></body></html>").is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Fallback timeout for command types without their own entry
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Built-in per-type defaults, in milliseconds
+const TYPE_DEFAULTS_MS: &[(&str, u64)] = &[
+    ("navigate", 30_000),
+    ("click", 10_000),
+    ("type", 10_000),
+    ("extract_dom", 15_000),
+    ("screenshot", 20_000),
+    ("execute_script", 15_000),
+    ("get_computed_style", 5_000),
+    ("get_attributes", 5_000),
+    ("drag_and_drop", 10_000),
+    ("select_option", 10_000),
+    ("set_viewport", 10_000),
+    ("get_history", 5_000),
+    ("go_back", 30_000),
+    ("go_forward", 30_000),
+    ("reload", 30_000),
+    ("open_tab", 10_000),
+    ("close_tab", 10_000),
+    ("page_info", 10_000),
+    ("get_console_logs", 10_000),
+    ("download", 120_000),
+];
+
+/// Timeouts for browser commands, keyed by command type.
+///
+/// The applicable timeout is, in priority order: the per-call override, the entry
+/// for the command type, then the global default.
+#[derive(Debug, Clone)]
+pub struct TimeoutRegistry {
+    default: Duration,
+    per_type: HashMap<String, Duration>,
+}
+
+impl Default for TimeoutRegistry {
+    fn default() -> Self {
+        Self {
+            default: DEFAULT_TIMEOUT,
+            per_type: TYPE_DEFAULTS_MS
+                .iter()
+                .map(|(name, ms)| (name.to_string(), Duration::from_millis(*ms)))
+                .collect(),
+        }
+    }
+}
+
+impl TimeoutRegistry {
+    /// Apply overrides written as `default=45000,navigate=60000` (milliseconds).
+    /// Malformed entries are logged and skipped.
+    pub fn with_overrides(mut self, spec: &str) -> Self {
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry
+                .split_once('=')
+                .map(|(name, ms)| (name.trim(), ms.trim().parse::<u64>()))
+                .filter(|(name, _)| !name.is_empty())
+                .and_then(|(name, ms)| Some((name, ms.ok()?)));
+            match parsed {
+                Some(("default", ms)) => self.default = Duration::from_millis(ms),
+                Some((name, ms)) => {
+                    self.per_type
+                        .insert(name.to_string(), Duration::from_millis(ms));
+                }
+                None => tracing::warn!("[BrowserMCP] Ignoring invalid timeout entry: {}", entry),
+            }
+        }
+        self
+    }
+
+    /// Timeout for a command type, preferring `per_call` when given
+    pub fn resolve(&self, command_type: &str, per_call: Option<Duration>) -> Duration {
+        per_call
+            .or_else(|| self.per_type.get(command_type).copied())
+            .unwrap_or(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_precedence() {
+        let registry = TimeoutRegistry::default().with_overrides("default=40000, click=2000");
+
+        // Per-call beats everything
+        assert_eq!(
+            registry.resolve("click", Some(Duration::from_millis(500))),
+            Duration::from_millis(500)
+        );
+        // Per-type config beats the built-in default for that type
+        assert_eq!(
+            registry.resolve("click", None),
+            Duration::from_millis(2_000)
+        );
+        // Built-in per-type default beats the global default
+        assert_eq!(registry.resolve("navigate", None), Duration::from_secs(30));
+        // Unknown types fall back to the configured global default
+        assert_eq!(
+            registry.resolve("wait_for_absence", None),
+            Duration::from_secs(40)
+        );
+    }
+
+    #[test]
+    fn test_invalid_overrides_are_skipped() {
+        let registry = TimeoutRegistry::default().with_overrides("navigate=soon,=5,click");
+        assert_eq!(registry.resolve("navigate", None), Duration::from_secs(30));
+        assert_eq!(registry.resolve("click", None), Duration::from_secs(10));
+    }
+}
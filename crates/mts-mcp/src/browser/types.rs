@@ -6,20 +6,41 @@ use serde::{Deserialize, Serialize};
 pub struct NavigateParams {
     /// The URL to navigate to (must be http:// or https://)
     pub url: String,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+
+    /// Timeout in milliseconds, overriding the configured default for this command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
 }
 
 /// Parameters for the browser_interaction tool
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct InteractionParams {
-    /// Action type: 'click', 'type', or 'scroll'
+    /// Action type: 'click', 'type', 'scroll', 'focus', 'blur', 'hover', or 'press_key'
     pub action: String,
 
-    /// CSS selector for the target element
+    /// CSS selector for the target element. Not needed for 'scroll' with a value.
+    #[serde(default)]
     pub selector: String,
 
-    /// Value to type (required for 'type' action)
+    /// Value to type (required for 'type' action), or the key to press (required
+    /// for 'press_key', e.g. 'Enter', 'Escape', 'Tab' or a single character).
+    /// For 'scroll', scrolls the window instead of to the selector: 'top',
+    /// 'bottom', a signed pixel offset like '+800' or '-400', or an absolute
+    /// pixel position like '1200'.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<String>,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+
+    /// Timeout in milliseconds, overriding the configured default for this command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
 }
 
 /// Parameters for the browser_scrape tool
@@ -32,8 +53,349 @@ pub struct ScrapeParams {
     /// Output format: 'text', 'html', or 'markdown' (default: 'text')
     #[serde(default = "default_format")]
     pub format: String,
+
+    /// Character encoding override, e.g. 'shift_jis' or 'windows-1251'
+    /// (default: the page's declared charset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+
+    /// Maximum size of the returned content in bytes (default: 100000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_content_bytes: Option<usize>,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+
+    /// Timeout in milliseconds, overriding the configured default for this command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
 }
 
 fn default_format() -> String {
     "text".to_string()
 }
+
+/// Image encoding for browser_screenshot
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ScreenshotFormat {
+    #[default]
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl ScreenshotFormat {
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Png => "image/png",
+            ScreenshotFormat::Jpeg => "image/jpeg",
+            ScreenshotFormat::Webp => "image/webp",
+        }
+    }
+
+    /// File extension for saved artifacts
+    pub fn extension(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Png => "png",
+            ScreenshotFormat::Jpeg => "jpg",
+            ScreenshotFormat::Webp => "webp",
+        }
+    }
+}
+
+/// Parameters for the browser_screenshot tool
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ScreenshotParams {
+    /// CSS selector of an element to capture on its own (default: the full page).
+    /// If nothing matches, the full page is captured with a warning.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+
+    /// Return a link to the saved image instead of inline base64 data
+    #[serde(default)]
+    pub as_resource_link: bool,
+
+    /// Also save the image to a temporary file and return its absolute path
+    #[serde(default)]
+    pub save_to_disk: bool,
+
+    /// Image format (default: png). PNG is lossless and keeps small text crisp but
+    /// is large on photo-heavy pages; jpeg or webp are usually several times
+    /// smaller, which cuts the cost of sending the image to a vision model.
+    #[serde(default)]
+    pub format: ScreenshotFormat,
+
+    /// Quality from 1 to 100 for jpeg and webp (default: the browser's). Around 60
+    /// to 80 keeps text readable at a fraction of the size; not accepted for png.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<u8>,
+
+    /// CSS selectors for elements to hide while capturing, e.g. cookie banners
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hide_selectors: Vec<String>,
+
+    /// Extra CSS injected while capturing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub css: Option<String>,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+}
+
+/// Parameters for the browser_get_computed_style tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetComputedStyleParams {
+    /// CSS selector for the element to inspect
+    pub selector: String,
+
+    /// CSS property names to report (default: display, visibility, color,
+    /// font-size, width, height)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Vec<String>>,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+}
+
+/// Parameters for the browser_get_attributes tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetAttributesParams {
+    /// CSS selector for the element to inspect
+    pub selector: String,
+
+    /// Attribute names to read, e.g. ['href', 'value', 'aria-label']
+    pub attributes: Vec<String>,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+}
+
+/// Parameters for the browser_evaluate tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EvaluateParams {
+    /// JavaScript expression to evaluate in the page, e.g.
+    /// `document.querySelectorAll('li').length` or `localStorage.getItem('token')`
+    pub expression: String,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+
+    /// Timeout in milliseconds for this call, overriding the configured default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Parameters for the browser_drag_and_drop tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DragAndDropParams {
+    /// CSS selector for the element to drag
+    pub source_selector: String,
+
+    /// CSS selector for the element to drop onto
+    pub target_selector: String,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+}
+
+/// Parameters for the browser_select tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SelectOptionParams {
+    /// CSS selector for the <select> element
+    pub selector: String,
+
+    /// Option value to choose, or its visible text when by_label is true
+    pub value: String,
+
+    /// Match the option's visible text instead of its value attribute
+    #[serde(default)]
+    pub by_label: bool,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+}
+
+/// Parameters for the browser_set_viewport tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SetViewportParams {
+    /// Viewport width in CSS pixels (200-4000), e.g. 390 for a phone
+    pub width: u32,
+
+    /// Viewport height in CSS pixels (200-4000)
+    pub height: u32,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+}
+
+/// Parameters for the browser_download tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DownloadParams {
+    /// The URL of the file to download (must be http:// or https://)
+    pub url: String,
+
+    /// Timeout in milliseconds, overriding the configured default for this command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Parameters for the browser_get_history tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetHistoryParams {
+    /// Number of most recent commands to return (default: 10)
+    #[serde(default = "default_history_limit")]
+    pub limit: usize,
+}
+
+impl Default for GetHistoryParams {
+    fn default() -> Self {
+        Self {
+            limit: default_history_limit(),
+        }
+    }
+}
+
+fn default_history_limit() -> usize {
+    10
+}
+
+/// Parameters for the browser_wait_for_selector tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WaitForSelectorParams {
+    /// CSS selector that should start matching (e.g. '#submit-button')
+    pub selector: String,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+
+    /// Maximum time to wait in milliseconds (default: 10000, max: 25000)
+    #[serde(default = "default_wait_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+/// Parameters for the browser_wait_for_absence tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WaitForAbsenceParams {
+    /// CSS selector that should stop matching (e.g. '.loading-spinner')
+    pub selector: String,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+
+    /// Maximum time to wait in milliseconds (default: 10000, max: 25000)
+    #[serde(default = "default_wait_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_wait_timeout_ms() -> u64 {
+    10_000
+}
+
+/// Parameters for the browser_scroll_and_collect tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScrollAndCollectParams {
+    /// CSS selector matching a single feed item (e.g. 'article.post')
+    pub item_selector: String,
+
+    /// Most scrolls to make before returning (default: 10, max: 50)
+    #[serde(default = "default_scroll_iterations")]
+    pub max_iterations: u32,
+
+    /// How long to wait for new items after each scroll, in milliseconds; a scroll
+    /// that adds none within this time ends the collection (default: 2000, max: 10000)
+    #[serde(default = "default_scroll_wait_ms")]
+    pub wait_ms: u64,
+
+    /// Output format for each item: 'text', 'html', or 'markdown' (default: 'text')
+    #[serde(default = "default_format")]
+    pub format: String,
+
+    /// Maximum size of the returned content in bytes (default: 100000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_content_bytes: Option<usize>,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+}
+
+fn default_scroll_iterations() -> u32 {
+    10
+}
+
+fn default_scroll_wait_ms() -> u64 {
+    2_000
+}
+
+/// Parameters for browser tools that only need a tab
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct TabParams {
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+}
+
+/// Parameters for the browser_open_tab tool
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct OpenTabParams {
+    /// Id for the new tab, e.g. 'docs' (default: generated)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+}
+
+/// Parameters for the browser_scrape_diff tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScrapeDiffParams {
+    /// Name for the snapshot to compare against, e.g. 'inbox'; each call replaces
+    /// the stored snapshot for this key
+    pub key: String,
+
+    /// CSS selector to extract (optional, defaults to body)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+
+    /// Output format to compare: 'text', 'html', or 'markdown' (default: 'text')
+    #[serde(default = "default_format")]
+    pub format: String,
+
+    /// Character encoding override, e.g. 'shift_jis' or 'windows-1251'
+    /// (default: the page's declared charset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+
+    /// Maximum size of the returned diff in bytes (default: 100000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_content_bytes: Option<usize>,
+
+    /// Tab to act in, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+
+    /// Timeout in milliseconds, overriding the configured default for this command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Parameters for the browser_console_logs tool
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ConsoleLogsParams {
+    /// Only return entries logged after this time, in Unix milliseconds; pass the
+    /// last entry's timestamp to fetch only newer output (default: everything buffered)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<u64>,
+
+    /// Tab to read, from browser_open_tab (default: the active tab)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+}
@@ -10,7 +10,11 @@ pub async fn check_token(
     request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    if request.uri().path() == "/status" || request.uri().path() == "/mcp-ui-proxy" {
+    // Liveness and readiness probes can't send the secret key
+    if matches!(
+        request.uri().path(),
+        "/status" | "/healthz" | "/readyz" | "/mcp-ui-proxy"
+    ) {
         return Ok(next.run(request).await);
     }
     let secret_key = request
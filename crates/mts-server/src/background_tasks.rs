@@ -1,14 +1,69 @@
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicI64, AtomicU8, Ordering};
-use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI64, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 
-use crate::routes::reply::MessageEvent;
+use crate::routes::reply::{ErrorCode, MessageEvent};
+
+/// Number of recent events kept so late subscribers can catch up
+const REPLAY_LIMIT: usize = 100;
+
+/// Events a task's channel holds for subscribers that haven't caught up yet
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 100;
+
+/// How often the reaper looks for stale tasks
+pub const DEFAULT_REAPER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long a finished task without subscribers is kept after its last activity
+pub const DEFAULT_TASK_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// How long a reply's idempotency key keeps pointing at the task it started
+pub const DEFAULT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// File the task records are saved to, in the state directory by default
+pub const TASK_STATE_FILE: &str = "background_tasks.json";
+
+/// Number of task records kept on disk; the least recently active are dropped first
+const RECORD_LIMIT: usize = 1000;
+
+/// Error recorded for tasks that were still running when the server stopped
+const INTERRUPTED_ERROR: &str = "The server restarted while this task was running";
+
+/// What a new task does when its session already has one running
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConcurrentTaskPolicy {
+    /// Refuse the new task, leaving the running one alone
+    #[default]
+    Reject,
+    /// Start the new task once the running one finishes
+    Queue,
+    /// Cancel the running task and start the new one
+    Replace,
+}
+
+/// Returned when a session already has a running task and the policy doesn't
+/// replace it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionBusy;
+
+impl std::fmt::Display for SessionBusy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Session already has a running task")
+    }
+}
+
+impl std::error::Error for SessionBusy {}
 
 /// Status of a background task
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum TaskStatus {
     Running = 0,
@@ -29,21 +84,69 @@ impl From<u8> for TaskStatus {
     }
 }
 
+impl TaskStatus {
+    /// Every status, in declaration order
+    pub const ALL: [TaskStatus; 4] = [
+        TaskStatus::Running,
+        TaskStatus::Completed,
+        TaskStatus::Error,
+        TaskStatus::Cancelled,
+    ];
+}
+
+impl std::str::FromStr for TaskStatus {
+    type Err = String;
+
+    /// Parse a status name as reported in `TaskStatusResponse`, ignoring case
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        TaskStatus::ALL
+            .into_iter()
+            .find(|status| format!("{:?}", status).eq_ignore_ascii_case(value))
+            .ok_or_else(|| format!("Unknown task status '{}'", value))
+    }
+}
+
+/// Result of asking to remove a task with `cleanup_task`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CleanupOutcome {
+    /// The task and its saved record were removed
+    Removed,
+    /// The task is still running or has subscribers, so it was kept
+    InUse,
+    /// Nothing is known about a task for this session
+    NotFound,
+}
+
+/// Result of deciding a tool call with `take_approval`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalOutcome {
+    /// The tool call was waiting on a decision, which the caller now delivers
+    Taken,
+    /// The tool call was already decided
+    AlreadyDecided,
+    /// The session's running task never asked about this tool call
+    NotFound,
+}
+
 /// Handle to a running background task
 struct TaskHandle {
     cancel_token: CancellationToken,
+    /// Cancelled once the task leaves the Running status
+    finished: CancellationToken,
     status: AtomicU8,
     last_activity: AtomicI64,
-    broadcaster: broadcast::Sender<MessageEvent>,
+    last_error: Mutex<Option<String>>,
+    broadcaster: TaskBroadcaster,
 }
 
 impl TaskHandle {
-    fn new(cancel_token: CancellationToken) -> Self {
-        let (broadcaster, _) = broadcast::channel(100);
+    fn new(cancel_token: CancellationToken, broadcaster: TaskBroadcaster) -> Self {
         Self {
             cancel_token,
+            finished: CancellationToken::new(),
             status: AtomicU8::new(TaskStatus::Running as u8),
             last_activity: AtomicI64::new(current_timestamp()),
+            last_error: Mutex::new(None),
             broadcaster,
         }
     }
@@ -53,13 +156,214 @@ impl TaskHandle {
             .store(current_timestamp(), Ordering::SeqCst);
     }
 
+    /// A task can be dropped once it has finished and nobody is listening
+    fn is_finished_and_unwatched(&self) -> bool {
+        self.get_status() != TaskStatus::Running && self.broadcaster.receiver_count() == 0
+    }
+
+    fn record(&self) -> TaskRecord {
+        TaskRecord {
+            status: self.get_status(),
+            last_activity_ms: self.last_activity.load(Ordering::SeqCst),
+            last_error: self.get_last_error(),
+        }
+    }
+
+    fn status_response(&self, session_id: &str) -> TaskStatusResponse {
+        self.record()
+            .status_response(session_id, self.broadcaster.receiver_count())
+    }
+
     fn set_status(&self, status: TaskStatus) {
         self.status.store(status as u8, Ordering::SeqCst);
+        if status != TaskStatus::Running {
+            self.finished.cancel();
+        }
+    }
+
+    /// Move a running task to a final status. Returns false if it had already
+    /// ended, e.g. when it was cancelled before it could complete.
+    fn finish(&self, status: TaskStatus) -> bool {
+        let finished = self
+            .status
+            .compare_exchange(
+                TaskStatus::Running as u8,
+                status as u8,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            )
+            .is_ok();
+        if finished {
+            self.finished.cancel();
+        }
+        finished
     }
 
     fn get_status(&self) -> TaskStatus {
         self.status.load(Ordering::SeqCst).into()
     }
+
+    fn set_last_error(&self, error: String) {
+        *self.last_error.lock().unwrap_or_else(|e| e.into_inner()) = Some(error);
+    }
+
+    fn get_last_error(&self) -> Option<String> {
+        self.last_error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+/// What is kept of a task once it is no longer tracked in memory, and saved to disk
+/// so clients can still learn how it ended after a restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TaskRecord {
+    status: TaskStatus,
+    last_activity_ms: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_error: Option<String>,
+}
+
+impl TaskRecord {
+    fn status_response(&self, session_id: &str, subscriber_count: usize) -> TaskStatusResponse {
+        TaskStatusResponse {
+            session_id: session_id.to_string(),
+            status: format!("{:?}", self.status),
+            last_activity_ms: self.last_activity_ms,
+            has_subscribers: subscriber_count > 0,
+            subscriber_count,
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+/// An event with its position in the task's stream, used as the SSE event id
+#[derive(Debug, Clone)]
+pub struct SequencedEvent {
+    /// Increases by one per event of the task, starting at 1; None for notices the
+    /// server makes up for a single subscriber
+    pub id: Option<u64>,
+    pub event: MessageEvent,
+}
+
+/// Broadcasts a task's events, keeping the most recent ones for subscribers that
+/// join late
+#[derive(Clone)]
+pub struct TaskBroadcaster {
+    /// Tells this task apart from earlier and later tasks of the same session
+    task_id: u64,
+    sender: broadcast::Sender<SequencedEvent>,
+    replay: Arc<Mutex<VecDeque<SequencedEvent>>>,
+    last_id: Arc<AtomicU64>,
+    /// Request ids of the ApprovalRequest events sent, mapped to whether each
+    /// still awaits a decision
+    approvals: Arc<Mutex<HashMap<String, bool>>>,
+}
+
+impl TaskBroadcaster {
+    fn new(task_id: u64, capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            task_id,
+            sender,
+            replay: Arc::new(Mutex::new(VecDeque::new())),
+            last_id: Arc::new(AtomicU64::new(0)),
+            approvals: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Id of the task, for marking how it ended
+    pub fn task_id(&self) -> u64 {
+        self.task_id
+    }
+
+    /// Send an event to current subscribers and keep it for later ones, returning
+    /// its id. Pings are not kept.
+    pub fn send(&self, event: MessageEvent) -> u64 {
+        if let MessageEvent::ApprovalRequest { request_id, .. } = &event {
+            self.approvals
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(request_id.clone(), true);
+        }
+        // Held across the send so a concurrent subscribe sees each event exactly once
+        let mut replay = self.replay.lock().unwrap_or_else(|e| e.into_inner());
+        let id = self.last_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let event = SequencedEvent {
+            id: Some(id),
+            event,
+        };
+        if !matches!(event.event, MessageEvent::Ping) {
+            replay.push_back(event.clone());
+            while replay.len() > REPLAY_LIMIT {
+                replay.pop_front();
+            }
+        }
+        // No subscribers is fine; the task keeps running in the background
+        let _ = self.sender.send(event);
+        id
+    }
+
+    /// Subscribe, replaying the buffered events after `last_event_id` (all of them
+    /// when None)
+    fn subscribe(&self, last_event_id: Option<u64>) -> TaskSubscription {
+        let replay = self.replay.lock().unwrap_or_else(|e| e.into_inner());
+        TaskSubscription {
+            replay: replay
+                .iter()
+                .filter(|event| event.id > last_event_id)
+                .cloned()
+                .collect(),
+            receiver: self.sender.subscribe(),
+        }
+    }
+
+    fn receiver_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    fn take_approval(&self, request_id: &str) -> ApprovalOutcome {
+        let mut approvals = self.approvals.lock().unwrap_or_else(|e| e.into_inner());
+        match approvals.get_mut(request_id) {
+            Some(pending) if *pending => {
+                *pending = false;
+                ApprovalOutcome::Taken
+            }
+            Some(_) => ApprovalOutcome::AlreadyDecided,
+            None => ApprovalOutcome::NotFound,
+        }
+    }
+}
+
+/// A subscriber's view of a task: the buffered recent events, then live ones
+pub struct TaskSubscription {
+    replay: VecDeque<SequencedEvent>,
+    receiver: broadcast::Receiver<SequencedEvent>,
+}
+
+impl TaskSubscription {
+    /// Next event, or None once the task stops broadcasting. If this subscriber fell
+    /// too far behind, an Error event without an id says how many events it missed.
+    pub async fn recv(&mut self) -> Option<SequencedEvent> {
+        if let Some(event) = self.replay.pop_front() {
+            return Some(event);
+        }
+        match self.receiver.recv().await {
+            Ok(event) => Some(event),
+            Err(RecvError::Lagged(dropped)) => Some(SequencedEvent {
+                id: None,
+                event: MessageEvent::Error {
+                    error: format!(
+                        "{} events were dropped while this subscriber fell behind; refetch the conversation",
+                        dropped
+                    ),
+                    error_code: ErrorCode::EventsDropped,
+                },
+            }),
+            Err(RecvError::Closed) => None,
+        }
+    }
 }
 
 fn current_timestamp() -> i64 {
@@ -76,11 +380,33 @@ pub struct TaskStatusResponse {
     pub status: String,
     pub last_activity_ms: i64,
     pub has_subscribers: bool,
+    /// Number of clients currently streaming this task's events
+    pub subscriber_count: usize,
+    /// Error that ended the task, when status is Error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
 }
 
 /// Manages background agent tasks that continue running when clients disconnect
 pub struct BackgroundTaskManager {
     tasks: RwLock<HashMap<String, Arc<TaskHandle>>>,
+    /// Latest known state of every task, including ones from before a restart
+    records: RwLock<HashMap<String, TaskRecord>>,
+    /// Where `records` is saved; None keeps them in memory only
+    store_path: Option<PathBuf>,
+    /// Serializes writes to `store_path`
+    store_lock: tokio::sync::Mutex<()>,
+    /// Capacity of each task's broadcast channel
+    channel_capacity: usize,
+    /// What registering a task does when its session already has one running
+    concurrent_task_policy: ConcurrentTaskPolicy,
+    /// Tasks started with an idempotency key, by session id and key, with the time
+    /// the key was claimed
+    idempotency_keys: Mutex<HashMap<(String, String), (TaskBroadcaster, Instant)>>,
+    /// How long an idempotency key is honoured
+    idempotency_ttl: Duration,
+    /// Id handed to the next registered task
+    next_task_id: AtomicU64,
 }
 
 impl Default for BackgroundTaskManager {
@@ -93,51 +419,255 @@ impl BackgroundTaskManager {
     pub fn new() -> Self {
         Self {
             tasks: RwLock::new(HashMap::new()),
+            records: RwLock::new(HashMap::new()),
+            store_path: None,
+            store_lock: tokio::sync::Mutex::new(()),
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            concurrent_task_policy: ConcurrentTaskPolicy::default(),
+            idempotency_keys: Mutex::new(HashMap::new()),
+            idempotency_ttl: DEFAULT_IDEMPOTENCY_TTL,
+            next_task_id: AtomicU64::new(1),
         }
     }
 
-    /// Register a new background task for a session
-    /// Returns a sender for broadcasting events to subscribers
+    /// Set how many events each task's channel holds for slow subscribers
+    ///
+    /// A subscriber that falls further behind than this loses events and is told to
+    /// refetch the conversation. Every slot is allocated up front and holds a full
+    /// message until all subscribers have read it, so memory grows with
+    /// capacity × running tasks × message size; raise it only for sessions that emit
+    /// bursts of tool-call messages.
+    pub fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = capacity.max(1);
+        self
+    }
+
+    /// Set what registering a task does when its session already has one running
+    pub fn concurrent_task_policy(mut self, policy: ConcurrentTaskPolicy) -> Self {
+        self.concurrent_task_policy = policy;
+        self
+    }
+
+    /// Set how long a retried reply with the same idempotency key gets the original
+    /// task's stream instead of starting a new one
+    pub fn idempotency_ttl(mut self, ttl: Duration) -> Self {
+        self.idempotency_ttl = ttl;
+        self
+    }
+
+    /// Whether a task refused with `SessionBusy` should wait for the session instead
+    pub fn queues_concurrent_tasks(&self) -> bool {
+        self.concurrent_task_policy == ConcurrentTaskPolicy::Queue
+    }
+
+    /// Create a manager that saves task records to `path`, loading the records a
+    /// previous run left there. Tasks that were still running are reported as errors,
+    /// since they can't be resumed.
+    pub fn load_or_default(path: PathBuf) -> Self {
+        let mut manager = Self::new();
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => match serde_json::from_str::<HashMap<String, TaskRecord>>(&contents) {
+                Ok(mut records) => {
+                    for record in records.values_mut() {
+                        if record.status == TaskStatus::Running {
+                            record.status = TaskStatus::Error;
+                            record.last_error = Some(INTERRUPTED_ERROR.to_string());
+                        }
+                    }
+                    manager.records = RwLock::new(records);
+                }
+                Err(e) => tracing::warn!("Ignoring corrupt {}: {}", path.display(), e),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => tracing::warn!("Could not read {}: {}", path.display(), e),
+        }
+        manager.store_path = Some(path);
+        manager
+    }
+
+    /// Remember a task's current state, saving all records if this manager has a
+    /// store path
+    async fn record(&self, session_id: &str, handle: &TaskHandle) {
+        let _guard = self.store_lock.lock().await;
+        let records = {
+            let mut records = self.records.write().await;
+            records.insert(session_id.to_string(), handle.record());
+            if records.len() > RECORD_LIMIT {
+                let oldest = records
+                    .iter()
+                    .min_by_key(|(_, record)| record.last_activity_ms)
+                    .map(|(id, _)| id.clone());
+                if let Some(oldest) = oldest {
+                    records.remove(&oldest);
+                }
+            }
+            records.clone()
+        };
+
+        let Some(path) = &self.store_path else {
+            return;
+        };
+        if let Err(e) = write_records(path, &records).await {
+            tracing::warn!("Could not save {}: {}", path.display(), e);
+        }
+    }
+
+    /// Create the broadcaster for a new task. Subscribers can join it before the task
+    /// is registered, e.g. while it waits for its session.
+    pub fn new_broadcaster(&self) -> TaskBroadcaster {
+        let task_id = self.next_task_id.fetch_add(1, Ordering::SeqCst);
+        TaskBroadcaster::new(task_id, self.channel_capacity)
+    }
+
+    /// Register a new background task for a session, broadcasting through a
+    /// broadcaster from `new_broadcaster`
+    /// Returns the broadcaster, or `SessionBusy` if the session has a running task
+    /// that the policy doesn't allow replacing
     pub async fn register_task(
         &self,
         session_id: String,
         cancel_token: CancellationToken,
-    ) -> broadcast::Sender<MessageEvent> {
-        let handle = Arc::new(TaskHandle::new(cancel_token));
-        let broadcaster = handle.broadcaster.clone();
+        broadcaster: TaskBroadcaster,
+    ) -> Result<TaskBroadcaster, SessionBusy> {
+        let handle = Arc::new(TaskHandle::new(cancel_token, broadcaster.clone()));
 
-        let mut tasks = self.tasks.write().await;
-        // Cancel any existing task for this session
-        if let Some(old_handle) = tasks.remove(&session_id) {
-            old_handle.cancel_token.cancel();
+        {
+            let mut tasks = self.tasks.write().await;
+            if let Some(old_handle) = tasks
+                .get(&session_id)
+                .filter(|handle| handle.get_status() == TaskStatus::Running)
+            {
+                if self.concurrent_task_policy != ConcurrentTaskPolicy::Replace {
+                    return Err(SessionBusy);
+                }
+                old_handle.cancel_token.cancel();
+            }
+            tasks.insert(session_id.clone(), handle.clone());
         }
-        tasks.insert(session_id, handle);
+        self.record(&session_id, &handle).await;
 
-        broadcaster
+        Ok(broadcaster)
     }
 
-    /// Subscribe to updates from a running task
+    /// Register a task once the session's running task, if any, has finished
+    /// Tasks waiting on the same session start one at a time, in no particular order
+    pub async fn register_task_when_idle(
+        &self,
+        session_id: String,
+        cancel_token: CancellationToken,
+        broadcaster: TaskBroadcaster,
+    ) -> TaskBroadcaster {
+        loop {
+            match self
+                .register_task(
+                    session_id.clone(),
+                    cancel_token.clone(),
+                    broadcaster.clone(),
+                )
+                .await
+            {
+                Ok(broadcaster) => return broadcaster,
+                Err(SessionBusy) => {
+                    let running = self.tasks.read().await.get(&session_id).cloned();
+                    if let Some(handle) = running {
+                        handle.finished.cancelled().await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Subscribe to updates from a running task, starting with its recent events
+    /// after `last_event_id`
     /// Returns None if no task is running for this session
-    pub async fn subscribe(&self, session_id: &str) -> Option<broadcast::Receiver<MessageEvent>> {
+    pub async fn subscribe(
+        &self,
+        session_id: &str,
+        last_event_id: Option<u64>,
+    ) -> Option<TaskSubscription> {
         let tasks = self.tasks.read().await;
         tasks.get(session_id).map(|handle| {
             handle.update_activity();
-            handle.broadcaster.subscribe()
+            handle.broadcaster.subscribe(last_event_id)
         })
     }
 
-    /// Get the status of a task
+    /// Claim `key` for the task behind `broadcaster`, unless a request with the same
+    /// key already claimed it within the TTL. In that case that task's stream is
+    /// returned instead, whether the task is queued, running or finished, replaying
+    /// its recent events after `last_event_id`.
+    pub fn claim_idempotency_key(
+        &self,
+        session_id: &str,
+        key: &str,
+        broadcaster: &TaskBroadcaster,
+        last_event_id: Option<u64>,
+    ) -> Option<TaskSubscription> {
+        let mut keys = self
+            .idempotency_keys
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let ttl = self.idempotency_ttl;
+        keys.retain(|_, (_, claimed_at)| claimed_at.elapsed() < ttl);
+        match keys.entry((session_id.to_string(), key.to_string())) {
+            Entry::Occupied(claimed) => Some(claimed.get().0.subscribe(last_event_id)),
+            Entry::Vacant(vacant) => {
+                vacant.insert((broadcaster.clone(), Instant::now()));
+                None
+            }
+        }
+    }
+
+    /// Give up the claim the task with `task_id` holds on `key`, so a retry starts a
+    /// new task
+    pub fn release_idempotency_key(&self, session_id: &str, key: &str, task_id: u64) {
+        let mut keys = self
+            .idempotency_keys
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let id = (session_id.to_string(), key.to_string());
+        if keys
+            .get(&id)
+            .is_some_and(|(broadcaster, _)| broadcaster.task_id() == task_id)
+        {
+            keys.remove(&id);
+        }
+    }
+
+    /// Get the status of a task, falling back to its last saved record once it is no
+    /// longer tracked
     pub async fn get_status(&self, session_id: &str) -> Option<TaskStatusResponse> {
+        if let Some(handle) = self.tasks.read().await.get(session_id) {
+            return Some(handle.status_response(session_id));
+        }
+        let records = self.records.read().await;
+        records
+            .get(session_id)
+            .map(|record| record.status_response(session_id, 0))
+    }
+
+    /// Get the status of every tracked task, optionally only those with `status`,
+    /// ordered by session id
+    pub async fn list_statuses(&self, status: Option<TaskStatus>) -> Vec<TaskStatusResponse> {
         let tasks = self.tasks.read().await;
-        tasks.get(session_id).map(|handle| TaskStatusResponse {
-            session_id: session_id.to_string(),
-            status: format!("{:?}", handle.get_status()),
-            last_activity_ms: handle.last_activity.load(Ordering::SeqCst),
-            has_subscribers: handle.broadcaster.receiver_count() > 0,
-        })
+        let records = self.records.read().await;
+        let live = tasks.iter().map(|(id, handle)| handle.status_response(id));
+        let saved = records
+            .iter()
+            .filter(|(id, _)| !tasks.contains_key(*id))
+            .map(|(id, record)| record.status_response(id, 0));
+        let mut statuses: Vec<_> = live
+            .chain(saved)
+            .filter(|response| {
+                status.is_none_or(|status| response.status == format!("{:?}", status))
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        statuses
     }
 
     /// Check if a task is running for a session
+    #[allow(dead_code)]
     pub async fn is_running(&self, session_id: &str) -> bool {
         let tasks = self.tasks.read().await;
         tasks
@@ -145,30 +675,57 @@ impl BackgroundTaskManager {
             .is_some_and(|h| h.get_status() == TaskStatus::Running)
     }
 
-    /// Mark a task as completed
-    pub async fn mark_completed(&self, session_id: &str) {
+    /// Claim the decision on a tool call the session's running task asked about,
+    /// so each ApprovalRequest is decided at most once
+    pub async fn take_approval(&self, session_id: &str, request_id: &str) -> ApprovalOutcome {
         let tasks = self.tasks.read().await;
-        if let Some(handle) = tasks.get(session_id) {
-            handle.set_status(TaskStatus::Completed);
-            handle.update_activity();
+        match tasks.get(session_id) {
+            Some(handle) if handle.get_status() == TaskStatus::Running => {
+                handle.broadcaster.take_approval(request_id)
+            }
+            _ => ApprovalOutcome::NotFound,
         }
     }
 
-    /// Mark a task as errored
-    pub async fn mark_error(&self, session_id: &str) {
+    /// The session's tracked task, if it is still the one with `task_id`
+    async fn current_task(&self, session_id: &str, task_id: u64) -> Option<Arc<TaskHandle>> {
         let tasks = self.tasks.read().await;
-        if let Some(handle) = tasks.get(session_id) {
-            handle.set_status(TaskStatus::Error);
-            handle.update_activity();
+        tasks
+            .get(session_id)
+            .filter(|handle| handle.broadcaster.task_id() == task_id)
+            .cloned()
+    }
+
+    /// Mark a task as completed. Does nothing once the task has ended, e.g. by
+    /// being cancelled, or once a newer task has taken over the session.
+    pub async fn mark_completed(&self, session_id: &str, task_id: u64) {
+        if let Some(handle) = self.current_task(session_id, task_id).await {
+            if handle.finish(TaskStatus::Completed) {
+                handle.update_activity();
+                self.record(session_id, &handle).await;
+            }
+        }
+    }
+
+    /// Mark a task as errored, keeping the error for status queries. Does nothing
+    /// once the task has ended or a newer task has taken over the session.
+    pub async fn mark_error(&self, session_id: &str, task_id: u64, error: impl Into<String>) {
+        if let Some(handle) = self.current_task(session_id, task_id).await {
+            if handle.finish(TaskStatus::Error) {
+                handle.set_last_error(error.into());
+                handle.update_activity();
+                self.record(session_id, &handle).await;
+            }
         }
     }
 
     /// Cancel a running task
     pub async fn cancel_task(&self, session_id: &str) -> bool {
-        let tasks = self.tasks.read().await;
-        if let Some(handle) = tasks.get(session_id) {
+        let handle = self.tasks.read().await.get(session_id).cloned();
+        if let Some(handle) = handle {
             handle.cancel_token.cancel();
             handle.set_status(TaskStatus::Cancelled);
+            self.record(session_id, &handle).await;
             true
         } else {
             false
@@ -183,27 +740,732 @@ impl BackgroundTaskManager {
         }
     }
 
-    /// Remove a completed/cancelled task from tracking
-    /// Should be called after task finishes and all subscribers have disconnected
-    pub async fn cleanup_task(&self, session_id: &str) {
-        let mut tasks = self.tasks.write().await;
-        if let Some(handle) = tasks.get(session_id) {
-            // Only cleanup if task is not running and has no subscribers
-            if handle.get_status() != TaskStatus::Running
-                && handle.broadcaster.receiver_count() == 0
-            {
+    /// Remove a completed/cancelled task from tracking, along with its saved record
+    /// Tasks that are still running or have subscribers are kept
+    pub async fn cleanup_task(&self, session_id: &str) -> CleanupOutcome {
+        {
+            let mut tasks = self.tasks.write().await;
+            if let Some(handle) = tasks.get(session_id) {
+                if !handle.is_finished_and_unwatched() {
+                    return CleanupOutcome::InUse;
+                }
                 tasks.remove(session_id);
             }
         }
+        if self.forget_record(session_id).await {
+            CleanupOutcome::Removed
+        } else {
+            CleanupOutcome::NotFound
+        }
     }
 
-    /// Get all running sessions
-    pub async fn running_sessions(&self) -> Vec<String> {
+    /// Drop a task's saved record, returning whether there was one
+    async fn forget_record(&self, session_id: &str) -> bool {
+        let _guard = self.store_lock.lock().await;
+        let records = {
+            let mut records = self.records.write().await;
+            if records.remove(session_id).is_none() {
+                return false;
+            }
+            records.clone()
+        };
+
+        if let Some(path) = &self.store_path {
+            if let Err(e) = write_records(path, &records).await {
+                tracing::warn!("Could not save {}: {}", path.display(), e);
+            }
+        }
+        true
+    }
+
+    /// Remove finished tasks without subscribers whose last activity is older than `ttl`
+    /// Returns the number of tasks removed
+    pub async fn reap_stale_tasks(&self, ttl: Duration) -> usize {
+        let cutoff = current_timestamp().saturating_sub(ttl.as_millis() as i64);
+        let mut tasks = self.tasks.write().await;
+        let before = tasks.len();
+        tasks.retain(|_, handle| {
+            !handle.is_finished_and_unwatched()
+                || handle.last_activity.load(Ordering::SeqCst) > cutoff
+        });
+        before - tasks.len()
+    }
+
+    /// Spawn a sweeper that calls `reap_stale_tasks` every `interval`
+    /// The sweeper stops once the manager is dropped
+    pub fn start_reaper(self: &Arc<Self>, interval: Duration, ttl: Duration) -> JoinHandle<()> {
+        let manager: Weak<Self> = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                let removed = manager.reap_stale_tasks(ttl).await;
+                if removed > 0 {
+                    tracing::debug!("Removed {} stale background tasks", removed);
+                }
+            }
+        })
+    }
+
+    /// Count tracked tasks grouped by status
+    pub async fn status_counts(&self) -> BTreeMap<String, usize> {
         let tasks = self.tasks.read().await;
-        tasks
-            .iter()
-            .filter(|(_, h)| h.get_status() == TaskStatus::Running)
-            .map(|(id, _)| id.clone())
-            .collect()
+        let mut counts = BTreeMap::new();
+        for handle in tasks.values() {
+            *counts
+                .entry(format!("{:?}", handle.get_status()))
+                .or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+async fn write_records(
+    path: &std::path::Path,
+    records: &HashMap<String, TaskRecord>,
+) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let temp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&temp_path, serde_json::to_vec_pretty(records)?).await?;
+    tokio::fs::rename(&temp_path, path).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_idempotency_key_replays_the_original_task() {
+        let manager = BackgroundTaskManager::new();
+        let broadcaster = manager.new_broadcaster();
+        assert!(manager
+            .claim_idempotency_key("session-1", "retry-me", &broadcaster, None)
+            .is_none());
+
+        // A retry while the task still waits to register joins it all the same
+        let retry = manager.new_broadcaster();
+        let mut queued = manager
+            .claim_idempotency_key("session-1", "retry-me", &retry, None)
+            .unwrap();
+        manager
+            .register_task(
+                "session-1".to_string(),
+                CancellationToken::new(),
+                broadcaster.clone(),
+            )
+            .await
+            .unwrap();
+        broadcaster.send(MessageEvent::Ping);
+        broadcaster.send(MessageEvent::Finish {
+            reason: "stop".to_string(),
+            token_state: Default::default(),
+        });
+        manager
+            .mark_completed("session-1", broadcaster.task_id())
+            .await;
+        assert!(matches!(
+            queued.recv().await.unwrap().event,
+            MessageEvent::Ping
+        ));
+
+        let mut subscription = manager
+            .claim_idempotency_key("session-1", "retry-me", &retry, None)
+            .unwrap();
+        let event = subscription.recv().await.unwrap();
+        assert_eq!(event.id, Some(2));
+        assert!(matches!(event.event, MessageEvent::Finish { .. }));
+
+        // Keys are scoped to their session
+        assert!(manager
+            .claim_idempotency_key("session-2", "retry-me", &retry, None)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_released_idempotency_key_can_be_claimed_again() {
+        let manager = BackgroundTaskManager::new();
+        let failed = manager.new_broadcaster();
+        let retry = manager.new_broadcaster();
+        assert!(manager
+            .claim_idempotency_key("session-1", "retry-me", &failed, None)
+            .is_none());
+
+        // Only the task holding the claim can release it
+        manager.release_idempotency_key("session-1", "retry-me", retry.task_id());
+        assert!(manager
+            .claim_idempotency_key("session-1", "retry-me", &retry, None)
+            .is_some());
+
+        manager.release_idempotency_key("session-1", "retry-me", failed.task_id());
+        assert!(manager
+            .claim_idempotency_key("session-1", "retry-me", &retry, None)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_expires() {
+        let manager = BackgroundTaskManager::new().idempotency_ttl(Duration::ZERO);
+        let broadcaster = manager.new_broadcaster();
+        assert!(manager
+            .claim_idempotency_key("session-1", "retry-me", &broadcaster, None)
+            .is_none());
+
+        let retry = manager.new_broadcaster();
+        assert!(manager
+            .claim_idempotency_key("session-1", "retry-me", &retry, None)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_error_message_is_reported_in_status() {
+        let manager = BackgroundTaskManager::new();
+        let task_id = manager
+            .register_task(
+                "session-1".to_string(),
+                CancellationToken::new(),
+                manager.new_broadcaster(),
+            )
+            .await
+            .unwrap()
+            .task_id();
+
+        let status = manager.get_status("session-1").await.unwrap();
+        assert_eq!(status.last_error, None);
+
+        manager
+            .mark_error("session-1", task_id, "Provider returned 429")
+            .await;
+
+        let status = manager.get_status("session-1").await.unwrap();
+        assert_eq!(status.status, "Error");
+        assert_eq!(status.last_error.as_deref(), Some("Provider returned 429"));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_task_stays_cancelled_when_it_finishes() {
+        let manager = BackgroundTaskManager::new();
+        let task_id = manager
+            .register_task(
+                "session-1".to_string(),
+                CancellationToken::new(),
+                manager.new_broadcaster(),
+            )
+            .await
+            .unwrap()
+            .task_id();
+
+        assert!(manager.cancel_task("session-1").await);
+        // The reply loop still reports how it ended once it notices the cancellation
+        manager.mark_completed("session-1", task_id).await;
+        manager
+            .mark_error("session-1", task_id, "Stream closed")
+            .await;
+
+        let status = manager.get_status("session-1").await.unwrap();
+        assert_eq!(status.status, "Cancelled");
+        assert_eq!(status.last_error, None);
+    }
+
+    #[tokio::test]
+    async fn test_list_statuses_filters_by_status() {
+        let manager = BackgroundTaskManager::new();
+        let mut task_ids = HashMap::new();
+        for id in ["session-b", "session-a", "session-c"] {
+            let broadcaster = manager
+                .register_task(
+                    id.to_string(),
+                    CancellationToken::new(),
+                    manager.new_broadcaster(),
+                )
+                .await
+                .unwrap();
+            task_ids.insert(id, broadcaster.task_id());
+        }
+        manager
+            .mark_completed("session-c", task_ids["session-c"])
+            .await;
+        let _subscription = manager.subscribe("session-a", None).await.unwrap();
+
+        let all = manager.list_statuses(None).await;
+        let ids: Vec<_> = all.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["session-a", "session-b", "session-c"]);
+        assert_eq!(all[0].subscriber_count, 1);
+        assert!(all[0].has_subscribers);
+        assert_eq!(all[1].subscriber_count, 0);
+
+        let status = "running".parse::<TaskStatus>().unwrap();
+        let running = manager.list_statuses(Some(status)).await;
+        let ids: Vec<_> = running.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["session-a", "session-b"]);
+
+        assert!("paused".parse::<TaskStatus>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reaper_removes_stale_finished_tasks() {
+        let manager = Arc::new(BackgroundTaskManager::new());
+        let mut task_ids = HashMap::new();
+        for id in ["finished", "watched", "running"] {
+            let broadcaster = manager
+                .register_task(
+                    id.to_string(),
+                    CancellationToken::new(),
+                    manager.new_broadcaster(),
+                )
+                .await
+                .unwrap();
+            task_ids.insert(id, broadcaster.task_id());
+        }
+        manager
+            .mark_completed("finished", task_ids["finished"])
+            .await;
+        manager.mark_completed("watched", task_ids["watched"]).await;
+        let subscription = manager.subscribe("watched", None).await.unwrap();
+
+        let reaper = manager.start_reaper(Duration::from_millis(10), Duration::ZERO);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let tasks = manager.tasks.read().await;
+        assert!(!tasks.contains_key("finished"));
+        assert!(tasks.contains_key("watched"));
+        assert!(tasks.contains_key("running"));
+        drop(tasks);
+        // The finished task's record outlives it
+        let status = manager.get_status("finished").await.unwrap();
+        assert_eq!(status.status, "Completed");
+
+        // Dropping the manager stops the reaper
+        drop(subscription);
+        drop(manager);
+        tokio::time::timeout(Duration::from_secs(1), reaper)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_task_removes_only_finished_unwatched_tasks() {
+        let manager = BackgroundTaskManager::new();
+        let task_id = manager
+            .register_task(
+                "session-1".to_string(),
+                CancellationToken::new(),
+                manager.new_broadcaster(),
+            )
+            .await
+            .unwrap()
+            .task_id();
+        assert_eq!(
+            manager.cleanup_task("session-1").await,
+            CleanupOutcome::InUse
+        );
+
+        manager.mark_completed("session-1", task_id).await;
+        let subscription = manager.subscribe("session-1", None).await.unwrap();
+        assert_eq!(
+            manager.cleanup_task("session-1").await,
+            CleanupOutcome::InUse
+        );
+
+        drop(subscription);
+        assert_eq!(
+            manager.cleanup_task("session-1").await,
+            CleanupOutcome::Removed
+        );
+        assert!(manager.get_status("session-1").await.is_none());
+        assert_eq!(
+            manager.cleanup_task("session-1").await,
+            CleanupOutcome::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reap_keeps_recently_active_tasks() {
+        let manager = BackgroundTaskManager::new();
+        let task_id = manager
+            .register_task(
+                "session-1".to_string(),
+                CancellationToken::new(),
+                manager.new_broadcaster(),
+            )
+            .await
+            .unwrap()
+            .task_id();
+        manager.mark_completed("session-1", task_id).await;
+
+        assert_eq!(manager.reap_stale_tasks(DEFAULT_TASK_TTL).await, 0);
+        assert_eq!(manager.reap_stale_tasks(Duration::ZERO).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_task_records_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TASK_STATE_FILE);
+
+        let manager = BackgroundTaskManager::load_or_default(path.clone());
+        let mut task_ids = HashMap::new();
+        for id in ["completed", "errored", "running"] {
+            let broadcaster = manager
+                .register_task(
+                    id.to_string(),
+                    CancellationToken::new(),
+                    manager.new_broadcaster(),
+                )
+                .await
+                .unwrap();
+            task_ids.insert(id, broadcaster.task_id());
+        }
+        manager
+            .mark_completed("completed", task_ids["completed"])
+            .await;
+        manager
+            .mark_error("errored", task_ids["errored"], "Provider returned 429")
+            .await;
+        drop(manager);
+
+        let restarted = BackgroundTaskManager::load_or_default(path.clone());
+        let status = restarted.get_status("completed").await.unwrap();
+        assert_eq!(status.status, "Completed");
+        assert_eq!(status.subscriber_count, 0);
+        let status = restarted.get_status("errored").await.unwrap();
+        assert_eq!(status.last_error.as_deref(), Some("Provider returned 429"));
+        let status = restarted.get_status("running").await.unwrap();
+        assert_eq!(status.status, "Error");
+        assert_eq!(status.last_error.as_deref(), Some(INTERRUPTED_ERROR));
+        assert!(restarted.subscribe("running", None).await.is_none());
+        assert!(!restarted.is_running("running").await);
+        assert_eq!(restarted.list_statuses(None).await.len(), 3);
+
+        std::fs::write(&path, "{not json").unwrap();
+        let recovered = BackgroundTaskManager::load_or_default(path);
+        assert!(recovered.get_status("completed").await.is_none());
+    }
+
+    fn error_event(error: &str) -> MessageEvent {
+        MessageEvent::Error {
+            error: error.to_string(),
+            error_code: ErrorCode::AgentError,
+        }
+    }
+
+    fn error_text(event: Option<SequencedEvent>) -> String {
+        match event.map(|e| e.event) {
+            Some(MessageEvent::Error { error, .. }) => error,
+            other => panic!("Expected an Error event, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_late_subscriber_replays_recent_events() {
+        let manager = BackgroundTaskManager::new();
+        let broadcaster = manager
+            .register_task(
+                "session-1".to_string(),
+                CancellationToken::new(),
+                manager.new_broadcaster(),
+            )
+            .await
+            .unwrap();
+
+        for i in 0..REPLAY_LIMIT + 5 {
+            broadcaster.send(error_event(&format!("event {}", i)));
+        }
+        broadcaster.send(MessageEvent::Ping);
+
+        let mut subscription = manager.subscribe("session-1", None).await.unwrap();
+        // The oldest events fell out of the buffer and pings were never kept
+        assert_eq!(error_text(subscription.recv().await), "event 5");
+        for _ in 1..REPLAY_LIMIT {
+            subscription.recv().await;
+        }
+
+        broadcaster.send(error_event("live"));
+        assert_eq!(error_text(subscription.recv().await), "live");
+    }
+
+    #[tokio::test]
+    async fn test_lagging_subscriber_is_told_about_dropped_events() {
+        let manager = BackgroundTaskManager::new();
+        let broadcaster = manager
+            .register_task(
+                "session-1".to_string(),
+                CancellationToken::new(),
+                manager.new_broadcaster(),
+            )
+            .await
+            .unwrap();
+        let mut subscription = manager.subscribe("session-1", None).await.unwrap();
+
+        for i in 0..150 {
+            broadcaster.send(error_event(&format!("event {}", i)));
+        }
+
+        // The channel keeps the newest events; the older ones are reported as dropped
+        let notice = subscription.recv().await;
+        assert_eq!(notice.as_ref().unwrap().id, None);
+        let notice = error_text(notice);
+        let (dropped, rest) = notice.split_once(' ').unwrap();
+        assert_eq!(
+            rest,
+            "events were dropped while this subscriber fell behind; refetch the conversation"
+        );
+        assert!(dropped.parse::<usize>().unwrap() > 0);
+        assert_eq!(
+            error_text(subscription.recv().await),
+            format!("event {}", dropped)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_larger_channel_keeps_slow_subscribers_in_sync() {
+        let manager = BackgroundTaskManager::new().channel_capacity(500);
+        let broadcaster = manager
+            .register_task(
+                "session-1".to_string(),
+                CancellationToken::new(),
+                manager.new_broadcaster(),
+            )
+            .await
+            .unwrap();
+        let mut subscription = manager.subscribe("session-1", None).await.unwrap();
+
+        for i in 0..300 {
+            broadcaster.send(error_event(&format!("event {}", i)));
+        }
+
+        for i in 0..300 {
+            assert_eq!(
+                error_text(subscription.recv().await),
+                format!("event {}", i)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_after_last_event_id() {
+        let manager = BackgroundTaskManager::new();
+        let broadcaster = manager
+            .register_task(
+                "session-1".to_string(),
+                CancellationToken::new(),
+                manager.new_broadcaster(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(broadcaster.send(error_event("first")), 1);
+        assert_eq!(broadcaster.send(MessageEvent::Ping), 2);
+        assert_eq!(broadcaster.send(error_event("second")), 3);
+        assert_eq!(broadcaster.send(error_event("third")), 4);
+
+        let mut subscription = manager.subscribe("session-1", Some(2)).await.unwrap();
+        let resumed = subscription.recv().await.unwrap();
+        assert_eq!(resumed.id, Some(3));
+        assert_eq!(error_text(Some(resumed)), "second");
+        assert_eq!(error_text(subscription.recv().await), "third");
+
+        broadcaster.send(error_event("live"));
+        let live = subscription.recv().await.unwrap();
+        assert_eq!(live.id, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_task_policies() {
+        let manager = BackgroundTaskManager::new();
+        let first = CancellationToken::new();
+        let first_id = manager
+            .register_task(
+                "session-1".to_string(),
+                first.clone(),
+                manager.new_broadcaster(),
+            )
+            .await
+            .unwrap()
+            .task_id();
+        assert_eq!(
+            manager
+                .register_task(
+                    "session-1".to_string(),
+                    CancellationToken::new(),
+                    manager.new_broadcaster()
+                )
+                .await
+                .err(),
+            Some(SessionBusy)
+        );
+        assert!(!first.is_cancelled());
+
+        manager.mark_completed("session-1", first_id).await;
+        assert!(manager
+            .register_task(
+                "session-1".to_string(),
+                CancellationToken::new(),
+                manager.new_broadcaster()
+            )
+            .await
+            .is_ok());
+
+        let manager =
+            BackgroundTaskManager::new().concurrent_task_policy(ConcurrentTaskPolicy::Replace);
+        let first = CancellationToken::new();
+        manager
+            .register_task(
+                "session-1".to_string(),
+                first.clone(),
+                manager.new_broadcaster(),
+            )
+            .await
+            .unwrap();
+        assert!(manager
+            .register_task(
+                "session-1".to_string(),
+                CancellationToken::new(),
+                manager.new_broadcaster()
+            )
+            .await
+            .is_ok());
+        assert!(first.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_queued_task_starts_after_running_one_finishes() {
+        let manager = Arc::new(
+            BackgroundTaskManager::new().concurrent_task_policy(ConcurrentTaskPolicy::Queue),
+        );
+        let first = CancellationToken::new();
+        let first_id = manager
+            .register_task(
+                "session-1".to_string(),
+                first.clone(),
+                manager.new_broadcaster(),
+            )
+            .await
+            .unwrap()
+            .task_id();
+
+        let queued = tokio::spawn({
+            let manager = manager.clone();
+            async move {
+                manager
+                    .register_task_when_idle(
+                        "session-1".to_string(),
+                        CancellationToken::new(),
+                        manager.new_broadcaster(),
+                    )
+                    .await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!queued.is_finished());
+
+        manager.mark_completed("session-1", first_id).await;
+        let broadcaster = tokio::time::timeout(Duration::from_secs(1), queued)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!first.is_cancelled());
+        assert!(manager.is_running("session-1").await);
+
+        broadcaster.send(error_event("second task"));
+        let mut subscription = manager.subscribe("session-1", None).await.unwrap();
+        assert_eq!(error_text(subscription.recv().await), "second task");
+    }
+
+    #[tokio::test]
+    async fn test_finishing_a_superseded_task_leaves_its_successor_running() {
+        let manager = Arc::new(
+            BackgroundTaskManager::new().concurrent_task_policy(ConcurrentTaskPolicy::Queue),
+        );
+        let first_id = manager
+            .register_task(
+                "session-1".to_string(),
+                CancellationToken::new(),
+                manager.new_broadcaster(),
+            )
+            .await
+            .unwrap()
+            .task_id();
+        let queued = tokio::spawn({
+            let manager = manager.clone();
+            async move {
+                manager
+                    .register_task_when_idle(
+                        "session-1".to_string(),
+                        CancellationToken::new(),
+                        manager.new_broadcaster(),
+                    )
+                    .await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Cancelling lets the queued task in before the first one has wound down
+        assert!(manager.cancel_task("session-1").await);
+        let second = tokio::time::timeout(Duration::from_secs(1), queued)
+            .await
+            .unwrap()
+            .unwrap();
+        manager
+            .mark_error("session-1", first_id, "Task was cancelled")
+            .await;
+        assert!(manager.is_running("session-1").await);
+        assert_eq!(
+            manager.get_status("session-1").await.unwrap().last_error,
+            None
+        );
+
+        manager.mark_completed("session-1", second.task_id()).await;
+        assert!(!manager.is_running("session-1").await);
+    }
+
+    #[tokio::test]
+    async fn test_each_approval_request_is_decided_once() {
+        let manager = BackgroundTaskManager::new();
+        let broadcaster = manager
+            .register_task(
+                "session-1".to_string(),
+                CancellationToken::new(),
+                manager.new_broadcaster(),
+            )
+            .await
+            .unwrap();
+        broadcaster.send(MessageEvent::ApprovalRequest {
+            request_id: "req-1".to_string(),
+            tool_name: "developer__shell".to_string(),
+            arguments: Default::default(),
+        });
+
+        assert_eq!(
+            manager.take_approval("session-1", "req-1").await,
+            ApprovalOutcome::Taken
+        );
+        assert_eq!(
+            manager.take_approval("session-1", "req-1").await,
+            ApprovalOutcome::AlreadyDecided
+        );
+        assert_eq!(
+            manager.take_approval("session-1", "req-2").await,
+            ApprovalOutcome::NotFound
+        );
+
+        broadcaster.send(MessageEvent::ApprovalRequest {
+            request_id: "req-2".to_string(),
+            tool_name: "developer__shell".to_string(),
+            arguments: Default::default(),
+        });
+        manager
+            .mark_completed("session-1", broadcaster.task_id())
+            .await;
+        assert_eq!(
+            manager.take_approval("session-1", "req-2").await,
+            ApprovalOutcome::NotFound
+        );
     }
 }
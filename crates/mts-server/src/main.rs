@@ -62,7 +62,35 @@ async fn main() -> anyhow::Result<()> {
                 McpCommand::Browser => {
                     let server_url = std::env::var("MTS_SERVER_URL")
                         .unwrap_or_else(|_| "http://localhost:3000".to_string());
-                    serve(BrowserServer::new(server_url)).await?
+                    let custom_instructions =
+                        std::env::var(mts::agents::extension::EXTENSION_INSTRUCTIONS_ENV).ok();
+                    let max_connections = std::env::var("MTS_BROWSER_MAX_CONNECTIONS")
+                        .ok()
+                        .and_then(|v| v.parse().ok());
+                    let timeout_overrides = std::env::var("MTS_BROWSER_TIMEOUTS").ok();
+                    let max_script_bytes = std::env::var("MTS_BROWSER_MAX_SCRIPT_BYTES")
+                        .ok()
+                        .and_then(|v| v.parse().ok());
+                    let connect_attempts = std::env::var("MTS_BROWSER_CONNECT_ATTEMPTS")
+                        .ok()
+                        .and_then(|v| v.parse().ok());
+                    let connect_backoff_ms = std::env::var("MTS_BROWSER_CONNECT_BACKOFF_MS")
+                        .ok()
+                        .and_then(|v| v.parse().ok());
+                    let keepalive_ms = std::env::var("MTS_BROWSER_KEEPALIVE_MS")
+                        .ok()
+                        .and_then(|v| v.parse().ok());
+                    serve(
+                        BrowserServer::new(server_url)
+                            .custom_instructions(custom_instructions)
+                            .max_connections(max_connections)
+                            .timeout_overrides(timeout_overrides)
+                            .max_script_bytes(max_script_bytes)
+                            .connect_attempts(connect_attempts)
+                            .connect_backoff_ms(connect_backoff_ms)
+                            .keepalive_interval_ms(keepalive_ms),
+                    )
+                    .await?
                 }
             }
         }
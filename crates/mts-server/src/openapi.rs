@@ -25,7 +25,7 @@ use mts::conversation::message::{
 };
 
 use crate::routes::recipe_utils::RecipeManifest;
-use crate::routes::reply::MessageEvent;
+use crate::routes::reply::{ErrorCode, MessageEvent};
 use utoipa::openapi::schema::{
     AdditionalProperties, AnyOfBuilder, ArrayBuilder, ObjectBuilder, OneOfBuilder, Schema,
     SchemaFormat, SchemaType,
@@ -328,6 +328,9 @@ derive_utoipa!(Icon as IconSchema);
     paths(
         super::routes::status::status,
         super::routes::status::diagnostics,
+        super::routes::health::healthz,
+        super::routes::health::readyz,
+        super::routes::metrics::metrics,
         super::routes::mcp_ui_proxy::mcp_ui_proxy,
         super::routes::config_management::backup_config,
         super::routes::config_management::detect_provider,
@@ -365,15 +368,20 @@ derive_utoipa!(Icon as IconSchema);
         super::routes::agent::update_router_tool_selector,
         super::routes::action_required::confirm_tool_action,
         super::routes::reply::reply,
+        super::routes::reply::approve_tool_call,
+        super::routes::reply::list_running_tasks,
         super::routes::session::list_sessions,
         super::routes::session::get_session,
         super::routes::session::get_session_insights,
         super::routes::session::update_session_name,
         super::routes::session::delete_session,
         super::routes::session::export_session,
+        super::routes::session::get_session_conversation,
         super::routes::session::import_session,
         super::routes::session::update_session_user_recipe_values,
         super::routes::session::edit_message,
+        super::routes::session::get_session_debug,
+        super::routes::session::update_session_debug,
         super::routes::schedule::create_schedule,
         super::routes::schedule::list_schedules,
         super::routes::schedule::delete_schedule,
@@ -400,7 +408,9 @@ derive_utoipa!(Icon as IconSchema);
         super::routes::tunnel::stop_tunnel,
         super::routes::tunnel::get_tunnel_status,
         super::routes::search::search_files,
+        super::routes::search::search_files_stream,
         super::routes::search::search_filenames,
+        super::routes::search::search_symbols,
         super::routes::search::replace_in_files,
     ),
     components(schemas(
@@ -427,7 +437,13 @@ derive_utoipa!(Icon as IconSchema);
         super::routes::config_management::GenerateDiagramResponse,
         super::routes::action_required::ConfirmToolActionRequest,
         super::routes::reply::ChatRequest,
+        super::routes::reply::ApprovalDecision,
+        super::background_tasks::TaskStatusResponse,
         super::routes::session::ImportSessionRequest,
+        super::routes::session::ConversationFormat,
+        super::routes::session::UpdateSessionDebugRequest,
+        super::routes::session::SessionDebugResponse,
+        mts::providers::debug_capture::ProviderExchange,
         super::routes::session::SessionListResponse,
         super::routes::session::UpdateSessionNameRequest,
         super::routes::session::UpdateSessionUserRecipeValuesRequest,
@@ -461,6 +477,7 @@ derive_utoipa!(Icon as IconSchema);
         SystemNotificationType,
         SystemNotificationContent,
         MessageEvent,
+        ErrorCode,
         JsonObjectSchema,
         RoleSchema,
         ProviderMetadata,
@@ -522,6 +539,9 @@ derive_utoipa!(Icon as IconSchema);
         mts::recipe::Response,
         mts::recipe::SubRecipe,
         mts::agents::types::RetryConfig,
+        mts::agents::types::FallbackProvider,
+        mts::context_mgmt::CompactionPolicy,
+        mts::config::MtsMode,
         mts::agents::types::SuccessCheck,
         super::routes::agent::UpdateProviderRequest,
         super::routes::agent::GetToolsQuery,
@@ -541,11 +561,24 @@ derive_utoipa!(Icon as IconSchema);
         super::routes::search::SearchFilesRequest,
         super::routes::search::SearchFilesResponse,
         super::routes::search::SearchMatch,
+        super::routes::search::FileSearchStats,
+        super::routes::search::FileMatchGroup,
+        super::routes::search::SearchSummary,
+        super::routes::search::SearchStreamEvent,
         super::routes::search::SearchFilenamesRequest,
         super::routes::search::SearchFilenamesResponse,
         super::routes::search::FilenameMatch,
+        super::routes::search::SearchSymbolsRequest,
+        super::routes::search::SearchSymbolsResponse,
+        super::routes::search::SymbolMatch,
+        super::routes::search::SymbolKind,
         super::routes::search::ReplaceRequest,
         super::routes::search::ReplaceResponse,
+        super::routes::search::FileDiff,
+        super::routes::metrics::MetricsResponse,
+        super::routes::health::HealthStatus,
+        super::routes::health::SubsystemHealth,
+        super::routes::health::HealthResponse,
     ))
 )]
 pub struct ApiDoc;
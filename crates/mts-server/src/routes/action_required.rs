@@ -76,7 +76,7 @@ mod tests {
 
         #[tokio::test(flavor = "multi_thread")]
         async fn test_tool_confirmation_endpoint() {
-            let state = AppState::new().await.unwrap();
+            let state = AppState::for_tests().await;
 
             let app = routes(state);
 
@@ -589,6 +589,8 @@ async fn stop_agent(
             status: StatusCode::NOT_FOUND,
         })?;
 
+    state.browser_manager.clear_history(&session_id).await;
+
     Ok(StatusCode::OK)
 }
 
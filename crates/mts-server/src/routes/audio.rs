@@ -395,7 +395,7 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_transcribe_endpoint_requires_auth() {
-        let state = AppState::new().await.unwrap();
+        let state = AppState::for_tests().await;
         let app = routes(state);
         // Test without auth header
         let request = Request::builder()
@@ -420,7 +420,7 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_transcribe_endpoint_validates_size() {
-        let state = AppState::new().await.unwrap();
+        let state = AppState::for_tests().await;
         let app = routes(state);
 
         let request = Request::builder()
@@ -446,7 +446,7 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_transcribe_endpoint_validates_mime_type() {
-        let state = AppState::new().await.unwrap();
+        let state = AppState::for_tests().await;
         let app = routes(state);
 
         let request = Request::builder()
@@ -2,53 +2,239 @@ use crate::state::AppState;
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        Path, Query, State,
     },
+    http::{header, StatusCode},
     response::IntoResponse,
-    routing::get,
-    Router,
+    routing::{delete, get},
+    Json, Router,
 };
 use futures::{sink::SinkExt, stream::StreamExt};
+use mts::config::Config;
+use mts::session::SessionManager;
+use mts_mcp::browser::artifacts::{
+    artifact_mime_type, artifact_path, artifacts_dir, save_artifact,
+};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{BTreeMap, HashMap, VecDeque},
+    path::PathBuf,
     sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{mpsc, Mutex, RwLock};
 use tracing::{error, info, warn};
 
 // =============================================================================
 // Message Protocol Types
 // =============================================================================
 
-/// Commands sent from frontend to backend to control the browser
+/// Commands sent from frontend to backend to control the browser.
+///
+/// Each command runs in the tab named by its `tab_id`, or in the active tab when
+/// it has none, so clients unaware of tabs keep working.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum BrowserCommand {
     Navigate {
         url: String,
+        /// Agent session that issued the navigation, when known
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
         command_id: String,
     },
     Click {
         selector: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
         command_id: String,
     },
     Type {
         selector: String,
         text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
         command_id: String,
     },
     ExtractDOM {
         selector: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
         command_id: String,
     },
+    /// Elements matching `hide_selectors` get `display: none` and `css` is injected
+    /// as a stylesheet while capturing; both are restored afterwards. With a
+    /// `selector`, only that element's bounding box is captured; if nothing matches,
+    /// the full page is captured and the successful result carries ElementNotFound.
+    /// The image is encoded as `format`, at `quality` (1-100) for jpeg and webp.
     Screenshot {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        selector: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        hide_selectors: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        css: Option<String>,
+        #[serde(default)]
+        format: ScreenshotFormat,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        quality: Option<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
         command_id: String,
     },
     ExecuteScript {
         script: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Resolves once an element matches `selector` (immediately if one already does),
+    /// or fails after `timeout_ms`; the frontend polls until then
+    WaitForSelector {
+        selector: String,
+        timeout_ms: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Resolves once no element matches `selector` (immediately if already absent),
+    /// or fails after `timeout_ms`
+    WaitForAbsence {
+        selector: String,
+        timeout_ms: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Scrolls to the bottom up to `max_iterations` times, waiting up to `wait_ms`
+    /// after each scroll for more `item_selector` matches and stopping once a scroll
+    /// adds none. Reports JSON `{"items", "iterations", "exhausted"}` with the
+    /// outerHTML of every distinct item seen, in page order.
+    ScrollAndCollect {
+        item_selector: String,
+        max_iterations: u32,
+        wait_ms: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Reports the computed values of `properties` for the first element matching
+    /// `selector` as a JSON object, or fails with ElementNotFound
+    GetComputedStyle {
+        selector: String,
+        properties: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Reports the values of `attributes` for the first element matching `selector`
+    /// as a JSON object, with null for absent attributes, or fails with ElementNotFound
+    GetAttributes {
+        selector: String,
+        attributes: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Drags the first match of `source_selector` onto the first match of
+    /// `target_selector` with the HTML5 sequence dragstart, dragover, drop, dragend.
+    /// Fails with ElementNotFound if either selector matches nothing.
+    DragAndDrop {
+        source_selector: String,
+        target_selector: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Chooses the option of the first `<select>` matching `selector` whose value
+    /// equals `value`, or whose visible text does when `by_label` is set, then
+    /// dispatches `input` and `change` so framework-bound forms react. Reports the
+    /// selected option's value as data; fails with ElementNotFound if the selector
+    /// or the option matches nothing.
+    SelectOption {
+        selector: String,
+        value: String,
+        #[serde(default)]
+        by_label: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Resizes the tab's viewport to `width` x `height` CSS pixels, so layout and
+    /// screenshots follow responsive breakpoints. Reports the applied size as JSON
+    /// `{"width": .., "height": ..}`, which may differ if the window can't shrink
+    /// that far. Doesn't need a page to be loaded.
+    SetViewport {
+        width: u32,
+        height: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Answered by the server with the last `limit` history entries as JSON
+    GetHistory {
+        limit: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Moves back in the page history and reports the resulting URL as data.
+    /// Like `GoForward` and `Reload` it only revisits pages that were already
+    /// loaded, so it skips the allowlist and consent checks.
+    GoBack {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Moves forward in the page history and reports the resulting URL as data
+    GoForward {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Reloads the current page and reports its URL as data
+    Reload {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Opens a blank tab with id `tab_id` and makes it the active tab. Answered
+    /// with an error here if the id is missing or already open.
+    OpenTab {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Closes `tab_id`, or the active tab when absent. If the active tab closes,
+    /// another open tab becomes active; the last tab can't be closed.
+    CloseTab {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Reports the page's title, description, canonical URL and OpenGraph and
+    /// Twitter card fields as JSON
+    PageInfo {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
+        command_id: String,
+    },
+    /// Reports the tab's buffered console output and uncaught errors logged after
+    /// `since` (Unix milliseconds) as a JSON list of `{level, message, timestamp}`.
+    /// The backend keeps the most recent 500 entries per tab, across navigations,
+    /// and drops them when the tab closes.
+    GetConsoleLogs {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        since: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
         command_id: String,
     },
+    /// Downloads `url` to disk. The server fetches the file itself rather than
+    /// forwarding the command, and reports a [`DownloadedFile`] as JSON.
+    Download { url: String, command_id: String },
 }
 
 impl BrowserCommand {
@@ -58,8 +244,25 @@ impl BrowserCommand {
             BrowserCommand::Click { command_id, .. } => command_id,
             BrowserCommand::Type { command_id, .. } => command_id,
             BrowserCommand::ExtractDOM { command_id, .. } => command_id,
-            BrowserCommand::Screenshot { command_id } => command_id,
+            BrowserCommand::Screenshot { command_id, .. } => command_id,
             BrowserCommand::ExecuteScript { command_id, .. } => command_id,
+            BrowserCommand::WaitForSelector { command_id, .. } => command_id,
+            BrowserCommand::WaitForAbsence { command_id, .. } => command_id,
+            BrowserCommand::ScrollAndCollect { command_id, .. } => command_id,
+            BrowserCommand::GetComputedStyle { command_id, .. } => command_id,
+            BrowserCommand::GetAttributes { command_id, .. } => command_id,
+            BrowserCommand::DragAndDrop { command_id, .. } => command_id,
+            BrowserCommand::SelectOption { command_id, .. } => command_id,
+            BrowserCommand::SetViewport { command_id, .. } => command_id,
+            BrowserCommand::GetHistory { command_id, .. } => command_id,
+            BrowserCommand::GoBack { command_id, .. } => command_id,
+            BrowserCommand::GoForward { command_id, .. } => command_id,
+            BrowserCommand::Reload { command_id, .. } => command_id,
+            BrowserCommand::OpenTab { command_id, .. } => command_id,
+            BrowserCommand::CloseTab { command_id, .. } => command_id,
+            BrowserCommand::PageInfo { command_id, .. } => command_id,
+            BrowserCommand::GetConsoleLogs { command_id, .. } => command_id,
+            BrowserCommand::Download { command_id, .. } => command_id,
         }
     }
 
@@ -71,26 +274,125 @@ impl BrowserCommand {
             BrowserCommand::ExtractDOM { .. } => "extract_dom",
             BrowserCommand::Screenshot { .. } => "screenshot",
             BrowserCommand::ExecuteScript { .. } => "execute_script",
+            BrowserCommand::WaitForSelector { .. } => "wait_for_selector",
+            BrowserCommand::WaitForAbsence { .. } => "wait_for_absence",
+            BrowserCommand::ScrollAndCollect { .. } => "scroll_and_collect",
+            BrowserCommand::GetComputedStyle { .. } => "get_computed_style",
+            BrowserCommand::GetAttributes { .. } => "get_attributes",
+            BrowserCommand::DragAndDrop { .. } => "drag_and_drop",
+            BrowserCommand::SelectOption { .. } => "select_option",
+            BrowserCommand::SetViewport { .. } => "set_viewport",
+            BrowserCommand::GetHistory { .. } => "get_history",
+            BrowserCommand::GoBack { .. } => "go_back",
+            BrowserCommand::GoForward { .. } => "go_forward",
+            BrowserCommand::Reload { .. } => "reload",
+            BrowserCommand::OpenTab { .. } => "open_tab",
+            BrowserCommand::CloseTab { .. } => "close_tab",
+            BrowserCommand::PageInfo { .. } => "page_info",
+            BrowserCommand::GetConsoleLogs { .. } => "get_console_logs",
+            BrowserCommand::Download { .. } => "download",
+        }
+    }
+
+    /// Tab the command targets; None means the active tab
+    pub fn tab_id(&self) -> Option<&str> {
+        match self {
+            BrowserCommand::Navigate { tab_id, .. }
+            | BrowserCommand::Click { tab_id, .. }
+            | BrowserCommand::Type { tab_id, .. }
+            | BrowserCommand::ExtractDOM { tab_id, .. }
+            | BrowserCommand::Screenshot { tab_id, .. }
+            | BrowserCommand::ExecuteScript { tab_id, .. }
+            | BrowserCommand::WaitForSelector { tab_id, .. }
+            | BrowserCommand::WaitForAbsence { tab_id, .. }
+            | BrowserCommand::ScrollAndCollect { tab_id, .. }
+            | BrowserCommand::GetComputedStyle { tab_id, .. }
+            | BrowserCommand::GetAttributes { tab_id, .. }
+            | BrowserCommand::DragAndDrop { tab_id, .. }
+            | BrowserCommand::SelectOption { tab_id, .. }
+            | BrowserCommand::SetViewport { tab_id, .. }
+            | BrowserCommand::GetHistory { tab_id, .. }
+            | BrowserCommand::GoBack { tab_id, .. }
+            | BrowserCommand::GoForward { tab_id, .. }
+            | BrowserCommand::Reload { tab_id, .. }
+            | BrowserCommand::OpenTab { tab_id, .. }
+            | BrowserCommand::CloseTab { tab_id, .. }
+            | BrowserCommand::PageInfo { tab_id, .. }
+            | BrowserCommand::GetConsoleLogs { tab_id, .. } => tab_id.as_deref(),
+            BrowserCommand::Download { .. } => None,
         }
     }
 
     pub fn url(&self) -> Option<&str> {
         match self {
             BrowserCommand::Navigate { url, .. } => Some(url),
+            BrowserCommand::Download { url, .. } => Some(url),
+            _ => None,
+        }
+    }
+
+    pub fn session_id(&self) -> Option<&str> {
+        match self {
+            BrowserCommand::Navigate { session_id, .. } => session_id.as_deref(),
             _ => None,
         }
     }
 
+    /// Whether the command operates on an already loaded page
+    pub fn requires_page(&self) -> bool {
+        !matches!(
+            self,
+            BrowserCommand::Navigate { .. }
+                | BrowserCommand::GetHistory { .. }
+                | BrowserCommand::OpenTab { .. }
+                | BrowserCommand::CloseTab { .. }
+                | BrowserCommand::SetViewport { .. }
+                | BrowserCommand::Download { .. }
+        )
+    }
+
     pub fn selector(&self) -> Option<&str> {
         match self {
             BrowserCommand::Click { selector, .. } => Some(selector),
             BrowserCommand::Type { selector, .. } => Some(selector),
             BrowserCommand::ExtractDOM { selector, .. } => selector.as_deref(),
+            BrowserCommand::Screenshot { selector, .. } => selector.as_deref(),
+            BrowserCommand::WaitForSelector { selector, .. } => Some(selector),
+            BrowserCommand::WaitForAbsence { selector, .. } => Some(selector),
+            BrowserCommand::ScrollAndCollect { item_selector, .. } => Some(item_selector),
+            BrowserCommand::GetComputedStyle { selector, .. } => Some(selector),
+            BrowserCommand::GetAttributes { selector, .. } => Some(selector),
+            BrowserCommand::DragAndDrop {
+                source_selector, ..
+            } => Some(source_selector),
+            BrowserCommand::SelectOption { selector, .. } => Some(selector),
             _ => None,
         }
     }
 }
 
+/// Image encoding requested by a Screenshot command
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScreenshotFormat {
+    #[default]
+    Png,
+    Jpeg,
+    Webp,
+}
+
+/// Machine-readable reason attached to a failed command result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BrowserErrorCode {
+    /// The command needs a page but nothing has been navigated to yet
+    NoPageLoaded,
+    /// No element matches the command's selector. Also set on a successful
+    /// Screenshot that fell back to the full page.
+    ElementNotFound,
+    /// The session navigated more often than the navigation rate limit allows
+    RateLimited,
+}
+
 /// Events sent from backend to frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -100,12 +402,19 @@ pub enum BrowserEvent {
         success: bool,
         data: Option<String>,
         error: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_code: Option<BrowserErrorCode>,
     },
+    /// `tab_id` is absent for the active tab
     NavigationComplete {
         url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
     },
     LoadingStateChanged {
         loading: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tab_id: Option<String>,
     },
     ConsentRequired {
         allowed_domains: Vec<String>,
@@ -113,29 +422,276 @@ pub enum BrowserEvent {
     Error {
         message: String,
     },
+    /// Asks the frontend to run a command; it answers with a CommandResult
+    /// carrying the same `command_id`
+    ExecuteCommand {
+        command: BrowserCommand,
+    },
+}
+
+/// Which side of the browser protocol a WebSocket connection is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionRole {
+    /// The app hosting the browser: runs commands and reports events
+    Frontend,
+    /// A command issuer such as the browser MCP server
+    #[default]
+    Client,
+}
+
+/// Query parameters for GET /ws/browser
+#[derive(Debug, Default, Deserialize)]
+pub struct BrowserSocketParams {
+    #[serde(default)]
+    pub role: ConnectionRole,
 }
 
 // =============================================================================
 // Browser Session Manager
 // =============================================================================
 
+/// How much an allowlisted domain is trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrustLevel {
+    /// Navigation is allowed without asking the user
+    AutoAllow,
+    /// Navigation is allowed once the user has consented to browser automation
+    RequireConsent,
+}
+
+/// URL reported before the browser has navigated anywhere
+const BLANK_PAGE_URL: &str = "about:blank";
+
+/// Tab that exists from the start and is active until another tab is opened
+pub const DEFAULT_TAB_ID: &str = "main";
+
+/// What the server knows about one browser tab
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabState {
+    pub current_url: String,
+    pub is_loading: bool,
+}
+
+impl Default for TabState {
+    fn default() -> Self {
+        Self {
+            current_url: BLANK_PAGE_URL.to_string(),
+            is_loading: false,
+        }
+    }
+}
+
+/// Default number of commands kept in the browser history
+pub const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// Default number of commands kept in the browser audit log
+pub const DEFAULT_AUDIT_LIMIT: usize = 500;
+
+/// Default number of commands held while the browser is disconnected or unconsented
+pub const DEFAULT_MAX_QUEUE_LEN: usize = 100;
+
+/// Default time consent lasts without being relied on
+pub const DEFAULT_CONSENT_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Default number of navigations each session may make per minute
+pub const DEFAULT_NAVIGATIONS_PER_MINUTE: u32 = 30;
+
+/// Default largest file a Download command will save
+pub const DEFAULT_MAX_DOWNLOAD_BYTES: u64 = 50 * 1024 * 1024;
+
+/// How long a download may take, redirects included; shorter than the
+/// browser_download tool's timeout so the tool sees the real error
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Redirects a download follows, each checked against the allowlist
+const MAX_DOWNLOAD_REDIRECTS: usize = 5;
+
+/// Navigation allowance left to one session, refilled continuously up to the
+/// per-minute limit
+struct NavigationBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Marks an allowlist entry as a regex over the full URL rather than a domain
+const REGEX_PATTERN_PREFIX: &str = "re:";
+
+/// Trust level of an allowlist entry, with the regex of a `re:` entry compiled
+/// once when the entry is added or loaded
+#[derive(Debug, Clone)]
+struct AllowlistEntry {
+    trust_level: TrustLevel,
+    regex: Option<regex::Regex>,
+}
+
+impl AllowlistEntry {
+    fn new(pattern: &str, trust_level: TrustLevel) -> Result<Self, regex::Error> {
+        let regex = pattern
+            .strip_prefix(REGEX_PATTERN_PREFIX)
+            .map(regex::Regex::new)
+            .transpose()?;
+        Ok(Self { trust_level, regex })
+    }
+
+    /// Match against a URL: regexes against the full URL, anything else as a
+    /// domain pattern for the host
+    fn matches(&self, pattern: &str, url: &url::Url, host: &str) -> bool {
+        match &self.regex {
+            Some(regex) => regex.is_match(url.as_str()),
+            None => matches_domain_pattern(host, pattern),
+        }
+    }
+}
+
+/// Compile allowlist entries, logging and skipping `re:` entries with an invalid regex
+fn compile_allowlist(
+    entries: impl IntoIterator<Item = (String, TrustLevel)>,
+) -> HashMap<String, AllowlistEntry> {
+    entries
+        .into_iter()
+        .filter_map(
+            |(pattern, trust_level)| match AllowlistEntry::new(&pattern, trust_level) {
+                Ok(entry) => Some((pattern, entry)),
+                Err(e) => {
+                    warn!(
+                        "[Browser] Skipping invalid allowlist regex '{}': {}",
+                        pattern, e
+                    );
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+/// File in the config directory that keeps the allowlist and consent across restarts
+pub const BROWSER_STATE_FILE: &str = "browser_allowlist.json";
+
+/// Allowlist and consent as saved to disk
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedBrowserState {
+    allowed_domains: HashMap<String, TrustLevel>,
+    user_consented: bool,
+    /// When consent was granted, in Unix milliseconds. Consent saved without it
+    /// counts as expired.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    consent_active_ms: Option<i64>,
+}
+
+/// Domains a fresh allowlist starts with, all gated on user consent
+pub const DEFAULT_ALLOWED_DOMAINS: &[&str] = &[
+    "*.github.com",
+    "*.stackoverflow.com",
+    "*.wikipedia.org",
+    "localhost",
+    "127.0.0.1",
+];
+
+/// Config flag letting scheduled sessions navigate consent-gated domains unattended
+pub const SCHEDULED_AUTO_CONSENT_CONFIG_KEY: &str = "MTS_BROWSER_SCHEDULED_AUTO_CONSENT";
+
+/// Audit record of a browser command, mirroring the `browser_audit` log line
+/// with the command's outcome
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrowserAuditEntry {
+    pub command_type: String,
+    pub command_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+    /// When the command was handled, as RFC 3339
+    pub timestamp: String,
+    /// Same values as [`BrowserHistoryEntry::outcome`], plus "answered" for
+    /// history queries the server handles itself
+    pub outcome: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// File saved by a Download command, reported as its result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub content_type: String,
+    /// URL the file was fetched from, after redirects
+    pub url: String,
+}
+
+/// A handled browser command and what became of it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrowserHistoryEntry {
+    pub command_id: String,
+    pub command_type: String,
+    /// URL or selector the command targeted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// "blocked", "consent_required", "rate_limited", "queued", "queue_full",
+    /// "no_page_loaded", "invalid_tab", "forwarded" or "downloading", then "succeeded"
+    /// or "failed" once the frontend or download reports a result
+    pub outcome: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 /// Manages the global browser WebSocket connection and state
 pub struct BrowserSessionManager {
-    /// WebSocket sender (if connected)
+    /// Frontend WebSocket sender (if connected)
     ws_sender: Arc<RwLock<Option<mpsc::UnboundedSender<BrowserEvent>>>>,
 
     /// Command queue for when browser is offline
     command_queue: Arc<RwLock<VecDeque<BrowserCommand>>>,
+    max_queue_len: usize,
+
+    /// Commands awaiting a frontend result, keyed by command id, with the
+    /// connection that issued them
+    pending_commands: Arc<RwLock<HashMap<String, mpsc::UnboundedSender<BrowserEvent>>>>,
 
-    /// Current browser state
-    current_url: Arc<RwLock<String>>,
-    is_loading: Arc<RwLock<bool>>,
+    /// Current browser state of each open tab, keyed by tab id
+    tabs: Arc<RwLock<HashMap<String, TabState>>>,
+    /// Tab that commands without a `tab_id` run in
+    active_tab: Arc<RwLock<String>>,
 
-    /// Security: allowed domains for navigation
-    allowed_domains: Arc<RwLock<HashSet<String>>>,
+    /// Security: allowed domain patterns for navigation and their trust level
+    allowed_domains: Arc<RwLock<HashMap<String, AllowlistEntry>>>,
 
     /// Security: user has consented to browser automation
     user_consented: Arc<RwLock<bool>>,
+    /// When consent was last granted or relied on, in Unix milliseconds
+    consent_active_ms: Arc<RwLock<Option<i64>>>,
+    /// Inactivity after which consent lapses; None keeps it until revoked
+    consent_ttl: Option<Duration>,
+
+    /// Security: scheduled sessions count as consented (off by default)
+    scheduled_auto_consent: Arc<RwLock<bool>>,
+
+    /// Navigations each session may make per minute; 0 disables the limit
+    navigations_per_minute: u32,
+    /// Remaining navigation allowance, keyed by session id ("" for none)
+    navigation_buckets: Arc<Mutex<HashMap<String, NavigationBucket>>>,
+
+    /// Largest file a Download command will save, in bytes
+    max_download_bytes: u64,
+
+    /// Command counters keyed by command type, then outcome
+    command_counts: Arc<RwLock<BTreeMap<String, BTreeMap<String, u64>>>>,
+
+    /// Recent commands with their outcomes, oldest first, keyed by session id
+    /// ("" for none)
+    history: Arc<RwLock<HashMap<String, VecDeque<BrowserHistoryEntry>>>>,
+    history_limit: usize,
+
+    /// Audit records of recent commands, oldest first. Unlike the history this
+    /// includes history queries and is sized for security review.
+    audit_log: Arc<RwLock<VecDeque<BrowserAuditEntry>>>,
+    audit_limit: usize,
+
+    /// Where allowlist and consent changes are saved, if anywhere
+    store_path: Option<PathBuf>,
+    /// Serializes writes to `store_path`
+    store_lock: Arc<Mutex<()>>,
 }
 
 impl Default for BrowserSessionManager {
@@ -146,36 +702,236 @@ impl Default for BrowserSessionManager {
 
 impl BrowserSessionManager {
     pub fn new() -> Self {
-        // Initialize with default allowed domains
-        let mut allowed_domains = HashSet::new();
-        allowed_domains.insert("*.github.com".to_string());
-        allowed_domains.insert("*.stackoverflow.com".to_string());
-        allowed_domains.insert("*.wikipedia.org".to_string());
-        allowed_domains.insert("localhost".to_string());
-        allowed_domains.insert("127.0.0.1".to_string());
+        Self::with_allowed_domains(
+            DEFAULT_ALLOWED_DOMAINS
+                .iter()
+                .map(|domain| domain.to_string())
+                .collect(),
+        )
+    }
+
+    /// Start with `domains` allowed instead of the defaults, all gated on user
+    /// consent. `re:` entries with an invalid regex are logged and skipped.
+    pub fn with_allowed_domains(domains: Vec<String>) -> Self {
+        let allowed_domains = compile_allowlist(
+            domains
+                .into_iter()
+                .map(|domain| (domain, TrustLevel::RequireConsent)),
+        );
 
         Self {
             ws_sender: Arc::new(RwLock::new(None)),
             command_queue: Arc::new(RwLock::new(VecDeque::new())),
-            current_url: Arc::new(RwLock::new("https://google.com".to_string())),
-            is_loading: Arc::new(RwLock::new(false)),
+            max_queue_len: DEFAULT_MAX_QUEUE_LEN,
+            pending_commands: Arc::new(RwLock::new(HashMap::new())),
+            tabs: Arc::new(RwLock::new(HashMap::from([(
+                DEFAULT_TAB_ID.to_string(),
+                TabState::default(),
+            )]))),
+            active_tab: Arc::new(RwLock::new(DEFAULT_TAB_ID.to_string())),
             allowed_domains: Arc::new(RwLock::new(allowed_domains)),
             user_consented: Arc::new(RwLock::new(false)),
+            consent_active_ms: Arc::new(RwLock::new(None)),
+            consent_ttl: Some(DEFAULT_CONSENT_TTL),
+            scheduled_auto_consent: Arc::new(RwLock::new(
+                Config::global()
+                    .get_param::<bool>(SCHEDULED_AUTO_CONSENT_CONFIG_KEY)
+                    .unwrap_or(false),
+            )),
+            navigations_per_minute: DEFAULT_NAVIGATIONS_PER_MINUTE,
+            navigation_buckets: Arc::new(Mutex::new(HashMap::new())),
+            max_download_bytes: DEFAULT_MAX_DOWNLOAD_BYTES,
+            command_counts: Arc::new(RwLock::new(BTreeMap::new())),
+            history: Arc::new(RwLock::new(HashMap::new())),
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            audit_log: Arc::new(RwLock::new(VecDeque::new())),
+            audit_limit: DEFAULT_AUDIT_LIMIT,
+            store_path: None,
+            store_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Restore the allowlist and consent saved at `path` and save later changes there.
+    /// A missing or corrupt file falls back to the defaults.
+    #[allow(dead_code)]
+    pub fn load_or_default(path: PathBuf) -> Self {
+        Self::load_or(path, Self::new())
+    }
+
+    /// Like [`Self::load_or_default`], but a missing or corrupt file falls back to
+    /// the allowlist and settings of `manager`
+    pub fn load_or(path: PathBuf, mut manager: Self) -> Self {
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => match serde_json::from_str::<PersistedBrowserState>(&contents) {
+                Ok(saved) => {
+                    manager.allowed_domains =
+                        Arc::new(RwLock::new(compile_allowlist(saved.allowed_domains)));
+                    manager.user_consented = Arc::new(RwLock::new(saved.user_consented));
+                    manager.consent_active_ms = Arc::new(RwLock::new(saved.consent_active_ms));
+                }
+                Err(e) => warn!(
+                    "[BrowserSession] Ignoring corrupt {}: {}",
+                    path.display(),
+                    e
+                ),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("[BrowserSession] Could not read {}: {}", path.display(), e),
+        }
+        manager.store_path = Some(path);
+        manager
+    }
+
+    /// Save the allowlist and consent, if this manager has a store path
+    async fn persist(&self) {
+        let Some(path) = &self.store_path else {
+            return;
+        };
+        let _guard = self.store_lock.lock().await;
+        let saved = PersistedBrowserState {
+            allowed_domains: self
+                .allowed_domains
+                .read()
+                .await
+                .iter()
+                .map(|(pattern, entry)| (pattern.clone(), entry.trust_level))
+                .collect(),
+            user_consented: *self.user_consented.read().await,
+            consent_active_ms: *self.consent_active_ms.read().await,
+        };
+        if let Err(e) = write_browser_state(path, &saved).await {
+            warn!("[BrowserSession] Could not save {}: {}", path.display(), e);
+        }
+    }
+
+    /// Set how many commands the history keeps
+    pub fn history_limit(mut self, limit: usize) -> Self {
+        self.history_limit = limit;
+        self
+    }
+
+    /// Set how many commands the audit log keeps
+    pub fn audit_limit(mut self, limit: usize) -> Self {
+        self.audit_limit = limit;
+        self
+    }
+
+    /// Set how long consent lasts without being relied on; None keeps it until
+    /// it is revoked
+    pub fn consent_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.consent_ttl = ttl;
+        self
+    }
+
+    /// Set how many commands can wait for a connection or consent. Everything
+    /// queued runs on reconnect, so this bounds what a runaway agent can pile up.
+    pub fn max_queue_len(mut self, limit: usize) -> Self {
+        self.max_queue_len = limit;
+        self
+    }
+
+    /// Set how many navigations each session may make per minute; 0 disables
+    /// the limit
+    pub fn navigations_per_minute(mut self, limit: u32) -> Self {
+        self.navigations_per_minute = limit;
+        self
+    }
+
+    /// Set the largest file a Download command will save, in bytes
+    pub fn max_download_bytes(mut self, limit: u64) -> Self {
+        self.max_download_bytes = limit;
+        self
+    }
+
+    /// Spend one of the session's navigations, or return how long until the
+    /// next one is available
+    pub async fn take_navigation_token(&self, session_id: Option<&str>) -> Result<(), Duration> {
+        if self.navigations_per_minute == 0 {
+            return Ok(());
+        }
+        let capacity = f64::from(self.navigations_per_minute);
+        let per_second = capacity / 60.0;
+        let now = Instant::now();
+        let mut buckets = self.navigation_buckets.lock().await;
+        // Buckets that have refilled completely carry no state worth keeping
+        buckets.retain(|_, bucket| {
+            bucket.tokens + now.duration_since(bucket.refilled_at).as_secs_f64() * per_second
+                < capacity
+        });
+
+        let bucket = buckets
+            .entry(session_id.unwrap_or_default().to_string())
+            .or_insert(NavigationBucket {
+                tokens: capacity,
+                refilled_at: now,
+            });
+        bucket.tokens = (bucket.tokens
+            + now.duration_since(bucket.refilled_at).as_secs_f64() * per_second)
+            .min(capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
         }
     }
 
-    /// Set the WebSocket sender when a client connects
+    /// Set the WebSocket sender when the frontend connects
     pub async fn set_sender(&self, sender: mpsc::UnboundedSender<BrowserEvent>) {
         let mut ws_sender = self.ws_sender.write().await;
         *ws_sender = Some(sender);
         info!("[BrowserSession] WebSocket sender set");
     }
 
-    /// Clear the WebSocket sender when client disconnects
-    pub async fn clear_sender(&self) {
+    /// Clear the WebSocket sender when the frontend disconnects, unless a newer
+    /// frontend connection has replaced it
+    pub async fn clear_sender(&self, sender: &mpsc::UnboundedSender<BrowserEvent>) {
         let mut ws_sender = self.ws_sender.write().await;
-        *ws_sender = None;
-        info!("[BrowserSession] WebSocket sender cleared");
+        if ws_sender
+            .as_ref()
+            .is_some_and(|current| current.same_channel(sender))
+        {
+            *ws_sender = None;
+            info!("[BrowserSession] WebSocket sender cleared");
+        }
+    }
+
+    /// Remember which connection a forwarded command's result goes back to
+    pub async fn register_pending(
+        &self,
+        command_id: String,
+        reply: mpsc::UnboundedSender<BrowserEvent>,
+    ) {
+        let mut pending = self.pending_commands.write().await;
+        // Issuers that disconnected will never read their results
+        pending.retain(|_, reply| !reply.is_closed());
+        pending.insert(command_id, reply);
+    }
+
+    /// Deliver a CommandResult to the connection that issued the command.
+    /// Returns false if no command with that id is pending.
+    pub async fn deliver_result(&self, event: BrowserEvent) -> bool {
+        let BrowserEvent::CommandResult { command_id, .. } = &event else {
+            return false;
+        };
+        let reply = self.pending_commands.write().await.remove(command_id);
+        match reply {
+            Some(reply) => reply.send(event).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Number of forwarded commands still waiting for a result
+    #[allow(dead_code)]
+    pub async fn pending_command_count(&self) -> usize {
+        self.pending_commands.read().await.len()
+    }
+
+    /// Whether a browser frontend is connected
+    pub async fn is_connected(&self) -> bool {
+        self.ws_sender.read().await.is_some()
     }
 
     /// Send an event to the connected frontend
@@ -191,11 +947,25 @@ impl BrowserSessionManager {
         }
     }
 
-    /// Queue a command when WebSocket is disconnected
-    pub async fn queue_command(&self, command: BrowserCommand) {
+    /// Queue a command when WebSocket is disconnected. Returns false, leaving the
+    /// queue unchanged, if it already holds `max_queue_len` commands.
+    pub async fn queue_command(&self, command: BrowserCommand) -> bool {
         let mut queue = self.command_queue.write().await;
+        if queue.len() >= self.max_queue_len {
+            warn!(
+                "[BrowserSession] Command queue full ({} commands), rejecting {} (command_id: {})",
+                queue.len(),
+                command.type_name(),
+                command.command_id()
+            );
+            return false;
+        }
         queue.push_back(command);
-        info!("[BrowserSession] Command queued (queue size: {})", queue.len());
+        info!(
+            "[BrowserSession] Command queued (queue size: {})",
+            queue.len()
+        );
+        true
     }
 
     /// Process queued commands when WebSocket reconnects
@@ -203,75 +973,385 @@ impl BrowserSessionManager {
         let mut queue = self.command_queue.write().await;
         let commands: Vec<BrowserCommand> = queue.drain(..).collect();
         if !commands.is_empty() {
-            info!("[BrowserSession] Processing {} queued commands", commands.len());
+            info!(
+                "[BrowserSession] Processing {} queued commands",
+                commands.len()
+            );
         }
         commands
     }
 
-    /// Update current URL
-    pub async fn set_current_url(&self, url: String) {
-        let mut current_url = self.current_url.write().await;
-        *current_url = url;
+    /// Number of commands waiting for a connection or consent
+    pub async fn queued_command_count(&self) -> usize {
+        self.command_queue.read().await.len()
     }
 
-    /// Get current URL
-    pub async fn get_current_url(&self) -> String {
-        self.current_url.read().await.clone()
+    /// Record the outcome of a handled command
+    pub async fn record_command(&self, command_type: &str, outcome: &str) {
+        let mut counts = self.command_counts.write().await;
+        *counts
+            .entry(command_type.to_string())
+            .or_default()
+            .entry(outcome.to_string())
+            .or_insert(0) += 1;
     }
 
-    /// Update loading state
-    pub async fn set_loading(&self, loading: bool) {
-        let mut is_loading = self.is_loading.write().await;
-        *is_loading = loading;
+    /// Get command counters keyed by command type, then outcome
+    pub async fn get_command_counts(&self) -> BTreeMap<String, BTreeMap<String, u64>> {
+        self.command_counts.read().await.clone()
     }
 
-    /// Check if URL is allowed
-    pub async fn is_url_allowed(&self, url: &str) -> bool {
-        // Parse URL to get domain
-        let parsed_url = match url::Url::parse(url) {
-            Ok(u) => u,
-            Err(_) => return false,
-        };
+    /// Append a handled command to its session's history, dropping the oldest
+    /// beyond the limit
+    pub async fn record_history(
+        &self,
+        session_id: Option<&str>,
+        command: &BrowserCommand,
+        outcome: &str,
+    ) {
+        let mut history = self.history.write().await;
+        let history = history
+            .entry(session_id.unwrap_or_default().to_string())
+            .or_default();
+        history.push_back(BrowserHistoryEntry {
+            command_id: command.command_id().to_string(),
+            command_type: command.type_name().to_string(),
+            target: command.url().or(command.selector()).map(str::to_string),
+            outcome: outcome.to_string(),
+            error: None,
+        });
+        while history.len() > self.history_limit {
+            history.pop_front();
+        }
+    }
+
+    /// Record the result the frontend reported for a command in the history
+    /// and audit log
+    pub async fn record_history_result(
+        &self,
+        command_id: &str,
+        success: bool,
+        error: Option<String>,
+    ) {
+        let outcome = if success { "succeeded" } else { "failed" };
+        if let Some(entry) = self
+            .audit_log
+            .write()
+            .await
+            .iter_mut()
+            .rev()
+            .find(|e| e.command_id == command_id)
+        {
+            entry.outcome = outcome.to_string();
+            entry.error = error.clone();
+        }
+        let mut history = self.history.write().await;
+        if let Some(entry) = history
+            .values_mut()
+            .flat_map(|entries| entries.iter_mut().rev())
+            .find(|e| e.command_id == command_id)
+        {
+            entry.outcome = outcome.to_string();
+            entry.error = error;
+        }
+    }
+
+    /// Append a handled command to the audit log, dropping the oldest beyond the limit
+    pub async fn record_audit(&self, command: &BrowserCommand, outcome: &str) {
+        let mut audit_log = self.audit_log.write().await;
+        audit_log.push_back(BrowserAuditEntry {
+            command_type: command.type_name().to_string(),
+            command_id: command.command_id().to_string(),
+            url: command.url().map(str::to_string),
+            selector: command.selector().map(str::to_string),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            outcome: outcome.to_string(),
+            error: None,
+        });
+        while audit_log.len() > self.audit_limit {
+            audit_log.pop_front();
+        }
+    }
 
-        let host = match parsed_url.host_str() {
-            Some(h) => h,
-            None => return false,
+    /// Get the last `limit` audit entries, oldest first
+    pub async fn get_audit_log(&self, limit: usize) -> Vec<BrowserAuditEntry> {
+        let audit_log = self.audit_log.read().await;
+        audit_log
+            .iter()
+            .skip(audit_log.len().saturating_sub(limit))
+            .cloned()
+            .collect()
+    }
+
+    /// Get the last `limit` history entries of a session, oldest first
+    pub async fn get_history(
+        &self,
+        session_id: Option<&str>,
+        limit: usize,
+    ) -> Vec<BrowserHistoryEntry> {
+        let history = self.history.read().await;
+        let Some(history) = history.get(session_id.unwrap_or_default()) else {
+            return Vec::new();
         };
+        history
+            .iter()
+            .skip(history.len().saturating_sub(limit))
+            .cloned()
+            .collect()
+    }
+
+    /// Forget the commands recorded for a session
+    pub async fn clear_history(&self, session_id: &str) {
+        self.history.write().await.remove(session_id);
+    }
+
+    /// Resolve a command's tab id, defaulting to the active tab
+    async fn resolve_tab(&self, tab_id: Option<&str>) -> String {
+        match tab_id {
+            Some(tab_id) => tab_id.to_string(),
+            None => self.active_tab.read().await.clone(),
+        }
+    }
+
+    /// Id of the tab that commands without a `tab_id` run in
+    pub async fn active_tab(&self) -> String {
+        self.active_tab.read().await.clone()
+    }
+
+    /// Check whether a tab is open
+    pub async fn has_tab(&self, tab_id: &str) -> bool {
+        self.tabs.read().await.contains_key(tab_id)
+    }
+
+    /// Open a blank tab and make it active. Returns false if the id is taken.
+    pub async fn open_tab(&self, tab_id: &str) -> bool {
+        let mut active_tab = self.active_tab.write().await;
+        let mut tabs = self.tabs.write().await;
+        if tabs.contains_key(tab_id) {
+            return false;
+        }
+        tabs.insert(tab_id.to_string(), TabState::default());
+        *active_tab = tab_id.to_string();
+        true
+    }
+
+    /// Close a tab, defaulting to the active one, and return its id. Closing the
+    /// active tab activates the default tab if open, else the first by id.
+    pub async fn close_tab(&self, tab_id: Option<&str>) -> Result<String, String> {
+        let mut active_tab = self.active_tab.write().await;
+        let mut tabs = self.tabs.write().await;
+        let tab_id = tab_id.unwrap_or(active_tab.as_str()).to_string();
+        if !tabs.contains_key(&tab_id) {
+            return Err(format!("No tab '{}' is open", tab_id));
+        }
+        if tabs.len() == 1 {
+            return Err(format!("Tab '{}' is the last open tab", tab_id));
+        }
+        tabs.remove(&tab_id);
+        if *active_tab == tab_id {
+            *active_tab = if tabs.contains_key(DEFAULT_TAB_ID) {
+                DEFAULT_TAB_ID.to_string()
+            } else {
+                tabs.keys().min().cloned().unwrap_or_default()
+            };
+        }
+        Ok(tab_id)
+    }
+
+    /// Update a tab's current URL, tracking tabs the frontend opened on its own
+    pub async fn set_current_url(&self, tab_id: Option<&str>, url: String) {
+        let tab_id = self.resolve_tab(tab_id).await;
+        self.tabs
+            .write()
+            .await
+            .entry(tab_id)
+            .or_default()
+            .current_url = url;
+    }
+
+    /// Get a tab's current URL, or None if the tab isn't open
+    pub async fn get_current_url(&self, tab_id: Option<&str>) -> Option<String> {
+        let tab_id = self.resolve_tab(tab_id).await;
+        let tabs = self.tabs.read().await;
+        tabs.get(&tab_id).map(|tab| tab.current_url.clone())
+    }
+
+    /// Check whether a tab has navigated to a page yet
+    pub async fn has_page_loaded(&self, tab_id: Option<&str>) -> bool {
+        self.get_current_url(tab_id)
+            .await
+            .is_some_and(|url| !url.is_empty() && url != BLANK_PAGE_URL)
+    }
+
+    /// Update a tab's loading state
+    pub async fn set_loading(&self, tab_id: Option<&str>, loading: bool) {
+        let tab_id = self.resolve_tab(tab_id).await;
+        self.tabs
+            .write()
+            .await
+            .entry(tab_id)
+            .or_default()
+            .is_loading = loading;
+    }
+
+    /// Check whether a tab is loading a page
+    pub async fn is_loading(&self, tab_id: Option<&str>) -> bool {
+        let tab_id = self.resolve_tab(tab_id).await;
+        let tabs = self.tabs.read().await;
+        tabs.get(&tab_id).is_some_and(|tab| tab.is_loading)
+    }
+
+    /// Trust level for a URL, or None if no allowlist entry matches.
+    /// When several entries match, AutoAllow wins.
+    pub async fn url_trust_level(&self, url: &str) -> Option<TrustLevel> {
+        // Parse URL to get domain
+        let parsed_url = url::Url::parse(url).ok()?;
+        let host = parsed_url.host_str()?;
 
         let allowed_domains = self.allowed_domains.read().await;
 
         // Check against patterns
-        for pattern in allowed_domains.iter() {
-            if matches_domain_pattern(host, pattern) {
-                return true;
+        let mut trust_level = None;
+        for (pattern, entry) in allowed_domains.iter() {
+            if entry.matches(pattern, &parsed_url, host) {
+                if entry.trust_level == TrustLevel::AutoAllow {
+                    return Some(TrustLevel::AutoAllow);
+                }
+                trust_level = Some(entry.trust_level);
             }
         }
 
-        false
+        trust_level
+    }
+
+    /// Add domain to allowlist, gated on user consent
+    #[allow(dead_code)]
+    pub async fn add_allowed_domain(&self, domain: String) -> Result<(), String> {
+        self.add_allowed_domain_with_trust(domain, TrustLevel::RequireConsent)
+            .await
+    }
+
+    /// Add domain to allowlist with an explicit trust level. Fails for `re:`
+    /// entries with an invalid regex.
+    #[allow(dead_code)]
+    pub async fn add_allowed_domain_with_trust(
+        &self,
+        domain: String,
+        trust_level: TrustLevel,
+    ) -> Result<(), String> {
+        let entry = AllowlistEntry::new(&domain, trust_level)
+            .map_err(|e| format!("Invalid allowlist regex '{}': {}", domain, e))?;
+        self.allowed_domains.write().await.insert(domain, entry);
+        self.persist().await;
+        Ok(())
     }
 
-    /// Add domain to allowlist
-    pub async fn add_allowed_domain(&self, domain: String) {
-        let mut allowed_domains = self.allowed_domains.write().await;
-        allowed_domains.insert(domain);
+    /// Remove a domain from the allowlist, returning whether it was present
+    pub async fn remove_allowed_domain(&self, domain: &str) -> bool {
+        let removed = self.allowed_domains.write().await.remove(domain).is_some();
+        if removed {
+            self.persist().await;
+        }
+        removed
     }
 
     /// Get allowed domains
     pub async fn get_allowed_domains(&self) -> Vec<String> {
         let allowed_domains = self.allowed_domains.read().await;
-        allowed_domains.iter().cloned().collect()
+        allowed_domains.keys().cloned().collect()
     }
 
     /// Set user consent
+    #[allow(dead_code)]
     pub async fn set_consent(&self, consented: bool) {
-        let mut user_consented = self.user_consented.write().await;
-        *user_consented = consented;
+        *self.user_consented.write().await = consented;
+        *self.consent_active_ms.write().await = consented.then(current_timestamp);
+        self.persist().await;
     }
 
-    /// Check if user has consented
+    /// Check if user has consented. Consent lapses once it goes unused for the
+    /// consent TTL; while it holds, each check counts as use and restarts the clock.
     pub async fn has_consent(&self) -> bool {
+        let now = current_timestamp();
+        {
+            let mut consented = self.user_consented.write().await;
+            if !*consented {
+                return false;
+            }
+            let mut active_ms = self.consent_active_ms.write().await;
+            if !self.consent_expired(*active_ms, now) {
+                *active_ms = Some(now);
+                return true;
+            }
+            *consented = false;
+            *active_ms = None;
+        }
+
+        info!("[BrowserSession] Consent expired after inactivity");
+        self.persist().await;
+        false
+    }
+
+    /// Check whether consent currently holds without counting as use, for
+    /// reporting it
+    pub async fn peek_consent(&self) -> bool {
         *self.user_consented.read().await
+            && !self.consent_expired(*self.consent_active_ms.read().await, current_timestamp())
     }
+
+    fn consent_expired(&self, active_ms: Option<i64>, now: i64) -> bool {
+        match (self.consent_ttl, active_ms) {
+            (None, _) => false,
+            (Some(ttl), Some(active_ms)) => now.saturating_sub(active_ms) >= ttl.as_millis() as i64,
+            (Some(_), None) => true,
+        }
+    }
+
+    /// Set whether scheduled sessions skip the consent gate
+    #[allow(dead_code)]
+    pub async fn set_scheduled_auto_consent(&self, enabled: bool) {
+        let mut scheduled_auto_consent = self.scheduled_auto_consent.write().await;
+        *scheduled_auto_consent = enabled;
+    }
+
+    /// Check whether a session may navigate consent-gated domains without the user.
+    /// Only scheduled sessions qualify, and only when enabled.
+    pub async fn has_session_consent(&self, session_id: Option<&str>) -> bool {
+        let Some(session_id) = session_id else {
+            return false;
+        };
+        if !*self.scheduled_auto_consent.read().await {
+            return false;
+        }
+        match SessionManager::get_session(session_id, false).await {
+            Ok(session) => session.schedule_id.is_some(),
+            Err(e) => {
+                warn!("[Browser] Could not look up session {}: {}", session_id, e);
+                false
+            }
+        }
+    }
+}
+
+fn current_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// Write the saved state through a temporary file so a crash can't leave it half written
+async fn write_browser_state(
+    path: &std::path::Path,
+    saved: &PersistedBrowserState,
+) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let temp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&temp_path, serde_json::to_vec_pretty(saved)?).await?;
+    tokio::fs::rename(&temp_path, path).await?;
+    Ok(())
 }
 
 /// Helper function to match domain patterns (supports wildcards)
@@ -281,8 +1361,7 @@ fn matches_domain_pattern(host: &str, pattern: &str) -> bool {
     }
 
     // Support wildcard patterns like "*.github.com"
-    if pattern.starts_with("*.") {
-        let suffix = &pattern[2..];
+    if let Some(suffix) = pattern.strip_prefix("*.") {
         return host.ends_with(suffix) || host == suffix;
     }
 
@@ -294,31 +1373,44 @@ fn matches_domain_pattern(host: &str, pattern: &str) -> bool {
 // =============================================================================
 
 /// WebSocket endpoint: GET /ws/browser
+///
+/// The frontend connects with `?role=frontend`; other connections issue commands
+/// and receive their results.
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
+    Query(params): Query<BrowserSocketParams>,
 ) -> impl IntoResponse {
-    info!("[Browser] WebSocket connection requested");
-    ws.on_upgrade(move |socket| handle_websocket(socket, state))
+    info!(
+        "[Browser] WebSocket connection requested (role: {:?})",
+        params.role
+    );
+    ws.on_upgrade(move |socket| handle_websocket(socket, state, params.role))
+}
+
+/// Session a command was issued for. Any command may carry one next to its own
+/// fields; navigation also reads it through [`BrowserCommand::session_id`].
+#[derive(Deserialize)]
+struct CommandSession {
+    #[serde(default)]
+    session_id: Option<String>,
 }
 
 /// Handle WebSocket connection
-async fn handle_websocket(socket: WebSocket, state: Arc<AppState>) {
+async fn handle_websocket(socket: WebSocket, state: Arc<AppState>, role: ConnectionRole) {
     info!("[Browser] WebSocket connection established");
 
     let (mut sender, mut receiver) = socket.split();
     let (tx, mut rx) = mpsc::unbounded_channel::<BrowserEvent>();
 
-    // Register the sender with the session manager
-    state.browser_manager.set_sender(tx.clone()).await;
+    if role == ConnectionRole::Frontend {
+        // Register the sender with the session manager
+        state.browser_manager.set_sender(tx.clone()).await;
 
-    // Process any queued commands
-    let queued_commands = state.browser_manager.process_queued_commands().await;
-    for command in queued_commands {
-        if let Ok(json) = serde_json::to_string(&command) {
-            if let Err(e) = sender.send(Message::Text(json.into())).await {
-                error!("[Browser] Failed to send queued command: {}", e);
-            }
+        // Hand over commands that waited for a frontend
+        let queued_commands = state.browser_manager.process_queued_commands().await;
+        for command in queued_commands {
+            let _ = tx.send(BrowserEvent::ExecuteCommand { command });
         }
     }
 
@@ -342,17 +1434,30 @@ async fn handle_websocket(socket: WebSocket, state: Arc<AppState>) {
     // Task to receive commands from frontend and process
     let recv_task = {
         let state = state.clone();
+        let reply = tx.clone();
         tokio::spawn(async move {
             while let Some(msg) = receiver.next().await {
                 match msg {
                     Ok(Message::Text(text)) => {
                         match serde_json::from_str::<BrowserCommand>(&text) {
                             Ok(command) => {
-                                handle_browser_command(command, &state).await;
-                            }
-                            Err(e) => {
-                                error!("[Browser] Failed to parse command: {}", e);
+                                let session_id = serde_json::from_str::<CommandSession>(&text)
+                                    .ok()
+                                    .and_then(|c| c.session_id);
+                                handle_browser_command(
+                                    command,
+                                    session_id.as_deref(),
+                                    &state,
+                                    &reply,
+                                )
+                                .await;
                             }
+                            Err(e) => match serde_json::from_str::<BrowserEvent>(&text) {
+                                Ok(event) => handle_browser_event(event, &state).await,
+                                Err(_) => {
+                                    error!("[Browser] Failed to parse command: {}", e);
+                                }
+                            },
                         }
                     }
                     Ok(Message::Close(_)) => {
@@ -383,44 +1488,191 @@ async fn handle_websocket(socket: WebSocket, state: Arc<AppState>) {
     }
 
     // Cleanup
-    state.browser_manager.clear_sender().await;
+    if role == ConnectionRole::Frontend {
+        state.browser_manager.clear_sender(&tx).await;
+    }
     info!("[Browser] WebSocket connection closed");
 }
 
-/// Handle incoming browser command
-async fn handle_browser_command(command: BrowserCommand, state: &AppState) {
-    let command_id = command.command_id().to_string();
-    let command_type = command.type_name();
-
-    // Audit logging
-    info!(
-        target: "browser_audit",
-        command_type = %command_type,
-        command_id = %command_id,
-        url = ?command.url(),
+/// Handle state updates reported by the frontend
+async fn handle_browser_event(event: BrowserEvent, state: &AppState) {
+    match event {
+        BrowserEvent::NavigationComplete { url, tab_id } => {
+            state
+                .browser_manager
+                .set_current_url(tab_id.as_deref(), url)
+                .await;
+        }
+        BrowserEvent::LoadingStateChanged { loading, tab_id } => {
+            state
+                .browser_manager
+                .set_loading(tab_id.as_deref(), loading)
+                .await;
+        }
+        BrowserEvent::CommandResult {
+            ref command_id,
+            success,
+            ref error,
+            ..
+        } => {
+            state
+                .browser_manager
+                .record_history_result(command_id, success, error.clone())
+                .await;
+            let command_id = command_id.clone();
+            if !state.browser_manager.deliver_result(event).await {
+                warn!(
+                    "[Browser] Dropping result for unknown command (command_id: {})",
+                    command_id
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Count a command's outcome and add it to the session's history and the audit log
+async fn record_outcome(
+    state: &AppState,
+    session_id: Option<&str>,
+    command: &BrowserCommand,
+    outcome: &str,
+) {
+    state
+        .browser_manager
+        .record_command(command.type_name(), outcome)
+        .await;
+    state
+        .browser_manager
+        .record_history(session_id, command, outcome)
+        .await;
+    state.browser_manager.record_audit(command, outcome).await;
+}
+
+/// Queue a command whose result is already pending, failing it right away if the
+/// queue is full
+async fn queue_for_later(state: &AppState, session_id: Option<&str>, command: BrowserCommand) {
+    if state.browser_manager.queue_command(command.clone()).await {
+        record_outcome(state, session_id, &command, "queued").await;
+        return;
+    }
+
+    record_outcome(state, session_id, &command, "queue_full").await;
+    state
+        .browser_manager
+        .deliver_result(BrowserEvent::CommandResult {
+            command_id: command.command_id().to_string(),
+            success: false,
+            data: None,
+            error: Some(
+                "Too many browser commands are waiting for the browser to connect or for \
+                 consent. Wait for them to run before issuing more."
+                    .to_string(),
+            ),
+            error_code: None,
+        })
+        .await;
+}
+
+/// Check that the command's tab exists, and open or close tabs for the tab
+/// commands so that later commands can target them right away
+async fn check_tab(command: &BrowserCommand, state: &AppState) -> Result<(), String> {
+    let manager = &state.browser_manager;
+    match command {
+        BrowserCommand::OpenTab { tab_id, .. } => {
+            let tab_id = tab_id
+                .as_deref()
+                .ok_or_else(|| "OpenTab needs a tab_id".to_string())?;
+            if !manager.open_tab(tab_id).await {
+                return Err(format!("Tab '{}' is already open", tab_id));
+            }
+            info!("[Browser] Opened tab '{}'", tab_id);
+        }
+        BrowserCommand::CloseTab { tab_id, .. } => {
+            let closed = manager.close_tab(tab_id.as_deref()).await?;
+            info!("[Browser] Closed tab '{}'", closed);
+        }
+        _ => {
+            if let Some(tab_id) = command.tab_id() {
+                if !manager.has_tab(tab_id).await {
+                    return Err(format!("No tab '{}' is open", tab_id));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handle incoming browser command. Results go back through `reply`: directly for
+/// commands answered here, or once the frontend reports them for forwarded ones.
+async fn handle_browser_command(
+    command: BrowserCommand,
+    session_id: Option<&str>,
+    state: &AppState,
+    reply: &mpsc::UnboundedSender<BrowserEvent>,
+) {
+    let command_id = command.command_id().to_string();
+    let command_type = command.type_name();
+
+    // Audit logging
+    info!(
+        target: "browser_audit",
+        command_type = %command_type,
+        command_id = %command_id,
+        url = ?command.url(),
         selector = ?command.selector(),
         timestamp = %chrono::Utc::now(),
         "Browser command received"
     );
 
-    // Security checks for navigation
-    if let BrowserCommand::Navigate { ref url, .. } = command {
+    // History queries are answered here and kept out of the history itself
+    if let BrowserCommand::GetHistory { limit, .. } = command {
+        state
+            .browser_manager
+            .record_audit(&command, "answered")
+            .await;
+        let history = state.browser_manager.get_history(session_id, limit).await;
+        let _ = reply.send(BrowserEvent::CommandResult {
+            command_id,
+            success: true,
+            data: serde_json::to_string(&history).ok(),
+            error: None,
+            error_code: None,
+        });
+        return;
+    }
+
+    if let Err(error) = check_tab(&command, state).await {
+        warn!("[Browser] {} (command_id: {})", error, command_id);
+        let _ = reply.send(BrowserEvent::CommandResult {
+            command_id: command_id.clone(),
+            success: false,
+            data: None,
+            error: Some(error),
+            error_code: None,
+        });
+        record_outcome(state, session_id, &command, "invalid_tab").await;
+        return;
+    }
+
+    // Security checks for navigation and downloads; back, forward and reload
+    // stay within already visited pages and are not checked
+    if let Some(url) = command.url() {
         // Check URL allowlist
-        if !state.browser_manager.is_url_allowed(url).await {
+        let trust_level = state.browser_manager.url_trust_level(url).await;
+        if trust_level.is_none() {
             warn!(
                 "[Browser] URL blocked by allowlist: {} (command_id: {})",
                 url, command_id
             );
 
-            let _ = state
-                .browser_manager
-                .send_event(BrowserEvent::CommandResult {
-                    command_id: command_id.clone(),
-                    success: false,
-                    data: None,
-                    error: Some(format!("URL '{}' not in allowlist", url)),
-                })
-                .await;
+            let _ = reply.send(BrowserEvent::CommandResult {
+                command_id: command_id.clone(),
+                success: false,
+                data: None,
+                error: Some(format!("URL '{}' not in allowlist", url)),
+                error_code: None,
+            });
 
             // Send consent required event with allowed domains
             let allowed_domains = state.browser_manager.get_allowed_domains().await;
@@ -429,11 +1681,19 @@ async fn handle_browser_command(command: BrowserCommand, state: &AppState) {
                 .send_event(BrowserEvent::ConsentRequired { allowed_domains })
                 .await;
 
+            record_outcome(state, session_id, &command, "blocked").await;
             return;
         }
 
-        // Check user consent
-        if !state.browser_manager.has_consent().await {
+        // Check user consent, unless the domain is trusted outright or the
+        // session is a scheduled run with no user to ask
+        if trust_level == Some(TrustLevel::RequireConsent)
+            && !state.browser_manager.has_consent().await
+            && !state
+                .browser_manager
+                .has_session_consent(command.session_id())
+                .await
+        {
             warn!(
                 "[Browser] User consent required (command_id: {})",
                 command_id
@@ -445,35 +1705,372 @@ async fn handle_browser_command(command: BrowserCommand, state: &AppState) {
                 .send_event(BrowserEvent::ConsentRequired { allowed_domains })
                 .await;
 
+            // Queued commands are flushed to the frontend, which doesn't
+            // download, so downloads fail and can be retried once consent is given
+            if let BrowserCommand::Download { .. } = command {
+                let _ = reply.send(BrowserEvent::CommandResult {
+                    command_id: command_id.clone(),
+                    success: false,
+                    data: None,
+                    error: Some(format!(
+                        "User consent is required before downloading from '{}'",
+                        url
+                    )),
+                    error_code: None,
+                });
+                record_outcome(state, session_id, &command, "consent_required").await;
+                return;
+            }
+
             // Queue the command for later execution
-            state.browser_manager.queue_command(command).await;
+            state
+                .browser_manager
+                .register_pending(command_id, reply.clone())
+                .await;
+            queue_for_later(state, session_id, command).await;
+            return;
+        }
+
+        // Navigations held for consent are bounded by the queue instead
+        let rate_limited = match command {
+            BrowserCommand::Navigate { .. } => state
+                .browser_manager
+                .take_navigation_token(command.session_id())
+                .await
+                .err(),
+            _ => None,
+        };
+        if let Some(retry_after) = rate_limited {
+            let retry_after_secs = retry_after.as_secs_f64().ceil() as u64;
+            warn!(
+                "[Browser] Navigation rate limit exceeded, retry after {}s (command_id: {})",
+                retry_after_secs, command_id
+            );
+            let _ = reply.send(BrowserEvent::CommandResult {
+                command_id: command_id.clone(),
+                success: false,
+                data: None,
+                error: Some(format!(
+                    "Navigation rate limit of {} per minute exceeded; retry after {} seconds",
+                    state.browser_manager.navigations_per_minute, retry_after_secs
+                )),
+                error_code: Some(BrowserErrorCode::RateLimited),
+            });
+            record_outcome(state, session_id, &command, "rate_limited").await;
             return;
         }
     }
 
-    // Forward command to frontend (the frontend will handle it and send results back)
+    if let BrowserCommand::Download { ref url, .. } = command {
+        record_outcome(state, session_id, &command, "downloading").await;
+        let manager = state.browser_manager.clone();
+        let reply = reply.clone();
+        let url = url.clone();
+        tokio::spawn(async move {
+            let result = match download_file(&manager, &url).await {
+                Ok(file) => {
+                    info!(
+                        "[Browser] Downloaded {} to {} ({} bytes, command_id: {})",
+                        file.url,
+                        file.path.display(),
+                        file.size,
+                        command_id
+                    );
+                    manager.record_history_result(&command_id, true, None).await;
+                    BrowserEvent::CommandResult {
+                        command_id,
+                        success: true,
+                        data: serde_json::to_string(&file).ok(),
+                        error: None,
+                        error_code: None,
+                    }
+                }
+                Err(error) => {
+                    warn!("[Browser] {} (command_id: {})", error, command_id);
+                    manager
+                        .record_history_result(&command_id, false, Some(error.clone()))
+                        .await;
+                    BrowserEvent::CommandResult {
+                        command_id,
+                        success: false,
+                        data: None,
+                        error: Some(error),
+                        error_code: None,
+                    }
+                }
+            };
+            let _ = reply.send(result);
+        });
+        return;
+    }
+
+    if command.requires_page()
+        && !state
+            .browser_manager
+            .has_page_loaded(command.tab_id())
+            .await
+    {
+        warn!(
+            "[Browser] {} requested before any page was loaded (command_id: {})",
+            command_type, command_id
+        );
+
+        let _ = reply.send(BrowserEvent::CommandResult {
+            command_id: command_id.clone(),
+            success: false,
+            data: None,
+            error: Some(
+                "No page is loaded yet. Use browser_navigate to open a URL first.".to_string(),
+            ),
+            error_code: Some(BrowserErrorCode::NoPageLoaded),
+        });
+
+        record_outcome(state, session_id, &command, "no_page_loaded").await;
+        return;
+    }
+
+    // Forward command to frontend; its CommandResult is delivered through `reply`
+    state
+        .browser_manager
+        .register_pending(command_id.clone(), reply.clone())
+        .await;
+    let forwarded = BrowserEvent::ExecuteCommand {
+        command: command.clone(),
+    };
+    if state.browser_manager.send_event(forwarded).await.is_err() {
+        warn!(
+            "[Browser] No frontend connected, queuing {} (command_id: {})",
+            command_type, command_id
+        );
+        queue_for_later(state, session_id, command).await;
+        return;
+    }
+
+    record_outcome(state, session_id, &command, "forwarded").await;
     info!(
-        "[Browser] Command processed: {} (command_id: {})",
+        "[Browser] Command forwarded: {} (command_id: {})",
         command_type, command_id
     );
+}
+
+/// Fetch `url` and save it as an artifact. Redirects are followed here so every
+/// hop is held to the allowlist, and the size limit is checked against
+/// Content-Length and again while the body streams in.
+async fn download_file(
+    manager: &BrowserSessionManager,
+    url: &str,
+) -> Result<DownloadedFile, String> {
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create download client: {}", e))?;
+
+    let mut url = url::Url::parse(url).map_err(|e| format!("Invalid URL '{}': {}", url, e))?;
+    let mut redirects = 0;
+    let mut response = loop {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!(
+                "Only http:// and https:// URLs can be downloaded, not '{}'",
+                url
+            ));
+        }
+        if manager.url_trust_level(url.as_str()).await.is_none() {
+            return Err(format!("URL '{}' not in allowlist", url));
+        }
+
+        let response = client
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|e| format!("Failed to download '{}': {}", url, e))?;
+        if !response.status().is_redirection() {
+            break response;
+        }
+
+        redirects += 1;
+        if redirects > MAX_DOWNLOAD_REDIRECTS {
+            return Err(format!(
+                "Download of '{}' redirected more than {} times",
+                url, MAX_DOWNLOAD_REDIRECTS
+            ));
+        }
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| format!("Redirect from '{}' has no Location", url))?;
+        url = url
+            .join(location)
+            .map_err(|e| format!("Invalid redirect from '{}': {}", url, e))?;
+    };
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Download of '{}' failed with HTTP {}",
+            url,
+            response.status()
+        ));
+    }
+
+    let max_bytes = manager.max_download_bytes;
+    let too_large = || format!("Download of '{}' exceeds the {} byte limit", url, max_bytes);
+    if response
+        .content_length()
+        .is_some_and(|length| length > max_bytes)
+    {
+        return Err(too_large());
+    }
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
 
-    // Note: In a full implementation, we would wait for the result from the Electron IPC
-    // and then send a CommandResult event. For now, we just log the command.
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to download '{}': {}", url, e))?
+    {
+        if (bytes.len() + chunk.len()) as u64 > max_bytes {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    let id = save_artifact(&bytes, &download_extension(&url))
+        .await
+        .map_err(|e| format!("Failed to save download of '{}': {}", url, e))?;
+    Ok(DownloadedFile {
+        path: artifacts_dir().join(id),
+        size: bytes.len() as u64,
+        content_type,
+        url: url.to_string(),
+    })
+}
+
+/// File extension for a download, from the last path segment, or "bin"
+fn download_extension(url: &url::Url) -> String {
+    url.path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .filter(|extension| {
+            !extension.is_empty()
+                && extension.len() <= 8
+                && extension.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+        .unwrap_or_else(|| "bin".to_string())
 }
 
 // =============================================================================
 // Route Configuration
 // =============================================================================
 
+/// Serve a file saved by the browser tools, such as a linked screenshot
+async fn get_artifact(Path(id): Path<String>) -> Result<impl IntoResponse, StatusCode> {
+    let path = artifact_path(&id).ok_or(StatusCode::NOT_FOUND)?;
+    let bytes = tokio::fs::read(path)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    Ok(([(header::CONTENT_TYPE, artifact_mime_type(&id))], bytes))
+}
+
+/// Query parameters for GET /browser/state
+#[derive(Debug, Deserialize)]
+struct BrowserStateQuery {
+    /// Tab to report on (default: the active tab)
+    tab_id: Option<String>,
+}
+
+/// Snapshot of the browser for rendering its chrome
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BrowserStateResponse {
+    pub tab_id: String,
+    pub current_url: String,
+    pub is_loading: bool,
+    pub allowed_domains: Vec<String>,
+    pub has_consent: bool,
+}
+
+/// Report a tab's URL and loading state with the allowlist and consent:
+/// GET /browser/state?tab_id=
+async fn get_browser_state(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<BrowserStateQuery>,
+) -> Result<Json<BrowserStateResponse>, StatusCode> {
+    let manager = &state.browser_manager;
+    let tab_id = match query.tab_id {
+        Some(tab_id) => tab_id,
+        None => manager.active_tab().await,
+    };
+    let current_url = manager
+        .get_current_url(Some(&tab_id))
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let mut allowed_domains = manager.get_allowed_domains().await;
+    allowed_domains.sort();
+
+    Ok(Json(BrowserStateResponse {
+        is_loading: manager.is_loading(Some(&tab_id)).await,
+        tab_id,
+        current_url,
+        allowed_domains,
+        // Polling must not keep consent alive
+        has_consent: manager.peek_consent().await,
+    }))
+}
+
+/// Query parameters for GET /browser/audit
+#[derive(Debug, Deserialize)]
+struct BrowserAuditQuery {
+    /// Most recent entries to return (default: all that are kept)
+    limit: Option<usize>,
+}
+
+/// List recent browser commands with their outcomes, oldest first:
+/// GET /browser/audit?limit=
+async fn get_browser_audit(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<BrowserAuditQuery>,
+) -> Json<Vec<BrowserAuditEntry>> {
+    Json(
+        state
+            .browser_manager
+            .get_audit_log(query.limit.unwrap_or(usize::MAX))
+            .await,
+    )
+}
+
+/// Revoke a domain from the allowlist: DELETE /browser/allowlist/{domain}
+async fn remove_allowed_domain(
+    State(state): State<Arc<AppState>>,
+    Path(domain): Path<String>,
+) -> StatusCode {
+    if state.browser_manager.remove_allowed_domain(&domain).await {
+        info!("[Browser] Removed '{}' from the allowlist", domain);
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/ws/browser", get(websocket_handler))
+        .route("/artifacts/{id}", get(get_artifact))
+        .route("/browser/state", get(get_browser_state))
+        .route("/browser/audit", get(get_browser_audit))
+        .route("/browser/allowlist/{domain}", delete(remove_allowed_domain))
         .with_state(state)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mts::session::SessionType;
 
     #[test]
     fn test_domain_pattern_matching() {
@@ -484,14 +2081,1101 @@ mod tests {
         assert!(matches_domain_pattern("localhost", "localhost"));
     }
 
+    #[test]
+    fn test_regex_allowlist_entries_match_full_url() {
+        let pattern = r"re:^https://.*\.corp\.internal/docs/";
+        let entry = AllowlistEntry::new(pattern, TrustLevel::RequireConsent).unwrap();
+        let matches = |url: &str| {
+            let url = url::Url::parse(url).unwrap();
+            entry.matches(pattern, &url, url.host_str().unwrap())
+        };
+
+        assert!(matches("https://wiki.corp.internal/docs/onboarding"));
+        assert!(matches("https://eng.wiki.corp.internal/docs/"));
+        assert!(!matches("https://wiki.corp.internal/admin"));
+        assert!(!matches("http://wiki.corp.internal/docs/onboarding"));
+        assert!(!matches("https://corp.internal.evil.com/docs/"));
+
+        // Literal and wildcard entries keep matching on the host alone
+        let url = url::Url::parse("https://api.github.com/repos").unwrap();
+        let wildcard = AllowlistEntry::new("*.github.com", TrustLevel::RequireConsent).unwrap();
+        assert!(wildcard.matches("*.github.com", &url, "api.github.com"));
+        assert!(AllowlistEntry::new("re:[unclosed", TrustLevel::RequireConsent).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_regex_entries_are_rejected() {
+        let manager = BrowserSessionManager::new();
+        let error = manager
+            .add_allowed_domain("re:^https://(docs".to_string())
+            .await
+            .unwrap_err();
+        assert!(error.contains("Invalid allowlist regex"));
+        manager
+            .add_allowed_domain(r"re:^https://.*\.corp\.internal/docs/".to_string())
+            .await
+            .unwrap();
+
+        let domains = manager.get_allowed_domains().await;
+        assert!(!domains.iter().any(|d| d.contains("(docs")));
+        assert_eq!(
+            manager
+                .url_trust_level("https://wiki.corp.internal/docs/a")
+                .await,
+            Some(TrustLevel::RequireConsent)
+        );
+        assert_eq!(
+            manager
+                .url_trust_level("https://wiki.corp.internal/other")
+                .await,
+            None
+        );
+    }
+
     #[test]
     fn test_command_id_extraction() {
         let cmd = BrowserCommand::Navigate {
             url: "https://example.com".to_string(),
+            session_id: None,
+            tab_id: None,
             command_id: "test-123".to_string(),
         };
         assert_eq!(cmd.command_id(), "test-123");
         assert_eq!(cmd.type_name(), "navigate");
         assert_eq!(cmd.url(), Some("https://example.com"));
     }
+
+    #[test]
+    fn test_drag_and_drop_carries_both_selectors() {
+        let json = r##"{"type":"DragAndDrop","source_selector":"#card-1","target_selector":"#done","command_id":"drag-1"}"##;
+        let command: BrowserCommand = serde_json::from_str(json).unwrap();
+        let BrowserCommand::DragAndDrop {
+            ref source_selector,
+            ref target_selector,
+            ..
+        } = command
+        else {
+            panic!("Expected DragAndDrop, got {:?}", command);
+        };
+        assert_eq!(source_selector, "#card-1");
+        assert_eq!(target_selector, "#done");
+        assert_eq!(command.type_name(), "drag_and_drop");
+        assert_eq!(command.selector(), Some("#card-1"));
+    }
+
+    #[test]
+    fn test_select_option_matches_by_value_by_default() {
+        let json = r##"{"type":"SelectOption","selector":"#country","value":"ca","command_id":"select-1"}"##;
+        let command: BrowserCommand = serde_json::from_str(json).unwrap();
+        let BrowserCommand::SelectOption {
+            ref value,
+            by_label,
+            ..
+        } = command
+        else {
+            panic!("Expected SelectOption, got {:?}", command);
+        };
+        assert_eq!(value, "ca");
+        assert!(!by_label);
+        assert_eq!(command.type_name(), "select_option");
+        assert_eq!(command.selector(), Some("#country"));
+        assert!(command.requires_page());
+    }
+
+    #[test]
+    fn test_screenshot_format_defaults_to_png() {
+        let command: BrowserCommand =
+            serde_json::from_str(r#"{"type":"Screenshot","command_id":"shot-1"}"#).unwrap();
+        assert!(matches!(
+            command,
+            BrowserCommand::Screenshot {
+                format: ScreenshotFormat::Png,
+                quality: None,
+                ..
+            }
+        ));
+
+        let command: BrowserCommand = serde_json::from_str(
+            r#"{"type":"Screenshot","format":"jpeg","quality":60,"command_id":"shot-2"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            command,
+            BrowserCommand::Screenshot {
+                format: ScreenshotFormat::Jpeg,
+                quality: Some(60),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_set_viewport_is_forwarded_before_navigation() {
+        let state = AppState::for_tests().await;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        state.browser_manager.set_sender(tx).await;
+        let (reply, _results) = mpsc::unbounded_channel();
+
+        let command: BrowserCommand = serde_json::from_str(
+            r#"{"type":"SetViewport","width":390,"height":844,"command_id":"viewport-1"}"#,
+        )
+        .unwrap();
+        assert_eq!(command.type_name(), "set_viewport");
+        handle_browser_command(command, None, &state, &reply).await;
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(BrowserEvent::ExecuteCommand {
+                command: BrowserCommand::SetViewport {
+                    width: 390,
+                    height: 844,
+                    ..
+                }
+            })
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_scrape_before_navigation_reports_no_page_loaded() {
+        let state = AppState::for_tests().await;
+        let (reply, mut results) = mpsc::unbounded_channel();
+
+        let command = BrowserCommand::ExtractDOM {
+            selector: None,
+            tab_id: None,
+            command_id: "scrape-1".to_string(),
+        };
+        handle_browser_command(command, None, &state, &reply).await;
+
+        match results.recv().await {
+            Some(BrowserEvent::CommandResult {
+                command_id,
+                success,
+                error_code,
+                ..
+            }) => {
+                assert_eq!(command_id, "scrape-1");
+                assert!(!success);
+                assert_eq!(error_code, Some(BrowserErrorCode::NoPageLoaded));
+            }
+            other => panic!("Expected CommandResult, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_navigation_rate_limit_exhausts_bucket() {
+        let state = AppState::for_tests().await;
+        state.browser_manager.set_consent(true).await;
+        let (tx, _rx) = mpsc::unbounded_channel();
+        state.browser_manager.set_sender(tx).await;
+        let (reply, mut results) = mpsc::unbounded_channel();
+        let navigate = |session: &str, i: u32| BrowserCommand::Navigate {
+            url: format!("https://github.com/{}", i),
+            session_id: Some(session.to_string()),
+            tab_id: None,
+            command_id: format!("{}-nav-{}", session, i),
+        };
+
+        for i in 0..DEFAULT_NAVIGATIONS_PER_MINUTE {
+            handle_browser_command(navigate("looping", i), None, &state, &reply).await;
+        }
+        assert!(results.try_recv().is_err());
+
+        handle_browser_command(
+            navigate("looping", DEFAULT_NAVIGATIONS_PER_MINUTE),
+            None,
+            &state,
+            &reply,
+        )
+        .await;
+        match results.try_recv() {
+            Ok(BrowserEvent::CommandResult {
+                success,
+                error,
+                error_code,
+                ..
+            }) => {
+                assert!(!success);
+                assert_eq!(error_code, Some(BrowserErrorCode::RateLimited));
+                let error = error.unwrap();
+                assert!(error.contains("retry after 2 seconds"), "{}", error);
+            }
+            other => panic!("Expected CommandResult, got {:?}", other),
+        }
+
+        // Other sessions keep their own allowance
+        handle_browser_command(navigate("other", 0), None, &state, &reply).await;
+        assert!(results.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_navigation_rate_limit_can_be_disabled() {
+        let manager = BrowserSessionManager::new().navigations_per_minute(0);
+        for _ in 0..DEFAULT_NAVIGATIONS_PER_MINUTE * 2 {
+            assert!(manager.take_navigation_token(None).await.is_ok());
+        }
+
+        let limited = BrowserSessionManager::new().navigations_per_minute(1);
+        assert!(limited.take_navigation_token(None).await.is_ok());
+        let retry_after = limited.take_navigation_token(None).await.unwrap_err();
+        assert!(retry_after > Duration::from_secs(59));
+    }
+
+    #[tokio::test]
+    async fn test_queue_rejects_commands_beyond_cap() {
+        let manager = BrowserSessionManager::new().max_queue_len(3);
+        let navigate = |i: usize| BrowserCommand::Navigate {
+            url: format!("https://github.com/{}", i),
+            session_id: None,
+            tab_id: None,
+            command_id: format!("nav-{}", i),
+        };
+
+        let mut accepted = 0;
+        for i in 0..10 {
+            if manager.queue_command(navigate(i)).await {
+                accepted += 1;
+            }
+        }
+        assert_eq!(accepted, 3);
+        assert_eq!(manager.queued_command_count().await, 3);
+
+        // The earliest commands are the ones kept
+        let queued: Vec<String> = manager
+            .process_queued_commands()
+            .await
+            .iter()
+            .map(|command| command.command_id().to_string())
+            .collect();
+        assert_eq!(queued, vec!["nav-0", "nav-1", "nav-2"]);
+        assert!(manager.queue_command(navigate(10)).await);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_command_rejected_by_full_queue_fails() {
+        let state = AppState::for_tests().await;
+        let (reply, mut results) = mpsc::unbounded_channel();
+
+        for i in 0..DEFAULT_MAX_QUEUE_LEN {
+            let command = BrowserCommand::Navigate {
+                url: "https://github.com/".to_string(),
+                session_id: None,
+                tab_id: None,
+                command_id: format!("nav-{}", i),
+            };
+            handle_browser_command(command, None, &state, &reply).await;
+        }
+        assert_eq!(
+            state.browser_manager.queued_command_count().await,
+            DEFAULT_MAX_QUEUE_LEN
+        );
+        assert!(results.try_recv().is_err());
+
+        let overflow = BrowserCommand::Navigate {
+            url: "https://github.com/".to_string(),
+            session_id: None,
+            tab_id: None,
+            command_id: "nav-overflow".to_string(),
+        };
+        handle_browser_command(overflow, None, &state, &reply).await;
+        match results.try_recv() {
+            Ok(BrowserEvent::CommandResult {
+                command_id,
+                success,
+                ..
+            }) => {
+                assert_eq!(command_id, "nav-overflow");
+                assert!(!success);
+            }
+            other => panic!("Expected CommandResult, got {:?}", other),
+        }
+        assert_eq!(
+            state.browser_manager.queued_command_count().await,
+            DEFAULT_MAX_QUEUE_LEN
+        );
+        assert_eq!(
+            state.browser_manager.pending_command_count().await,
+            DEFAULT_MAX_QUEUE_LEN
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_auto_allow_domain_skips_consent_gate() {
+        let state = AppState::for_tests().await;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        state.browser_manager.set_sender(tx).await;
+        let (reply, _results) = mpsc::unbounded_channel();
+        state
+            .browser_manager
+            .add_allowed_domain_with_trust("docs.internal".to_string(), TrustLevel::AutoAllow)
+            .await
+            .unwrap();
+
+        let trusted = BrowserCommand::Navigate {
+            url: "https://docs.internal/guide".to_string(),
+            session_id: None,
+            tab_id: None,
+            command_id: "nav-trusted".to_string(),
+        };
+        handle_browser_command(trusted, None, &state, &reply).await;
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(BrowserEvent::ExecuteCommand { .. })
+        ));
+        assert_eq!(state.browser_manager.queued_command_count().await, 0);
+
+        let gated = BrowserCommand::Navigate {
+            url: "https://en.wikipedia.org/wiki/Rust".to_string(),
+            session_id: None,
+            tab_id: None,
+            command_id: "nav-gated".to_string(),
+        };
+        handle_browser_command(gated, None, &state, &reply).await;
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(BrowserEvent::ConsentRequired { .. })
+        ));
+        assert_eq!(state.browser_manager.queued_command_count().await, 1);
+
+        let counts = state.browser_manager.get_command_counts().await;
+        assert_eq!(counts["navigate"]["forwarded"], 1);
+        assert_eq!(counts["navigate"]["queued"], 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_history_moves_bypass_allowlist() {
+        let state = AppState::for_tests().await;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        state.browser_manager.set_sender(tx).await;
+        let (reply, _results) = mpsc::unbounded_channel();
+        // Not on the allowlist, but already loaded
+        state
+            .browser_manager
+            .set_current_url(None, "https://example.com/form".to_string())
+            .await;
+
+        for command in [
+            BrowserCommand::GoBack {
+                tab_id: None,
+                command_id: "back-1".to_string(),
+            },
+            BrowserCommand::GoForward {
+                tab_id: None,
+                command_id: "forward-1".to_string(),
+            },
+            BrowserCommand::Reload {
+                tab_id: None,
+                command_id: "reload-1".to_string(),
+            },
+        ] {
+            assert!(command.requires_page());
+            assert_eq!(command.url(), None);
+            handle_browser_command(command, None, &state, &reply).await;
+            assert!(matches!(
+                rx.try_recv(),
+                Ok(BrowserEvent::ExecuteCommand { .. })
+            ));
+        }
+
+        assert_eq!(state.browser_manager.queued_command_count().await, 0);
+        let counts = state.browser_manager.get_command_counts().await;
+        for command_type in ["go_back", "go_forward", "reload"] {
+            assert_eq!(counts[command_type]["forwarded"], 1);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_scheduled_session_skips_consent_gate() {
+        let state = AppState::for_tests().await;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        state.browser_manager.set_sender(tx).await;
+        let (reply, _results) = mpsc::unbounded_channel();
+        state.browser_manager.set_scheduled_auto_consent(true).await;
+
+        let scheduled = SessionManager::create_session(
+            std::path::PathBuf::default(),
+            "scheduled-browser".to_string(),
+            SessionType::Scheduled,
+        )
+        .await
+        .unwrap();
+        SessionManager::update_session(&scheduled.id)
+            .schedule_id(Some("nightly-digest".to_string()))
+            .apply()
+            .await
+            .unwrap();
+        let interactive = SessionManager::create_session(
+            std::path::PathBuf::default(),
+            "interactive-browser".to_string(),
+            SessionType::User,
+        )
+        .await
+        .unwrap();
+
+        let navigate = |session_id: &str| BrowserCommand::Navigate {
+            url: "https://en.wikipedia.org/wiki/Cron".to_string(),
+            session_id: Some(session_id.to_string()),
+            tab_id: None,
+            command_id: format!("nav-{}", session_id),
+        };
+
+        handle_browser_command(navigate(&scheduled.id), None, &state, &reply).await;
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(BrowserEvent::ExecuteCommand { .. })
+        ));
+        assert_eq!(state.browser_manager.queued_command_count().await, 0);
+
+        handle_browser_command(navigate(&interactive.id), None, &state, &reply).await;
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(BrowserEvent::ConsentRequired { .. })
+        ));
+        assert_eq!(state.browser_manager.queued_command_count().await, 1);
+
+        // Once disabled, scheduled sessions are gated like any other
+        state
+            .browser_manager
+            .set_scheduled_auto_consent(false)
+            .await;
+        handle_browser_command(navigate(&scheduled.id), None, &state, &reply).await;
+        assert_eq!(state.browser_manager.queued_command_count().await, 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_frontend_result_is_delivered_to_issuer() {
+        let state = AppState::for_tests().await;
+        let (frontend, mut frontend_rx) = mpsc::unbounded_channel();
+        state.browser_manager.set_sender(frontend).await;
+        let (reply, mut results) = mpsc::unbounded_channel();
+        state
+            .browser_manager
+            .set_current_url(None, "https://github.com".to_string())
+            .await;
+
+        let click = BrowserCommand::Click {
+            selector: "#submit".to_string(),
+            tab_id: None,
+            command_id: "click-1".to_string(),
+        };
+        handle_browser_command(click, None, &state, &reply).await;
+
+        let Ok(BrowserEvent::ExecuteCommand { command }) = frontend_rx.try_recv() else {
+            panic!("Expected the command to reach the frontend");
+        };
+        assert_eq!(command.command_id(), "click-1");
+        assert_eq!(state.browser_manager.pending_command_count().await, 1);
+        assert!(results.try_recv().is_err());
+
+        let result = BrowserEvent::CommandResult {
+            command_id: "click-1".to_string(),
+            success: true,
+            data: Some("clicked".to_string()),
+            error: None,
+            error_code: None,
+        };
+        handle_browser_event(result.clone(), &state).await;
+
+        match results.try_recv() {
+            Ok(BrowserEvent::CommandResult {
+                command_id, data, ..
+            }) => {
+                assert_eq!(command_id, "click-1");
+                assert_eq!(data.as_deref(), Some("clicked"));
+            }
+            other => panic!("Expected CommandResult, got {:?}", other),
+        }
+        assert_eq!(state.browser_manager.pending_command_count().await, 0);
+        assert_eq!(
+            state.browser_manager.get_history(None, 1).await[0].outcome,
+            "succeeded"
+        );
+
+        // A repeated or unknown result has nobody waiting for it
+        assert!(!state.browser_manager.deliver_result(result).await);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_tabs_keep_their_own_state() {
+        let state = AppState::for_tests().await;
+        let (frontend, mut frontend_rx) = mpsc::unbounded_channel();
+        state.browser_manager.set_sender(frontend).await;
+        let (reply, mut results) = mpsc::unbounded_channel();
+        let manager = &state.browser_manager;
+
+        handle_browser_event(
+            BrowserEvent::NavigationComplete {
+                url: "https://github.com/issues".to_string(),
+                tab_id: None,
+            },
+            &state,
+        )
+        .await;
+        let open = BrowserCommand::OpenTab {
+            tab_id: Some("docs".to_string()),
+            command_id: "open-1".to_string(),
+        };
+        handle_browser_command(open, None, &state, &reply).await;
+        assert!(matches!(
+            frontend_rx.try_recv(),
+            Ok(BrowserEvent::ExecuteCommand { .. })
+        ));
+        assert_eq!(manager.active_tab().await, "docs");
+
+        // The new tab is blank while the default tab keeps its page
+        assert!(!manager.has_page_loaded(None).await);
+        assert!(manager.has_page_loaded(Some(DEFAULT_TAB_ID)).await);
+        handle_browser_event(
+            BrowserEvent::LoadingStateChanged {
+                loading: true,
+                tab_id: Some("docs".to_string()),
+            },
+            &state,
+        )
+        .await;
+        assert!(manager.is_loading(Some("docs")).await);
+        assert!(!manager.is_loading(Some(DEFAULT_TAB_ID)).await);
+
+        let click = |tab_id: Option<&str>, command_id: &str| BrowserCommand::Click {
+            selector: "#submit".to_string(),
+            tab_id: tab_id.map(str::to_string),
+            command_id: command_id.to_string(),
+        };
+        handle_browser_command(click(None, "click-docs"), None, &state, &reply).await;
+        assert!(matches!(
+            results.try_recv(),
+            Ok(BrowserEvent::CommandResult {
+                error_code: Some(BrowserErrorCode::NoPageLoaded),
+                ..
+            })
+        ));
+        handle_browser_command(
+            click(Some(DEFAULT_TAB_ID), "click-main"),
+            None,
+            &state,
+            &reply,
+        )
+        .await;
+        assert!(matches!(
+            frontend_rx.try_recv(),
+            Ok(BrowserEvent::ExecuteCommand { .. })
+        ));
+
+        // Closing the active tab falls back to the default tab
+        let close = BrowserCommand::CloseTab {
+            tab_id: None,
+            command_id: "close-1".to_string(),
+        };
+        handle_browser_command(close, None, &state, &reply).await;
+        assert!(!manager.has_tab("docs").await);
+        assert_eq!(manager.active_tab().await, DEFAULT_TAB_ID);
+        assert_eq!(
+            manager.get_current_url(None).await.as_deref(),
+            Some("https://github.com/issues")
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_invalid_tab_commands_are_rejected() {
+        let state = AppState::for_tests().await;
+        let (frontend, mut frontend_rx) = mpsc::unbounded_channel();
+        state.browser_manager.set_sender(frontend).await;
+        let (reply, mut results) = mpsc::unbounded_channel();
+
+        let commands = [
+            BrowserCommand::ExtractDOM {
+                selector: None,
+                tab_id: Some("missing".to_string()),
+                command_id: "scrape-1".to_string(),
+            },
+            BrowserCommand::OpenTab {
+                tab_id: Some(DEFAULT_TAB_ID.to_string()),
+                command_id: "open-1".to_string(),
+            },
+            BrowserCommand::CloseTab {
+                tab_id: None,
+                command_id: "close-1".to_string(),
+            },
+        ];
+        let expected_errors = [
+            "No tab 'missing' is open",
+            "Tab 'main' is already open",
+            "Tab 'main' is the last open tab",
+        ];
+        for (command, expected) in commands.into_iter().zip(expected_errors) {
+            handle_browser_command(command, None, &state, &reply).await;
+            match results.try_recv() {
+                Ok(BrowserEvent::CommandResult { success, error, .. }) => {
+                    assert!(!success);
+                    assert_eq!(error.as_deref(), Some(expected));
+                }
+                other => panic!("Expected CommandResult, got {:?}", other),
+            }
+        }
+
+        assert!(frontend_rx.try_recv().is_err());
+        assert!(state.browser_manager.has_tab(DEFAULT_TAB_ID).await);
+        let counts = state.browser_manager.get_command_counts().await;
+        assert_eq!(counts["close_tab"]["invalid_tab"], 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_issued_commands_appear_in_their_session_history() {
+        let state = AppState::for_tests().await;
+        let (tx, _rx) = mpsc::unbounded_channel();
+        state.browser_manager.set_sender(tx).await;
+        let (reply, mut results) = mpsc::unbounded_channel();
+
+        let blocked = BrowserCommand::Navigate {
+            url: "https://example.com".to_string(),
+            session_id: Some("session-1".to_string()),
+            tab_id: None,
+            command_id: "nav-1".to_string(),
+        };
+        handle_browser_command(blocked, Some("session-1"), &state, &reply).await;
+        let scrape = BrowserCommand::ExtractDOM {
+            selector: Some("main".to_string()),
+            tab_id: None,
+            command_id: "scrape-1".to_string(),
+        };
+        handle_browser_command(scrape, Some("session-1"), &state, &reply).await;
+        let other = BrowserCommand::Click {
+            selector: "#submit".to_string(),
+            tab_id: None,
+            command_id: "click-1".to_string(),
+        };
+        handle_browser_command(other, Some("session-2"), &state, &reply).await;
+        while results.try_recv().is_ok() {}
+
+        let query = BrowserCommand::GetHistory {
+            limit: 10,
+            tab_id: None,
+            command_id: "history-1".to_string(),
+        };
+        handle_browser_command(query, Some("session-1"), &state, &reply).await;
+
+        let Some(BrowserEvent::CommandResult {
+            command_id,
+            data: Some(data),
+            ..
+        }) = results.recv().await
+        else {
+            panic!("Expected history result");
+        };
+        assert_eq!(command_id, "history-1");
+        let history: Vec<BrowserHistoryEntry> = serde_json::from_str(&data).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].command_id, "nav-1");
+        assert_eq!(history[0].target.as_deref(), Some("https://example.com"));
+        assert_eq!(history[0].outcome, "blocked");
+        assert_eq!(history[1].command_id, "scrape-1");
+        assert_eq!(history[1].outcome, "no_page_loaded");
+
+        let manager = &state.browser_manager;
+        assert_eq!(manager.get_history(Some("session-1"), 1).await.len(), 1);
+        assert!(manager.get_history(None, 10).await.is_empty());
+        manager.clear_history("session-1").await;
+        assert!(manager.get_history(Some("session-1"), 10).await.is_empty());
+        let remaining = manager.get_history(Some("session-2"), 10).await;
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].command_id, "click-1");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_artifact_route_serves_saved_file() {
+        use axum::body::Body;
+        use tower::ServiceExt;
+
+        let id = mts_mcp::browser::artifacts::save_artifact(b"png-bytes", "png")
+            .await
+            .unwrap();
+        let state = AppState::for_tests().await;
+
+        let request = axum::http::Request::builder()
+            .uri(format!("/artifacts/{}", id))
+            .body(Body::empty())
+            .unwrap();
+        let response = routes(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"png-bytes");
+
+        let request = axum::http::Request::builder()
+            .uri("/artifacts/..")
+            .body(Body::empty())
+            .unwrap();
+        let response = routes(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    /// Serve a small file, an oversized file and redirects on a local port
+    async fn spawn_download_server() -> String {
+        let app = Router::new()
+            .route(
+                "/files/Report.TXT",
+                get(|| async { ([(header::CONTENT_TYPE, "text/plain")], "report body") }),
+            )
+            .route(
+                "/moved",
+                get(|| async { axum::response::Redirect::temporary("/files/Report.TXT") }),
+            )
+            .route(
+                "/offsite",
+                get(|| async { axum::response::Redirect::temporary("https://example.com/x.zip") }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_download_saves_file_after_redirect() {
+        let base = spawn_download_server().await;
+        let manager = BrowserSessionManager::new();
+
+        let file = download_file(&manager, &format!("{}/moved", base))
+            .await
+            .unwrap();
+        assert_eq!(file.url, format!("{}/files/Report.TXT", base));
+        assert_eq!(file.size, 11);
+        assert_eq!(file.content_type, "text/plain");
+        assert_eq!(file.path.extension().unwrap(), "txt");
+        assert_eq!(tokio::fs::read(&file.path).await.unwrap(), b"report body");
+
+        let err = download_file(&manager, &format!("{}/offsite", base))
+            .await
+            .unwrap_err();
+        assert_eq!(err, "URL 'https://example.com/x.zip' not in allowlist");
+    }
+
+    #[tokio::test]
+    async fn test_download_rejects_files_over_limit() {
+        let base = spawn_download_server().await;
+        let manager = BrowserSessionManager::new().max_download_bytes(4);
+
+        let err = download_file(&manager, &format!("{}/files/Report.TXT", base))
+            .await
+            .unwrap_err();
+        assert!(err.contains("exceeds the 4 byte limit"), "{}", err);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_download_outside_allowlist_is_blocked() {
+        let state = AppState::for_tests().await;
+        let (reply, mut results) = mpsc::unbounded_channel();
+
+        let command = BrowserCommand::Download {
+            url: "https://example.com/setup.exe".to_string(),
+            command_id: "download-1".to_string(),
+        };
+        handle_browser_command(command, None, &state, &reply).await;
+        let Ok(BrowserEvent::CommandResult { success, error, .. }) = results.try_recv() else {
+            panic!("Expected a command result");
+        };
+        assert!(!success);
+        assert_eq!(
+            error.as_deref(),
+            Some("URL 'https://example.com/setup.exe' not in allowlist")
+        );
+
+        let counts = state.browser_manager.get_command_counts().await;
+        assert_eq!(counts["download"]["blocked"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_and_consent_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(BROWSER_STATE_FILE);
+
+        let manager = BrowserSessionManager::load_or_default(path.clone());
+        assert_eq!(manager.get_allowed_domains().await.len(), 5);
+        manager
+            .add_allowed_domain("wiki.corp.internal".to_string())
+            .await
+            .unwrap();
+        assert!(manager.remove_allowed_domain("*.github.com").await);
+        manager.set_consent(true).await;
+
+        let restarted = BrowserSessionManager::load_or_default(path.clone());
+        let domains = restarted.get_allowed_domains().await;
+        assert!(domains.contains(&"wiki.corp.internal".to_string()));
+        assert!(!domains.contains(&"*.github.com".to_string()));
+        assert!(restarted.has_consent().await);
+
+        std::fs::write(&path, "{not json").unwrap();
+        let recovered = BrowserSessionManager::load_or_default(path);
+        assert!(recovered
+            .get_allowed_domains()
+            .await
+            .contains(&"*.github.com".to_string()));
+        assert!(!recovered.has_consent().await);
+    }
+
+    #[tokio::test]
+    async fn test_configured_allowed_domains_replace_defaults() {
+        let manager = BrowserSessionManager::with_allowed_domains(vec![
+            "docs.corp.internal".to_string(),
+            "re:^https://(docs".to_string(),
+        ]);
+        assert_eq!(
+            manager.get_allowed_domains().await,
+            vec!["docs.corp.internal".to_string()]
+        );
+        assert_eq!(
+            manager
+                .url_trust_level("https://docs.corp.internal/a")
+                .await,
+            Some(TrustLevel::RequireConsent)
+        );
+        assert_eq!(manager.url_trust_level("https://github.com").await, None);
+
+        // Saved allowlists still win over the configured defaults
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(BROWSER_STATE_FILE);
+        BrowserSessionManager::load_or_default(path.clone())
+            .add_allowed_domain("wiki.corp.internal".to_string())
+            .await
+            .unwrap();
+        let restarted = BrowserSessionManager::load_or(
+            path,
+            BrowserSessionManager::with_allowed_domains(vec!["docs.corp.internal".to_string()]),
+        );
+        let domains = restarted.get_allowed_domains().await;
+        assert!(domains.contains(&"wiki.corp.internal".to_string()));
+        assert!(!domains.contains(&"docs.corp.internal".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_consent_expires_after_inactivity() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(BROWSER_STATE_FILE);
+        let manager = BrowserSessionManager::load_or_default(path.clone())
+            .consent_ttl(Some(Duration::from_millis(200)));
+        manager.set_consent(true).await;
+
+        // Relying on consent keeps it alive
+        for _ in 0..3 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert!(manager.has_consent().await);
+        }
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(!manager.has_consent().await);
+        let restarted = BrowserSessionManager::load_or_default(path.clone());
+        assert!(!restarted.has_consent().await);
+
+        // Consent saved before expiry existed never carries over
+        std::fs::write(
+            &path,
+            r#"{"allowed_domains":{"localhost":"RequireConsent"},"user_consented":true}"#,
+        )
+        .unwrap();
+        let legacy = BrowserSessionManager::load_or_default(path);
+        assert!(!legacy.has_consent().await);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_expired_consent_asks_again() {
+        let state = AppState::for_tests().await;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        state.browser_manager.set_sender(tx).await;
+        let (reply, _results) = mpsc::unbounded_channel();
+        // Consent granted an hour ago and left unused since
+        state.browser_manager.set_consent(true).await;
+        *state.browser_manager.consent_active_ms.write().await =
+            Some(current_timestamp() - 60 * 60 * 1000);
+
+        let command = BrowserCommand::Navigate {
+            url: "https://github.com/".to_string(),
+            session_id: None,
+            tab_id: None,
+            command_id: "nav-1".to_string(),
+        };
+        handle_browser_command(command, None, &state, &reply).await;
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(BrowserEvent::ConsentRequired { .. })
+        ));
+        assert_eq!(state.browser_manager.queued_command_count().await, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_remove_allowed_domain_route() {
+        use axum::body::Body;
+        use tower::ServiceExt;
+
+        let state = AppState::for_tests().await;
+        assert!(state
+            .browser_manager
+            .url_trust_level("https://en.wikipedia.org/wiki/Rust")
+            .await
+            .is_some());
+
+        let delete_domain = |domain: &str| {
+            axum::http::Request::builder()
+                .method("DELETE")
+                .uri(format!("/browser/allowlist/{}", domain))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = routes(state.clone())
+            .oneshot(delete_domain("*.wikipedia.org"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(state
+            .browser_manager
+            .url_trust_level("https://en.wikipedia.org/wiki/Rust")
+            .await
+            .is_none());
+
+        let response = routes(state)
+            .oneshot(delete_domain("*.wikipedia.org"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_browser_state_route() {
+        use axum::body::Body;
+        use tower::ServiceExt;
+
+        let state = AppState::for_tests().await;
+        let manager = &state.browser_manager;
+        manager
+            .set_current_url(None, "https://github.com/".to_string())
+            .await;
+        manager.set_loading(None, true).await;
+        manager.set_consent(true).await;
+
+        let get_state = |uri: &str| {
+            axum::http::Request::builder()
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = routes(state.clone())
+            .oneshot(get_state("/browser/state"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let browser: BrowserStateResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(browser.tab_id, DEFAULT_TAB_ID);
+        assert_eq!(browser.current_url, "https://github.com/");
+        assert!(browser.is_loading);
+        assert!(browser.has_consent);
+        assert!(browser
+            .allowed_domains
+            .contains(&"*.github.com".to_string()));
+
+        let response = routes(state)
+            .oneshot(get_state("/browser/state?tab_id=missing"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_browser_audit_route() {
+        use axum::body::Body;
+        use tower::ServiceExt;
+
+        let state = AppState::for_tests().await;
+        state.browser_manager.set_consent(true).await;
+        let (tx, _rx) = mpsc::unbounded_channel();
+        state.browser_manager.set_sender(tx).await;
+        let (reply, _results) = mpsc::unbounded_channel();
+
+        let blocked = BrowserCommand::Navigate {
+            url: "https://example.com".to_string(),
+            session_id: None,
+            tab_id: None,
+            command_id: "nav-1".to_string(),
+        };
+        handle_browser_command(blocked, None, &state, &reply).await;
+        let allowed = BrowserCommand::Navigate {
+            url: "https://github.com".to_string(),
+            session_id: None,
+            tab_id: None,
+            command_id: "nav-2".to_string(),
+        };
+        handle_browser_command(allowed, None, &state, &reply).await;
+        state
+            .browser_manager
+            .record_history_result("nav-2", true, None)
+            .await;
+        let query = BrowserCommand::GetHistory {
+            limit: 10,
+            tab_id: None,
+            command_id: "history-1".to_string(),
+        };
+        handle_browser_command(query, None, &state, &reply).await;
+
+        let get_audit = |uri: &str| {
+            axum::http::Request::builder()
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = routes(state.clone())
+            .oneshot(get_audit("/browser/audit"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let audit: Vec<BrowserAuditEntry> = serde_json::from_slice(&body).unwrap();
+        let outcomes: Vec<(&str, &str)> = audit
+            .iter()
+            .map(|entry| (entry.command_id.as_str(), entry.outcome.as_str()))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("nav-1", "blocked"),
+                ("nav-2", "succeeded"),
+                ("history-1", "answered")
+            ]
+        );
+        assert_eq!(audit[0].command_type, "navigate");
+        assert_eq!(audit[0].url.as_deref(), Some("https://example.com"));
+        assert!(chrono::DateTime::parse_from_rfc3339(&audit[0].timestamp).is_ok());
+
+        let response = routes(state)
+            .oneshot(get_audit("/browser/audit?limit=1"))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let audit: Vec<BrowserAuditEntry> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].command_id, "history-1");
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_keeps_most_recent_entries() {
+        let manager = BrowserSessionManager::new().audit_limit(2);
+        for i in 0..3 {
+            let command = BrowserCommand::Click {
+                selector: format!("#button-{}", i),
+                tab_id: None,
+                command_id: format!("click-{}", i),
+            };
+            manager.record_audit(&command, "forwarded").await;
+        }
+
+        let audit = manager.get_audit_log(usize::MAX).await;
+        assert_eq!(audit.len(), 2);
+        assert_eq!(audit[0].command_id, "click-1");
+        assert_eq!(audit[1].selector.as_deref(), Some("#button-2"));
+    }
 }
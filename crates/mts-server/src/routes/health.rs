@@ -0,0 +1,198 @@
+use crate::state::AppState;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use mts::config::Config;
+use mts::providers::create_with_named_model;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::{IntoParams, ToSchema};
+
+/// How long the optional provider check waits for the provider to answer
+const PROVIDER_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Health of the server or one of its subsystems
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    Unavailable,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SubsystemHealth {
+    pub status: HealthStatus,
+    pub detail: String,
+}
+
+impl SubsystemHealth {
+    fn ok(detail: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Ok,
+            detail: detail.into(),
+        }
+    }
+
+    fn unavailable(detail: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Unavailable,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HealthResponse {
+    /// Ok only when every checked subsystem is ok
+    pub status: HealthStatus,
+    /// Checked subsystems by name; empty for the liveness probe
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub subsystems: BTreeMap<String, SubsystemHealth>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct ReadinessQuery {
+    /// Also check that the configured provider can be created and answers a
+    /// model listing request. This calls out to the provider, so it is opt-in.
+    #[serde(default)]
+    pub check_provider: bool,
+}
+
+#[utoipa::path(get, path = "/healthz",
+    responses(
+        (status = 200, description = "The server is running", body = HealthResponse),
+    )
+)]
+async fn healthz() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: HealthStatus::Ok,
+        subsystems: BTreeMap::new(),
+    })
+}
+
+#[utoipa::path(get, path = "/readyz",
+    params(ReadinessQuery),
+    responses(
+        (status = 200, description = "Every checked subsystem is ready", body = HealthResponse),
+        (status = 503, description = "At least one checked subsystem is unavailable", body = HealthResponse),
+    )
+)]
+async fn readyz(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ReadinessQuery>,
+) -> (StatusCode, Json<HealthResponse>) {
+    let mut subsystems = BTreeMap::new();
+
+    let browser = if state.browser_manager.is_connected().await {
+        SubsystemHealth::ok("browser frontend connected")
+    } else {
+        SubsystemHealth::unavailable("no browser frontend connected to /ws/browser")
+    };
+    subsystems.insert("browser".to_string(), browser);
+
+    if query.check_provider {
+        subsystems.insert("provider".to_string(), check_provider().await);
+    }
+
+    let ready = subsystems
+        .values()
+        .all(|subsystem| subsystem.status == HealthStatus::Ok);
+    let (code, status) = if ready {
+        (StatusCode::OK, HealthStatus::Ok)
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, HealthStatus::Unavailable)
+    };
+    (code, Json(HealthResponse { status, subsystems }))
+}
+
+/// Create the configured provider and ask it for its models. Providers that can't
+/// list models are reported as ok once they are created.
+async fn check_provider() -> SubsystemHealth {
+    let config = Config::global();
+    let (Ok(provider_name), Ok(model_name)) = (config.get_mts_provider(), config.get_mts_model())
+    else {
+        return SubsystemHealth::unavailable("no provider and model configured");
+    };
+    let provider = match create_with_named_model(&provider_name, &model_name).await {
+        Ok(provider) => provider,
+        Err(e) => {
+            return SubsystemHealth::unavailable(format!(
+                "could not create provider '{}': {}",
+                provider_name, e
+            ))
+        }
+    };
+
+    match tokio::time::timeout(PROVIDER_CHECK_TIMEOUT, provider.fetch_supported_models()).await {
+        Ok(Ok(Some(_))) => SubsystemHealth::ok(format!("provider '{}' reachable", provider_name)),
+        Ok(Ok(None)) => SubsystemHealth::ok(format!(
+            "provider '{}' configured; it does not list models, so reachability was not checked",
+            provider_name
+        )),
+        Ok(Err(e)) => {
+            SubsystemHealth::unavailable(format!("provider '{}' unreachable: {}", provider_name, e))
+        }
+        Err(_) => SubsystemHealth::unavailable(format!(
+            "provider '{}' did not answer within {}s",
+            provider_name,
+            PROVIDER_CHECK_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+pub fn routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use tokio::sync::mpsc;
+    use tower::ServiceExt;
+
+    async fn get_json(state: Arc<AppState>, uri: &str) -> (StatusCode, HealthResponse) {
+        let request = axum::http::Request::builder()
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        let response = routes(state).oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_readiness_follows_browser_connection() {
+        let state = AppState::for_tests().await;
+
+        let (status, health) = get_json(state.clone(), "/healthz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(health.status, HealthStatus::Ok);
+
+        let (status, health) = get_json(state.clone(), "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(health.status, HealthStatus::Unavailable);
+        assert_eq!(
+            health.subsystems["browser"].status,
+            HealthStatus::Unavailable
+        );
+        assert!(!health.subsystems.contains_key("provider"));
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        state.browser_manager.set_sender(tx).await;
+        let (status, health) = get_json(state, "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(health.subsystems["browser"].status, HealthStatus::Ok);
+    }
+}
@@ -0,0 +1,211 @@
+use crate::background_tasks::TaskStatus;
+use crate::state::AppState;
+use axum::{
+    extract::State,
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{atomic::Ordering, Arc, OnceLock};
+use utoipa::ToSchema;
+
+/// Content type of the Prometheus text exposition format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+static PROMETHEUS: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Handle to the recorder behind the `metrics` macros, installed globally on first use.
+///
+/// Histograms are drained on each scrape; they only get one sample per session,
+/// so no separate upkeep task is needed.
+pub fn prometheus_handle() -> &'static PrometheusHandle {
+    PROMETHEUS.get_or_init(|| {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        if let Err(e) = metrics::set_global_recorder(recorder) {
+            tracing::warn!("Prometheus recorder not installed: {}", e);
+        }
+        handle
+    })
+}
+
+/// Whether the client asked for the Prometheus text format, as scrapers do
+fn wants_prometheus(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| {
+            accept.contains("application/openmetrics-text")
+                || (accept.contains("text/plain") && !accept.contains("application/json"))
+        })
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsResponse {
+    /// Tracked background tasks grouped by status
+    pub tasks_by_status: BTreeMap<String, usize>,
+    /// Sessions started since the server booted
+    pub total_sessions: usize,
+    /// Browser commands grouped by command type, then outcome
+    pub browser_commands: BTreeMap<String, BTreeMap<String, u64>>,
+    /// Browser commands waiting for a connection or consent
+    pub queued_browser_commands: usize,
+}
+
+/// Render the snapshot followed by everything recorded through the
+/// `metrics` macros, in the Prometheus text format
+fn render_prometheus(snapshot: &MetricsResponse) -> String {
+    // Statuses without tasks report 0 rather than keeping their last value
+    for status in TaskStatus::ALL {
+        let status = format!("{:?}", status);
+        let count = snapshot.tasks_by_status.get(&status).copied().unwrap_or(0);
+        metrics::gauge!("mts_background_tasks", "status" => status).set(count as f64);
+    }
+    metrics::counter!("mts_sessions_created_total").absolute(snapshot.total_sessions as u64);
+    for (command_type, outcomes) in &snapshot.browser_commands {
+        for (outcome, count) in outcomes {
+            metrics::counter!(
+                "mts_browser_commands_total",
+                "command_type" => command_type.clone(),
+                "outcome" => outcome.clone()
+            )
+            .absolute(*count);
+        }
+    }
+    metrics::gauge!("mts_queued_browser_commands").set(snapshot.queued_browser_commands as f64);
+
+    prometheus_handle().render()
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Snapshot of in-process counters. Clients accepting \
+            text/plain or OpenMetrics, such as Prometheus, get the Prometheus text format \
+            instead, which also has tool call, session and token counters.",
+            body = MetricsResponse),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+    )
+)]
+async fn metrics(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
+    let snapshot = MetricsResponse {
+        tasks_by_status: state.background_tasks.status_counts().await,
+        total_sessions: state.session_counter.load(Ordering::SeqCst),
+        browser_commands: state.browser_manager.get_command_counts().await,
+        queued_browser_commands: state.browser_manager.queued_command_count().await,
+    };
+    if wants_prometheus(&headers) {
+        (
+            [(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+            render_prometheus(&snapshot),
+        )
+            .into_response()
+    } else {
+        Json(snapshot).into_response()
+    }
+}
+
+pub fn routes(state: Arc<AppState>) -> Router {
+    // Install the recorder before any handler records to it
+    prometheus_handle();
+    Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use tokio_util::sync::CancellationToken;
+    use tower::ServiceExt;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_metrics_reports_activity() {
+        let state = AppState::for_tests().await;
+        state
+            .background_tasks
+            .register_task(
+                "metrics-session".to_string(),
+                CancellationToken::new(),
+                state.background_tasks.new_broadcaster(),
+            )
+            .await
+            .unwrap();
+        state
+            .browser_manager
+            .record_command("navigate", "forwarded")
+            .await;
+
+        let app = routes(state);
+        let request = Request::builder()
+            .uri("/metrics")
+            .header("x-secret-key", "test-secret")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["tasksByStatus"]["Running"], 1);
+        assert_eq!(json["browserCommands"]["navigate"]["forwarded"], 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_metrics_renders_prometheus_for_scrapers() {
+        let state = AppState::for_tests().await;
+        state
+            .browser_manager
+            .record_command("click", "succeeded")
+            .await;
+        let app = routes(state);
+        metrics::counter!("mts_tool_calls_total", "tool_name" => "developer__shell").increment(1);
+
+        let request = Request::builder()
+            .uri("/metrics")
+            .header(
+                "accept",
+                "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5",
+            )
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            PROMETHEUS_CONTENT_TYPE
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains(r#"mts_tool_calls_total{tool_name="developer__shell"}"#));
+        assert!(text
+            .contains(r#"mts_browser_commands_total{command_type="click",outcome="succeeded"} 1"#));
+        assert!(text.contains(r#"mts_background_tasks{status="Cancelled"} 0"#));
+    }
+
+    #[test]
+    fn test_json_is_preferred_when_accepted() {
+        let mut headers = HeaderMap::new();
+        assert!(!wants_prometheus(&headers));
+        headers.insert(
+            header::ACCEPT,
+            "application/json, text/plain, */*".parse().unwrap(),
+        );
+        assert!(!wants_prometheus(&headers));
+        headers.insert(header::ACCEPT, "text/plain".parse().unwrap());
+        assert!(wants_prometheus(&headers));
+    }
+}
@@ -4,7 +4,9 @@ pub mod audio;
 pub mod browser;
 pub mod config_management;
 pub mod errors;
+pub mod health;
 pub mod mcp_ui_proxy;
+pub mod metrics;
 pub mod recipe;
 pub mod recipe_utils;
 pub mod reply;
@@ -24,12 +26,14 @@ use axum::Router;
 pub fn configure(state: Arc<crate::state::AppState>, secret_key: String) -> Router {
     Router::new()
         .merge(status::routes())
+        .merge(health::routes(state.clone()))
         .merge(reply::routes(state.clone()))
         .merge(action_required::routes(state.clone()))
         .merge(agent::routes(state.clone()))
         .merge(audio::routes(state.clone()))
         .merge(browser::routes(state.clone()))
         .merge(config_management::routes(state.clone()))
+        .merge(metrics::routes(state.clone()))
         .merge(recipe::routes(state.clone()))
         .merge(search::routes(state.clone()))
         .merge(session::routes(state.clone()))
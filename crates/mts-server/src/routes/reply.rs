@@ -1,27 +1,44 @@
+use crate::background_tasks::{
+    ApprovalOutcome, BackgroundTaskManager, CleanupOutcome, SequencedEvent, SessionBusy,
+    TaskBroadcaster, TaskStatus, TaskSubscription,
+};
+use crate::routes::errors::ErrorResponse;
 use crate::state::AppState;
 use axum::{
-    extract::{DefaultBodyLimit, Path, State},
-    http::{self, StatusCode},
-    response::IntoResponse,
-    routing::{get, post},
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{self, HeaderMap, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
     Json, Router,
 };
 use bytes::Bytes;
+use flate2::{write::GzEncoder, Compression};
 use futures::{stream::StreamExt, Stream};
+use mts::agents::types::{FallbackProvider, ModelOverride, RetryConfig};
 use mts::agents::{AgentEvent, SessionConfig};
-use mts::conversation::message::{Message, MessageContent, TokenState};
+use mts::config::{Config, MtsMode};
+use mts::context_mgmt::CompactionPolicy;
+use mts::conversation::message::{
+    ActionRequired, ActionRequiredData, Message, MessageContent, TokenState,
+};
 use mts::conversation::Conversation;
+use mts::permission::permission_confirmation::PrincipalType;
+use mts::permission::{Permission, PermissionConfirmation};
+use mts::providers::errors::ProviderError;
 use mts::session::SessionManager;
-use rmcp::model::ServerNotification;
+use rmcp::model::{JsonObject, ServerNotification};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     convert::Infallible,
+    io::Write,
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::mpsc;
 use tokio::time::timeout;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::sync::CancellationToken;
@@ -34,6 +51,8 @@ fn track_tool_telemetry(content: &MessageContent, all_messages: &[Message]) {
                     tool_name = %tool_call.name,
                     "Tool call started"
                 );
+                metrics::counter!("mts_tool_calls_total", "tool_name" => tool_call.name.to_string())
+                    .increment(1);
             }
         }
         MessageContent::ToolResponse(tool_response) => {
@@ -68,6 +87,12 @@ fn track_tool_telemetry(content: &MessageContent, all_messages: &[Message]) {
                 result = %result_status,
                 "Tool call completed"
             );
+            metrics::counter!(
+                "mts_tool_completions_total",
+                "tool_name" => tool_name.to_string(),
+                "result" => result_status
+            )
+            .increment(1);
         }
         _ => {}
     }
@@ -79,18 +104,164 @@ pub struct ChatRequest {
     session_id: String,
     recipe_name: Option<String>,
     recipe_version: Option<String>,
+    /// Opaque tags stored with the session, merged into any existing metadata
+    #[serde(default)]
+    metadata: Option<HashMap<String, String>>,
+    /// Auto-compaction policy for this session (default: global threshold)
+    #[serde(default)]
+    compaction: Option<CompactionPolicy>,
+    /// Providers to fail over to, in order, if the session's provider errors out
+    #[serde(default)]
+    fallback_providers: Option<Vec<FallbackProvider>>,
+    /// Modes pinned to individual tools by name, e.g. `{"developer__text_editor": "approve"}`
+    /// to require approval for edits even in auto mode
+    #[serde(default)]
+    tool_overrides: Option<HashMap<String, MtsMode>>,
+    /// Maximum agent turns without user input for this reply, at least 1
+    /// (default: the global limit)
+    #[serde(default)]
+    max_turns: Option<u32>,
+    /// Token budget for the whole session, at least 1; the reply stops once the
+    /// session's accumulated total crosses it
+    #[serde(default)]
+    max_total_tokens: Option<i32>,
+    /// Success checks and retries to run when the agent finishes
+    #[serde(default)]
+    retry_config: Option<RetryConfig>,
+    /// Model to run this reply on. The override is scoped to this single request:
+    /// the session keeps its own provider and model for later replies
+    #[serde(default)]
+    model_override: Option<String>,
+    /// Provider for `model_override` (default: the session's provider)
+    #[serde(default)]
+    provider_override: Option<String>,
+    /// Client-chosen key that makes the request safe to retry: a later request for
+    /// the same session with the same key streams the task this one started, while
+    /// it runs or shortly after it finishes, instead of starting another
+    #[serde(default)]
+    idempotency_key: Option<String>,
+}
+
+impl ChatRequest {
+    /// Check the per-request limits before starting the agent
+    fn validate(&self) -> Result<(), String> {
+        if self.max_turns == Some(0) {
+            return Err("max_turns must be at least 1".to_string());
+        }
+        if self.max_total_tokens.is_some_and(|budget| budget < 1) {
+            return Err("max_total_tokens must be at least 1".to_string());
+        }
+        if let Some(retry_config) = &self.retry_config {
+            retry_config.validate()?;
+        }
+        if self.provider_override.is_some() && self.model_override.is_none() {
+            return Err("provider_override needs a model_override".to_string());
+        }
+        if self
+            .idempotency_key
+            .as_ref()
+            .is_some_and(|key| key.trim().is_empty())
+        {
+            return Err("idempotency_key must not be empty".to_string());
+        }
+        Ok(())
+    }
+
+    /// Pair the model override with its provider and check the provider is registered
+    /// and, when it lists its models, knows the model
+    async fn resolve_model_override(&self) -> Result<Option<ModelOverride>, String> {
+        let Some(model) = self.model_override.clone() else {
+            return Ok(None);
+        };
+        let provider = match self.provider_override.clone() {
+            Some(provider) => provider,
+            None => SessionManager::get_session(&self.session_id, false)
+                .await
+                .ok()
+                .and_then(|session| session.provider_name)
+                .or_else(|| Config::global().get_mts_provider().ok())
+                .ok_or("no provider configured for model_override")?,
+        };
+
+        let Some((metadata, _)) = mts::providers::providers()
+            .await
+            .into_iter()
+            .find(|(metadata, _)| metadata.name == provider)
+        else {
+            return Err(format!("unknown provider '{}'", provider));
+        };
+        let known = metadata.known_models.is_empty()
+            || metadata.known_models.iter().any(|info| info.name == model);
+        if !known {
+            return Err(format!(
+                "unknown model '{}' for provider '{}'",
+                model, provider
+            ));
+        }
+        Ok(Some(ModelOverride { provider, model }))
+    }
 }
 
 pub struct SseResponse {
     rx: ReceiverStream<String>,
+    gzip: bool,
 }
 
 impl SseResponse {
-    fn new(rx: ReceiverStream<String>) -> Self {
-        Self { rx }
+    pub(crate) fn new(rx: ReceiverStream<String>) -> Self {
+        Self { rx, gzip: false }
+    }
+
+    /// Gzip the stream when the request's Accept-Encoding allows it
+    pub(crate) fn compressed_for(mut self, headers: &HeaderMap) -> Self {
+        self.gzip = accepts_gzip(headers);
+        self
     }
 }
 
+/// Whether an Accept-Encoding header lists gzip (or `*`) without `q=0`
+fn accepts_gzip(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(http::header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|coding| {
+            let mut parts = coding.split(';').map(str::trim);
+            let name = parts.next().unwrap_or_default();
+            let refused = parts.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+            (name.eq_ignore_ascii_case("gzip") || name == "*") && !refused
+        })
+}
+
+/// Compress one event and sync-flush it, so the client can decode every event as
+/// soon as it arrives instead of waiting for the encoder to fill a block
+fn gzip_event(encoder: &mut GzEncoder<Vec<u8>>, event: &[u8]) -> std::io::Result<Bytes> {
+    encoder.write_all(event)?;
+    encoder.flush()?;
+    Ok(Bytes::from(std::mem::take(encoder.get_mut())))
+}
+
+/// Gzip a stream of SSE frames, ending with the gzip trailer once the stream ends
+fn gzip_stream(events: SseResponse) -> impl Stream<Item = std::io::Result<Bytes>> + Send + 'static {
+    let encoder = GzEncoder::new(Vec::new(), Compression::default());
+    futures::stream::unfold(Some((events, encoder)), |state| async move {
+        let (mut events, mut encoder) = state?;
+        match events.next().await {
+            Some(Ok(event)) => {
+                let chunk = gzip_event(&mut encoder, &event);
+                Some((chunk, Some((events, encoder))))
+            }
+            None => Some((encoder.finish().map(Bytes::from), None)),
+        }
+    })
+}
+
 impl Stream for SseResponse {
     type Item = Result<Bytes, Infallible>;
 
@@ -103,15 +274,62 @@ impl Stream for SseResponse {
 
 impl IntoResponse for SseResponse {
     fn into_response(self) -> axum::response::Response {
-        let stream = self;
-        let body = axum::body::Body::from_stream(stream);
-
-        http::Response::builder()
+        let builder = http::Response::builder()
             .header("Content-Type", "text/event-stream")
             .header("Cache-Control", "no-cache")
             .header("Connection", "keep-alive")
-            .body(body)
-            .unwrap()
+            .header("Vary", "Accept-Encoding");
+
+        if self.gzip {
+            builder
+                .header("Content-Encoding", "gzip")
+                .body(axum::body::Body::from_stream(gzip_stream(self)))
+                .unwrap()
+        } else {
+            builder.body(axum::body::Body::from_stream(self)).unwrap()
+        }
+    }
+}
+
+/// Machine-readable cause of an Error event, stable across releases; the event's
+/// `error` text is meant for display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The session's agent could not be created
+    AgentUnavailable,
+    /// The session could not be read
+    SessionUnavailable,
+    /// The request had no message to reply to
+    InvalidRequest,
+    /// The agent could not start the reply
+    ReplyStartFailed,
+    /// The provider is rate limiting requests; retrying later may succeed
+    RateLimited,
+    /// The provider rejected the configured credentials
+    ProviderAuthFailed,
+    /// The conversation no longer fits in the model's context window
+    ContextLengthExceeded,
+    /// The provider failed for another reason
+    ProviderError,
+    /// The agent failed while replying
+    AgentError,
+    /// The subscriber fell behind and missed events; refetch the conversation
+    EventsDropped,
+    /// The server could not encode the event
+    Internal,
+}
+
+impl ErrorCode {
+    /// Classify an error from the agent's reply stream
+    fn from_agent_error(error: &anyhow::Error) -> Self {
+        match error.downcast_ref::<ProviderError>() {
+            Some(ProviderError::RateLimitExceeded { .. }) => ErrorCode::RateLimited,
+            Some(ProviderError::Authentication(_)) => ErrorCode::ProviderAuthFailed,
+            Some(ProviderError::ContextLengthExceeded(_)) => ErrorCode::ContextLengthExceeded,
+            Some(_) => ErrorCode::ProviderError,
+            None => ErrorCode::AgentError,
+        }
     }
 }
 
@@ -120,10 +338,17 @@ impl IntoResponse for SseResponse {
 pub enum MessageEvent {
     Message {
         message: Message,
+        /// Only present when the reply was started with `inline_token_state=true`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        token_state: Option<TokenState>,
+    },
+    /// Sent after a message whenever the session's token counts have changed
+    TokenUpdate {
         token_state: TokenState,
     },
     Error {
         error: String,
+        error_code: ErrorCode,
     },
     Finish {
         reason: String,
@@ -141,9 +366,40 @@ pub enum MessageEvent {
     UpdateConversation {
         conversation: Conversation,
     },
+    /// Sent after a message asking to confirm a tool call. The agent waits until
+    /// the call is approved or denied through `POST /sessions/{session_id}/approve`.
+    ApprovalRequest {
+        request_id: String,
+        tool_name: String,
+        arguments: JsonObject,
+    },
     Ping,
 }
 
+/// ApprovalRequest events for the tool confirmations a message asks for
+fn approval_requests(message: &Message) -> Vec<MessageEvent> {
+    message
+        .content
+        .iter()
+        .filter_map(|content| match content {
+            MessageContent::ActionRequired(ActionRequired {
+                data:
+                    ActionRequiredData::ToolConfirmation {
+                        id,
+                        tool_name,
+                        arguments,
+                        ..
+                    },
+            }) => Some(MessageEvent::ApprovalRequest {
+                request_id: id.clone(),
+                tool_name: tool_name.clone(),
+                arguments: arguments.clone(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
 async fn get_token_state(session_id: &str) -> TokenState {
     SessionManager::get_session(session_id, false)
         .await
@@ -165,48 +421,199 @@ async fn get_token_state(session_id: &str) -> TokenState {
         .unwrap_or_default()
 }
 
+/// How often a running reply sends a Ping to keep the stream alive
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_millis(500);
+/// How long a reply waits on the agent stream before checking for cancellation
+/// and heartbeats again; lower values honor cancellation more responsively
+pub const DEFAULT_STREAM_POLL_TIMEOUT: Duration = Duration::from_millis(500);
+/// Largest `/reply` request body accepted, which bounds the conversation a client
+/// can send in one request
+pub const DEFAULT_MAX_REPLY_BODY_BYTES: usize = 50 * 1024 * 1024;
+
+/// Header an EventSource client sends on reconnect with the id of the last event it saw
+const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+
+/// Serialize an event, reporting a serialization failure as an Error event
+fn event_json(event: &MessageEvent) -> String {
+    serde_json::to_string(event).unwrap_or_else(|e| {
+        serde_json::json!({
+            "type": "Error",
+            "error": format!("Failed to serialize event: {}", e),
+            "error_code": ErrorCode::Internal,
+        })
+        .to_string()
+    })
+}
+
+/// Frame serialized event data for SSE, with an `id:` line when the event has an id
+/// a client can resume from
+pub(crate) fn sse_frame(id: Option<u64>, json: &str) -> String {
+    match id {
+        Some(id) => format!("id: {}\ndata: {}\n\n", id, json),
+        None => format!("data: {}\n\n", json),
+    }
+}
+
+/// Id of the last event an EventSource client saw, from its Last-Event-ID header.
+/// An unparseable id replays everything still buffered.
+fn last_event_id(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(LAST_EVENT_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+}
+
+/// Forward a task subscription to an SSE stream until the task finishes or the
+/// client disconnects. Recent events are replayed first; if the subscriber falls
+/// behind, it gets an Error event counting the dropped events so the client can
+/// refetch the conversation.
+fn forward_subscription(mut subscription: TaskSubscription) -> ReceiverStream<String> {
+    let (tx, rx) = mpsc::channel(100);
+
+    tokio::spawn(async move {
+        // None once the broadcaster closes, i.e. the task finished
+        while let Some(SequencedEvent { id, event }) = subscription.recv().await {
+            let json = event_json(&event);
+
+            if tx.send(sse_frame(id, &json)).await.is_err() {
+                // Client disconnected
+                break;
+            }
+
+            // If this is a Finish event, we're done
+            if matches!(event, MessageEvent::Finish { .. }) {
+                break;
+            }
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+/// What a reply task needs to give up before its agent stream has started
+struct FailedReply<'a> {
+    tx: &'a mpsc::Sender<String>,
+    broadcaster: &'a TaskBroadcaster,
+    bg_tasks: &'a BackgroundTaskManager,
+    session_id: &'a str,
+    idempotency_key: Option<&'a str>,
+}
+
+impl FailedReply<'_> {
+    /// Report the error and finish the stream so subscribers stop waiting, then
+    /// release the idempotency key so a retry starts over
+    async fn report(&self, error: String, error_code: ErrorCode) {
+        stream_event(
+            MessageEvent::Error {
+                error: error.clone(),
+                error_code,
+            },
+            self.tx,
+            Some(self.broadcaster),
+        )
+        .await;
+        stream_event(
+            MessageEvent::Finish {
+                reason: "error".to_string(),
+                token_state: get_token_state(self.session_id).await,
+            },
+            self.tx,
+            Some(self.broadcaster),
+        )
+        .await;
+        let task_id = self.broadcaster.task_id();
+        if let Some(key) = self.idempotency_key {
+            self.bg_tasks
+                .release_idempotency_key(self.session_id, key, task_id);
+        }
+        self.bg_tasks
+            .mark_error(self.session_id, task_id, error)
+            .await;
+    }
+}
+
 /// Stream event to the connected client and optionally broadcast to background subscribers
 async fn stream_event(
     event: MessageEvent,
     tx: &mpsc::Sender<String>,
-    broadcaster: Option<&broadcast::Sender<MessageEvent>>,
+    broadcaster: Option<&TaskBroadcaster>,
 ) {
-    let json = serde_json::to_string(&event).unwrap_or_else(|e| {
-        format!(
-            r#"{{"type":"Error","error":"Failed to serialize event: {}"}}"#,
-            e
-        )
-    });
+    let json = event_json(&event);
 
-    // Broadcast to background subscribers (ignore errors - subscribers may have disconnected)
-    if let Some(bc) = broadcaster {
-        let _ = bc.send(event);
-    }
+    // Broadcast to background subscribers, who may have disconnected
+    let id = broadcaster.map(|bc| bc.send(event));
 
     // Send to connected client (ignore if client disconnected - task continues in background)
-    if tx.send(format!("data: {}\n\n", json)).await.is_err() {
+    if tx.send(sse_frame(id, &json)).await.is_err() {
         tracing::info!("client disconnected, task continues in background");
         // Note: We do NOT cancel the task here - it continues running
     }
 }
 
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct ReplyQuery {
+    /// Also embed the token state in every Message event, for clients that
+    /// don't handle TokenUpdate events yet
+    #[serde(default)]
+    pub inline_token_state: bool,
+}
+
 #[allow(clippy::too_many_lines)]
 #[utoipa::path(
     post,
     path = "/reply",
+    params(ReplyQuery),
     request_body = ChatRequest,
     responses(
-        (status = 200, description = "Streaming response initiated",
+        (status = 200, description = "Streaming response initiated, or the stream of the task a request with the same idempotency_key started",
          body = MessageEvent,
          content_type = "text/event-stream"),
+        (status = 409, description = "Session already has a running reply"),
+        (status = 413, description = "Request body is larger than the configured limit", body = ErrorResponse),
         (status = 424, description = "Agent not initialized"),
         (status = 500, description = "Internal server error")
     )
 )]
 pub async fn reply(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<ReplyQuery>,
+    headers: HeaderMap,
     Json(request): Json<ChatRequest>,
 ) -> Result<SseResponse, StatusCode> {
+    if let Err(e) = request.validate() {
+        tracing::warn!("Rejecting reply request: {}", e);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let model_override = match request.resolve_model_override().await {
+        Ok(model_override) => model_override,
+        Err(e) => {
+            tracing::warn!("Rejecting reply request: {}", e);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+
+    let session_id = request.session_id.clone();
+    let idempotency_key = request.idempotency_key.clone();
+    let broadcaster = state.background_tasks.new_broadcaster();
+
+    // A retry of an accepted request joins that task's stream, even while it is queued
+    if let Some(key) = &idempotency_key {
+        if let Some(subscription) = state.background_tasks.claim_idempotency_key(
+            &session_id,
+            key,
+            &broadcaster,
+            last_event_id(&headers),
+        ) {
+            tracing::info!(
+                "Joining existing reply for session {} by idempotency key",
+                session_id
+            );
+            return Ok(
+                SseResponse::new(forward_subscription(subscription)).compressed_for(&headers)
+            );
+        }
+    }
+
     let session_start = std::time::Instant::now();
 
     tracing::info!(
@@ -215,8 +622,8 @@ pub async fn reply(
         interface = "ui",
         "Session started"
     );
-
-    let session_id = request.session_id.clone();
+    metrics::counter!("mts_session_starts_total", "session_type" => "app", "interface" => "ui")
+        .increment(1);
 
     if let Some(recipe_name) = request.recipe_name.clone() {
         if state.mark_recipe_run_if_absent(&session_id).await {
@@ -233,6 +640,14 @@ pub async fn reply(
                 interface = "ui",
                 "Recipe execution started"
             );
+            metrics::counter!(
+                "mts_recipe_runs_total",
+                "recipe_name" => recipe_name,
+                "recipe_version" => recipe_version,
+                "session_type" => "app",
+                "interface" => "ui"
+            )
+            .increment(1);
         }
     }
 
@@ -241,32 +656,81 @@ pub async fn reply(
     let cancel_token = CancellationToken::new();
 
     let messages = Conversation::new_unvalidated(request.messages);
+    let metadata = request.metadata;
+    let compaction = request.compaction;
+    let fallback_providers = request.fallback_providers;
+    let tool_overrides = request.tool_overrides;
+    let max_turns = request.max_turns;
+    let max_total_tokens = request.max_total_tokens;
+    let inline_token_state = query.inline_token_state;
+    let heartbeat_period = state.heartbeat_interval;
+    let stream_poll_timeout = state.stream_poll_timeout;
+    let retry_config = request.retry_config;
 
     let task_cancel = cancel_token.clone();
     let task_tx = tx.clone();
 
-    // Register this task with the background task manager for durable execution
-    let broadcaster = state
+    // Register this task with the background task manager for durable execution.
+    // If the session is busy, a queued reply registers once the running one finishes
+    let registered = match state
         .background_tasks
-        .register_task(session_id.clone(), cancel_token.clone())
-        .await;
+        .register_task(
+            session_id.clone(),
+            cancel_token.clone(),
+            broadcaster.clone(),
+        )
+        .await
+    {
+        Ok(_) => true,
+        Err(SessionBusy) if state.background_tasks.queues_concurrent_tasks() => {
+            tracing::info!("Queueing reply for busy session {}", session_id);
+            false
+        }
+        Err(SessionBusy) => {
+            tracing::warn!("Rejecting reply for busy session {}", session_id);
+            if let Some(key) = &idempotency_key {
+                state.background_tasks.release_idempotency_key(
+                    &session_id,
+                    key,
+                    broadcaster.task_id(),
+                );
+            }
+            return Err(StatusCode::CONFLICT);
+        }
+    };
     let bg_tasks = state.background_tasks.clone();
     let bg_session_id = session_id.clone();
+    let scope_session_id = session_id.clone();
+
+    let task = async move {
+        if !registered {
+            bg_tasks
+                .register_task_when_idle(
+                    bg_session_id.clone(),
+                    task_cancel.clone(),
+                    broadcaster.clone(),
+                )
+                .await;
+        }
+        let task_id = broadcaster.task_id();
+        let failed = FailedReply {
+            tx: &task_tx,
+            broadcaster: &broadcaster,
+            bg_tasks: &bg_tasks,
+            session_id: &bg_session_id,
+            idempotency_key: idempotency_key.as_deref(),
+        };
 
-    drop(tokio::spawn(async move {
         let agent = match state.get_agent(session_id.clone()).await {
             Ok(agent) => agent,
             Err(e) => {
                 tracing::error!("Failed to get session agent: {}", e);
-                let _ = stream_event(
-                    MessageEvent::Error {
-                        error: format!("Failed to get session agent: {}", e),
-                    },
-                    &task_tx,
-                    Some(&broadcaster),
-                )
-                .await;
-                bg_tasks.mark_error(&bg_session_id).await;
+                failed
+                    .report(
+                        format!("Failed to get session agent: {}", e),
+                        ErrorCode::AgentUnavailable,
+                    )
+                    .await;
                 return;
             }
         };
@@ -275,38 +739,49 @@ pub async fn reply(
             Ok(metadata) => metadata,
             Err(e) => {
                 tracing::error!("Failed to read session for {}: {}", session_id, e);
-                let _ = stream_event(
-                    MessageEvent::Error {
-                        error: format!("Failed to read session: {}", e),
-                    },
-                    &task_tx,
-                    Some(&broadcaster),
-                )
-                .await;
-                bg_tasks.mark_error(&bg_session_id).await;
+                failed
+                    .report(
+                        format!("Failed to read session: {}", e),
+                        ErrorCode::SessionUnavailable,
+                    )
+                    .await;
                 return;
             }
         };
 
+        if let Some(metadata) = metadata.filter(|m| !m.is_empty()) {
+            let mut merged = session.metadata.clone().unwrap_or_default();
+            merged.extend(metadata);
+            if let Err(e) = SessionManager::update_session(&session_id)
+                .metadata(Some(merged))
+                .apply()
+                .await
+            {
+                tracing::warn!("Failed to store session metadata for {}: {}", session_id, e);
+            }
+        }
+
         let session_config = SessionConfig {
             id: session_id.clone(),
             schedule_id: session.schedule_id.clone(),
-            max_turns: None,
-            retry_config: None,
+            max_turns,
+            retry_config,
+            compaction,
+            fallback_providers,
+            tool_overrides,
+            max_total_tokens,
+            model_override,
         };
 
         let user_message = match messages.last() {
             Some(msg) => msg,
             _ => {
-                let _ = stream_event(
-                    MessageEvent::Error {
-                        error: "Reply started with empty messages".to_string(),
-                    },
-                    &task_tx,
-                    Some(&broadcaster),
-                )
-                .await;
-                bg_tasks.mark_error(&bg_session_id).await;
+                failed
+                    .report(
+                        "Reply started with empty messages".to_string(),
+                        ErrorCode::InvalidRequest,
+                    )
+                    .await;
                 return;
             }
         };
@@ -322,23 +797,19 @@ pub async fn reply(
             Ok(stream) => stream,
             Err(e) => {
                 tracing::error!("Failed to start reply stream: {:?}", e);
-                stream_event(
-                    MessageEvent::Error {
-                        error: e.to_string(),
-                    },
-                    &task_tx,
-                    Some(&broadcaster),
-                )
-                .await;
-                bg_tasks.mark_error(&bg_session_id).await;
+                failed
+                    .report(e.to_string(), ErrorCode::ReplyStartFailed)
+                    .await;
                 return;
             }
         };
 
         let mut all_messages = messages.clone();
 
-        let mut heartbeat_interval = tokio::time::interval(Duration::from_millis(500));
-        let mut task_error = false;
+        let mut heartbeat_interval = tokio::time::interval(heartbeat_period);
+        let mut task_error = None;
+        let mut finish_reason = "stop";
+        let mut last_token_state: Option<TokenState> = None;
         loop {
             tokio::select! {
                 _ = task_cancel.cancelled() => {
@@ -350,7 +821,7 @@ pub async fn reply(
                     bg_tasks.update_activity(&bg_session_id).await;
                     stream_event(MessageEvent::Ping, &tx, Some(&broadcaster)).await;
                 }
-                response = timeout(Duration::from_millis(500), stream.next()) => {
+                response = timeout(stream_poll_timeout, stream.next()) => {
                     match response {
                         Ok(Some(Ok(AgentEvent::Message(message)))) => {
                             for content in &message.content {
@@ -360,9 +831,34 @@ pub async fn reply(
                             all_messages.push(message.clone());
 
                             let token_state = get_token_state(&session_id).await;
+                            let budget_exceeded = max_total_tokens
+                                .is_some_and(|budget| token_state.accumulated_total_tokens >= budget);
 
                             bg_tasks.update_activity(&bg_session_id).await;
-                            stream_event(MessageEvent::Message { message, token_state }, &tx, Some(&broadcaster)).await;
+                            let approvals = approval_requests(&message);
+                            stream_event(MessageEvent::Message {
+                                message,
+                                token_state: inline_token_state.then(|| token_state.clone()),
+                            }, &tx, Some(&broadcaster)).await;
+                            for approval in approvals {
+                                stream_event(approval, &tx, Some(&broadcaster)).await;
+                            }
+
+                            if last_token_state.as_ref() != Some(&token_state) {
+                                stream_event(MessageEvent::TokenUpdate { token_state: token_state.clone() }, &tx, Some(&broadcaster)).await;
+                                last_token_state = Some(token_state);
+                            }
+
+                            if budget_exceeded {
+                                tracing::warn!(
+                                    session_id = %session_id,
+                                    max_total_tokens = ?max_total_tokens,
+                                    "Session token budget exceeded, stopping agent"
+                                );
+                                finish_reason = "token_budget_exceeded";
+                                task_cancel.cancel();
+                                break;
+                            }
                         }
                         Ok(Some(Ok(AgentEvent::HistoryReplaced(new_messages)))) => {
                             all_messages = new_messages.clone();
@@ -384,11 +880,12 @@ pub async fn reply(
                             stream_event(
                                 MessageEvent::Error {
                                     error: e.to_string(),
+                                    error_code: ErrorCode::from_agent_error(&e),
                                 },
                                 &tx,
                                 Some(&broadcaster),
                             ).await;
-                            task_error = true;
+                            task_error = Some(e.to_string());
                             break;
                         }
                         Ok(None) => {
@@ -405,6 +902,19 @@ pub async fn reply(
         }
 
         let session_duration = session_start.elapsed();
+        metrics::counter!(
+            "mts_session_completions_total",
+            "session_type" => "app",
+            "interface" => "ui",
+            "exit_type" => "normal"
+        )
+        .increment(1);
+        metrics::histogram!(
+            "mts_session_duration_seconds",
+            "session_type" => "app",
+            "interface" => "ui"
+        )
+        .record(session_duration.as_secs_f64());
 
         if let Ok(session) = SessionManager::get_session(&session_id, true).await {
             let total_tokens = session.total_tokens.unwrap_or(0);
@@ -433,6 +943,12 @@ pub async fn reply(
                     interface = "ui",
                     "Session tokens"
                 );
+                metrics::counter!(
+                    "mts_session_tokens_total",
+                    "session_type" => "app",
+                    "interface" => "ui"
+                )
+                .increment(total_tokens as u64);
             }
         } else {
             tracing::info!(
@@ -458,7 +974,7 @@ pub async fn reply(
 
         let _ = stream_event(
             MessageEvent::Finish {
-                reason: "stop".to_string(),
+                reason: finish_reason.to_string(),
                 token_state: final_token_state,
             },
             &task_tx,
@@ -466,23 +982,34 @@ pub async fn reply(
         )
         .await;
 
-        // Mark task as completed or errored in background task manager
-        if task_error {
-            bg_tasks.mark_error(&bg_session_id).await;
+        // Mark task as completed or errored in background task manager. A retry of
+        // a failed reply starts over rather than replaying the error
+        if let Some(error) = task_error {
+            if let Some(key) = &idempotency_key {
+                bg_tasks.release_idempotency_key(&bg_session_id, key, task_id);
+            }
+            bg_tasks.mark_error(&bg_session_id, task_id, error).await;
         } else {
-            bg_tasks.mark_completed(&bg_session_id).await;
+            bg_tasks.mark_completed(&bg_session_id, task_id).await;
         }
-    }));
-    Ok(SseResponse::new(stream))
+    };
+    // Scope the session id so provider requests can be attributed to it
+    drop(tokio::spawn(mts::session_context::with_session_id(
+        Some(scope_session_id),
+        task,
+    )));
+    Ok(SseResponse::new(stream).compressed_for(&headers))
 }
 
 /// Subscribe to updates from an existing running agent task
-/// Returns SSE stream of events from the background task
+/// Returns SSE stream of events from the background task. With a Last-Event-ID
+/// header, only buffered events after that id are replayed.
 #[utoipa::path(
     get,
     path = "/sessions/{session_id}/subscribe",
     params(
-        ("session_id" = String, Path, description = "Session ID to subscribe to")
+        ("session_id" = String, Path, description = "Session ID to subscribe to"),
+        ("Last-Event-ID" = Option<u64>, Header, description = "Id of the last event received, to resume after it")
     ),
     responses(
         (status = 200, description = "Subscribed to session events",
@@ -494,53 +1021,16 @@ pub async fn reply(
 pub async fn subscribe_to_session(
     State(state): State<Arc<AppState>>,
     Path(session_id): Path<String>,
+    headers: HeaderMap,
 ) -> Result<SseResponse, StatusCode> {
     // Try to subscribe to the background task
-    let receiver = state
+    let subscription = state
         .background_tasks
-        .subscribe(&session_id)
+        .subscribe(&session_id, last_event_id(&headers))
         .await
         .ok_or(StatusCode::NOT_FOUND)?;
 
-    let (tx, rx) = mpsc::channel(100);
-    let stream = ReceiverStream::new(rx);
-
-    // Spawn a task to forward events from the broadcaster to the SSE stream
-    tokio::spawn(async move {
-        let mut receiver = receiver;
-        loop {
-            match receiver.recv().await {
-                Ok(event) => {
-                    let json = serde_json::to_string(&event).unwrap_or_else(|e| {
-                        format!(
-                            r#"{{"type":"Error","error":"Failed to serialize event: {}"}}"#,
-                            e
-                        )
-                    });
-
-                    if tx.send(format!("data: {}\n\n", json)).await.is_err() {
-                        // Client disconnected
-                        break;
-                    }
-
-                    // If this is a Finish event, we're done
-                    if matches!(event, MessageEvent::Finish { .. }) {
-                        break;
-                    }
-                }
-                Err(broadcast::error::RecvError::Closed) => {
-                    // Broadcaster closed, task finished
-                    break;
-                }
-                Err(broadcast::error::RecvError::Lagged(_)) => {
-                    // We missed some events, continue
-                    continue;
-                }
-            }
-        }
-    });
-
-    Ok(SseResponse::new(stream))
+    Ok(SseResponse::new(forward_subscription(subscription)).compressed_for(&headers))
 }
 
 /// Get the status of a background task for a session
@@ -568,6 +1058,36 @@ pub async fn get_task_status(
         .ok_or(StatusCode::NOT_FOUND)
 }
 
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct RunningTasksQuery {
+    /// Only list tasks with this status, e.g. 'running' or 'error'
+    pub status: Option<String>,
+}
+
+/// List background tasks with their status and subscriber counts
+#[utoipa::path(
+    get,
+    path = "/sessions/running",
+    params(RunningTasksQuery),
+    responses(
+        (status = 200, description = "Tracked background tasks",
+         body = Vec<crate::background_tasks::TaskStatusResponse>),
+        (status = 400, description = "Unknown status filter")
+    )
+)]
+pub async fn list_running_tasks(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RunningTasksQuery>,
+) -> Result<Json<Vec<crate::background_tasks::TaskStatusResponse>>, (StatusCode, String)> {
+    let status = query
+        .status
+        .as_deref()
+        .map(str::parse::<TaskStatus>)
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    Ok(Json(state.background_tasks.list_statuses(status).await))
+}
+
 /// Cancel a running background task
 #[utoipa::path(
     post,
@@ -591,18 +1111,127 @@ pub async fn cancel_task(
     }
 }
 
+/// Decision on a tool call announced by an ApprovalRequest event
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct ApprovalDecision {
+    /// The `request_id` of the ApprovalRequest event
+    pub request_id: String,
+    pub approved: bool,
+}
+
+/// Approve or deny a tool call the session's running task is waiting on
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/approve",
+    params(
+        ("session_id" = String, Path, description = "Session whose tool call to decide")
+    ),
+    request_body = ApprovalDecision,
+    responses(
+        (status = 200, description = "Decision passed to the agent"),
+        (status = 404, description = "No running task for this session, or it never asked about this tool call"),
+        (status = 409, description = "The tool call was already decided")
+    )
+)]
+pub async fn approve_tool_call(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Json(decision): Json<ApprovalDecision>,
+) -> Result<StatusCode, StatusCode> {
+    match state
+        .background_tasks
+        .take_approval(&session_id, &decision.request_id)
+        .await
+    {
+        ApprovalOutcome::Taken => {}
+        ApprovalOutcome::AlreadyDecided => return Err(StatusCode::CONFLICT),
+        ApprovalOutcome::NotFound => return Err(StatusCode::NOT_FOUND),
+    }
+    let agent = state.get_agent_for_route(session_id).await?;
+    let permission = if decision.approved {
+        Permission::AllowOnce
+    } else {
+        Permission::DenyOnce
+    };
+    agent
+        .handle_confirmation(
+            decision.request_id,
+            PermissionConfirmation {
+                principal_type: PrincipalType::Tool,
+                permission,
+            },
+        )
+        .await;
+    Ok(StatusCode::OK)
+}
+
+/// Forget a finished background task and its saved status
+#[utoipa::path(
+    delete,
+    path = "/sessions/{session_id}/task",
+    params(
+        ("session_id" = String, Path, description = "Session ID whose task to remove")
+    ),
+    responses(
+        (status = 200, description = "Task removed"),
+        (status = 404, description = "No task for this session"),
+        (status = 409, description = "Task is still running or has subscribers")
+    )
+)]
+pub async fn delete_task(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    match state.background_tasks.cleanup_task(&session_id).await {
+        CleanupOutcome::Removed => Ok(StatusCode::OK),
+        CleanupOutcome::InUse => Err(StatusCode::CONFLICT),
+        CleanupOutcome::NotFound => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Replace the bare 413 returned for an oversized `/reply` body with a JSON error
+/// that names the limit
+async fn explain_body_limit(State(limit): State<usize>, response: Response) -> Response {
+    if response.status() != StatusCode::PAYLOAD_TOO_LARGE {
+        return response;
+    }
+    let limit = if limit % (1024 * 1024) == 0 {
+        format!("{} MB", limit / (1024 * 1024))
+    } else {
+        format!("{} bytes", limit)
+    };
+    ErrorResponse {
+        message: format!(
+            "Request body is larger than the {} limit for /reply. Compact the conversation or \
+             raise MTS_REPLY_MAX_BODY_BYTES in the server config.",
+            limit
+        ),
+        status: StatusCode::PAYLOAD_TOO_LARGE,
+    }
+    .into_response()
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
+    let max_body_bytes = state.max_reply_body_bytes;
     Router::new()
         .route(
             "/reply",
-            post(reply).layer(DefaultBodyLimit::max(50 * 1024 * 1024)),
+            post(reply)
+                .layer(DefaultBodyLimit::max(max_body_bytes))
+                .layer(middleware::map_response_with_state(
+                    max_body_bytes,
+                    explain_body_limit,
+                )),
         )
         .route(
             "/sessions/{session_id}/subscribe",
             get(subscribe_to_session),
         )
+        .route("/sessions/running", get(list_running_tasks))
         .route("/sessions/{session_id}/task-status", get(get_task_status))
         .route("/sessions/{session_id}/cancel-task", post(cancel_task))
+        .route("/sessions/{session_id}/approve", post(approve_tool_call))
+        .route("/sessions/{session_id}/task", delete(delete_task))
         .with_state(state)
 }
 
@@ -610,15 +1239,148 @@ pub fn routes(state: Arc<AppState>) -> Router {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_sse_frame_carries_event_id() {
+        let json = event_json(&MessageEvent::Ping);
+        assert_eq!(
+            sse_frame(Some(7), &json),
+            "id: 7\ndata: {\"type\":\"Ping\"}\n\n"
+        );
+        assert_eq!(sse_frame(None, &json), "data: {\"type\":\"Ping\"}\n\n");
+    }
+
+    #[test]
+    fn test_accepts_gzip_respects_q_values() {
+        let headers = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                http::header::ACCEPT_ENCODING,
+                http::HeaderValue::from_static(value),
+            );
+            headers
+        };
+        assert!(accepts_gzip(&headers("gzip, deflate, br")));
+        assert!(accepts_gzip(&headers("br;q=1.0, GZIP;q=0.5")));
+        assert!(accepts_gzip(&headers("*")));
+        assert!(!accepts_gzip(&headers("gzip;q=0, deflate")));
+        assert!(!accepts_gzip(&headers("identity")));
+        assert!(!accepts_gzip(&HeaderMap::new()));
+    }
+
+    #[tokio::test]
+    async fn test_gzip_stream_keeps_each_event_decodable() {
+        let frames = vec![
+            sse_frame(Some(1), &event_json(&MessageEvent::Ping)),
+            sse_frame(Some(2), &"x".repeat(10_000)),
+        ];
+        let (tx, rx) = mpsc::channel(frames.len());
+        for frame in &frames {
+            tx.send(frame.clone()).await.unwrap();
+        }
+        drop(tx);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            http::header::ACCEPT_ENCODING,
+            http::HeaderValue::from_static("gzip"),
+        );
+        let response = SseResponse::new(ReceiverStream::new(rx))
+            .compressed_for(&headers)
+            .into_response();
+        assert_eq!(response.headers()["content-encoding"], "gzip");
+
+        // Every chunk but the trailer decodes to exactly one complete event
+        let mut decoder = flate2::write::GzDecoder::new(Vec::new());
+        let mut chunks = response.into_body().into_data_stream();
+        for frame in &frames {
+            let chunk = chunks.next().await.unwrap().unwrap();
+            decoder.write_all(&chunk).unwrap();
+            decoder.flush().unwrap();
+            assert_eq!(std::mem::take(decoder.get_mut()), frame.as_bytes());
+        }
+        while let Some(chunk) = chunks.next().await {
+            decoder.write_all(&chunk.unwrap()).unwrap();
+        }
+        assert!(decoder.finish().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_error_code_classifies_provider_errors() {
+        let rate_limited = anyhow::Error::new(ProviderError::RateLimitExceeded {
+            details: "slow down".to_string(),
+            retry_delay: None,
+        });
+        assert_eq!(
+            ErrorCode::from_agent_error(&rate_limited),
+            ErrorCode::RateLimited
+        );
+        let auth = anyhow::Error::new(ProviderError::Authentication("bad key".to_string()));
+        assert_eq!(
+            ErrorCode::from_agent_error(&auth),
+            ErrorCode::ProviderAuthFailed
+        );
+        let server = anyhow::Error::new(ProviderError::ServerError("502".to_string()));
+        assert_eq!(
+            ErrorCode::from_agent_error(&server),
+            ErrorCode::ProviderError
+        );
+        assert_eq!(
+            ErrorCode::from_agent_error(&anyhow::anyhow!("tool crashed")),
+            ErrorCode::AgentError
+        );
+
+        let json = event_json(&MessageEvent::Error {
+            error: "Rate limit exceeded".to_string(),
+            error_code: ErrorCode::RateLimited,
+        });
+        assert!(json.contains("\"error_code\":\"rate_limited\""));
+    }
+
+    #[test]
+    fn test_message_event_omits_token_state_unless_inlined() {
+        let message = Message::user().with_text("hi");
+        let json = event_json(&MessageEvent::Message {
+            message: message.clone(),
+            token_state: None,
+        });
+        assert!(!json.contains("token_state"));
+
+        let json = event_json(&MessageEvent::Message {
+            message,
+            token_state: Some(TokenState::default()),
+        });
+        assert!(json.contains("\"token_state\":{"));
+    }
+
+    #[test]
+    fn test_tool_confirmations_become_approval_requests() {
+        let mut arguments = JsonObject::new();
+        arguments.insert("command".to_string(), "rm -rf build".into());
+        let message = Message::assistant()
+            .with_text("Running a command")
+            .with_action_required("req-1", "developer__shell".to_string(), arguments, None);
+
+        let approvals = approval_requests(&message);
+        assert_eq!(approvals.len(), 1);
+        let json = event_json(&approvals[0]);
+        assert!(json.contains("\"type\":\"ApprovalRequest\""));
+        assert!(json.contains("\"request_id\":\"req-1\""));
+        assert!(json.contains("\"tool_name\":\"developer__shell\""));
+        assert!(json.contains("\"command\":\"rm -rf build\""));
+
+        assert!(approval_requests(&Message::assistant().with_text("done")).is_empty());
+    }
+
     mod integration_tests {
         use super::*;
+        use crate::background_tasks::ConcurrentTaskPolicy;
         use axum::{body::Body, http::Request};
         use mts::conversation::message::Message;
         use tower::ServiceExt;
 
         #[tokio::test(flavor = "multi_thread")]
         async fn test_reply_endpoint() {
-            let state = AppState::new().await.unwrap();
+            let state = AppState::for_tests().await;
 
             let app = routes(state);
 
@@ -633,6 +1395,255 @@ mod tests {
                         session_id: "test-session".to_string(),
                         recipe_name: None,
                         recipe_version: None,
+                        metadata: None,
+                        compaction: None,
+                        fallback_providers: None,
+                        tool_overrides: None,
+                        max_turns: None,
+                        max_total_tokens: None,
+                        retry_config: None,
+                        model_override: None,
+                        provider_override: None,
+                        idempotency_key: None,
+                    })
+                    .unwrap(),
+                ))
+                .unwrap();
+
+            let response = app.oneshot(request).await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_reply_rejects_zero_max_turns() {
+            let state = AppState::for_tests().await;
+
+            let app = routes(state);
+
+            let request = Request::builder()
+                .uri("/reply")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("x-secret-key", "test-secret")
+                .body(Body::from(
+                    serde_json::json!({
+                        "messages": [Message::user().with_text("test message")],
+                        "session_id": "test-session",
+                        "max_turns": 0,
+                    })
+                    .to_string(),
+                ))
+                .unwrap();
+
+            let response = app.oneshot(request).await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        fn idempotent_request(session_id: &str) -> Request<Body> {
+            Request::builder()
+                .uri("/reply")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("x-secret-key", "test-secret")
+                .body(Body::from(
+                    serde_json::json!({
+                        "messages": [Message::user().with_text("test message")],
+                        "session_id": session_id,
+                        "idempotency_key": "attempt-1",
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        }
+
+        /// Reads a reply stream to its end, failing if it never finishes
+        async fn read_stream(response: axum::response::Response) -> String {
+            let body = tokio::time::timeout(
+                Duration::from_secs(30),
+                axum::body::to_bytes(response.into_body(), usize::MAX),
+            )
+            .await
+            .expect("reply stream never finished")
+            .unwrap();
+            String::from_utf8(body.to_vec()).unwrap()
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_reply_retry_while_queued_joins_the_queued_task() {
+            let mut state = (*AppState::for_tests().await).clone();
+            state.background_tasks = Arc::new(
+                BackgroundTaskManager::new().concurrent_task_policy(ConcurrentTaskPolicy::Queue),
+            );
+            let state = Arc::new(state);
+            let session_id = "queued-idempotent-session";
+            let blocker = state
+                .background_tasks
+                .register_task(
+                    session_id.to_string(),
+                    CancellationToken::new(),
+                    state.background_tasks.new_broadcaster(),
+                )
+                .await
+                .unwrap();
+            let app = routes(state.clone());
+
+            let first = app
+                .clone()
+                .oneshot(idempotent_request(session_id))
+                .await
+                .unwrap();
+            assert_eq!(first.status(), StatusCode::OK);
+            let retry = app.oneshot(idempotent_request(session_id)).await.unwrap();
+            assert_eq!(retry.status(), StatusCode::OK);
+
+            // Both requests wait on the one queued task and see the same stream
+            state
+                .background_tasks
+                .mark_completed(session_id, blocker.task_id())
+                .await;
+            let first = read_stream(first).await;
+            let retry = read_stream(retry).await;
+            assert_eq!(first.matches("\"type\":\"Finish\"").count(), 1);
+            assert_eq!(retry, first);
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_reply_retry_after_early_error_starts_a_new_task() {
+            let state = AppState::for_tests().await;
+            let app = routes(state.clone());
+            let session_id = "missing-idempotent-session";
+
+            // The session doesn't exist, so the reply fails before reaching the agent
+            let first = app
+                .clone()
+                .oneshot(idempotent_request(session_id))
+                .await
+                .unwrap();
+            let first = read_stream(first).await;
+            assert!(first.contains("\"type\":\"Error\""));
+            assert!(first.contains("\"type\":\"Finish\""));
+
+            // The failed reply released its key, so claiming it succeeds
+            let probe = state.background_tasks.new_broadcaster();
+            assert!(state
+                .background_tasks
+                .claim_idempotency_key(session_id, "attempt-1", &probe, None)
+                .is_none());
+            state.background_tasks.release_idempotency_key(
+                session_id,
+                "attempt-1",
+                probe.task_id(),
+            );
+
+            let retry = app.oneshot(idempotent_request(session_id)).await.unwrap();
+            assert_eq!(retry.status(), StatusCode::OK);
+            assert!(read_stream(retry).await.contains("\"type\":\"Finish\""));
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_reply_explains_oversized_body() {
+            let mut state = (*AppState::for_tests().await).clone();
+            state.max_reply_body_bytes = 1024;
+
+            let request = Request::builder()
+                .uri("/reply")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("x-secret-key", "test-secret")
+                .body(Body::from(
+                    serde_json::json!({
+                        "messages": [Message::user().with_text("x".repeat(4096))],
+                        "session_id": "test-session",
+                    })
+                    .to_string(),
+                ))
+                .unwrap();
+
+            let response = routes(Arc::new(state)).oneshot(request).await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert!(error["message"]
+                .as_str()
+                .unwrap()
+                .contains("larger than the 1024 bytes limit"));
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_reply_rejects_unknown_model_override() {
+            let state = AppState::for_tests().await;
+
+            for (provider, model) in [
+                ("no-such-provider", "claude-sonnet-4-5"),
+                ("anthropic", "no-such-model"),
+            ] {
+                let request = Request::builder()
+                    .uri("/reply")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .header("x-secret-key", "test-secret")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "messages": [Message::user().with_text("test message")],
+                            "session_id": "test-session",
+                            "model_override": model,
+                            "provider_override": provider,
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap();
+
+                let response = routes(state.clone()).oneshot(request).await.unwrap();
+
+                assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            }
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_reply_rejects_zero_max_total_tokens() {
+            let state = AppState::for_tests().await;
+
+            let app = routes(state);
+
+            let request = Request::builder()
+                .uri("/reply")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("x-secret-key", "test-secret")
+                .body(Body::from(
+                    serde_json::json!({
+                        "messages": [Message::user().with_text("test message")],
+                        "session_id": "test-session",
+                        "max_total_tokens": 0,
+                    })
+                    .to_string(),
+                ))
+                .unwrap();
+
+            let response = app.oneshot(request).await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_approve_without_running_task_is_not_found() {
+            let state = AppState::for_tests().await;
+
+            let app = routes(state);
+
+            let request = Request::builder()
+                .uri("/sessions/idle-session/approve")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    serde_json::to_string(&ApprovalDecision {
+                        request_id: "req-1".to_string(),
+                        approved: true,
                     })
                     .unwrap(),
                 ))
@@ -640,7 +1651,47 @@ mod tests {
 
             let response = app.oneshot(request).await.unwrap();
 
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_approve_decides_each_pending_tool_call_once() {
+            let state = AppState::for_tests().await;
+            let session_id = "approval-session";
+            let broadcaster = state
+                .background_tasks
+                .register_task(
+                    session_id.to_string(),
+                    CancellationToken::new(),
+                    state.background_tasks.new_broadcaster(),
+                )
+                .await
+                .unwrap();
+            broadcaster.send(MessageEvent::ApprovalRequest {
+                request_id: "req-1".to_string(),
+                tool_name: "developer__shell".to_string(),
+                arguments: JsonObject::new(),
+            });
+            let approve = |request_id: &str| {
+                Request::builder()
+                    .uri(format!("/sessions/{}/approve", session_id))
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .header("x-secret-key", "test-secret")
+                    .body(Body::from(
+                        serde_json::json!({ "request_id": request_id, "approved": true })
+                            .to_string(),
+                    ))
+                    .unwrap()
+            };
+            let app = routes(state.clone());
+
+            let response = app.clone().oneshot(approve("req-1")).await.unwrap();
             assert_eq!(response.status(), StatusCode::OK);
+            let response = app.clone().oneshot(approve("req-1")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CONFLICT);
+            let response = app.oneshot(approve("req-2")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
         }
     }
 }
@@ -237,6 +237,14 @@ async fn run_now_handler(
         interface = "server",
         "Recipe execution started"
     );
+    metrics::counter!(
+        "mts_recipe_runs_total",
+        "recipe_name" => recipe_display_name,
+        "recipe_version" => recipe_version_tag.to_string(),
+        "session_type" => "schedule",
+        "interface" => "server"
+    )
+    .increment(1);
 
     tracing::info!("Server: Calling scheduler.run_now() for job '{}'", id);
 
@@ -1,12 +1,35 @@
 use axum::{http::StatusCode, routing::post, Json, Router};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use grep_matcher::Matcher;
+use grep_regex::{RegexMatcher, RegexMatcherBuilder};
+use grep_searcher::{
+    BinaryDetection, Encoding, Searcher, SearcherBuilder, Sink, SinkContext, SinkContextKind,
+    SinkFinish, SinkMatch,
+};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
 use serde::{Deserialize, Serialize};
-use std::{process::Stdio, sync::Arc};
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::CancellationToken;
 use utoipa::ToSchema;
 
+use crate::routes::reply::{sse_frame, SseResponse};
 use crate::state::AppState;
 
+/// Directories never searched, on top of what .gitignore files exclude
+const EXCLUDED_DIRS: &[&str] = &["node_modules", ".git", "dist", "build", "out"];
+
+/// Longest a content search runs when the request sets no `timeout_ms`
+const DEFAULT_SEARCH_TIMEOUT: Duration = Duration::from_secs(30);
+
 // Request types
 #[derive(Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -22,7 +45,44 @@ pub struct SearchFilesRequest {
     pub include_pattern: Option<String>,
     pub exclude_pattern: Option<String>,
     pub max_results: Option<usize>,
+    /// Matches to skip before the first one returned, for paging through results.
+    /// Matches are ordered by file path, then line number. Setting it, even to 0,
+    /// makes `totalMatches` count every match rather than stop at `maxResults`.
+    pub offset: Option<usize>,
     pub context_lines: Option<u8>,
+    /// Queries to OR-combine in one search, used instead of `query` when non-empty.
+    /// Each match is labeled with the query that matched it; an empty entry is rejected.
+    pub queries: Option<Vec<String>>,
+    /// Return matches bucketed by file in `groups` instead of the flat `matches` list
+    #[serde(default)]
+    pub group_by_file: bool,
+    /// Skip files excluded by .gitignore/.ignore files and the usual build and
+    /// dependency directories (default: true)
+    #[serde(default = "default_respect_gitignore")]
+    pub respect_gitignore: bool,
+    /// Also search hidden files and directories
+    #[serde(default)]
+    pub search_hidden: bool,
+    /// Stop searching after this many milliseconds and return what was found so far
+    /// with `timedOut` set (default: 30000)
+    pub timeout_ms: Option<u64>,
+    /// Only count matches: `totalMatches` and `totalFiles` are filled in, while
+    /// `matches`, `groups` and `fileStats` stay empty
+    #[serde(default)]
+    pub count_only: bool,
+    /// Search files that look binary (they contain NUL bytes) as text instead of
+    /// skipping them. Binary files can produce very long, garbled match lines, so
+    /// narrow the search with `includePattern` when turning this on.
+    #[serde(default)]
+    pub search_binary: bool,
+    /// Decode files from this encoding (a WHATWG label such as "utf-16le" or
+    /// "windows-1252") instead of reading them as UTF-8. Files starting with a
+    /// UTF-8 or UTF-16 byte order mark are decoded from that either way.
+    pub encoding: Option<String>,
+}
+
+fn default_respect_gitignore() -> bool {
+    true
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -31,6 +91,24 @@ pub struct SearchFilenamesRequest {
     pub query: String,
     pub working_dir: String,
     pub max_results: Option<usize>,
+    /// Match the query as a fuzzy subsequence of each file's relative path and rank
+    /// results by score, instead of as a substring of the file name
+    #[serde(default)]
+    pub fuzzy: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSymbolsRequest {
+    /// Symbol name to look for, matched case-insensitively as a substring
+    pub query: String,
+    pub working_dir: String,
+    /// Only return symbols named exactly `query` (case-sensitive)
+    #[serde(default)]
+    pub exact: bool,
+    /// Only return symbols of these kinds (default: all)
+    pub kinds: Option<Vec<SymbolKind>>,
+    pub max_results: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -39,15 +117,21 @@ pub struct ReplaceRequest {
     pub query: String,
     pub replacement: String,
     pub working_dir: String,
-    #[serde(default)]
-    pub case_sensitive: bool,
+    /// Defaults to false for literal queries and to true for regex ones, so a regex
+    /// only matches case-insensitively when the caller asks for it
+    pub case_sensitive: Option<bool>,
     #[serde(default)]
     pub use_regex: bool,
     #[serde(default)]
     pub whole_word: bool,
     pub include_pattern: Option<String>,
     pub exclude_pattern: Option<String>,
+    /// Files to update, relative to `working_dir` or absolute; every one must be inside
+    /// `working_dir`
     pub file_paths: Option<Vec<String>>,
+    /// Report what would change as unified diffs without writing any files
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 // Response types
@@ -57,18 +141,76 @@ pub struct SearchMatch {
     pub file_path: String,
     pub line_number: usize,
     pub column: usize,
+    /// Byte offset in the line where the first match starts; same as `column`
+    pub match_start: usize,
+    /// Byte offset in the line just past the end of the first match
+    pub match_end: usize,
+    /// Byte ranges `[start, end)` of every match in the line, in order
+    pub submatches: Vec<(usize, usize)>,
     pub line_text: String,
+    /// Up to `context_lines` lines right before the match, including nearby matched lines
     pub context_before: Vec<String>,
+    /// Up to `context_lines` lines right after the match, including nearby matched lines
     pub context_after: Vec<String>,
+    /// Which of the request's `queries` matched, when several were given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_query: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSearchStats {
+    pub file_path: String,
+    pub matched_lines: usize,
+    pub matches: usize,
+    pub bytes_searched: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMatchGroup {
+    pub file_path: String,
+    pub matches: Vec<SearchMatch>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchFilesResponse {
+    /// Flat list of matches; empty when `group_by_file` was requested
     pub matches: Vec<SearchMatch>,
+    /// Matches bucketed by file in the order files were found, when `group_by_file` was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<FileMatchGroup>>,
+    /// Stats for every file with matches, not just those on this page
+    pub file_stats: Vec<FileSearchStats>,
+    pub total_files: usize,
+    /// Matched lines in every file when `offset` or `count_only` is set; otherwise
+    /// those found before the search stopped at `max_results`
+    pub total_matches: usize,
+    /// More matches follow this page
+    pub truncated: bool,
+    /// The search hit its timeout, so the results and totals are partial
+    pub timed_out: bool,
+}
+
+/// Totals sent as the last event of a streamed search
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSummary {
+    pub file_stats: Vec<FileSearchStats>,
     pub total_files: usize,
+    /// Counted as in [`SearchFilesResponse::total_matches`]
     pub total_matches: usize,
     pub truncated: bool,
+    pub timed_out: bool,
+}
+
+/// Events of `/search/files/stream`: matches file by file, then one `Done`
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(tag = "type")]
+pub enum SearchStreamEvent {
+    Match(SearchMatch),
+    Done(SearchSummary),
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -76,6 +218,9 @@ pub struct SearchFilesResponse {
 pub struct FilenameMatch {
     pub path: String,
     pub name: String,
+    /// Fuzzy match score, higher is better; only set for fuzzy searches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<i64>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -85,18 +230,64 @@ pub struct SearchFilenamesResponse {
     pub truncated: bool,
 }
 
+/// What a definition found by a symbol search defines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Fn,
+    Struct,
+    Enum,
+    Union,
+    Trait,
+    Type,
+    Const,
+    Static,
+    Mod,
+    Macro,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolMatch {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub file_path: String,
+    pub line_number: usize,
+    /// The defining line, trimmed
+    pub line: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSymbolsResponse {
+    pub symbols: Vec<SymbolMatch>,
+    pub truncated: bool,
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ReplaceResponse {
     pub files_modified: usize,
     pub total_replacements: usize,
+    /// Per-file unified diffs of the changes, when `dry_run` was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diffs: Option<Vec<FileDiff>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDiff {
+    pub file_path: String,
+    pub diff: String,
 }
 
 // Routes
 pub fn routes(_state: Arc<AppState>) -> Router {
     Router::new()
         .route("/search/files", post(search_files))
+        .route("/search/files/stream", post(search_files_stream))
         .route("/search/filenames", post(search_filenames))
+        .route("/search/symbols", post(search_symbols))
         .route("/search/replace", post(replace_in_files))
 }
 
@@ -106,6 +297,7 @@ pub fn routes(_state: Arc<AppState>) -> Router {
     request_body = SearchFilesRequest,
     responses(
         (status = 200, description = "Search completed successfully", body = SearchFilesResponse),
+        (status = 400, description = "Bad request - invalid pattern or glob"),
         (status = 401, description = "Unauthorized - invalid secret key"),
         (status = 500, description = "Internal server error")
     )
@@ -113,160 +305,783 @@ pub fn routes(_state: Arc<AppState>) -> Router {
 async fn search_files(
     Json(req): Json<SearchFilesRequest>,
 ) -> Result<Json<SearchFilesResponse>, StatusCode> {
-    let max_results = req.max_results.unwrap_or(1000);
-    let context_lines = req.context_lines.unwrap_or(2);
+    let (collector, walker) = prepare_search(&req)?;
+    // Dropped with this future when the client goes away, which stops the search
+    let cancel = CancellationToken::new();
+    let _cancel_on_drop = cancel.clone().drop_guard();
+    let mut collector = collector.with_cancellation(cancel);
+    let collector = tokio::task::spawn_blocking(move || {
+        collector.search(walker.build());
+        collector
+    })
+    .await
+    .map_err(|e| {
+        tracing::error!("Search task failed: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let timed_out = collector.timed_out;
+    // Counting keeps no matches, so only a timeout leaves anything out
+    let truncated = if req.count_only {
+        timed_out
+    } else {
+        collector.truncated
+    };
+    let total_matches = collector.match_total;
+    let (matches, mut file_stats) = collector.finish();
+    let total_files = file_stats.len();
+    if req.count_only {
+        file_stats.clear();
+    }
+    let (matches, groups) = if req.group_by_file {
+        (Vec::new(), Some(group_by_file(matches)))
+    } else {
+        (matches, None)
+    };
+
+    Ok(Json(SearchFilesResponse {
+        matches,
+        groups,
+        file_stats,
+        total_files,
+        total_matches,
+        truncated,
+        timed_out,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/search/files/stream",
+    request_body = SearchFilesRequest,
+    responses(
+        (status = 200, description = "Streaming search started",
+         body = SearchStreamEvent,
+         content_type = "text/event-stream"),
+        (status = 400, description = "Bad request - invalid pattern or glob"),
+        (status = 401, description = "Unauthorized - invalid secret key")
+    )
+)]
+async fn search_files_stream(
+    Json(req): Json<SearchFilesRequest>,
+) -> Result<SseResponse, StatusCode> {
+    let (collector, walker) = prepare_search(&req)?;
+    let count_only = req.count_only;
+    let (tx, rx) = mpsc::channel(100);
+    let mut collector = collector.streaming(tx.clone());
+
+    tokio::task::spawn_blocking(move || {
+        collector.search(walker.build());
+        // Nobody is listening any more once the search was cancelled
+        if collector.cancelled {
+            return;
+        }
+        let summary = SearchSummary {
+            total_files: collector.file_stats.len(),
+            total_matches: collector.match_total,
+            truncated: if count_only {
+                collector.timed_out
+            } else {
+                collector.truncated
+            },
+            timed_out: collector.timed_out,
+            file_stats: if count_only {
+                Vec::new()
+            } else {
+                collector.file_stats
+            },
+        };
+        let _ = tx.blocking_send(search_event_frame(&SearchStreamEvent::Done(summary)));
+    });
+
+    Ok(SseResponse::new(ReceiverStream::new(rx)))
+}
 
-    // Build ripgrep command
-    let mut cmd = Command::new("rg");
+fn search_event_frame(event: &SearchStreamEvent) -> String {
+    let json = serde_json::to_string(event).unwrap_or_else(|e| {
+        tracing::error!("Failed to serialize search event: {}", e);
+        "{}".to_string()
+    });
+    sse_frame(None, &json)
+}
 
-    // Use JSON output for easier parsing
-    cmd.arg("--json");
+/// Build the matcher and file walk for a search request. Count-only searches keep
+/// no matches or context; they and paged searches count every match.
+fn prepare_search(req: &SearchFilesRequest) -> Result<(MatchCollector, WalkBuilder), StatusCode> {
+    let (max_results, context_lines) = if req.count_only {
+        (0, 0)
+    } else {
+        (
+            req.max_results.unwrap_or(1000),
+            req.context_lines.unwrap_or(2),
+        )
+    };
 
-    // Context lines
-    if context_lines > 0 {
-        cmd.arg("--context").arg(context_lines.to_string());
+    // Several queries are OR-combined, and each stays literal unless use_regex is set
+    let queries = req.queries.clone().filter(|q| !q.is_empty());
+    // An empty pattern matches every line, which is never what a caller means
+    if queries
+        .as_ref()
+        .is_some_and(|queries| queries.iter().any(String::is_empty))
+    {
+        tracing::warn!("Empty entry in search queries");
+        return Err(StatusCode::BAD_REQUEST);
     }
+    let patterns = queries.clone().unwrap_or_else(|| vec![req.query.clone()]);
+    let matcher = RegexMatcherBuilder::new()
+        .case_insensitive(!req.case_sensitive)
+        .word(req.whole_word)
+        .fixed_strings(!req.use_regex)
+        .line_terminator(Some(b'\n'))
+        .build_many(&patterns)
+        .map_err(|e| {
+            tracing::warn!("Invalid search pattern: {}", e);
+            StatusCode::BAD_REQUEST
+        })?;
+
+    let walker = file_walker(
+        &req.working_dir,
+        req.include_pattern.as_deref(),
+        req.exclude_pattern.as_deref(),
+        req.respect_gitignore,
+        req.search_hidden,
+    )
+    .map_err(|e| {
+        tracing::warn!("Invalid search glob: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
 
-    // Max count per file
-    cmd.arg("--max-count").arg(max_results.to_string());
+    let encoding = req
+        .encoding
+        .as_deref()
+        .map(Encoding::new)
+        .transpose()
+        .map_err(|e| {
+            tracing::warn!("Invalid search encoding: {}", e);
+            StatusCode::BAD_REQUEST
+        })?;
 
-    // Case sensitivity
-    if !req.case_sensitive {
-        cmd.arg("--ignore-case");
+    let labeler = queries.map(|queries| {
+        QueryLabeler::new(queries, req.use_regex, req.case_sensitive, req.whole_word)
+    });
+    let timeout = req
+        .timeout_ms
+        .map_or(DEFAULT_SEARCH_TIMEOUT, Duration::from_millis);
+    let mut collector = MatchCollector::new(matcher, context_lines as usize, max_results)
+        .with_text_options(req.search_binary, encoding)
+        .with_labeler(labeler)
+        .with_offset(req.offset.unwrap_or(0))
+        .with_deadline(Instant::now() + timeout);
+    if req.count_only || req.offset.is_some() {
+        collector = collector.counting_all();
     }
+    Ok((collector, walker))
+}
+
+/// Walk `working_dir` restricted by comma-separated include and exclude globs. With
+/// `respect_gitignore`, ignore files are honored and [`EXCLUDED_DIRS`] skipped; hidden
+/// files are only walked with `search_hidden`.
+fn file_walker(
+    working_dir: &str,
+    include_pattern: Option<&str>,
+    exclude_pattern: Option<&str>,
+    respect_gitignore: bool,
+    search_hidden: bool,
+) -> Result<WalkBuilder, ignore::Error> {
+    let split = |patterns: Option<&str>| {
+        patterns
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
 
-    // Whole word
-    if req.whole_word {
-        cmd.arg("--word-regexp");
+    let mut overrides = OverrideBuilder::new(working_dir);
+    for pattern in split(include_pattern) {
+        overrides.add(&pattern)?;
+    }
+    for pattern in split(exclude_pattern) {
+        overrides.add(&format!("!{}", pattern))?;
+    }
+    if respect_gitignore {
+        for dir in EXCLUDED_DIRS {
+            overrides.add(&format!("!{}/**", dir))?;
+        }
     }
 
-    // Fixed strings (not regex) by default
-    if !req.use_regex {
-        cmd.arg("--fixed-strings");
+    let mut walker = WalkBuilder::new(working_dir);
+    walker
+        .overrides(overrides.build()?)
+        .hidden(!search_hidden)
+        .parents(respect_gitignore)
+        .ignore(respect_gitignore)
+        .git_ignore(respect_gitignore)
+        .git_global(respect_gitignore)
+        .git_exclude(respect_gitignore)
+        .sort_by_file_name(|a, b| a.cmp(b));
+    Ok(walker)
+}
+
+/// Bucket matches by file, keeping files in first-seen order and matches in their original order
+fn group_by_file(matches: Vec<SearchMatch>) -> Vec<FileMatchGroup> {
+    let mut groups: Vec<FileMatchGroup> = Vec::new();
+    let mut index_by_path: HashMap<String, usize> = HashMap::new();
+    for search_match in matches {
+        let index = *index_by_path
+            .entry(search_match.file_path.clone())
+            .or_insert_with(|| {
+                groups.push(FileMatchGroup {
+                    file_path: search_match.file_path.clone(),
+                    matches: Vec::new(),
+                });
+                groups.len() - 1
+            });
+        groups[index].matches.push(search_match);
     }
+    groups
+}
 
-    // Include/exclude patterns
-    if let Some(ref include) = req.include_pattern {
-        for pattern in include.split(',') {
-            let pattern = pattern.trim();
-            if !pattern.is_empty() {
-                cmd.arg("--glob").arg(pattern);
-            }
+/// Compile a request query with the same options the search applies to it
+fn query_regex(
+    query: &str,
+    use_regex: bool,
+    case_sensitive: bool,
+    whole_word: bool,
+) -> Result<regex::Regex, regex::Error> {
+    let pattern = if use_regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    let pattern = if whole_word {
+        format!(r"\b(?:{})\b", pattern)
+    } else {
+        pattern
+    };
+    regex::RegexBuilder::new(&pattern)
+        .case_insensitive(!case_sensitive)
+        .build()
+}
+
+/// Replace a file's contents by writing a temporary file next to it and renaming it
+/// into place, so the file is never left half-written. It keeps its permissions.
+fn write_atomically(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| std::io::Error::other("not a file path"))?;
+    let temp_path = path.with_file_name(format!(".{}.replace-tmp", name.to_string_lossy()));
+    // Never clobber an existing file that happens to have the temporary name
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)?;
+    let result = file
+        .write_all(contents.as_bytes())
+        .and_then(|_| file.sync_all())
+        .and_then(|_| std::fs::metadata(path))
+        .and_then(|metadata| std::fs::set_permissions(&temp_path, metadata.permissions()))
+        .and_then(|_| std::fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Put back the original contents of files a replace already rewrote, returning the
+/// ones that couldn't be restored
+fn restore_files(written: Vec<(String, PathBuf, String)>) -> Vec<String> {
+    written
+        .into_iter()
+        .filter_map(|(file_path, target, original)| {
+            let e = write_atomically(&target, &original).err()?;
+            tracing::error!("Failed to restore {}: {}", file_path, e);
+            Some(file_path)
+        })
+        .collect()
+}
+
+/// Resolve a file to update against the canonical `root`, following symlinks.
+/// Paths outside `root` are an error; None means the file should be skipped, because
+/// it is missing or a symlink inside `root` that points out of it.
+fn resolve_replace_target(root: &Path, file_path: &str) -> Result<Option<PathBuf>, String> {
+    let path = root.join(file_path);
+    let target = match path.canonicalize() {
+        Ok(target) => target,
+        Err(e) => {
+            tracing::warn!("Failed to resolve file {}: {}", file_path, e);
+            return Ok(None);
+        }
+    };
+    if target.starts_with(root) {
+        return Ok(Some(target));
+    }
+
+    // Where the link itself lives decides between skipping it and rejecting the path
+    let link = path
+        .parent()
+        .and_then(|parent| parent.canonicalize().ok())
+        .zip(path.file_name())
+        .map(|(parent, name)| parent.join(name));
+    match link {
+        Some(link) if link.starts_with(root) && link.is_symlink() => {
+            tracing::warn!(
+                "Skipping {}, a symlink to {} outside the working directory",
+                file_path,
+                target.display()
+            );
+            Ok(None)
         }
+        _ => Err(format!("{} is outside the working directory", file_path)),
     }
+}
 
-    if let Some(ref exclude) = req.exclude_pattern {
-        for pattern in exclude.split(',') {
-            let pattern = pattern.trim();
-            if !pattern.is_empty() {
-                cmd.arg("--glob").arg(format!("!{}", pattern));
+/// Check that every `$N`, `$name` and `${name}` reference in a regex replacement
+/// names a capture group of `re`. References are read the way `Regex::replace_all`
+/// expands them, where a missing group silently becomes empty text.
+fn check_group_references(re: &regex::Regex, replacement: &str) -> Result<(), String> {
+    let mut rest = replacement;
+    while let Some((_, after)) = rest.split_once('$') {
+        let (reference, tail) = if let Some(escaped) = after.strip_prefix('$') {
+            rest = escaped;
+            continue;
+        } else if let Some(braced) = after.strip_prefix('{') {
+            // An unclosed brace is kept as literal text
+            braced.split_once('}').unwrap_or(("", after))
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            after.split_at(end)
+        };
+        rest = tail;
+        if reference.is_empty() {
+            continue;
+        }
+
+        if let Ok(index) = reference.parse::<usize>() {
+            if index >= re.captures_len() {
+                return Err(format!(
+                    "Replacement refers to group ${}, but the pattern only has groups 0 to {}",
+                    index,
+                    re.captures_len() - 1
+                ));
+            }
+        } else if !re.capture_names().flatten().any(|name| name == reference) {
+            let mut message = format!(
+                "Replacement refers to group '{}', but the pattern has no group by that name",
+                reference
+            );
+            let digits = reference.find(|c: char| !c.is_ascii_digit()).unwrap_or(0);
+            if digits > 0 {
+                let (group, text) = reference.split_at(digits);
+                message.push_str(&format!(
+                    "; write ${{{}}}{} to follow group {} with text",
+                    group, text, group
+                ));
             }
+            return Err(message);
+        }
+    }
+    Ok(())
+}
+
+/// Labels matches with the first request query that matches them
+struct QueryLabeler {
+    queries: Vec<(String, regex::Regex)>,
+}
+
+impl QueryLabeler {
+    fn new(queries: Vec<String>, use_regex: bool, case_sensitive: bool, whole_word: bool) -> Self {
+        let queries = queries
+            .into_iter()
+            .filter_map(|query| {
+                // Queries the labeler can't compile stay unlabeled rather than failing the search
+                let regex = query_regex(&query, use_regex, case_sensitive, whole_word).ok()?;
+                Some((query, regex))
+            })
+            .collect();
+        Self { queries }
+    }
+
+    fn label(&self, text: &str) -> Option<String> {
+        self.queries
+            .iter()
+            .find(|(_, regex)| regex.is_match(text))
+            .map(|(query, _)| query.clone())
+    }
+}
+
+/// Searches files one at a time, accumulating matches and per-file statistics
+struct MatchCollector {
+    matcher: RegexMatcher,
+    searcher: Searcher,
+    labeler: Option<QueryLabeler>,
+    context_lines: usize,
+    max_results: usize,
+    /// Matches still to pass over before the first one kept
+    skip: usize,
+    /// Keep searching once `max_results` matches are kept, only counting the rest
+    count_all: bool,
+    /// When set, each file's matches are sent here as SSE frames instead of kept
+    stream: Option<mpsc::Sender<String>>,
+    /// When set, the search stops at this instant with whatever it found
+    deadline: Option<Instant>,
+    /// When set, the search stops once this is cancelled
+    cancel: Option<CancellationToken>,
+    matches: Vec<SearchMatch>,
+    /// Matches kept or sent so far
+    returned: usize,
+    /// Matched lines seen so far, including skipped and uncollected ones
+    match_total: usize,
+    file_stats: Vec<FileSearchStats>,
+    /// Matches were found past the last one kept
+    truncated: bool,
+    /// The stream's receiver went away or the search was cancelled, so it stopped early
+    cancelled: bool,
+    /// The deadline passed, so the search stopped early
+    timed_out: bool,
+}
+
+/// Searcher reporting line numbers and `context_lines` of context. Files with a NUL
+/// byte are skipped as binary unless `search_binary` is set.
+fn build_searcher(
+    context_lines: usize,
+    search_binary: bool,
+    encoding: Option<Encoding>,
+) -> Searcher {
+    let binary_detection = if search_binary {
+        BinaryDetection::none()
+    } else {
+        BinaryDetection::quit(b'\x00')
+    };
+    SearcherBuilder::new()
+        .line_number(true)
+        .before_context(context_lines)
+        .after_context(context_lines)
+        .binary_detection(binary_detection)
+        .encoding(encoding)
+        .build()
+}
+
+impl MatchCollector {
+    fn new(matcher: RegexMatcher, context_lines: usize, max_results: usize) -> Self {
+        Self {
+            matcher,
+            searcher: build_searcher(context_lines, false, None),
+            labeler: None,
+            context_lines,
+            max_results,
+            skip: 0,
+            count_all: false,
+            stream: None,
+            deadline: None,
+            cancel: None,
+            matches: Vec::new(),
+            returned: 0,
+            match_total: 0,
+            file_stats: Vec::new(),
+            truncated: false,
+            cancelled: false,
+            timed_out: false,
         }
     }
 
-    // Always exclude common directories
-    cmd.arg("--glob").arg("!node_modules/**");
-    cmd.arg("--glob").arg("!.git/**");
-    cmd.arg("--glob").arg("!dist/**");
-    cmd.arg("--glob").arg("!build/**");
-    cmd.arg("--glob").arg("!out/**");
+    /// Send matches to `stream` once each file is searched rather than collecting them
+    fn streaming(mut self, stream: mpsc::Sender<String>) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    /// Search binary files as text, and decode files from `encoding` when given
+    fn with_text_options(mut self, search_binary: bool, encoding: Option<Encoding>) -> Self {
+        self.searcher = build_searcher(self.context_lines, search_binary, encoding);
+        self
+    }
 
-    // Add query and working directory
-    cmd.arg(&req.query);
-    cmd.arg(&req.working_dir);
+    fn with_labeler(mut self, labeler: Option<QueryLabeler>) -> Self {
+        self.labeler = labeler;
+        self
+    }
 
-    // Execute command
-    cmd.stdout(Stdio::piped());
-    cmd.stderr(Stdio::piped());
+    /// Pass over the first `offset` matches instead of keeping them
+    fn with_offset(mut self, offset: usize) -> Self {
+        self.skip = offset;
+        self
+    }
 
-    let mut child = cmd.spawn().map_err(|e| {
-        tracing::error!("Failed to spawn ripgrep: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    /// Stop walking and searching once `deadline` passes
+    fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
 
-    let stdout = child
-        .stdout
-        .take()
-        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
-    let reader = BufReader::new(stdout);
-    let mut lines = reader.lines();
-
-    let mut matches = Vec::new();
-    let mut file_count = std::collections::HashSet::new();
-    let mut current_context_before: Vec<String> = Vec::new();
-    let mut truncated = false;
-
-    while let Some(line) = lines
-        .next_line()
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-    {
-        if matches.len() >= max_results {
-            truncated = true;
-            break;
-        }
-
-        // Parse JSON output from ripgrep
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&line) {
-            if json["type"] == "match" {
-                let data = &json["data"];
-
-                if let (Some(path), Some(line_number), Some(lines_obj)) = (
-                    data["path"]["text"].as_str(),
-                    data["line_number"].as_u64(),
-                    data["lines"].as_object(),
-                ) {
-                    let line_text = lines_obj["text"]
-                        .as_str()
-                        .unwrap_or("")
-                        .trim_end()
-                        .to_string();
-
-                    // Get column from first submatch if available
-                    let column = data["submatches"]
-                        .as_array()
-                        .and_then(|arr| arr.first())
-                        .and_then(|sm| sm["start"].as_u64())
-                        .unwrap_or(0) as usize;
-
-                    file_count.insert(path.to_string());
-
-                    matches.push(SearchMatch {
-                        file_path: path.to_string(),
-                        line_number: line_number as usize,
-                        column,
-                        line_text,
-                        context_before: current_context_before.clone(),
-                        context_after: Vec::new(), // Will be filled in post-processing if needed
-                    });
-
-                    current_context_before.clear();
-                }
-            } else if json["type"] == "context" {
-                // Context line before or after match
-                let data = &json["data"];
-                if let Some(text) = data["lines"]["text"].as_str() {
-                    current_context_before.push(text.trim_end().to_string());
-                    if current_context_before.len() > context_lines as usize {
-                        current_context_before.remove(0);
-                    }
+    fn past_deadline(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Stop walking and searching once `cancel` is cancelled
+    fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Whether the search was cancelled, noting it if so
+    fn check_cancelled(&mut self) -> bool {
+        self.cancelled |= self.cancel.as_ref().is_some_and(|c| c.is_cancelled());
+        self.cancelled
+    }
+
+    /// Count every match in the walk rather than stopping after `max_results`
+    fn counting_all(mut self) -> Self {
+        self.count_all = true;
+        self
+    }
+
+    /// Search every file the walk yields in path order, stopping once more than
+    /// `max_results` matches are found unless counting all of them, or once the
+    /// deadline passes or the search is cancelled
+    fn search(&mut self, walk: ignore::Walk) {
+        let mut files = Vec::new();
+        for entry in walk {
+            if self.check_cancelled() {
+                tracing::debug!("Search cancelled while listing files");
+                return;
+            }
+            if self.past_deadline() {
+                self.timed_out = true;
+                break;
+            }
+            match entry {
+                Ok(entry) if entry.file_type().is_some_and(|t| t.is_file()) => {
+                    files.push(entry.into_path())
                 }
+                Ok(_) => {}
+                Err(e) => tracing::debug!("Skipping unreadable path: {}", e),
+            }
+        }
+        files.sort();
+
+        for path in files {
+            if self.timed_out || self.past_deadline() {
+                self.timed_out = true;
+                break;
             }
+            self.search_file(&path);
+            if (self.truncated && !self.count_all) || self.check_cancelled() {
+                break;
+            }
+        }
+        if self.timed_out {
+            tracing::warn!("Search timed out after {} matched lines", self.match_total);
+            self.truncated = true;
         }
     }
 
-    // Wait for command to finish
-    let _ = child.wait().await;
+    fn search_file(&mut self, path: &Path) {
+        let mut sink = FileSink {
+            matcher: &self.matcher,
+            labeler: self.labeler.as_ref(),
+            file_path: path.to_string_lossy().into_owned(),
+            context_lines: self.context_lines,
+            skip: self.skip,
+            remaining: self.max_results.saturating_sub(self.returned),
+            count_all: self.count_all,
+            deadline: self.deadline,
+            cancel: self.cancel.as_ref(),
+            timed_out: false,
+            matches: Vec::new(),
+            recent_lines: VecDeque::new(),
+            matched_lines: 0,
+            match_count: 0,
+            bytes_searched: 0,
+            truncated: false,
+        };
+        if let Err(e) = self.searcher.search_path(&self.matcher, path, &mut sink) {
+            tracing::debug!("Failed to search {}: {}", path.display(), e);
+        }
 
-    let total_matches = matches.len();
-    let total_files = file_count.len();
+        if sink.matched_lines > 0 {
+            self.file_stats.push(FileSearchStats {
+                file_path: sink.file_path,
+                matched_lines: sink.matched_lines,
+                matches: sink.match_count,
+                bytes_searched: sink.bytes_searched,
+            });
+        }
+        self.skip = sink.skip;
+        self.returned += sink.matches.len();
+        self.match_total += sink.matched_lines;
+        self.truncated |= sink.truncated;
+        self.timed_out |= sink.timed_out;
 
-    Ok(Json(SearchFilesResponse {
-        matches,
-        total_files,
-        total_matches,
-        truncated,
-    }))
+        let Some(stream) = &self.stream else {
+            self.matches.append(&mut sink.matches);
+            return;
+        };
+        for search_match in sink.matches {
+            let frame = search_event_frame(&SearchStreamEvent::Match(search_match));
+            if stream.blocking_send(frame).is_err() {
+                self.cancelled = true;
+                return;
+            }
+        }
+    }
+
+    fn finish(self) -> (Vec<SearchMatch>, Vec<FileSearchStats>) {
+        (self.matches, self.file_stats)
+    }
+}
+
+/// Collects the matches of a single file
+struct FileSink<'a> {
+    matcher: &'a RegexMatcher,
+    labeler: Option<&'a QueryLabeler>,
+    file_path: String,
+    context_lines: usize,
+    /// Matches still to pass over before this file may keep any
+    skip: usize,
+    /// Matches this file may add before the overall limit is reached
+    remaining: usize,
+    /// Keep counting matched lines past the limit instead of stopping
+    count_all: bool,
+    deadline: Option<Instant>,
+    cancel: Option<&'a CancellationToken>,
+    /// The deadline passed partway through the file
+    timed_out: bool,
+    matches: Vec<SearchMatch>,
+    /// The last `context_lines` lines reported, matched or not, by line number
+    recent_lines: VecDeque<(usize, String)>,
+    /// Matched lines seen, whether kept or not
+    matched_lines: usize,
+    match_count: usize,
+    bytes_searched: u64,
+    truncated: bool,
+}
+
+fn line_text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).trim_end().to_string()
+}
+
+impl FileSink<'_> {
+    /// Add a line to the trailing context of the matches shortly before it, and keep
+    /// it as leading context for the next match. The searcher reports each line only
+    /// once, so a line between two close matches is shared between them here.
+    fn push_line(&mut self, line_number: usize, text: String) {
+        for earlier in self.matches.iter_mut().rev() {
+            if earlier.line_number + self.context_lines < line_number {
+                break;
+            }
+            if earlier.line_number < line_number {
+                earlier.context_after.push(text.clone());
+            }
+        }
+        self.recent_lines.push_back((line_number, text));
+        if self.recent_lines.len() > self.context_lines {
+            self.recent_lines.pop_front();
+        }
+    }
+
+    /// Whether the search must stop here because the deadline passed or the
+    /// search was cancelled
+    fn check_stop(&mut self) -> bool {
+        self.timed_out = self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        self.timed_out || self.cancel.is_some_and(CancellationToken::is_cancelled)
+    }
+
+    fn context_before(&self, line_number: usize) -> Vec<String> {
+        self.recent_lines
+            .iter()
+            .filter(|(number, _)| number + self.context_lines >= line_number)
+            .map(|(_, text)| text.clone())
+            .collect()
+    }
+}
+
+impl Sink for FileSink<'_> {
+    type Error = std::io::Error;
+
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, Self::Error> {
+        if self.check_stop() {
+            return Ok(false);
+        }
+        let keep = self.skip == 0 && self.matches.len() < self.remaining;
+        if self.skip == 0 && !keep {
+            self.truncated = true;
+            if !self.count_all {
+                return Ok(false);
+            }
+        }
+        self.matched_lines += 1;
+
+        let line = mat.bytes();
+        let mut submatches = Vec::new();
+        self.matcher
+            .find_iter(line, |m| {
+                submatches.push(m);
+                self.match_count += 1;
+                true
+            })
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+        let line_number = mat.line_number().unwrap_or(0) as usize;
+        let line_text = line_text(line);
+        if !keep {
+            // Skipped lines and those past the limit still give context to kept matches
+            self.skip = self.skip.saturating_sub(1);
+            self.push_line(line_number, line_text);
+            return Ok(true);
+        }
+
+        let first_match = submatches.first().copied();
+        let matched_query = self.labeler.and_then(|labeler| {
+            let matched = first_match.map_or(line, |m| &line[m]);
+            labeler.label(&String::from_utf8_lossy(matched))
+        });
+        self.matches.push(SearchMatch {
+            file_path: self.file_path.clone(),
+            line_number,
+            column: first_match.map_or(0, |m| m.start()),
+            match_start: first_match.map_or(0, |m| m.start()),
+            match_end: first_match.map_or(0, |m| m.end()),
+            submatches: submatches.iter().map(|m| (m.start(), m.end())).collect(),
+            line_text: line_text.clone(),
+            context_before: self.context_before(line_number),
+            context_after: Vec::new(),
+            matched_query,
+        });
+        self.push_line(line_number, line_text);
+        Ok(true)
+    }
+
+    fn context(
+        &mut self,
+        _searcher: &Searcher,
+        context: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        if self.check_stop() {
+            return Ok(false);
+        }
+        if matches!(
+            context.kind(),
+            SinkContextKind::Before | SinkContextKind::After
+        ) {
+            let line_number = context.line_number().unwrap_or(0) as usize;
+            self.push_line(line_number, line_text(context.bytes()));
+        }
+        Ok(true)
+    }
+
+    fn finish(&mut self, _searcher: &Searcher, finish: &SinkFinish) -> Result<(), Self::Error> {
+        self.bytes_searched = finish.byte_count();
+        Ok(())
+    }
 }
 
 #[utoipa::path(
@@ -284,48 +1099,170 @@ async fn search_filenames(
 ) -> Result<Json<SearchFilenamesResponse>, StatusCode> {
     let max_results = req.max_results.unwrap_or(1000);
 
-    // First, get all files
-    let mut cmd = Command::new("rg");
-    cmd.arg("--files");
-    cmd.arg(&req.working_dir);
-
-    cmd.stdout(Stdio::piped());
-    cmd.stderr(Stdio::piped());
+    let (matches, truncated) = tokio::task::spawn_blocking(move || {
+        let files = WalkBuilder::new(&req.working_dir)
+            .build()
+            .flatten()
+            .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()));
+        if req.fuzzy {
+            let root = Path::new(&req.working_dir);
+            return fuzzy_filename_matches(root, &req.query, files, max_results);
+        }
 
-    let output = cmd.output().await.map_err(|e| {
-        tracing::error!("Failed to execute ripgrep --files: {}", e);
+        // Simple case-insensitive substring match
+        let query_lower = req.query.to_lowercase();
+        let matches: Vec<_> = files
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy();
+                name.to_lowercase().contains(&query_lower)
+            })
+            .take(max_results)
+            .map(|entry| FilenameMatch {
+                path: entry.path().to_string_lossy().into_owned(),
+                name: entry.file_name().to_string_lossy().into_owned(),
+                score: None,
+            })
+            .collect();
+        let truncated = matches.len() >= max_results;
+        (matches, truncated)
+    })
+    .await
+    .map_err(|e| {
+        tracing::error!("Filename search task failed: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    let files_output = String::from_utf8_lossy(&output.stdout);
+    Ok(Json(SearchFilenamesResponse { matches, truncated }))
+}
 
-    // Filter files by query
-    let mut matches = Vec::new();
-    let query_lower = req.query.to_lowercase();
+/// Score each file's path relative to `root` against `query`, returning the best
+/// `max_results` matches first and whether any were left out
+fn fuzzy_filename_matches(
+    root: &Path,
+    query: &str,
+    files: impl Iterator<Item = ignore::DirEntry>,
+    max_results: usize,
+) -> (Vec<FilenameMatch>, bool) {
+    let matcher = SkimMatcherV2::default();
+    let mut matches: Vec<_> = files
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            let score = matcher.fuzzy_match(&relative.to_string_lossy(), query)?;
+            Some(FilenameMatch {
+                path: entry.path().to_string_lossy().into_owned(),
+                name: entry.file_name().to_string_lossy().into_owned(),
+                score: Some(score),
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    let truncated = matches.len() > max_results;
+    matches.truncate(max_results);
+    (matches, truncated)
+}
 
-    for line in files_output.lines() {
-        if matches.len() >= max_results {
-            break;
-        }
+/// A Rust item definition: visibility and qualifiers such as `pub(crate) const async
+/// unsafe extern "C"`, then the item keyword and its name
+static RUST_DEFINITION: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(
+        r#"^\s*(?:pub(?:\s*\([^)]*\))?\s+)?(?:default\s+)?(?:(?:const|async|unsafe|extern(?:\s+"[^"]*")?)\s+)*(?P<kind>fn|struct|enum|union|trait|type|const|static|mod|macro_rules!)\s*(?:mut\s+)?(?P<name>[A-Za-z_][A-Za-z0-9_]*)"#,
+    )
+    .expect("valid definition regex")
+});
 
-        let path = line.trim();
-        let name = std::path::Path::new(path)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or(path);
+/// The symbol a line of Rust defines, if any
+fn rust_definition(line: &str) -> Option<(SymbolKind, &str)> {
+    let captures = RUST_DEFINITION.captures(line)?;
+    let kind = match &captures["kind"] {
+        "fn" => SymbolKind::Fn,
+        "struct" => SymbolKind::Struct,
+        "enum" => SymbolKind::Enum,
+        "union" => SymbolKind::Union,
+        "trait" => SymbolKind::Trait,
+        "type" => SymbolKind::Type,
+        "const" => SymbolKind::Const,
+        "static" => SymbolKind::Static,
+        "mod" => SymbolKind::Mod,
+        _ => SymbolKind::Macro,
+    };
+    Some((kind, captures.name("name")?.as_str()))
+}
 
-        // Simple case-insensitive substring match
-        if name.to_lowercase().contains(&query_lower) {
-            matches.push(FilenameMatch {
-                path: path.to_string(),
-                name: name.to_string(),
-            });
-        }
+/// Find where symbols are defined, rather than every line that mentions them.
+///
+/// Only Rust sources are searched so far.
+#[utoipa::path(
+    post,
+    path = "/search/symbols",
+    request_body = SearchSymbolsRequest,
+    responses(
+        (status = 200, description = "Symbol search completed successfully", body = SearchSymbolsResponse),
+        (status = 400, description = "Bad request - working directory not found"),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+async fn search_symbols(
+    Json(req): Json<SearchSymbolsRequest>,
+) -> Result<Json<SearchSymbolsResponse>, StatusCode> {
+    let max_results = req.max_results.unwrap_or(1000);
+    if !Path::new(&req.working_dir).is_dir() {
+        return Err(StatusCode::BAD_REQUEST);
     }
+    let walker = file_walker(&req.working_dir, Some("*.rs"), None, true, false).map_err(|e| {
+        tracing::error!("Failed to set up symbol search: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let (symbols, truncated) = tokio::task::spawn_blocking(move || {
+        let query = req.query.to_lowercase();
+        let wanted = |kind: SymbolKind, name: &str| {
+            let named = if req.exact {
+                name == req.query
+            } else {
+                name.to_lowercase().contains(&query)
+            };
+            named && req.kinds.as_ref().is_none_or(|kinds| kinds.contains(&kind))
+        };
 
-    let truncated = matches.len() >= max_results;
+        let mut symbols = Vec::new();
+        let files = walker
+            .build()
+            .flatten()
+            .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()));
+        for entry in files {
+            // Sources that aren't UTF-8 aren't Rust
+            let Ok(contents) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            for (index, line) in contents.lines().enumerate() {
+                let Some((kind, name)) = rust_definition(line) else {
+                    continue;
+                };
+                if !wanted(kind, name) {
+                    continue;
+                }
+                if symbols.len() == max_results {
+                    return (symbols, true);
+                }
+                symbols.push(SymbolMatch {
+                    name: name.to_string(),
+                    kind,
+                    file_path: entry.path().to_string_lossy().into_owned(),
+                    line_number: index + 1,
+                    line: line.trim().to_string(),
+                });
+            }
+        }
+        (symbols, false)
+    })
+    .await
+    .map_err(|e| {
+        tracing::error!("Symbol search task failed: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
 
-    Ok(Json(SearchFilenamesResponse { matches, truncated }))
+    Ok(Json(SearchSymbolsResponse { symbols, truncated }))
 }
 
 #[utoipa::path(
@@ -334,39 +1271,83 @@ async fn search_filenames(
     request_body = ReplaceRequest,
     responses(
         (status = 200, description = "Replace operation completed successfully", body = ReplaceResponse),
-        (status = 400, description = "Bad request - invalid regex pattern"),
+        (status = 400, description = "Bad request - invalid regex pattern, replacement group reference or file path outside working_dir"),
         (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 408, description = "Searching for the files to update timed out"),
         (status = 500, description = "Internal server error")
     )
 )]
 async fn replace_in_files(
     Json(req): Json<ReplaceRequest>,
-) -> Result<Json<ReplaceResponse>, StatusCode> {
+) -> Result<Json<ReplaceResponse>, (StatusCode, String)> {
     use std::fs;
-    use std::io::Write;
 
     let mut files_modified = 0;
     let mut total_replacements = 0;
+    let mut diffs = req.dry_run.then(Vec::new);
+    let case_sensitive = req.case_sensitive.unwrap_or(req.use_regex);
+
+    let re =
+        query_regex(&req.query, req.use_regex, case_sensitive, req.whole_word).map_err(|e| {
+            tracing::error!("Invalid regex pattern: {}", e);
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid regex pattern: {}", e),
+            )
+        })?;
+    if req.use_regex {
+        check_group_references(&re, &req.replacement).map_err(|e| {
+            tracing::warn!("Invalid replacement: {}", e);
+            (StatusCode::BAD_REQUEST, e)
+        })?;
+    }
+
+    let root = fs::canonicalize(&req.working_dir).map_err(|e| {
+        tracing::warn!("Invalid working directory {}: {}", req.working_dir, e);
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Working directory {} is not accessible", req.working_dir),
+        )
+    })?;
 
     // Get list of files to process
     let file_paths = if let Some(paths) = req.file_paths {
         paths
     } else {
-        // Search for files matching the query
+        // Search the canonical root, so the files found resolve against it below
         let search_req = SearchFilesRequest {
             query: req.query.clone(),
-            working_dir: req.working_dir.clone(),
-            case_sensitive: req.case_sensitive,
+            working_dir: root.to_string_lossy().into_owned(),
+            case_sensitive,
             use_regex: req.use_regex,
             whole_word: req.whole_word,
             include_pattern: req.include_pattern.clone(),
             exclude_pattern: req.exclude_pattern.clone(),
             max_results: Some(10000),
+            offset: None,
             context_lines: Some(0),
+            queries: None,
+            group_by_file: false,
+            respect_gitignore: true,
+            search_hidden: false,
+            timeout_ms: None,
+            count_only: false,
+            search_binary: false,
+            encoding: None,
         };
 
-        let search_result = search_files(Json(search_req)).await?;
-        let unique_files: std::collections::HashSet<String> = search_result
+        let search_result = search_files(Json(search_req))
+            .await
+            .map_err(|status| (status, "Failed to search for files to update".to_string()))?;
+        // Replacing in only the files found so far would be a partial edit
+        if search_result.timed_out {
+            return Err((
+                StatusCode::REQUEST_TIMEOUT,
+                "Searching for files to update timed out; narrow the query or pass filePaths"
+                    .to_string(),
+            ));
+        }
+        let unique_files: std::collections::BTreeSet<String> = search_result
             .0
             .matches
             .into_iter()
@@ -376,10 +1357,26 @@ async fn replace_in_files(
         unique_files.into_iter().collect()
     };
 
-    // Process each file
+    // Check every target before changing anything
+    let mut targets = Vec::new();
     for file_path in file_paths {
+        match resolve_replace_target(&root, &file_path) {
+            Ok(Some(target)) => targets.push((file_path, target)),
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!("Rejecting replace: {}", e);
+                return Err((StatusCode::BAD_REQUEST, e));
+            }
+        }
+    }
+
+    // Files already rewritten, with their original contents in case a later write fails
+    let mut written = Vec::new();
+
+    // Process each file
+    for (file_path, target) in targets {
         // Read file
-        let content = match fs::read_to_string(&file_path) {
+        let content = match fs::read_to_string(&target) {
             Ok(c) => c,
             Err(e) => {
                 tracing::warn!("Failed to read file {}: {}", file_path, e);
@@ -387,63 +1384,870 @@ async fn replace_in_files(
             }
         };
 
-        // Perform replacement
+        // Perform replacement. Capture groups like $1 are only expanded in regex mode.
+        let count = re.find_iter(&content).count();
+        if count == 0 {
+            continue;
+        }
+        total_replacements += count;
+        files_modified += 1;
         let new_content = if req.use_regex {
-            // Use regex replacement
-            match regex::Regex::new(&req.query) {
-                Ok(re) => {
-                    let count = re.find_iter(&content).count();
-                    if count > 0 {
-                        total_replacements += count;
-                        files_modified += 1;
-                        re.replace_all(&content, req.replacement.as_str())
-                            .to_string()
-                    } else {
-                        continue;
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Invalid regex pattern: {}", e);
-                    return Err(StatusCode::BAD_REQUEST);
-                }
-            }
+            re.replace_all(&content, req.replacement.as_str())
         } else {
-            // Simple string replacement
-            let search_str = if req.case_sensitive {
-                req.query.as_str()
-            } else {
-                // For case-insensitive, we need a different approach
-                // This is a simplified version - for production, use regex
-                req.query.as_str()
-            };
-
-            let count = content.matches(search_str).count();
-            if count > 0 {
-                total_replacements += count;
-                files_modified += 1;
-                content.replace(search_str, &req.replacement)
-            } else {
-                continue;
-            }
+            re.replace_all(&content, regex::NoExpand(&req.replacement))
         };
 
-        // Write back to file
-        match fs::File::create(&file_path) {
-            Ok(mut file) => {
-                if let Err(e) = file.write_all(new_content.as_bytes()) {
-                    tracing::error!("Failed to write file {}: {}", file_path, e);
-                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        if let Some(diffs) = &mut diffs {
+            let diff = similar::TextDiff::from_lines(content.as_str(), new_content.as_ref())
+                .unified_diff()
+                .header(&file_path, &file_path)
+                .to_string();
+            diffs.push(FileDiff { file_path, diff });
+            continue;
+        }
+
+        // Write back to file, undoing the earlier files if this one fails
+        if let Err(e) = write_atomically(&target, &new_content) {
+            tracing::error!("Failed to write file {}: {}", file_path, e);
+            let mut message = format!("Failed to write {}: {}", file_path, e);
+            if !written.is_empty() {
+                let unrestored = restore_files(written);
+                if unrestored.is_empty() {
+                    message.push_str("; files already updated were restored");
+                } else {
+                    message.push_str(&format!(
+                        "; these files were already updated and could not be restored: {}",
+                        unrestored.join(", ")
+                    ));
                 }
             }
-            Err(e) => {
-                tracing::error!("Failed to create file {}: {}", file_path, e);
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
-            }
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, message));
         }
+        written.push((file_path, target, content));
     }
 
     Ok(Json(ReplaceResponse {
         files_modified,
         total_replacements,
+        diffs,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search_dir(
+        dir: &Path,
+        patterns: &[&str],
+        context_lines: usize,
+        max_results: usize,
+        labeler: Option<QueryLabeler>,
+    ) -> MatchCollector {
+        let matcher = RegexMatcherBuilder::new()
+            .case_insensitive(true)
+            .fixed_strings(true)
+            .line_terminator(Some(b'\n'))
+            .build_many(patterns)
+            .unwrap();
+        let walker = file_walker(dir.to_str().unwrap(), None, None, true, false).unwrap();
+        let mut collector =
+            MatchCollector::new(matcher, context_lines, max_results).with_labeler(labeler);
+        collector.search(walker.build());
+        collector
+    }
+
+    fn file_name(path: &str) -> &str {
+        Path::new(path).file_name().unwrap().to_str().unwrap()
+    }
+
+    #[test]
+    fn test_search_reports_per_file_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("a.rs"),
+            "fn main() {}\nlet foo = 1;\nfoo(foo);\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("b.rs"), "foo\n").unwrap();
+        std::fs::write(dir.path().join("c.rs"), "bar\n").unwrap();
+
+        let collector = search_dir(dir.path(), &["foo"], 0, 100, None);
+        assert!(!collector.truncated);
+        let (matches, file_stats) = collector.finish();
+
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[0].line_number, 2);
+        assert_eq!(matches[0].column, 4);
+        assert_eq!((matches[0].match_start, matches[0].match_end), (4, 7));
+        assert_eq!(matches[0].line_text, "let foo = 1;");
+        assert_eq!(matches[1].submatches, vec![(0, 3), (4, 7)]);
+        assert_eq!(file_stats.len(), 2);
+        assert_eq!(file_name(&file_stats[0].file_path), "a.rs");
+        assert_eq!(file_stats[0].matched_lines, 2);
+        assert_eq!(file_stats[0].matches, 3);
+        assert_eq!(file_stats[0].bytes_searched, 36);
+        assert_eq!(file_name(&file_stats[1].file_path), "b.rs");
+        assert_eq!(file_stats[1].matches, 1);
+    }
+
+    #[test]
+    fn test_search_collects_context_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\nfoo\nthree\nfour\n").unwrap();
+
+        let (matches, _) = search_dir(dir.path(), &["foo"], 1, 100, None).finish();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].context_before, vec!["two"]);
+        assert_eq!(matches[0].context_after, vec!["three"]);
+    }
+
+    #[test]
+    fn test_close_matches_share_context_symmetrically() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = "one\ntwo\nfoo 1\nthree\nfoo 2\nfour\nfive\nsix\n";
+        std::fs::write(dir.path().join("a.txt"), fixture).unwrap();
+
+        let (matches, _) = search_dir(dir.path(), &["foo"], 2, 100, None).finish();
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].context_before, vec!["one", "two"]);
+        assert_eq!(matches[0].context_after, vec!["three", "foo 2"]);
+        assert_eq!(matches[1].context_before, vec!["foo 1", "three"]);
+        assert_eq!(matches[1].context_after, vec!["four", "five"]);
+
+        let (matches, _) = search_dir(dir.path(), &["foo"], 0, 100, None).finish();
+        assert!(matches[0].context_before.is_empty());
+        assert!(matches[1].context_after.is_empty());
+    }
+
+    #[test]
+    fn test_search_truncates_at_max_results() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "foo\nfoo\nfoo\n").unwrap();
+        std::fs::write(dir.path().join("b.rs"), "foo\n").unwrap();
+
+        let collector = search_dir(dir.path(), &["foo"], 0, 2, None);
+        assert!(collector.truncated);
+        let (matches, file_stats) = collector.finish();
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(file_stats.len(), 1);
+        assert_eq!(file_stats[0].matched_lines, 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_pages_through_matches_in_path_order() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("a")).unwrap();
+        std::fs::write(dir.path().join("b.rs"), "foo\nfoo\n").unwrap();
+        std::fs::write(dir.path().join("a.rs"), "foo\n").unwrap();
+        std::fs::write(dir.path().join("a").join("z.rs"), "foo\nbar\nfoo\n").unwrap();
+        let request = |offset| SearchFilesRequest {
+            max_results: Some(2),
+            offset: Some(offset),
+            context_lines: Some(1),
+            ..search_request("foo", dir.path())
+        };
+        let page = |response: &SearchFilesResponse| {
+            response
+                .matches
+                .iter()
+                .map(|m| {
+                    let path = Path::new(&m.file_path).strip_prefix(dir.path()).unwrap();
+                    format!("{}:{}", path.display(), m.line_number)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let Json(first) = search_files(Json(request(0))).await.unwrap();
+        assert_eq!(page(&first), ["a/z.rs:1", "a/z.rs:3"]);
+        assert_eq!(first.total_matches, 5);
+        assert_eq!(first.total_files, 3);
+        assert!(first.truncated);
+
+        let Json(second) = search_files(Json(request(2))).await.unwrap();
+        assert_eq!(page(&second), ["a.rs:1", "b.rs:1"]);
+        assert_eq!(second.total_matches, 5);
+        assert!(second.truncated);
+        // Matched lines outside the page still show up as context
+        assert_eq!(second.matches[1].context_after, ["foo"]);
+
+        let Json(last) = search_files(Json(request(4))).await.unwrap();
+        assert_eq!(page(&last), ["b.rs:2"]);
+        assert_eq!(last.matches[0].context_before, ["foo"]);
+        assert!(!last.truncated);
+    }
+
+    #[test]
+    fn test_search_skips_excluded_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("node_modules")).unwrap();
+        std::fs::write(dir.path().join("node_modules").join("dep.js"), "foo\n").unwrap();
+        std::fs::write(dir.path().join("index.js"), "foo\n").unwrap();
+
+        let (matches, _) = search_dir(dir.path(), &["foo"], 0, 100, None).finish();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(file_name(&matches[0].file_path), "index.js");
+    }
+
+    #[test]
+    fn test_walker_can_include_ignored_and_hidden_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("node_modules")).unwrap();
+        std::fs::write(dir.path().join("node_modules").join("dep.js"), "").unwrap();
+        std::fs::write(dir.path().join(".env"), "").unwrap();
+        std::fs::write(dir.path().join(".ignore"), "generated.js\n").unwrap();
+        std::fs::write(dir.path().join("generated.js"), "").unwrap();
+        std::fs::write(dir.path().join("index.js"), "").unwrap();
+
+        let walked = |respect_gitignore, search_hidden| {
+            let root = dir.path().to_str().unwrap();
+            let walker = file_walker(root, None, None, respect_gitignore, search_hidden).unwrap();
+            walker
+                .build()
+                .flatten()
+                .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+                .map(|entry| {
+                    let path = entry.path().strip_prefix(dir.path()).unwrap();
+                    path.to_string_lossy().replace('\\', "/")
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(walked(true, false), ["index.js"]);
+        assert_eq!(walked(true, true), [".env", ".ignore", "index.js"]);
+        assert_eq!(
+            walked(false, false),
+            ["generated.js", "index.js", "node_modules/dep.js"]
+        );
+    }
+
+    #[test]
+    fn test_search_labels_matches_by_query() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "// todo: tidy\nok\n// FIXME(x)\n").unwrap();
+
+        let labeler = QueryLabeler::new(
+            vec!["TODO".to_string(), "FIXME".to_string()],
+            false,
+            false,
+            false,
+        );
+        let (matches, _) =
+            search_dir(dir.path(), &["TODO", "FIXME"], 0, 100, Some(labeler)).finish();
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].matched_query.as_deref(), Some("TODO"));
+        assert_eq!(matches[1].matched_query.as_deref(), Some("FIXME"));
+    }
+
+    #[test]
+    fn test_group_by_file_buckets_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "foo\nbar\nfoo\n").unwrap();
+        std::fs::write(dir.path().join("b.rs"), "foo\n").unwrap();
+
+        let (matches, _) = search_dir(dir.path(), &["foo"], 0, 100, None).finish();
+
+        let groups = group_by_file(matches);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(file_name(&groups[0].file_path), "a.rs");
+        let lines: Vec<usize> = groups[0].matches.iter().map(|m| m.line_number).collect();
+        assert_eq!(lines, vec![1, 3]);
+        assert_eq!(file_name(&groups[1].file_path), "b.rs");
+        assert_eq!(groups[1].matches.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_case_insensitive_literal_replace() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "foo Foo FOO food $1\n").unwrap();
+        let untouched = dir.path().join("b.txt");
+        std::fs::write(&untouched, "bar\n").unwrap();
+
+        let request = ReplaceRequest {
+            query: "Foo".to_string(),
+            replacement: "baz$1".to_string(),
+            working_dir: dir.path().to_string_lossy().into_owned(),
+            case_sensitive: Some(false),
+            use_regex: false,
+            whole_word: true,
+            include_pattern: None,
+            exclude_pattern: None,
+            file_paths: Some(vec![
+                path.to_string_lossy().into_owned(),
+                untouched.to_string_lossy().into_owned(),
+            ]),
+            dry_run: false,
+        };
+
+        let response = replace_in_files(Json(request)).await.unwrap().0;
+        assert_eq!(response.files_modified, 1);
+        assert_eq!(response.total_replacements, 3);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "baz$1 baz$1 baz$1 food $1\n"
+        );
+        assert_eq!(std::fs::read_to_string(&untouched).unwrap(), "bar\n");
+    }
+
+    #[tokio::test]
+    async fn test_regex_replace_is_case_sensitive_unless_asked() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        let request = |case_sensitive| {
+            let mut request =
+                replace_request(dir.path(), vec![path.to_string_lossy().into_owned()]);
+            request.query = r"f(o+)".to_string();
+            request.replacement = "b${1}".to_string();
+            request.use_regex = true;
+            request.case_sensitive = case_sensitive;
+            request
+        };
+
+        std::fs::write(&path, "foo Foo FOO\n").unwrap();
+        let response = replace_in_files(Json(request(None))).await.unwrap().0;
+        assert_eq!(response.total_replacements, 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "boo Foo FOO\n");
+
+        std::fs::write(&path, "foo Foo FOO\n").unwrap();
+        let response = replace_in_files(Json(request(Some(false))))
+            .await
+            .unwrap()
+            .0;
+        assert_eq!(response.total_replacements, 3);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "boo boo bOO\n");
+    }
+
+    #[test]
+    fn test_replacement_group_references_are_checked() {
+        let re = regex::Regex::new(r"(?P<key>\w+)=(\d+)").unwrap();
+        for valid in [
+            "$1",
+            "${2}px",
+            "$key: $2",
+            "${key}_x",
+            "$$1 costs $",
+            "${unclosed",
+        ] {
+            assert_eq!(check_group_references(&re, valid), Ok(()), "{}", valid);
+        }
+
+        let error = check_group_references(&re, "$3").unwrap_err();
+        assert!(error.contains("only has groups 0 to 2"), "{}", error);
+        let error = check_group_references(&re, "${value}").unwrap_err();
+        assert!(error.contains("no group by that name"), "{}", error);
+        let error = check_group_references(&re, "$1a").unwrap_err();
+        assert!(error.contains("write ${1}a"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_replace_rejects_dangling_group_reference() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "width=10\n").unwrap();
+
+        let request = ReplaceRequest {
+            query: r"(\w+)=\d+".to_string(),
+            replacement: "$1=$2".to_string(),
+            working_dir: dir.path().to_string_lossy().into_owned(),
+            case_sensitive: Some(true),
+            use_regex: true,
+            whole_word: false,
+            include_pattern: None,
+            exclude_pattern: None,
+            file_paths: Some(vec![path.to_string_lossy().into_owned()]),
+            dry_run: false,
+        };
+
+        let (status, message) = replace_in_files(Json(request)).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(message.contains("$2"), "{}", message);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "width=10\n");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_replace_previews_diffs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "keep\nold value\nkeep\n").unwrap();
+
+        let request = ReplaceRequest {
+            query: "old".to_string(),
+            replacement: "new".to_string(),
+            working_dir: dir.path().to_string_lossy().into_owned(),
+            case_sensitive: Some(true),
+            use_regex: false,
+            whole_word: false,
+            include_pattern: None,
+            exclude_pattern: None,
+            file_paths: None,
+            dry_run: true,
+        };
+
+        let response = replace_in_files(Json(request)).await.unwrap().0;
+        assert_eq!(response.files_modified, 1);
+        assert_eq!(response.total_replacements, 1);
+        let diffs = response.diffs.unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].file_path, path.to_string_lossy());
+        assert!(diffs[0].diff.contains("-old value\n+new value\n"));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "keep\nold value\nkeep\n"
+        );
+    }
+
+    /// A search for `query` in `working_dir` with the defaults a JSON request gets
+    fn search_request(query: &str, working_dir: &Path) -> SearchFilesRequest {
+        SearchFilesRequest {
+            query: query.to_string(),
+            working_dir: working_dir.to_string_lossy().into_owned(),
+            case_sensitive: false,
+            use_regex: false,
+            whole_word: false,
+            include_pattern: None,
+            exclude_pattern: None,
+            max_results: None,
+            offset: None,
+            context_lines: None,
+            queries: None,
+            group_by_file: false,
+            respect_gitignore: true,
+            search_hidden: false,
+            timeout_ms: None,
+            count_only: false,
+            search_binary: false,
+            encoding: None,
+        }
+    }
+
+    fn replace_request(working_dir: &Path, file_paths: Vec<String>) -> ReplaceRequest {
+        ReplaceRequest {
+            query: "old".to_string(),
+            replacement: "new".to_string(),
+            working_dir: working_dir.to_string_lossy().into_owned(),
+            case_sensitive: Some(true),
+            use_regex: false,
+            whole_word: false,
+            include_pattern: None,
+            exclude_pattern: None,
+            file_paths: Some(file_paths),
+            dry_run: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replace_rejects_paths_outside_working_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let inside = dir.path().join("a.txt");
+        std::fs::write(&inside, "old\n").unwrap();
+        let outside_dir = tempfile::tempdir().unwrap();
+        let outside = outside_dir.path().join("hosts");
+        std::fs::write(&outside, "old\n").unwrap();
+
+        for escape in [
+            outside.to_string_lossy().into_owned(),
+            format!(
+                "../{}/hosts",
+                outside_dir.path().file_name().unwrap().to_string_lossy()
+            ),
+        ] {
+            let request = replace_request(dir.path(), vec!["a.txt".to_string(), escape]);
+            let (status, message) = replace_in_files(Json(request)).await.unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert!(
+                message.contains("outside the working directory"),
+                "{}",
+                message
+            );
+        }
+        assert_eq!(std::fs::read_to_string(&inside).unwrap(), "old\n");
+        assert_eq!(std::fs::read_to_string(&outside).unwrap(), "old\n");
+
+        // Relative paths resolve against the working directory
+        let request = replace_request(dir.path(), vec!["a.txt".to_string()]);
+        let response = replace_in_files(Json(request)).await.unwrap().0;
+        assert_eq!(response.files_modified, 1);
+        assert_eq!(std::fs::read_to_string(&inside).unwrap(), "new\n");
+    }
+
+    #[tokio::test]
+    async fn test_replace_with_relative_working_dir() {
+        let dir = tempfile::Builder::new()
+            .prefix("replace-test")
+            .tempdir_in(".")
+            .unwrap();
+        // The directory is created in the current one, so its name is a relative path to it
+        let relative = Path::new(dir.path().file_name().unwrap());
+        std::fs::write(dir.path().join("a.txt"), "old\n").unwrap();
+
+        let mut request = replace_request(relative, Vec::new());
+        request.file_paths = None;
+        let response = replace_in_files(Json(request)).await.unwrap().0;
+        assert_eq!(response.files_modified, 1);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "new\n"
+        );
+
+        // Listed files resolve against the working directory too
+        std::fs::write(dir.path().join("a.txt"), "old\n").unwrap();
+        let request = replace_request(relative, vec!["a.txt".to_string()]);
+        let response = replace_in_files(Json(request)).await.unwrap().0;
+        assert_eq!(response.files_modified, 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_replace_restores_updated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("a.txt");
+        let second = dir.path().join("b.txt");
+        std::fs::write(&first, "old\n").unwrap();
+        std::fs::write(&second, "old\n").unwrap();
+        // Occupy the temporary name so writing the second file fails
+        std::fs::create_dir(dir.path().join(".b.txt.replace-tmp")).unwrap();
+
+        let request = replace_request(dir.path(), vec!["a.txt".to_string(), "b.txt".to_string()]);
+        let (status, message) = replace_in_files(Json(request)).await.unwrap_err();
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(message.contains("Failed to write b.txt"), "{}", message);
+        assert!(message.contains("were restored"), "{}", message);
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "old\n");
+        assert_eq!(std::fs::read_to_string(&second).unwrap(), "old\n");
+        assert!(!dir.path().join(".a.txt.replace-tmp").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomically_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.sh");
+        std::fs::write(&path, "echo old\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        write_atomically(&path, "echo new\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "echo new\n");
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_replace_skips_symlinks_leading_outside() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside_dir = tempfile::tempdir().unwrap();
+        let outside = outside_dir.path().join("secret.txt");
+        std::fs::write(&outside, "old\n").unwrap();
+        let link = dir.path().join("link.txt");
+        std::os::unix::fs::symlink(&outside, &link).unwrap();
+
+        let request = replace_request(dir.path(), vec![link.to_string_lossy().into_owned()]);
+        let response = replace_in_files(Json(request)).await.unwrap().0;
+        assert_eq!(response.files_modified, 0);
+        assert_eq!(std::fs::read_to_string(&outside).unwrap(), "old\n");
+    }
+
+    #[tokio::test]
+    async fn test_search_stream_sends_matches_then_totals() {
+        async fn stream_events(request: SearchFilesRequest) -> Vec<serde_json::Value> {
+            use futures::StreamExt;
+
+            let stream = search_files_stream(Json(request)).await.unwrap();
+            stream
+                .map(|frame| {
+                    let frame = String::from_utf8(frame.unwrap().to_vec()).unwrap();
+                    let json = frame.strip_prefix("data: ").unwrap().trim_end();
+                    serde_json::from_str(json).unwrap()
+                })
+                .collect()
+                .await
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "foo\nfoo\n").unwrap();
+        std::fs::write(dir.path().join("b.rs"), "foo\n").unwrap();
+        let request = |offset| SearchFilesRequest {
+            max_results: Some(2),
+            offset,
+            context_lines: Some(0),
+            ..search_request("foo", dir.path())
+        };
+
+        let events = stream_events(request(None)).await;
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0]["type"], "Match");
+        assert_eq!(events[0]["lineNumber"], 1);
+        assert_eq!(events[1]["lineNumber"], 2);
+        assert_eq!(events[2]["type"], "Done");
+        assert_eq!(events[2]["totalMatches"], 2);
+        assert_eq!(events[2]["totalFiles"], 1);
+        assert_eq!(events[2]["truncated"], true);
+        assert_eq!(events[2]["timedOut"], false);
+
+        // The batch endpoint counts the same way
+        let Json(batch) = search_files(Json(request(None))).await.unwrap();
+        assert_eq!(batch.total_matches, 2);
+        assert!(batch.truncated);
+
+        // Paging, even from the start, counts every match on both endpoints
+        let paged = stream_events(request(Some(0))).await;
+        assert_eq!(paged.len(), 3);
+        assert_eq!(paged[2]["totalMatches"], 3);
+        assert_eq!(paged[2]["totalFiles"], 2);
+        let Json(batch) = search_files(Json(request(Some(0)))).await.unwrap();
+        assert_eq!(batch.total_matches, 3);
+        assert_eq!(batch.total_files, 2);
+    }
+
+    #[tokio::test]
+    async fn test_fuzzy_filename_search_ranks_by_score() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("routes")).unwrap();
+        for name in ["reply.rs", "recipe_parser.rs", "session.rs"] {
+            std::fs::write(dir.path().join("routes").join(name), "").unwrap();
+        }
+        let request = |fuzzy| SearchFilenamesRequest {
+            query: "rtsrply".to_string(),
+            working_dir: dir.path().to_string_lossy().into_owned(),
+            max_results: None,
+            fuzzy,
+        };
+
+        let response = search_filenames(Json(request(true))).await.unwrap().0;
+        let names: Vec<_> = response.matches.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["reply.rs"]);
+        assert!(response.matches[0].score.unwrap() > 0);
+
+        let response = search_filenames(Json(request(false))).await.unwrap().0;
+        assert!(response.matches.is_empty());
+    }
+
+    #[test]
+    fn test_rust_definition_recognizes_items() {
+        assert_eq!(
+            rust_definition("pub async fn reply("),
+            Some((SymbolKind::Fn, "reply"))
+        );
+        assert_eq!(
+            rust_definition("    pub(crate) const unsafe extern \"C\" fn raw() {}"),
+            Some((SymbolKind::Fn, "raw"))
+        );
+        assert_eq!(
+            rust_definition("const DEFAULT_SEARCH_TIMEOUT: Duration = x;"),
+            Some((SymbolKind::Const, "DEFAULT_SEARCH_TIMEOUT"))
+        );
+        assert_eq!(
+            rust_definition("static mut COUNTER: u32 = 0;"),
+            Some((SymbolKind::Static, "COUNTER"))
+        );
+        assert_eq!(
+            rust_definition("macro_rules! impl_tool {"),
+            Some((SymbolKind::Macro, "impl_tool"))
+        );
+        assert_eq!(
+            rust_definition("pub(super) enum Mode {"),
+            Some((SymbolKind::Enum, "Mode"))
+        );
+        assert_eq!(rust_definition("    // fn commented_out()"), None);
+        assert_eq!(rust_definition("    let reply = reply(x);"), None);
+        assert_eq!(rust_definition("impl Reply for Agent {"), None);
+    }
+
+    #[tokio::test]
+    async fn test_symbol_search_finds_definitions_not_uses() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("agent.rs"),
+            "pub struct Reply;\n\npub async fn reply() -> Reply {\n    reply_inner()\n}\n\nfn reply_inner() -> Reply {\n    Reply\n}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "fn reply() {}\n").unwrap();
+        let request = |exact, kinds| SearchSymbolsRequest {
+            query: "reply".to_string(),
+            working_dir: dir.path().to_string_lossy().into_owned(),
+            exact,
+            kinds,
+            max_results: None,
+        };
+
+        let response = search_symbols(Json(request(false, None))).await.unwrap().0;
+        let found: Vec<_> = response
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind, s.line_number))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("Reply", SymbolKind::Struct, 1),
+                ("reply", SymbolKind::Fn, 3),
+                ("reply_inner", SymbolKind::Fn, 7),
+            ]
+        );
+        assert!(response.symbols[1].file_path.ends_with("agent.rs"));
+        assert_eq!(response.symbols[1].line, "pub async fn reply() -> Reply {");
+
+        let response = search_symbols(Json(request(true, None))).await.unwrap().0;
+        assert_eq!(response.symbols.len(), 1);
+        assert_eq!(response.symbols[0].line_number, 3);
+
+        let kinds = Some(vec![SymbolKind::Struct]);
+        let response = search_symbols(Json(request(false, kinds))).await.unwrap().0;
+        assert_eq!(response.symbols.len(), 1);
+        assert_eq!(response.symbols[0].name, "Reply");
+
+        let mut missing = request(false, None);
+        missing.working_dir = dir.path().join("missing").to_string_lossy().into_owned();
+        assert_eq!(
+            search_symbols(Json(missing)).await.unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_filename_matches_prefers_tighter_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["reply.rs", "replay_test.rs"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let files = WalkBuilder::new(dir.path())
+            .build()
+            .flatten()
+            .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()));
+
+        let (matches, truncated) = fuzzy_filename_matches(dir.path(), "rply", files, 1);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].name, "reply.rs");
+        assert!(truncated);
+    }
+
+    #[tokio::test]
+    async fn test_count_only_search_returns_totals_without_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "foo\nfoo foo\n").unwrap();
+        std::fs::write(dir.path().join("b.rs"), "foo\n").unwrap();
+        std::fs::write(dir.path().join("c.rs"), "bar\n").unwrap();
+        let request = SearchFilesRequest {
+            max_results: Some(1),
+            count_only: true,
+            ..search_request("foo", dir.path())
+        };
+
+        let Json(counts) = search_files(Json(request)).await.unwrap();
+        assert_eq!(counts.total_matches, 3);
+        assert_eq!(counts.total_files, 2);
+        assert!(counts.matches.is_empty());
+        assert!(counts.file_stats.is_empty());
+        assert!(!counts.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_binary_and_encoded_files_are_searched_on_request() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("data.bin"), b"foo\x00\x01\nfoo\n").unwrap();
+        let utf16: Vec<u8> = "bar foo\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        std::fs::write(dir.path().join("notes.txt"), utf16).unwrap();
+        let request = |search_binary, encoding: Option<&str>| SearchFilesRequest {
+            search_binary,
+            encoding: encoding.map(str::to_string),
+            ..search_request("foo", dir.path())
+        };
+
+        let Json(default) = search_files(Json(request(false, None))).await.unwrap();
+        assert_eq!(default.total_matches, 0);
+
+        let Json(binary) = search_files(Json(request(true, None))).await.unwrap();
+        assert_eq!(binary.total_matches, 2);
+        assert!(binary
+            .matches
+            .iter()
+            .all(|m| m.file_path.ends_with("data.bin")));
+
+        let Json(decoded) = search_files(Json(request(false, Some("utf-16le"))))
+            .await
+            .unwrap();
+        assert_eq!(decoded.total_matches, 1);
+        assert_eq!(decoded.matches[0].line_text, "bar foo");
+
+        let err = search_files(Json(request(false, Some("not-an-encoding"))))
+            .await
+            .unwrap_err();
+        assert_eq!(err, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_search_returns_partial_results_on_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "foo\n").unwrap();
+        let request = |timeout_ms| SearchFilesRequest {
+            timeout_ms: Some(timeout_ms),
+            ..search_request("foo", dir.path())
+        };
+
+        let Json(expired) = search_files(Json(request(0))).await.unwrap();
+        assert!(expired.timed_out);
+        assert!(expired.truncated);
+        assert!(expired.matches.is_empty());
+
+        let Json(finished) = search_files(Json(request(10_000))).await.unwrap();
+        assert!(!finished.timed_out);
+        assert!(!finished.truncated);
+        assert_eq!(finished.matches.len(), 1);
+    }
+
+    #[test]
+    fn test_cancelled_search_stops_before_searching() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "foo\n").unwrap();
+        let walker = file_walker(&dir.path().to_string_lossy(), None, None, true, false).unwrap();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let matcher = RegexMatcherBuilder::new()
+            .fixed_strings(true)
+            .line_terminator(Some(b'\n'))
+            .build("foo")
+            .unwrap();
+        let mut collector = MatchCollector::new(matcher, 0, 10).with_cancellation(cancel);
+
+        collector.search(walker.build());
+        assert!(collector.cancelled);
+        assert_eq!(collector.match_total, 0);
+        assert!(collector.matches.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_rejects_invalid_regex() {
+        let dir = tempfile::tempdir().unwrap();
+        let request = SearchFilesRequest {
+            use_regex: true,
+            ..search_request("foo(", dir.path())
+        };
+
+        let result = search_files(Json(request)).await;
+        assert_eq!(result.err(), Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_search_rejects_empty_query_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "foo\nbar\n").unwrap();
+        let request = SearchFilesRequest {
+            queries: Some(vec!["foo".to_string(), String::new()]),
+            ..search_request("", dir.path())
+        };
+
+        let result = search_files(Json(request)).await;
+        assert_eq!(result.err(), Some(StatusCode::BAD_REQUEST));
+    }
+}
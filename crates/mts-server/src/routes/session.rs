@@ -1,7 +1,9 @@
 use crate::routes::errors::ErrorResponse;
 use crate::routes::recipe_utils::{apply_recipe_to_agent, build_recipe_with_parameter_values};
 use crate::state::AppState;
-use axum::extract::State;
+use axum::extract::{Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
 use axum::routing::post;
 use axum::{
     extract::Path,
@@ -9,13 +11,18 @@ use axum::{
     routing::{delete, get, put},
     Json, Router,
 };
+use mts::config::Config;
+use mts::conversation::message::{Message, MessageContent};
+use mts::conversation::Conversation;
+use mts::providers::debug_capture::{self, ProviderExchange, PROVIDER_DEBUG_CONFIG_KEY};
 use mts::recipe::Recipe;
 use mts::session::session_manager::SessionInsights;
 use mts::session::{Session, SessionManager};
+use rmcp::model::Role;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 #[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -24,6 +31,22 @@ pub struct SessionListResponse {
     sessions: Vec<Session>,
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConversationFormat {
+    #[default]
+    Json,
+    Markdown,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct ConversationQuery {
+    /// `json` (default) returns the `Conversation`; `markdown` returns a
+    /// readable transcript of the user-visible messages
+    #[serde(default)]
+    pub format: ConversationFormat,
+}
+
 #[derive(Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateSessionNameRequest {
@@ -74,6 +97,21 @@ pub struct EditMessageResponse {
     session_id: String,
 }
 
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSessionDebugRequest {
+    /// Whether to capture raw provider requests and responses for this session
+    enabled: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDebugResponse {
+    enabled: bool,
+    /// Most recent provider exchanges, oldest first, with secrets redacted
+    exchanges: Vec<ProviderExchange>,
+}
+
 const MAX_NAME_LENGTH: usize = 200;
 
 #[utoipa::path(
@@ -121,6 +159,105 @@ async fn get_session(Path(session_id): Path<String>) -> Result<Json<Session>, St
 
     Ok(Json(session))
 }
+#[utoipa::path(
+    get,
+    path = "/sessions/{session_id}/conversation",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session"),
+        ConversationQuery
+    ),
+    responses(
+        (status = 200, description = "Stored conversation, or a transcript when format=markdown", content(
+            ("application/json" = Conversation),
+            ("text/markdown" = String)
+        )),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn get_session_conversation(
+    Path(session_id): Path<String>,
+    Query(query): Query<ConversationQuery>,
+) -> Result<Response, StatusCode> {
+    // Read straight from storage so clients can reload history without an
+    // active reply stream.
+    let session = SessionManager::get_session(&session_id, true)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    let conversation = session.conversation.unwrap_or_default();
+
+    match query.format {
+        ConversationFormat::Json => Ok(Json(conversation).into_response()),
+        ConversationFormat::Markdown => Ok((
+            [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+            conversation_to_markdown(&session.name, &conversation),
+        )
+            .into_response()),
+    }
+}
+
+fn conversation_to_markdown(title: &str, conversation: &Conversation) -> String {
+    let mut md = format!("# {}\n\n", title);
+    for message in conversation
+        .messages()
+        .iter()
+        .filter(|m| m.metadata.user_visible)
+    {
+        let body = message_to_markdown(message);
+        if body.is_empty() {
+            continue;
+        }
+        let heading = match message.role {
+            Role::User => "User",
+            Role::Assistant => "Assistant",
+        };
+        md.push_str(&format!("## {}\n\n{}\n\n", heading, body));
+    }
+    md.trim_end().to_string() + "\n"
+}
+
+fn message_to_markdown(message: &Message) -> String {
+    let mut parts = Vec::new();
+    for content in &message.content {
+        match content {
+            MessageContent::Text(text) => parts.push(text.text.trim().to_string()),
+            MessageContent::ToolRequest(request) => match &request.tool_call {
+                Ok(call) => parts.push(format!(
+                    "**Tool call:** `{}`\n```json\n{}\n```",
+                    call.name,
+                    serde_json::to_string_pretty(&call.arguments)
+                        .unwrap_or_else(|_| "{}".to_string())
+                )),
+                Err(e) => parts.push(format!("**Tool call failed:** {}", e)),
+            },
+            MessageContent::ToolResponse(response) => match &response.tool_result {
+                Ok(result) => {
+                    let text = result
+                        .content
+                        .iter()
+                        .filter_map(|c| c.as_text().map(|t| t.text.as_str()))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    parts.push(format!("**Tool result:**\n```\n{}\n```", text.trim()));
+                }
+                Err(e) => parts.push(format!("**Tool error:** {}", e)),
+            },
+            MessageContent::Image(image) => parts.push(format!("*[Image: {}]*", image.mime_type)),
+            MessageContent::SystemNotification(notification) => {
+                parts.push(format!("*{}*", notification.msg))
+            }
+            _ => {}
+        }
+    }
+    parts.retain(|p| !p.is_empty());
+    parts.join("\n\n")
+}
+
 #[utoipa::path(
     get,
     path = "/sessions/insights",
@@ -390,12 +527,81 @@ async fn edit_message(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/sessions/{session_id}/debug",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "Captured provider exchanges", body = SessionDebugResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn get_session_debug(Path(session_id): Path<String>) -> Json<SessionDebugResponse> {
+    let exchanges = debug_capture::exchanges(&session_id);
+    Json(SessionDebugResponse {
+        enabled: exchanges.is_some(),
+        exchanges: exchanges.unwrap_or_default(),
+    })
+}
+
+#[utoipa::path(
+    put,
+    path = "/sessions/{session_id}/debug",
+    request_body = UpdateSessionDebugRequest,
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "Debug capture updated"),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 403, description = "Provider debugging is not enabled on this server"),
+        (status = 404, description = "Session not found")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn update_session_debug(
+    Path(session_id): Path<String>,
+    Json(request): Json<UpdateSessionDebugRequest>,
+) -> Result<StatusCode, StatusCode> {
+    if !request.enabled {
+        debug_capture::disable(&session_id);
+        return Ok(StatusCode::OK);
+    }
+
+    let allowed = Config::global()
+        .get_param::<bool>(PROVIDER_DEBUG_CONFIG_KEY)
+        .unwrap_or(false);
+    if !allowed {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    SessionManager::get_session(&session_id, false)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    debug_capture::enable(&session_id);
+
+    Ok(StatusCode::OK)
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/sessions", get(list_sessions))
         .route("/sessions/{session_id}", get(get_session))
         .route("/sessions/{session_id}", delete(delete_session))
         .route("/sessions/{session_id}/export", get(export_session))
+        .route(
+            "/sessions/{session_id}/conversation",
+            get(get_session_conversation),
+        )
         .route("/sessions/import", post(import_session))
         .route("/sessions/insights", get(get_session_insights))
         .route("/sessions/{session_id}/name", put(update_session_name))
@@ -404,5 +610,30 @@ pub fn routes(state: Arc<AppState>) -> Router {
             put(update_session_user_recipe_values),
         )
         .route("/sessions/{session_id}/edit_message", post(edit_message))
+        .route(
+            "/sessions/{session_id}/debug",
+            get(get_session_debug).put(update_session_debug),
+        )
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversation_to_markdown_skips_hidden_messages() {
+        let conversation = Conversation::new_unvalidated(vec![
+            Message::user().with_text("What is in this folder?"),
+            Message::user().with_text("internal reminder").agent_only(),
+            Message::assistant().with_text("Two files."),
+        ]);
+
+        let md = conversation_to_markdown("Folder chat", &conversation);
+
+        assert_eq!(
+            md,
+            "# Folder chat\n\n## User\n\nWhat is in this folder?\n\n## Assistant\n\nTwo files.\n"
+        );
+    }
+}
@@ -1,16 +1,60 @@
 use axum::http::StatusCode;
+use mts::config::paths::Paths;
+use mts::config::Config;
 use mts::execution::manager::AgentManager;
 use mts::scheduler_trait::SchedulerTrait;
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 
-use crate::background_tasks::BackgroundTaskManager;
-use crate::routes::browser::BrowserSessionManager;
+use crate::background_tasks::{
+    BackgroundTaskManager, ConcurrentTaskPolicy, DEFAULT_CHANNEL_CAPACITY, DEFAULT_IDEMPOTENCY_TTL,
+    DEFAULT_REAPER_INTERVAL, DEFAULT_TASK_TTL, TASK_STATE_FILE,
+};
+use crate::routes::browser::{
+    BrowserSessionManager, BROWSER_STATE_FILE, DEFAULT_AUDIT_LIMIT, DEFAULT_CONSENT_TTL,
+    DEFAULT_HISTORY_LIMIT, DEFAULT_MAX_DOWNLOAD_BYTES, DEFAULT_MAX_QUEUE_LEN,
+    DEFAULT_NAVIGATIONS_PER_MINUTE,
+};
+use crate::routes::reply::{
+    DEFAULT_HEARTBEAT_INTERVAL, DEFAULT_MAX_REPLY_BODY_BYTES, DEFAULT_STREAM_POLL_TIMEOUT,
+};
 use crate::tunnel::TunnelManager;
 
+/// Files the server keeps state in across restarts
+#[derive(Debug, Clone)]
+pub struct StatePaths {
+    /// Records of finished background tasks
+    pub background_tasks: PathBuf,
+    /// The browser allowlist and consent flag
+    pub browser: PathBuf,
+}
+
+impl StatePaths {
+    /// The configured paths, defaulting to the state and config directories
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            background_tasks: config
+                .get_param::<String>("MTS_BACKGROUND_TASK_STATE_PATH")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| Paths::state_dir().join(TASK_STATE_FILE)),
+            browser: Paths::config_dir().join(BROWSER_STATE_FILE),
+        }
+    }
+
+    /// Keep all state files in `dir`
+    #[allow(dead_code)]
+    pub fn in_dir(dir: &Path) -> Self {
+        Self {
+            background_tasks: dir.join(TASK_STATE_FILE),
+            browser: dir.join(BROWSER_STATE_FILE),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct AppState {
     pub(crate) agent_manager: Arc<AgentManager>,
@@ -23,14 +67,128 @@ pub struct AppState {
     pub background_tasks: Arc<BackgroundTaskManager>,
     /// Manages the browser WebSocket connection and state
     pub browser_manager: Arc<BrowserSessionManager>,
+    /// How often running replies send a Ping heartbeat
+    pub heartbeat_interval: Duration,
+    /// How long replies wait on the agent stream between checks; this bounds how
+    /// quickly a cancelled reply notices it should stop
+    pub stream_poll_timeout: Duration,
+    /// Largest request body `/reply` accepts
+    pub max_reply_body_bytes: usize,
 }
 
 impl AppState {
     pub async fn new() -> anyhow::Result<Arc<AppState>> {
+        Self::with_state_paths(StatePaths::from_config(Config::global())).await
+    }
+
+    /// State kept in a fresh temporary directory, so tests don't share state files
+    #[cfg(test)]
+    pub async fn for_tests() -> Arc<AppState> {
+        let dir = std::env::temp_dir().join(format!("mts-server-{}", uuid::Uuid::new_v4()));
+        Self::with_state_paths(StatePaths::in_dir(&dir))
+            .await
+            .unwrap()
+    }
+
+    /// State persisted to `paths` rather than the configured locations
+    pub async fn with_state_paths(paths: StatePaths) -> anyhow::Result<Arc<AppState>> {
         let agent_manager = AgentManager::instance().await?;
         let tunnel_manager = Arc::new(TunnelManager::new());
-        let background_tasks = Arc::new(BackgroundTaskManager::new());
-        let browser_manager = Arc::new(BrowserSessionManager::new());
+        let config = Config::global();
+        let channel_capacity = config
+            .get_param::<usize>("MTS_BACKGROUND_TASK_CHANNEL_CAPACITY")
+            .unwrap_or(DEFAULT_CHANNEL_CAPACITY);
+        // "reject", "queue" or "replace"
+        let concurrent_reply_policy = config
+            .get_param::<ConcurrentTaskPolicy>("MTS_CONCURRENT_REPLY_POLICY")
+            .unwrap_or_default();
+        let idempotency_ttl = config
+            .get_param::<u64>("MTS_REPLY_IDEMPOTENCY_TTL_SECS")
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_IDEMPOTENCY_TTL);
+        let background_tasks = Arc::new(
+            BackgroundTaskManager::load_or_default(paths.background_tasks)
+                .channel_capacity(channel_capacity)
+                .concurrent_task_policy(concurrent_reply_policy)
+                .idempotency_ttl(idempotency_ttl),
+        );
+        let reaper_interval = config
+            .get_param::<u64>("MTS_BACKGROUND_TASK_REAPER_INTERVAL_SECS")
+            .ok()
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_REAPER_INTERVAL);
+        let task_ttl = config
+            .get_param::<u64>("MTS_BACKGROUND_TASK_TTL_SECS")
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_TASK_TTL);
+        background_tasks.start_reaper(reaper_interval, task_ttl);
+        let max_queue_len = config
+            .get_param::<usize>("MTS_BROWSER_MAX_QUEUE_LEN")
+            .unwrap_or(DEFAULT_MAX_QUEUE_LEN);
+        // 0 keeps browser consent until it is revoked
+        let consent_ttl = config
+            .get_param::<u64>("MTS_BROWSER_CONSENT_TTL_SECS")
+            .map(|secs| (secs > 0).then(|| Duration::from_secs(secs)))
+            .unwrap_or(Some(DEFAULT_CONSENT_TTL));
+        // 0 lets sessions navigate without a rate limit
+        let navigations_per_minute = config
+            .get_param::<u32>("MTS_BROWSER_NAVIGATIONS_PER_MINUTE")
+            .unwrap_or(DEFAULT_NAVIGATIONS_PER_MINUTE);
+        let history_limit = config
+            .get_param::<usize>("MTS_BROWSER_HISTORY_LIMIT")
+            .unwrap_or(DEFAULT_HISTORY_LIMIT);
+        let audit_limit = config
+            .get_param::<usize>("MTS_BROWSER_AUDIT_LIMIT")
+            .unwrap_or(DEFAULT_AUDIT_LIMIT);
+        let max_download_bytes = config
+            .get_param::<u64>("MTS_BROWSER_MAX_DOWNLOAD_BYTES")
+            .unwrap_or(DEFAULT_MAX_DOWNLOAD_BYTES);
+        // A JSON list, or a comma-separated string from the environment
+        let allowed_domains = config
+            .get_param::<Vec<String>>("MTS_BROWSER_ALLOWED_DOMAINS")
+            .or_else(|_| {
+                config
+                    .get_param::<String>("MTS_BROWSER_ALLOWED_DOMAINS")
+                    .map(|list| {
+                        list.split(',')
+                            .map(str::trim)
+                            .filter(|domain| !domain.is_empty())
+                            .map(String::from)
+                            .collect()
+                    })
+            })
+            .ok();
+        let default_browser_manager = match allowed_domains {
+            Some(domains) => BrowserSessionManager::with_allowed_domains(domains),
+            None => BrowserSessionManager::new(),
+        };
+        let browser_manager = Arc::new(
+            BrowserSessionManager::load_or(paths.browser, default_browser_manager)
+                .max_queue_len(max_queue_len)
+                .consent_ttl(consent_ttl)
+                .navigations_per_minute(navigations_per_minute)
+                .history_limit(history_limit)
+                .audit_limit(audit_limit)
+                .max_download_bytes(max_download_bytes),
+        );
+        let heartbeat_interval = config
+            .get_param::<u64>("MTS_HEARTBEAT_INTERVAL_MS")
+            .ok()
+            .filter(|ms| *ms > 0)
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_HEARTBEAT_INTERVAL);
+        let stream_poll_timeout = config
+            .get_param::<u64>("MTS_STREAM_POLL_TIMEOUT_MS")
+            .ok()
+            .filter(|ms| *ms > 0)
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_STREAM_POLL_TIMEOUT);
+        let max_reply_body_bytes = config
+            .get_param::<usize>("MTS_REPLY_MAX_BODY_BYTES")
+            .ok()
+            .filter(|bytes| *bytes > 0)
+            .unwrap_or(DEFAULT_MAX_REPLY_BODY_BYTES);
 
         Ok(Arc::new(Self {
             agent_manager,
@@ -40,6 +198,9 @@ impl AppState {
             tunnel_manager,
             background_tasks,
             browser_manager,
+            heartbeat_interval,
+            stream_poll_timeout,
+            max_reply_body_bytes,
         }))
     }
 
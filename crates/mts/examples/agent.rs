@@ -45,6 +45,11 @@ async fn main() -> anyhow::Result<()> {
         schedule_id: None,
         max_turns: None,
         retry_config: None,
+        compaction: None,
+        fallback_providers: None,
+        tool_overrides: None,
+        max_total_tokens: None,
+        model_override: None,
     };
 
     let user_message = Message::user()
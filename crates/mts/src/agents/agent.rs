@@ -10,7 +10,10 @@ use uuid::Uuid;
 
 use super::final_output_tool::FinalOutputTool;
 use super::platform_tools;
-use super::tool_execution::{ToolCallResult, CHAT_MODE_TOOL_SKIPPED_RESPONSE, DECLINED_RESPONSE};
+use super::tool_execution::{
+    ToolCallResult, CHAT_MODE_TOOL_SKIPPED_RESPONSE, DECLINED_RESPONSE,
+    PLAN_MODE_TOOL_SKIPPED_RESPONSE,
+};
 use crate::action_required_manager::ActionRequiredManager;
 use crate::agents::extension::{ExtensionConfig, ExtensionError, ExtensionResult, ToolInfo};
 use crate::agents::extension_manager::{get_parameter_names, ExtensionManager};
@@ -26,12 +29,10 @@ use crate::agents::subagent_tool::{
 };
 use crate::agents::tool_route_manager::ToolRouteManager;
 use crate::agents::tool_router_index_manager::ToolRouterIndexManager;
-use crate::agents::types::SessionConfig;
+use crate::agents::types::{FallbackProvider, SessionConfig};
 use crate::agents::types::{FrontendTool, SharedProvider, ToolResultReceiver};
 use crate::config::{get_enabled_extensions, Config, MtsMode};
-use crate::context_mgmt::{
-    check_if_compaction_needed, compact_messages, DEFAULT_COMPACTION_THRESHOLD,
-};
+use crate::context_mgmt::{check_if_compaction_needed, compact_messages};
 use crate::conversation::message::{
     ActionRequiredData, Message, MessageContent, SystemNotificationType, ToolRequest,
 };
@@ -61,6 +62,9 @@ use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, instrument, warn};
 
 const DEFAULT_MAX_TURNS: u32 = 1000;
+const DEFAULT_RATE_LIMIT_RETRIES: u32 = 3;
+const RATE_LIMIT_INITIAL_BACKOFF_MS: u64 = 5_000;
+const RATE_LIMIT_MAX_BACKOFF_MS: u64 = 120_000;
 const COMPACTION_THINKING_TEXT: &str = "mts is compacting the conversation...";
 pub const MANUAL_COMPACT_TRIGGERS: &[&str] =
     &["Please compact this conversation", "/compact", "/summarize"];
@@ -151,6 +155,17 @@ where
     })
 }
 
+/// How long to wait before the given retry of a rate-limited turn, with jitter
+fn rate_limit_backoff(attempt: u32) -> std::time::Duration {
+    crate::providers::retry::RetryConfig::new(
+        DEFAULT_RATE_LIMIT_RETRIES as usize,
+        RATE_LIMIT_INITIAL_BACKOFF_MS,
+        2.0,
+        RATE_LIMIT_MAX_BACKOFF_MS,
+    )
+    .delay_for_attempt(attempt as usize)
+}
+
 impl Agent {
     pub fn new() -> Self {
         // Create channels with buffer size 32 (adjust if needed)
@@ -252,6 +267,7 @@ impl Agent {
         &self,
         unfixed_conversation: Conversation,
         working_dir: &std::path::Path,
+        tool_overrides: HashMap<String, MtsMode>,
     ) -> Result<ReplyContext> {
         let unfixed_messages = unfixed_conversation.messages().clone();
         let (conversation, issues) = fix_conversation(unfixed_conversation.clone());
@@ -275,6 +291,22 @@ impl Agent {
         self.tool_inspection_manager
             .update_permission_inspector_mode(mts_mode)
             .await;
+        self.tool_inspection_manager
+            .update_permission_inspector_tool_overrides(tool_overrides)
+            .await;
+        let declared_read_only = tools
+            .iter()
+            .filter(|tool| {
+                tool.annotations
+                    .as_ref()
+                    .and_then(|annotations| annotations.read_only_hint)
+                    .unwrap_or(false)
+            })
+            .map(|tool| tool.name.to_string())
+            .collect();
+        self.tool_inspection_manager
+            .update_permission_inspector_declared_read_only(declared_read_only)
+            .await;
 
         Ok(ReplyContext {
             conversation,
@@ -782,11 +814,12 @@ impl Agent {
             .clone()
             .ok_or_else(|| anyhow::anyhow!("Session {} has no conversation", session_config.id))?;
 
+        let compaction_policy = session_config.compaction.clone().unwrap_or_default();
         let needs_auto_compact = !is_manual_compact
             && check_if_compaction_needed(
                 self.provider().await?.as_ref(),
                 &conversation,
-                None,
+                &compaction_policy,
                 &session,
             )
             .await?;
@@ -798,15 +831,9 @@ impl Agent {
                 conversation
             } else {
                 if !is_manual_compact {
-                    let config = crate::config::Config::global();
-                    let threshold = config
-                        .get_param::<f64>("MTS_AUTO_COMPACT_THRESHOLD")
-                        .unwrap_or(DEFAULT_COMPACTION_THRESHOLD);
-                    let threshold_percentage = (threshold * 100.0) as u32;
-
                     let inline_msg = format!(
-                        "Exceeded auto-compact threshold of {}%. Performing auto-compaction...",
-                        threshold_percentage
+                        "Exceeded auto-compact threshold of {}. Performing auto-compaction...",
+                        compaction_policy.describe()
                     );
 
                     yield AgentEvent::Message(
@@ -868,7 +895,11 @@ impl Agent {
         cancel_token: Option<CancellationToken>,
     ) -> Result<BoxStream<'_, Result<AgentEvent>>> {
         let context = self
-            .prepare_reply_context(conversation, &session.working_dir)
+            .prepare_reply_context(
+                conversation,
+                &session.working_dir,
+                session_config.tool_overrides.clone().unwrap_or_default(),
+            )
             .await?;
         let ReplyContext {
             mut conversation,
@@ -889,11 +920,46 @@ impl Agent {
                 warn!("Failed to generate session description: {}", e);
             }
         });
+        // Used instead of the session's provider for this reply only, by a model
+        // override or a fallback that took over
+        let mut provider_override = match &session_config.model_override {
+            Some(model_override) => Some(
+                crate::providers::create_with_named_model(
+                    &model_override.provider,
+                    &model_override.model,
+                )
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to create {} ({}) for this reply",
+                        model_override.provider, model_override.model
+                    )
+                })?,
+            ),
+            None => None,
+        };
 
         Ok(Box::pin(async_stream::try_stream! {
             let _ = reply_span.enter();
             let mut turns_taken = 0u32;
             let max_turns = session_config.max_turns.unwrap_or(DEFAULT_MAX_TURNS);
+            let mut fallback_providers = session_config
+                .fallback_providers
+                .clone()
+                .unwrap_or_default()
+                .into_iter();
+            let max_rate_limit_retries = session_config
+                .retry_config
+                .as_ref()
+                .and_then(|retry_config| retry_config.rate_limit_retries)
+                .unwrap_or(DEFAULT_RATE_LIMIT_RETRIES);
+            let mut rate_limit_retries = 0u32;
+            if let Some(model_override) = &session_config.model_override {
+                yield AgentEvent::ModelChange {
+                    model: model_override.model.clone(),
+                    mode: "override".to_string(),
+                };
+            }
 
             loop {
                 if is_token_cancelled(&cancel_token) {
@@ -925,8 +991,12 @@ impl Agent {
                     &self.extension_manager,
                 ).await;
 
+                let reply_provider = match &provider_override {
+                    Some(provider) => Arc::clone(provider),
+                    None => self.provider().await?,
+                };
                 let mut stream = Self::stream_response_from_provider(
-                    self.provider().await?,
+                    Arc::clone(&reply_provider),
                     &system_prompt,
                     conversation_with_moim.messages(),
                     &tools,
@@ -937,6 +1007,7 @@ impl Agent {
                 let mut messages_to_add = Conversation::default();
                 let mut tools_updated = false;
                 let mut did_recovery_compact_this_iteration = false;
+                let mut retry_turn = false;
 
                 while let Some(next) = stream.next().await {
                     if is_token_cancelled(&cancel_token) {
@@ -946,8 +1017,7 @@ impl Agent {
                     match next {
                         Ok((response, usage)) => {
                             // Emit model change event if provider is lead-worker
-                            let provider = self.provider().await?;
-                            if let Some(lead_worker) = provider.as_lead_worker() {
+                            if let Some(lead_worker) = reply_provider.as_lead_worker() {
                                 if let Some(ref usage) = usage {
                                     let active_model = usage.model.clone();
                                     let (lead_model, worker_model) = lead_worker.get_model_info();
@@ -1001,25 +1071,39 @@ impl Agent {
                                     request_to_response_map.insert(request.id.clone(), tool_response_messages[idx].clone());
                                 }
 
-                                for (idx, request) in frontend_requests.iter().enumerate() {
-                                    let mut frontend_tool_stream = self.handle_frontend_tool_request(
-                                        request,
-                                        tool_response_messages[idx].clone(),
-                                    );
+                                if mts_mode != MtsMode::Plan {
+                                    for (idx, request) in frontend_requests.iter().enumerate() {
+                                        let mut frontend_tool_stream = self.handle_frontend_tool_request(
+                                            request,
+                                            tool_response_messages[idx].clone(),
+                                        );
 
-                                    while let Some(msg) = frontend_tool_stream.try_next().await? {
-                                        yield AgentEvent::Message(msg);
+                                        while let Some(msg) = frontend_tool_stream.try_next().await? {
+                                            yield AgentEvent::Message(msg);
+                                        }
                                     }
                                 }
-                                if mts_mode == MtsMode::Chat {
-                                    // Skip all remaining tool calls in chat mode
-                                    for request in remaining_requests.iter() {
+                                // The requests were already surfaced in the yielded response; in chat
+                                // and plan mode they are answered without being dispatched
+                                let skipped = match mts_mode {
+                                    MtsMode::Chat => Some((
+                                        CHAT_MODE_TOOL_SKIPPED_RESPONSE,
+                                        remaining_requests.iter().collect::<Vec<_>>(),
+                                    )),
+                                    MtsMode::Plan => Some((
+                                        PLAN_MODE_TOOL_SKIPPED_RESPONSE,
+                                        frontend_requests.iter().chain(remaining_requests.iter()).collect(),
+                                    )),
+                                    _ => None,
+                                };
+                                if let Some((skipped_response, skipped_requests)) = skipped {
+                                    for request in skipped_requests {
                                         if let Some(response_msg) = request_to_response_map.get(&request.id) {
                                             let mut response = response_msg.lock().await;
                                             *response = response.clone().with_tool_response(
                                                 request.id.clone(),
                                                 Ok(CallToolResult {
-                                                    content: vec![Content::text(CHAT_MODE_TOOL_SKIPPED_RESPONSE)],
+                                                    content: vec![Content::text(skipped_response)],
                                                     structured_content: None,
                                                     is_error: Some(false),
                                                     meta: None,
@@ -1192,6 +1276,58 @@ impl Agent {
                         Err(ref provider_err) => {
                             crate::posthog::emit_error(provider_err.telemetry_type());
                             error!("Error: {}", provider_err);
+
+                            if let ProviderError::RateLimitExceeded { retry_delay, .. } = provider_err {
+                                if rate_limit_retries < max_rate_limit_retries {
+                                    rate_limit_retries += 1;
+                                    let delay = retry_delay
+                                        .unwrap_or_else(|| rate_limit_backoff(rate_limit_retries));
+                                    yield AgentEvent::Message(
+                                        Message::assistant().with_system_notification(
+                                            SystemNotificationType::InlineMessage,
+                                            format!(
+                                                "Rate limited, retrying in {}s ({}/{})...",
+                                                delay.as_millis().div_ceil(1000),
+                                                rate_limit_retries,
+                                                max_rate_limit_retries
+                                            ),
+                                        )
+                                    );
+                                    match &cancel_token {
+                                        Some(token) => {
+                                            tokio::select! {
+                                                _ = tokio::time::sleep(delay) => {}
+                                                _ = token.cancelled() => {}
+                                            }
+                                        }
+                                        None => tokio::time::sleep(delay).await,
+                                    }
+                                    retry_turn = true;
+                                    break;
+                                }
+                            }
+
+                            if let Some((fallback, provider)) =
+                                Self::next_fallback_provider(&mut fallback_providers).await
+                            {
+                                yield AgentEvent::Message(
+                                    Message::assistant().with_system_notification(
+                                        SystemNotificationType::InlineMessage,
+                                        format!(
+                                            "Provider failed ({provider_err}). Retrying with {} ({})...",
+                                            fallback.provider, fallback.model
+                                        ),
+                                    )
+                                );
+                                yield AgentEvent::ModelChange {
+                                    model: fallback.model,
+                                    mode: "fallback".to_string(),
+                                };
+                                provider_override = Some(provider);
+                                retry_turn = true;
+                                break;
+                            }
+
                             yield AgentEvent::Message(
                                 Message::assistant().with_text(
                                     format!("Ran into this error: {provider_err}.\n\nPlease retry if you think this is a transient or recoverable error.")
@@ -1219,7 +1355,7 @@ impl Agent {
                             yield AgentEvent::Message(message);
                             exit_chat = true;
                         }
-                    } else if did_recovery_compact_this_iteration {
+                    } else if did_recovery_compact_this_iteration || retry_turn {
                         // Avoid setting exit_chat; continue from last user message in the conversation
                     } else {
                         match self.handle_retry_logic(&mut conversation, &session_config, &initial_messages).await {
@@ -1280,6 +1416,31 @@ impl Agent {
             .context("Failed to persist provider config to session")
     }
 
+    /// Create the first remaining fallback provider that can be created. It only serves
+    /// the current reply; the session's own provider is left as it was.
+    async fn next_fallback_provider(
+        fallbacks: &mut impl Iterator<Item = FallbackProvider>,
+    ) -> Option<(FallbackProvider, Arc<dyn Provider>)> {
+        for fallback in fallbacks {
+            match crate::providers::create_with_named_model(&fallback.provider, &fallback.model)
+                .await
+            {
+                Ok(provider) => {
+                    info!(
+                        "Switched to fallback provider {} ({})",
+                        fallback.provider, fallback.model
+                    );
+                    return Some((fallback, provider));
+                }
+                Err(e) => warn!(
+                    "Skipping fallback provider {} ({}): {}",
+                    fallback.provider, fallback.model, e
+                ),
+            }
+        }
+        None
+    }
+
     pub async fn update_router_tool_selector(
         &self,
         provider: Option<Arc<dyn Provider>>,
@@ -1571,7 +1732,12 @@ impl Agent {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::ModelConfig;
+    use crate::providers::base::{ProviderMetadata, ProviderUsage, Usage};
     use crate::recipe::Response;
+    use crate::session::session_manager::SessionType;
+    use async_trait::async_trait;
+    use std::collections::HashSet;
 
     #[tokio::test]
     async fn test_add_final_output_tool() -> Result<()> {
@@ -1608,6 +1774,355 @@ mod tests {
         Ok(())
     }
 
+    /// Answers with fixed text, or fails every request when `reply` is `None`
+    struct ScriptedProvider {
+        reply: Option<&'static str>,
+    }
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::new("fallback-test", "", "", "", vec![""], "", vec![])
+        }
+
+        fn get_name(&self) -> &str {
+            "fallback-test"
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            match self.reply {
+                Some(text) => Ok((
+                    Message::assistant().with_text(text),
+                    ProviderUsage::new("fallback-model".to_string(), Usage::default()),
+                )),
+                None => Err(ProviderError::ServerError("model outage".to_string())),
+            }
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new("fallback-model").unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reply_fails_over_to_fallback_provider() -> Result<()> {
+        crate::providers::register_test_provider::<ScriptedProvider, _>(|_| {
+            Box::pin(async {
+                Ok(ScriptedProvider {
+                    reply: Some("answered by fallback"),
+                })
+            })
+        })
+        .await;
+
+        let agent = Agent::new();
+        let session = SessionManager::create_session(
+            std::path::PathBuf::default(),
+            "fallback-test".to_string(),
+            SessionType::Hidden,
+        )
+        .await?;
+        let failing: Arc<dyn Provider> = Arc::new(ScriptedProvider { reply: None });
+        agent.update_provider(failing.clone(), &session.id).await?;
+
+        let session_config = SessionConfig {
+            id: session.id,
+            schedule_id: None,
+            max_turns: None,
+            retry_config: None,
+            compaction: None,
+            fallback_providers: Some(vec![FallbackProvider {
+                provider: "fallback-test".to_string(),
+                model: "fallback-model".to_string(),
+            }]),
+            tool_overrides: None,
+            max_total_tokens: None,
+            model_override: None,
+        };
+
+        let mut stream = agent
+            .reply(Message::user().with_text("hello"), session_config, None)
+            .await?;
+
+        let mut switched = false;
+        let mut answered = false;
+        while let Some(event) = stream.next().await {
+            match event? {
+                AgentEvent::ModelChange { model, mode } => {
+                    switched = model == "fallback-model" && mode == "fallback";
+                }
+                AgentEvent::Message(message) => {
+                    answered |= message.as_concat_text().contains("answered by fallback");
+                }
+                _ => {}
+            }
+        }
+
+        assert!(switched, "Expected a ModelChange to the fallback provider");
+        assert!(answered, "Expected the fallback provider to answer");
+        assert!(
+            Arc::ptr_eq(&agent.provider().await?, &failing),
+            "The fallback should only serve that reply, not replace the session's provider"
+        );
+        Ok(())
+    }
+
+    /// Rate-limits the first `limited` requests, then answers
+    struct RateLimitedProvider {
+        limited: std::sync::atomic::AtomicU32,
+    }
+
+    #[async_trait]
+    impl Provider for RateLimitedProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::new("rate-limit-test", "", "", "", vec![""], "", vec![])
+        }
+
+        fn get_name(&self) -> &str {
+            "rate-limit-test"
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            use std::sync::atomic::Ordering;
+            if self
+                .limited
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err(ProviderError::RateLimitExceeded {
+                    details: "slow down".to_string(),
+                    retry_delay: Some(std::time::Duration::from_millis(10)),
+                });
+            }
+            Ok((
+                Message::assistant().with_text("answered after backoff"),
+                ProviderUsage::new("rate-limit-model".to_string(), Usage::default()),
+            ))
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new("rate-limit-model").unwrap()
+        }
+
+        // Naming the session must not use up the rate-limited requests
+        async fn generate_session_name(
+            &self,
+            _messages: &Conversation,
+        ) -> Result<String, ProviderError> {
+            Ok("rate limit test".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reply_backs_off_after_rate_limit() -> Result<()> {
+        let agent = Agent::new();
+        let session = SessionManager::create_session(
+            std::path::PathBuf::default(),
+            "rate-limit-test".to_string(),
+            SessionType::Hidden,
+        )
+        .await?;
+        agent
+            .update_provider(
+                Arc::new(RateLimitedProvider { limited: 2.into() }),
+                &session.id,
+            )
+            .await?;
+
+        let session_config = SessionConfig {
+            id: session.id,
+            schedule_id: None,
+            max_turns: None,
+            retry_config: None,
+            compaction: None,
+            fallback_providers: None,
+            tool_overrides: None,
+            max_total_tokens: None,
+            model_override: None,
+        };
+
+        let mut stream = agent
+            .reply(Message::user().with_text("hello"), session_config, None)
+            .await?;
+
+        let mut notices = 0;
+        let mut answered = false;
+        while let Some(event) = stream.next().await {
+            if let AgentEvent::Message(message) = event? {
+                for content in &message.content {
+                    if let MessageContent::SystemNotification(notification) = content {
+                        if notification.msg.starts_with("Rate limited, retrying in") {
+                            notices += 1;
+                        }
+                    }
+                }
+                answered |= message.as_concat_text().contains("answered after backoff");
+            }
+        }
+
+        assert_eq!(notices, 2);
+        assert!(answered, "Expected the reply to resume after backing off");
+        Ok(())
+    }
+
+    /// Asks for a frontend tool and an extension tool, then answers once it has
+    /// their responses
+    struct ToolCallingProvider;
+
+    #[async_trait]
+    impl Provider for ToolCallingProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::new("plan-mode-test", "", "", "", vec![""], "", vec![])
+        }
+
+        fn get_name(&self) -> &str {
+            "plan-mode-test"
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            let answered = messages.last().is_some_and(|message| {
+                message
+                    .content
+                    .iter()
+                    .any(|content| matches!(content, MessageContent::ToolResponse(_)))
+            });
+            let message = if answered {
+                Message::assistant().with_text("here is the plan")
+            } else {
+                Message::assistant()
+                    .with_tool_request(
+                        "frontend-call",
+                        Ok(CallToolRequestParam {
+                            name: "pick_file".into(),
+                            arguments: None,
+                        }),
+                    )
+                    .with_tool_request(
+                        "extension-call",
+                        Ok(CallToolRequestParam {
+                            name: "developer__shell".into(),
+                            arguments: None,
+                        }),
+                    )
+            };
+            Ok((
+                message,
+                ProviderUsage::new("plan-mode-model".to_string(), Usage::default()),
+            ))
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new("plan-mode-model").unwrap()
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_plan_mode_answers_tool_calls_without_dispatching_them() -> Result<()> {
+        let agent = Agent::new();
+        agent
+            .add_extension(ExtensionConfig::Frontend {
+                name: "frontend".to_string(),
+                description: String::new(),
+                tools: vec![Tool::new(
+                    "pick_file",
+                    "Let the user pick a file",
+                    serde_json::Map::new(),
+                )],
+                instructions: None,
+                bundled: None,
+                available_tools: vec![],
+            })
+            .await?;
+        let session = SessionManager::create_session(
+            std::path::PathBuf::default(),
+            "plan-mode-test".to_string(),
+            SessionType::Hidden,
+        )
+        .await?;
+        agent
+            .update_provider(Arc::new(ToolCallingProvider), &session.id)
+            .await?;
+
+        let session_config = SessionConfig {
+            id: session.id,
+            schedule_id: None,
+            max_turns: None,
+            retry_config: None,
+            compaction: None,
+            fallback_providers: None,
+            tool_overrides: None,
+            max_total_tokens: None,
+            model_override: None,
+        };
+
+        // The mode is read while the stream runs, so keep it set until it ends
+        std::env::set_var("MTS_MODE", "plan");
+        let events = async {
+            let stream = agent
+                .reply(Message::user().with_text("hello"), session_config, None)
+                .await?;
+            // A dispatched frontend tool would wait for a result that never comes
+            anyhow::Ok(
+                tokio::time::timeout(
+                    std::time::Duration::from_secs(10),
+                    stream.collect::<Vec<_>>(),
+                )
+                .await,
+            )
+        }
+        .await;
+        std::env::remove_var("MTS_MODE");
+        let events = events?.expect("plan mode dispatched a tool call");
+
+        let mut skipped = HashSet::new();
+        for event in events {
+            let AgentEvent::Message(message) = event? else {
+                continue;
+            };
+            for content in &message.content {
+                match content {
+                    MessageContent::FrontendToolRequest(request) => {
+                        panic!("plan mode dispatched frontend tool {}", request.id)
+                    }
+                    MessageContent::ToolResponse(response) => {
+                        let result = response.tool_result.as_ref().unwrap();
+                        assert_eq!(
+                            result.content[0].as_text().unwrap().text,
+                            PLAN_MODE_TOOL_SKIPPED_RESPONSE
+                        );
+                        skipped.insert(response.id.clone());
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        assert_eq!(
+            skipped,
+            HashSet::from(["frontend-call".to_string(), "extension-call".to_string()])
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_tool_inspection_manager_has_all_inspectors() -> Result<()> {
         let agent = Agent::new();
@@ -18,6 +18,9 @@ use thiserror::Error;
 use tracing::warn;
 use utoipa::ToSchema;
 
+/// Environment variable a builtin extension's `instructions` are handed to its child process in
+pub const EXTENSION_INSTRUCTIONS_ENV: &str = "MTS_EXTENSION_INSTRUCTIONS";
+
 #[derive(Error, Debug)]
 #[error("process quit before initialization: stderr = {stderr}")]
 pub struct ProcessExit {
@@ -279,6 +282,9 @@ pub enum ExtensionConfig {
         bundled: Option<bool>,
         #[serde(default)]
         available_tools: Vec<String>,
+        /// Extra guidance appended to the extension's instructions for this session only
+        #[serde(default)]
+        instructions: Option<String>,
     },
     /// Platform extensions that have direct access to the agent etc and run in the agent process
     #[serde(rename = "platform")]
@@ -362,6 +368,7 @@ impl Default for ExtensionConfig {
             timeout: Some(config::DEFAULT_EXTENSION_TIMEOUT),
             bundled: Some(true),
             available_tools: Vec::new(),
+            instructions: None,
         }
     }
 }
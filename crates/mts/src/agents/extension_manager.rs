@@ -32,7 +32,7 @@ use super::extension::{
 };
 use super::tool_execution::ToolCallResult;
 use super::types::SharedProvider;
-use crate::agents::extension::{Envs, ProcessExit};
+use crate::agents::extension::{Envs, ProcessExit, EXTENSION_INSTRUCTIONS_ENV};
 use crate::agents::extension_malware_check;
 use crate::agents::mcp_client::{McpClient, McpClientTrait};
 use crate::config::search_path::SearchPaths;
@@ -505,6 +505,7 @@ impl ExtensionManager {
                 timeout,
                 bundled: _,
                 available_tools: _,
+                instructions,
             } => {
                 let cmd = std::env::current_exe()
                     .and_then(|path| {
@@ -523,6 +524,11 @@ impl ExtensionManager {
                     })?;
                 let command = Command::new(cmd).configure(|command| {
                     command.arg("mcp").arg(name);
+                    // Only this session's config decides, never whatever the host process inherited
+                    command.env_remove(EXTENSION_INSTRUCTIONS_ENV);
+                    if let Some(instructions) = instructions {
+                        command.env(EXTENSION_INSTRUCTIONS_ENV, instructions);
+                    }
                 });
                 let client = child_process_client(command, timeout, self.provider.clone()).await?;
                 Box::new(client)
@@ -1303,6 +1309,7 @@ mod tests {
                 timeout: None,
                 bundled: None,
                 available_tools,
+                instructions: None,
             };
             let extension = Extension::new(config, client, None, None);
             self.extensions
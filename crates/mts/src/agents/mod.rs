@@ -31,4 +31,6 @@ pub use extension::ExtensionConfig;
 pub use extension_manager::ExtensionManager;
 pub use prompt_manager::PromptManager;
 pub use subagent_task_config::TaskConfig;
-pub use types::{FrontendTool, RetryConfig, SessionConfig, SuccessCheck};
+pub use types::{
+    FallbackProvider, FrontendTool, ModelOverride, RetryConfig, SessionConfig, SuccessCheck,
+};
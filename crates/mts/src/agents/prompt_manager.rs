@@ -181,6 +181,15 @@ impl<'a> SystemPromptBuilder<'a, PromptManager> {
             );
         }
 
+        if mts_mode == MtsMode::Plan {
+            system_prompt_extras.push(
+                "Right now you are in plan mode: tool calls you make are recorded as a proposed plan \
+                for the user to review and are not executed. Propose the complete sequence of tool \
+                calls needed for the task."
+                    .to_string(),
+            );
+        }
+
         let sanitized_system_prompt_extras: Vec<String> = system_prompt_extras
             .into_iter()
             .map(|extra| sanitize_unicode_tags(&extra))
@@ -316,6 +316,7 @@ mod tests {
             on_failure: None,
             timeout_seconds: Some(60),
             on_failure_timeout_seconds: Some(120),
+            rate_limit_retries: None,
         }
     }
 
@@ -424,6 +425,7 @@ mod tests {
             on_failure: None,
             timeout_seconds: None,
             on_failure_timeout_seconds: None,
+            rate_limit_retries: None,
         };
 
         let timeout = get_retry_timeout(&retry_config);
@@ -438,6 +440,7 @@ mod tests {
             on_failure: None,
             timeout_seconds: Some(120),
             on_failure_timeout_seconds: None,
+            rate_limit_retries: None,
         };
 
         let timeout = get_retry_timeout(&retry_config);
@@ -452,6 +455,7 @@ mod tests {
             on_failure: None,
             timeout_seconds: None,
             on_failure_timeout_seconds: None,
+            rate_limit_retries: None,
         };
 
         let timeout = get_on_failure_timeout(&retry_config);
@@ -469,6 +473,7 @@ mod tests {
             on_failure: None,
             timeout_seconds: None,
             on_failure_timeout_seconds: Some(900),
+            rate_limit_retries: None,
         };
 
         let timeout = get_on_failure_timeout(&retry_config);
@@ -483,6 +488,7 @@ mod tests {
             on_failure: None,
             timeout_seconds: Some(60),
             on_failure_timeout_seconds: Some(300),
+            rate_limit_retries: None,
         };
 
         let retry_timeout = get_retry_timeout(&retry_config);
@@ -150,6 +150,11 @@ fn get_agent_messages(
             schedule_id: None,
             max_turns: task_config.max_turns.map(|v| v as u32),
             retry_config: recipe.retry,
+            compaction: None,
+            fallback_providers: None,
+            tool_overrides: None,
+            max_total_tokens: None,
+            model_override: None,
         };
 
         let mut stream = crate::session_context::with_session_id(Some(session_id.clone()), async {
@@ -48,6 +48,12 @@ pub const CHAT_MODE_TOOL_SKIPPED_RESPONSE: &str = "Let the user know the tool ca
                                         2. **Outline Steps** - Break down the steps.\n \
                                         If needed, adjust the explanation based on user preferences or questions.";
 
+pub const PLAN_MODE_TOOL_SKIPPED_RESPONSE: &str = "This tool call was NOT executed: mts is in plan mode, \
+                                        so it has been recorded as a step of the proposed plan. \
+                                        Assume it would succeed and keep proposing the tool calls needed to \
+                                        finish the task. When the plan is complete, summarize it for the user \
+                                        as a numbered list of steps and stop. Do not claim that any step has run.";
+
 impl Agent {
     pub(crate) fn handle_approval_tool_requests<'a>(
         &'a self,
@@ -1,7 +1,10 @@
+use crate::config::MtsMode;
+use crate::context_mgmt::CompactionPolicy;
 use crate::mcp_utils::ToolResult;
 use crate::providers::base::Provider;
 use rmcp::model::{CallToolResult, Tool};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 use utoipa::ToSchema;
@@ -34,6 +37,9 @@ pub struct RetryConfig {
     /// Timeout in seconds for on_failure commands (default: 600 seconds)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub on_failure_timeout_seconds: Option<u64>,
+    /// Times a reply backs off and retries after the provider rate-limits it (default: 3)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit_retries: Option<u32>,
 }
 
 impl RetryConfig {
@@ -61,6 +67,25 @@ impl RetryConfig {
     }
 }
 
+/// A provider and model to switch to when the current provider fails
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FallbackProvider {
+    /// Registered provider name, e.g. "openai"
+    pub provider: String,
+    /// Model to use with that provider
+    pub model: String,
+}
+
+/// A provider and model to run a single reply on, leaving the session's own
+/// provider in place for later replies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelOverride {
+    /// Registered provider name, e.g. "openai"
+    pub provider: String,
+    /// Model to use with that provider
+    pub model: String,
+}
+
 /// A single success check to validate recipe completion
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(tag = "type")]
@@ -92,4 +117,19 @@ pub struct SessionConfig {
     /// Retry configuration for automated validation and recovery
     #[serde(skip_serializing_if = "Option::is_none")]
     pub retry_config: Option<RetryConfig>,
+    /// Auto-compaction policy; `None` uses the global threshold
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compaction: Option<CompactionPolicy>,
+    /// Providers to fail over to, in order, when the current provider errors out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_providers: Option<Vec<FallbackProvider>>,
+    /// Modes pinned to individual tools by name, taking precedence over the session's mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_overrides: Option<HashMap<String, MtsMode>>,
+    /// Token budget for the session; the server stops the reply once the accumulated total crosses it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total_tokens: Option<i32>,
+    /// Provider and model for this reply only; the session's provider is untouched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_override: Option<ModelOverride>,
 }
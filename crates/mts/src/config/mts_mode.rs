@@ -1,26 +1,76 @@
+use std::fmt;
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum MtsMode {
     Auto,
     Approve,
     SmartApprove,
+    /// Talk to the model without tools: tool calls are skipped and some providers
+    /// are not offered tools at all
     Chat,
+    /// Tools are offered so the model can propose concrete calls, but none are
+    /// dispatched; the proposed calls form a plan to review before switching to Auto
+    Plan,
+}
+
+impl MtsMode {
+    /// Every mode, in the order they are offered to users
+    pub const ALL: [MtsMode; 5] = [
+        MtsMode::Auto,
+        MtsMode::Approve,
+        MtsMode::SmartApprove,
+        MtsMode::Chat,
+        MtsMode::Plan,
+    ];
+
+    /// The canonical name of the mode, as accepted by `FromStr` and used in config
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MtsMode::Auto => "auto",
+            MtsMode::Approve => "approve",
+            MtsMode::SmartApprove => "smart_approve",
+            MtsMode::Chat => "chat",
+            MtsMode::Plan => "plan",
+        }
+    }
+}
+
+impl fmt::Display for MtsMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 impl FromStr for MtsMode {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "auto" => Ok(MtsMode::Auto),
-            "approve" => Ok(MtsMode::Approve),
-            "smart_approve" => Ok(MtsMode::SmartApprove),
-            "chat" => Ok(MtsMode::Chat),
-            _ => Err(format!("invalid mode: {}", s)),
+        MtsMode::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == s)
+            .ok_or_else(|| format!("invalid mode: {}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_names_round_trip() {
+        for mode in MtsMode::ALL {
+            assert_eq!(MtsMode::from_str(mode.as_str()), Ok(mode));
+            assert_eq!(mode.to_string(), mode.as_str());
+            assert_eq!(
+                serde_json::to_value(mode).unwrap(),
+                serde_json::Value::String(mode.as_str().to_string())
+            );
         }
+        assert!(MtsMode::from_str("yolo").is_err());
     }
 }
@@ -7,8 +7,9 @@ use crate::providers::errors::ProviderError;
 use crate::{config::Config, token_counter::create_token_counter};
 use anyhow::Result;
 use rmcp::model::Role;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
+use utoipa::ToSchema;
 
 pub const DEFAULT_COMPACTION_THRESHOLD: f64 = 0.8;
 
@@ -27,6 +28,57 @@ const MANUAL_COMPACT_CONTINUATION_TEXT: &str =
 Do not mention that you read a summary or that conversation summarization occurred.
 Just continue the conversation naturally based on the summarized context";
 
+/// Per-session auto-compaction policy
+///
+/// Unset thresholds fall back to `MTS_AUTO_COMPACT_THRESHOLD` for tokens and no
+/// limit for messages. Compaction runs when either threshold is crossed.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CompactionPolicy {
+    /// Whether to auto-compact this session at all
+    #[serde(default = "default_compaction_enabled")]
+    pub enabled: bool,
+    /// Fraction of the context limit (0.0-1.0) that triggers compaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_threshold: Option<f64>,
+    /// Number of agent-visible messages that triggers compaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_threshold: Option<usize>,
+}
+
+fn default_compaction_enabled() -> bool {
+    true
+}
+
+impl Default for CompactionPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            token_threshold: None,
+            message_threshold: None,
+        }
+    }
+}
+
+impl CompactionPolicy {
+    /// Token threshold for this policy, falling back to the global config
+    pub fn effective_token_threshold(&self) -> f64 {
+        self.token_threshold.unwrap_or_else(|| {
+            Config::global()
+                .get_param::<f64>("MTS_AUTO_COMPACT_THRESHOLD")
+                .unwrap_or(DEFAULT_COMPACTION_THRESHOLD)
+        })
+    }
+
+    /// Human-readable description of the thresholds, for inline notifications
+    pub fn describe(&self) -> String {
+        let tokens = format!("{}%", (self.effective_token_threshold() * 100.0) as u32);
+        match self.message_threshold {
+            Some(messages) => format!("{} of context or {} messages", tokens, messages),
+            None => tokens,
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct SummarizeContext {
     messages: String,
@@ -164,20 +216,31 @@ pub async fn compact_messages(
     ))
 }
 
-/// Check if messages exceed the auto-compaction threshold
+/// Check if messages exceed the auto-compaction thresholds of `policy`
 pub async fn check_if_compaction_needed(
     provider: &dyn Provider,
     conversation: &Conversation,
-    threshold_override: Option<f64>,
+    policy: &CompactionPolicy,
     session: &crate::session::Session,
 ) -> Result<bool> {
+    if !policy.enabled {
+        return Ok(false);
+    }
+
     let messages = conversation.messages();
-    let config = Config::global();
-    let threshold = threshold_override.unwrap_or_else(|| {
-        config
-            .get_param::<f64>("MTS_AUTO_COMPACT_THRESHOLD")
-            .unwrap_or(DEFAULT_COMPACTION_THRESHOLD)
-    });
+
+    if let Some(max_messages) = policy.message_threshold {
+        let visible_messages = messages.iter().filter(|m| m.is_agent_visible()).count();
+        if max_messages > 0 && visible_messages > max_messages {
+            debug!(
+                "Compaction check: {} messages exceed threshold of {}",
+                visible_messages, max_messages
+            );
+            return Ok(true);
+        }
+    }
+
+    let threshold = policy.effective_token_threshold();
 
     let context_limit = provider.get_model_config().context_limit();
 
@@ -538,6 +601,20 @@ mod tests {
             .expect("compaction should produce a valid conversation");
     }
 
+    #[test]
+    fn test_compaction_policy_defaults() {
+        let policy: CompactionPolicy =
+            serde_json::from_str(r#"{"message_threshold": 40}"#).unwrap();
+        assert!(policy.enabled);
+        assert_eq!(policy.message_threshold, Some(40));
+
+        let policy = CompactionPolicy {
+            token_threshold: Some(0.5),
+            ..Default::default()
+        };
+        assert_eq!(policy.describe(), "50%");
+    }
+
     #[tokio::test]
     async fn test_progressive_removal_on_context_exceeded() {
         let response_message = Message::assistant().with_text("<mock summary>");
@@ -6,13 +6,46 @@ use crate::permission::permission_judge::PermissionCheckResult;
 use crate::tool_inspection::{InspectionAction, InspectionResult, ToolInspector};
 use anyhow::Result;
 use async_trait::async_trait;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+/// Tools that only read state, which smart approve mode runs without asking, as
+/// (extension, tool) pairs. A tool only matches when it comes from the listed
+/// extension, since another extension's tool of the same name could do anything.
+/// browser_screenshot is left out because it can write the image to disk.
+///
+/// Other tools opt in by declaring `readOnlyHint: true` in their MCP tool
+/// annotations (`annotations(read_only_hint = true)` with the rmcp `#[tool]`
+/// macro); the agent passes those to the inspector whenever it lists tools.
+pub const READ_ONLY_TOOLS: &[(&str, &str)] = &[
+    ("browser", "browser_scrape"),
+    ("browser", "browser_scrape_diff"),
+    ("browser", "browser_scroll_and_collect"),
+    ("browser", "browser_page_info"),
+    ("browser", "browser_console_logs"),
+    ("browser", "browser_extract_structured_data"),
+    ("browser", "browser_wait_for_selector"),
+    ("browser", "browser_wait_for_absence"),
+    ("browser", "browser_get_history"),
+    ("browser", "browser_get_computed_style"),
+    ("browser", "browser_get_attributes"),
+];
+
+/// Whether a tool is known to be free of side effects, by its prefixed
+/// `extension__tool` name
+pub fn is_read_only(tool_name: &str) -> bool {
+    tool_name
+        .split_once("__")
+        .is_some_and(|tool| READ_ONLY_TOOLS.contains(&tool))
+}
+
 /// Permission Inspector that handles tool permission checking
 pub struct PermissionInspector {
     mode: Arc<Mutex<MtsMode>>,
+    tool_overrides: Arc<Mutex<HashMap<String, MtsMode>>>,
+    /// Tools whose servers declared them read-only; only smart approve mode trusts these
+    declared_read_only: Arc<Mutex<HashSet<String>>>,
     readonly_tools: HashSet<String>,
     regular_tools: HashSet<String>,
     pub permission_manager: Arc<Mutex<PermissionManager>>,
@@ -26,6 +59,8 @@ impl PermissionInspector {
     ) -> Self {
         Self {
             mode: Arc::new(Mutex::new(mode)),
+            tool_overrides: Arc::new(Mutex::new(HashMap::new())),
+            declared_read_only: Arc::new(Mutex::new(HashSet::new())),
             readonly_tools,
             regular_tools,
             permission_manager: Arc::new(Mutex::new(PermissionManager::default())),
@@ -40,6 +75,8 @@ impl PermissionInspector {
     ) -> Self {
         Self {
             mode: Arc::new(Mutex::new(mode)),
+            tool_overrides: Arc::new(Mutex::new(HashMap::new())),
+            declared_read_only: Arc::new(Mutex::new(HashSet::new())),
             readonly_tools,
             regular_tools,
             permission_manager,
@@ -52,6 +89,18 @@ impl PermissionInspector {
         *mode = new_mode;
     }
 
+    /// Replace the modes pinned to individual tools, which take precedence over the mode
+    pub async fn update_tool_overrides(&self, overrides: HashMap<String, MtsMode>) {
+        let mut tool_overrides = self.tool_overrides.lock().await;
+        *tool_overrides = overrides;
+    }
+
+    /// Replace the tools declared read-only through their `readOnlyHint` annotation
+    pub async fn update_declared_read_only(&self, tools: HashSet<String>) {
+        let mut declared_read_only = self.declared_read_only.lock().await;
+        *declared_read_only = tools;
+    }
+
     /// Process inspection results into permission decisions
     /// This method takes all inspection results and converts them into a PermissionCheckResult
     /// that can be used by the agent to determine which tools to approve, deny, or ask for approval
@@ -133,14 +182,25 @@ impl ToolInspector for PermissionInspector {
     ) -> Result<Vec<InspectionResult>> {
         let mut results = Vec::new();
         let permission_manager = self.permission_manager.lock().await;
-        let mode = self.mode.lock().await;
+        let session_mode = *self.mode.lock().await;
+        let tool_overrides = self.tool_overrides.lock().await;
+        let declared_read_only = self.declared_read_only.lock().await;
 
         for request in tool_requests {
             if let Ok(tool_call) = &request.tool_call {
                 let tool_name = &tool_call.name;
 
-                let action = match *mode {
-                    MtsMode::Chat => continue,
+                // Tools never run in chat or plan sessions, whatever their overrides say
+                if matches!(session_mode, MtsMode::Chat | MtsMode::Plan) {
+                    continue;
+                }
+                let tool_override = tool_overrides.get(tool_name.as_ref()).copied();
+                let mode = tool_override.unwrap_or(session_mode);
+                let smart_read_only = mode == MtsMode::SmartApprove
+                    && (is_read_only(tool_name) || declared_read_only.contains(tool_name.as_ref()));
+
+                let action = match mode {
+                    MtsMode::Chat | MtsMode::Plan => InspectionAction::Deny,
                     MtsMode::Auto => InspectionAction::Allow,
                     MtsMode::Approve | MtsMode::SmartApprove => {
                         // 1. Check user-defined permission first
@@ -153,9 +213,11 @@ impl ToolInspector for PermissionInspector {
                                 }
                             }
                         }
-                        // 2. Check if it's a readonly or regular tool (both pre-approved)
+                        // 2. Check if it's a readonly or regular tool (both pre-approved),
+                        //    or a read-only tool that smart approve runs without asking
                         else if self.readonly_tools.contains(tool_name.as_ref())
                             || self.regular_tools.contains(tool_name.as_ref())
+                            || smart_read_only
                         {
                             InspectionAction::Allow
                         }
@@ -174,20 +236,31 @@ impl ToolInspector for PermissionInspector {
 
                 let reason = match &action {
                     InspectionAction::Allow => {
-                        if *mode == MtsMode::Auto {
+                        if tool_override == Some(MtsMode::Auto) {
+                            "Tool pinned to auto mode".to_string()
+                        } else if mode == MtsMode::Auto {
                             "Auto mode - all tools approved".to_string()
                         } else if self.readonly_tools.contains(tool_name.as_ref()) {
                             "Tool marked as read-only".to_string()
                         } else if self.regular_tools.contains(tool_name.as_ref()) {
                             "Tool pre-approved".to_string()
+                        } else if smart_read_only {
+                            "Read-only tool approved in smart approve mode".to_string()
                         } else {
                             "User permission allows this tool".to_string()
                         }
                     }
-                    InspectionAction::Deny => "User permission denies this tool".to_string(),
+                    InspectionAction::Deny => match tool_override {
+                        Some(mode @ (MtsMode::Chat | MtsMode::Plan)) => {
+                            format!("Tool pinned to {} mode, which never runs tools", mode)
+                        }
+                        _ => "User permission denies this tool".to_string(),
+                    },
                     InspectionAction::RequireApproval(_) => {
                         if tool_name == MANAGE_EXTENSIONS_TOOL_NAME_COMPLETE {
                             "Extension management requires user approval".to_string()
+                        } else if let Some(mode) = tool_override {
+                            format!("Tool pinned to {} mode requires user approval", mode)
                         } else {
                             "Tool requires user approval".to_string()
                         }
@@ -208,3 +281,102 @@ impl ToolInspector for PermissionInspector {
         Ok(results)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::CallToolRequestParam;
+    use rmcp::object;
+
+    fn tool_request(id: &str, name: &str) -> ToolRequest {
+        ToolRequest {
+            id: id.to_string(),
+            tool_call: Ok(CallToolRequestParam {
+                name: name.to_string().into(),
+                arguments: Some(object!({})),
+            }),
+            thought_signature: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_overrides_take_precedence_over_mode() {
+        let inspector = PermissionInspector::new(MtsMode::Auto, HashSet::new(), HashSet::new());
+        inspector
+            .update_tool_overrides(HashMap::from([
+                ("test__write_file".to_string(), MtsMode::Approve),
+                ("test__delete_file".to_string(), MtsMode::Chat),
+            ]))
+            .await;
+
+        let requests = vec![
+            tool_request("read", "test__read_file"),
+            tool_request("write", "test__write_file"),
+            tool_request("delete", "test__delete_file"),
+        ];
+        let results = inspector.inspect(&requests, &[]).await.unwrap();
+        let check = inspector.process_inspection_results(&requests, &results);
+
+        let ids = |requests: &[ToolRequest]| -> Vec<String> {
+            requests.iter().map(|r| r.id.clone()).collect()
+        };
+        assert_eq!(ids(&check.approved), vec!["read"]);
+        assert_eq!(ids(&check.needs_approval), vec!["write"]);
+        assert_eq!(ids(&check.denied), vec!["delete"]);
+    }
+
+    #[tokio::test]
+    async fn test_tool_overrides_do_not_run_tools_in_chat_mode() {
+        let inspector = PermissionInspector::new(MtsMode::Chat, HashSet::new(), HashSet::new());
+        inspector
+            .update_tool_overrides(HashMap::from([(
+                "test__read_file".to_string(),
+                MtsMode::Auto,
+            )]))
+            .await;
+
+        let requests = vec![tool_request("read", "test__read_file")];
+        let results = inspector.inspect(&requests, &[]).await.unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_is_read_only_matches_extension_and_tool() {
+        assert!(is_read_only("browser__browser_page_info"));
+        assert!(!is_read_only("browser__browser_navigate"));
+        assert!(!is_read_only("browser__browser_screenshot"));
+        assert!(!is_read_only("browser_scrape"));
+        assert!(!is_read_only("other__browser_scrape"));
+        assert!(!is_read_only("replace_in_files"));
+    }
+
+    #[tokio::test]
+    async fn test_smart_approve_runs_read_only_tools() {
+        let inspector =
+            PermissionInspector::new(MtsMode::SmartApprove, HashSet::new(), HashSet::new());
+        inspector
+            .update_declared_read_only(HashSet::from(["custom__lookup".to_string()]))
+            .await;
+
+        let requests = vec![
+            tool_request("scrape", "browser__browser_scrape"),
+            tool_request("lookup", "custom__lookup"),
+            tool_request("navigate", "browser__browser_navigate"),
+            tool_request("replace", "replace_in_files"),
+        ];
+        let results = inspector.inspect(&requests, &[]).await.unwrap();
+        let check = inspector.process_inspection_results(&requests, &results);
+
+        let ids = |requests: &[ToolRequest]| -> Vec<String> {
+            requests.iter().map(|r| r.id.clone()).collect()
+        };
+        assert_eq!(ids(&check.approved), vec!["scrape", "lookup"]);
+        assert_eq!(ids(&check.needs_approval), vec!["navigate", "replace"]);
+
+        // Approve mode still asks before every tool
+        inspector.update_mode(MtsMode::Approve).await;
+        let results = inspector.inspect(&requests, &[]).await.unwrap();
+        let check = inspector.process_inspection_results(&requests, &results);
+        assert!(check.approved.is_empty());
+    }
+}
@@ -134,6 +134,9 @@ impl ClaudeCodeProvider {
             MtsMode::Chat => {
                 // Chat mode doesn't need permission flags
             }
+            MtsMode::Plan => {
+                cmd.arg("--permission-mode").arg("plan");
+            }
         }
         Ok(())
     }
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use utoipa::ToSchema;
+
+/// Config flag that must be set before debug capture can be enabled for a session
+pub const PROVIDER_DEBUG_CONFIG_KEY: &str = "MTS_PROVIDER_DEBUG";
+
+/// Most recent exchanges kept per session
+const MAX_EXCHANGES_PER_SESSION: usize = 20;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Keys whose values are always redacted, compared case-insensitively
+const SECRET_KEYS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "x-api-key",
+    "authorization",
+    "access_token",
+    "refresh_token",
+    "id_token",
+    "client_secret",
+    "secret",
+    "password",
+];
+
+/// A raw provider request and everything written back for it
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderExchange {
+    pub timestamp: DateTime<Utc>,
+    /// Model config and payload sent to the provider
+    #[schema(value_type = Object)]
+    pub request: Value,
+    /// Response entries in the order received; errors appear as `{"error": ...}`
+    #[schema(value_type = Vec<Object>)]
+    pub responses: Vec<Value>,
+}
+
+impl ProviderExchange {
+    pub fn new(request: Value) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            request,
+            responses: Vec::new(),
+        }
+    }
+}
+
+/// Sessions with capture enabled, mapped to their captured exchanges
+static CAPTURES: Lazy<Mutex<HashMap<String, VecDeque<ProviderExchange>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn enable(session_id: &str) {
+    let mut captures = CAPTURES.lock().unwrap();
+    captures.entry(session_id.to_string()).or_default();
+}
+
+/// Stop capturing for a session and drop what was captured
+pub fn disable(session_id: &str) {
+    CAPTURES.lock().unwrap().remove(session_id);
+}
+
+pub fn is_enabled(session_id: &str) -> bool {
+    CAPTURES.lock().unwrap().contains_key(session_id)
+}
+
+/// Captured exchanges for a session, or `None` if capture is not enabled
+pub fn exchanges(session_id: &str) -> Option<Vec<ProviderExchange>> {
+    let captures = CAPTURES.lock().unwrap();
+    captures
+        .get(session_id)
+        .map(|exchanges| exchanges.iter().cloned().collect())
+}
+
+/// Store an exchange with secrets redacted; ignored unless capture is enabled
+pub fn record(session_id: &str, mut exchange: ProviderExchange) {
+    let mut captures = CAPTURES.lock().unwrap();
+    let Some(exchanges) = captures.get_mut(session_id) else {
+        return;
+    };
+
+    redact_secrets(&mut exchange.request);
+    exchange.responses.iter_mut().for_each(redact_secrets);
+
+    if exchanges.len() >= MAX_EXCHANGES_PER_SESSION {
+        exchanges.pop_front();
+    }
+    exchanges.push_back(exchange);
+}
+
+/// Replace credential-like values in place: values under secret keys and bearer tokens
+pub fn redact_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_ascii_lowercase();
+                if SECRET_KEYS.contains(&key.as_str()) || key.ends_with("_api_key") {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_secrets(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        Value::String(text) if text.starts_with("Bearer ") => {
+            *text = format!("Bearer {}", REDACTED);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_secrets() {
+        let mut value = json!({
+            "model": "gpt-4o",
+            "max_tokens": 1024,
+            "headers": {"Authorization": "Bearer sk-123", "X-Api-Key": "abc"},
+            "extra": [{"openai_api_key": "sk-456"}, "Bearer xyz"],
+        });
+        redact_secrets(&mut value);
+
+        assert_eq!(value["model"], "gpt-4o");
+        assert_eq!(value["max_tokens"], 1024);
+        assert_eq!(value["headers"]["Authorization"], REDACTED);
+        assert_eq!(value["headers"]["X-Api-Key"], REDACTED);
+        assert_eq!(value["extra"][0]["openai_api_key"], REDACTED);
+        assert_eq!(value["extra"][1], "Bearer [REDACTED]");
+    }
+}
@@ -114,6 +114,20 @@ pub async fn refresh_custom_providers() -> Result<()> {
     Ok(())
 }
 
+/// Register an extra provider so tests can create it by name
+#[cfg(test)]
+pub(crate) async fn register_test_provider<P, F>(constructor: F)
+where
+    P: Provider + 'static,
+    F: Fn(ModelConfig) -> futures::future::BoxFuture<'static, Result<P>> + Send + Sync + 'static,
+{
+    get_registry()
+        .await
+        .write()
+        .unwrap()
+        .register::<P, F>(constructor, false);
+}
+
 async fn get_from_registry(name: &str) -> Result<ProviderEntry> {
     let guard = get_registry().await.read().unwrap();
     guard
@@ -9,6 +9,7 @@ pub mod canonical;
 pub mod claude_code;
 pub mod cursor_agent;
 pub mod databricks;
+pub mod debug_capture;
 pub mod embedding;
 pub mod errors;
 mod factory;
@@ -27,7 +28,7 @@ pub mod openrouter;
 pub mod pricing;
 pub mod provider_registry;
 pub mod provider_test;
-mod retry;
+pub(crate) mod retry;
 pub mod sagemaker_tgi;
 pub mod snowflake;
 pub mod testprovider;
@@ -39,6 +40,8 @@ pub mod utils_universal_openai_stream;
 pub mod venice;
 pub mod xai;
 
+#[cfg(test)]
+pub(crate) use factory::register_test_provider;
 pub use factory::{
     create, create_with_default_model, create_with_named_model, providers, refresh_custom_providers,
 };
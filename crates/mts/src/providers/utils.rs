@@ -1,4 +1,5 @@
 use super::base::Usage;
+use super::debug_capture::{self, ProviderExchange};
 use super::errors::GoogleErrorCode;
 use crate::config::paths::Paths;
 use crate::model::ModelConfig;
@@ -160,12 +161,33 @@ pub fn map_http_error_to_provider_error(
     error
 }
 
+/// How long a Retry-After header asks to wait, given in seconds or as an HTTP date
+pub fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
 pub async fn handle_status_openai_compat(response: Response) -> Result<Response, ProviderError> {
     let status = response.status();
     if !status.is_success() {
+        let retry_after = parse_retry_after(response.headers());
         let body = response.text().await.unwrap_or_default();
         let payload = serde_json::from_str::<Value>(&body).ok();
-        return Err(map_http_error_to_provider_error(status, payload));
+        let mut error = map_http_error_to_provider_error(status, payload);
+        if let ProviderError::RateLimitExceeded { retry_delay, .. } = &mut error {
+            *retry_delay = retry_delay.or(retry_after);
+        }
+        return Err(error);
     }
     Ok(response)
 }
@@ -441,6 +463,8 @@ pub fn unescape_json_values(value: &Value) -> Value {
 pub struct RequestLog {
     writer: Option<BufWriter<File>>,
     temp_path: PathBuf,
+    /// Copy of the exchange for sessions with provider debug capture enabled
+    capture: Option<(String, ProviderExchange)>,
 }
 
 pub const LOGS_TO_KEEP: usize = 10;
@@ -451,6 +475,7 @@ impl RequestLog {
         Payload: Serialize,
     {
         let logs_dir = Paths::in_state_dir("logs");
+        std::fs::create_dir_all(&logs_dir)?;
 
         let request_id = Uuid::new_v4();
         let temp_name = format!("llm_request.{request_id}.jsonl");
@@ -470,9 +495,14 @@ impl RequestLog {
         });
         writeln!(writer, "{}", serde_json::to_string(&data)?)?;
 
+        let capture = crate::session_context::current_session_id()
+            .filter(|id| debug_capture::is_enabled(id))
+            .map(|id| (id, ProviderExchange::new(data)));
+
         Ok(Self {
             writer: Some(writer),
             temp_path,
+            capture,
         })
     }
 
@@ -482,6 +512,9 @@ impl RequestLog {
             .as_mut()
             .ok_or_else(|| anyhow!("logger is finished"))?;
         writeln!(writer, "{}", serde_json::to_string(line)?)?;
+        if let Some((_, exchange)) = self.capture.as_mut() {
+            exchange.responses.push(line.clone());
+        }
         Ok(())
     }
 
@@ -505,6 +538,9 @@ impl RequestLog {
     }
 
     fn finish(&mut self) -> Result<()> {
+        if let Some((session_id, exchange)) = self.capture.take() {
+            debug_capture::record(&session_id, exchange);
+        }
         if let Some(mut writer) = self.writer.take() {
             writer.flush()?;
             let logs_dir = Paths::in_state_dir("logs");
@@ -690,6 +726,25 @@ mod tests {
             .contains("Unsupported image format"));
     }
 
+    #[test]
+    fn test_parse_retry_after() {
+        use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("12"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(12)));
+
+        let later = (chrono::Utc::now() + chrono::Duration::seconds(60)).to_rfc2822();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(&later).unwrap());
+        let delay = parse_retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(50) && delay <= Duration::from_secs(60));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
     #[test]
     fn test_sanitize_function_name() {
         assert_eq!(sanitize_function_name("hello-world"), "hello-world");
@@ -923,4 +978,28 @@ mod tests {
             Some(Duration::from_secs(42))
         );
     }
+
+    async fn log_exchange(session_id: &str) {
+        crate::session_context::with_session_id(Some(session_id.to_string()), async {
+            let model_config = ModelConfig::new("test-model").unwrap();
+            let mut log = RequestLog::start(&model_config, &json!({"messages": []})).unwrap();
+            log.write(&json!({"text": "hello"}), None).unwrap();
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_request_log_debug_capture() {
+        debug_capture::enable("debug-capture-on");
+        log_exchange("debug-capture-on").await;
+        log_exchange("debug-capture-off").await;
+
+        let exchanges = debug_capture::exchanges("debug-capture-on").unwrap();
+        assert_eq!(exchanges.len(), 1);
+        assert_eq!(exchanges[0].request["input"], json!({"messages": []}));
+        assert_eq!(exchanges[0].responses[0]["data"]["text"], "hello");
+
+        assert!(debug_capture::exchanges("debug-capture-off").is_none());
+        debug_capture::disable("debug-capture-on");
+    }
 }
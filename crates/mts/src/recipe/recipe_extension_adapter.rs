@@ -51,6 +51,8 @@ enum RecipeExtensionConfigInternal {
         bundled: Option<bool>,
         #[serde(default)]
         available_tools: Vec<String>,
+        #[serde(default)]
+        instructions: Option<String>,
     },
     #[serde(rename = "platform")]
     Platform {
@@ -149,7 +151,8 @@ impl From<RecipeExtensionConfigInternal> for ExtensionConfig {
                 display_name,
                 timeout,
                 bundled,
-                available_tools
+                available_tools,
+                instructions
             },
             Platform {
                 bundled,
@@ -217,6 +220,7 @@ mod tests {
                 "timeout": 120,
                 "bundled": true,
                 "available_tools": ["tool_a", "tool_b"],
+                "instructions": "Stay on docs.rs",
             }]
         }))
         .expect("failed to deserialize extensions");
@@ -232,6 +236,7 @@ mod tests {
                 timeout,
                 bundled,
                 available_tools,
+                instructions,
             } => {
                 assert_eq!(name, "test-builtin");
                 assert_eq!(description, "");
@@ -242,6 +247,7 @@ mod tests {
                     available_tools,
                     &vec!["tool_a".to_string(), "tool_b".to_string()]
                 );
+                assert_eq!(instructions.as_deref(), Some("Stay on docs.rs"));
             }
             other => panic!("unexpected extension variant: {:?}", other),
         }
@@ -269,6 +275,7 @@ mod tests {
                 timeout,
                 bundled,
                 available_tools,
+                instructions,
             } => {
                 assert_eq!(name, "null-description-builtin");
                 assert_eq!(description, "");
@@ -276,6 +283,7 @@ mod tests {
                 assert!(timeout.is_none());
                 assert!(bundled.is_none());
                 assert!(available_tools.is_empty());
+                assert!(instructions.is_none());
             }
             other => panic!("unexpected extension variant: {:?}", other),
         }
@@ -763,6 +763,11 @@ async fn execute_job(
         schedule_id: Some(job.id.clone()),
         max_turns: None,
         retry_config: None,
+        compaction: None,
+        fallback_providers: None,
+        tool_overrides: None,
+        max_total_tokens: None,
+        model_override: None,
     };
 
     let session_id = session_config.id.clone();
@@ -19,7 +19,7 @@ use tokio::sync::OnceCell;
 use tracing::{info, warn};
 use utoipa::ToSchema;
 
-const CURRENT_SCHEMA_VERSION: i32 = 6;
+const CURRENT_SCHEMA_VERSION: i32 = 7;
 pub const SESSIONS_FOLDER: &str = "sessions";
 pub const DB_NAME: &str = "sessions.db";
 
@@ -95,6 +95,8 @@ pub struct Session {
     pub message_count: usize,
     pub provider_name: Option<String>,
     pub model_config: Option<ModelConfig>,
+    /// Opaque tags supplied by integrators, e.g. external ticket or user ids
+    pub metadata: Option<HashMap<String, String>>,
 }
 
 pub struct SessionUpdateBuilder {
@@ -115,6 +117,7 @@ pub struct SessionUpdateBuilder {
     user_recipe_values: Option<Option<HashMap<String, String>>>,
     provider_name: Option<Option<String>>,
     model_config: Option<Option<ModelConfig>>,
+    metadata: Option<Option<HashMap<String, String>>>,
 }
 
 #[derive(Serialize, ToSchema, Debug)]
@@ -144,6 +147,7 @@ impl SessionUpdateBuilder {
             user_recipe_values: None,
             provider_name: None,
             model_config: None,
+            metadata: None,
         }
     }
 
@@ -238,6 +242,11 @@ impl SessionUpdateBuilder {
         self
     }
 
+    pub fn metadata(mut self, metadata: Option<HashMap<String, String>>) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
     pub async fn apply(self) -> Result<()> {
         SessionManager::apply_update(self).await
     }
@@ -415,6 +424,7 @@ impl Default for Session {
             message_count: 0,
             provider_name: None,
             model_config: None,
+            metadata: None,
         }
     }
 }
@@ -440,6 +450,9 @@ impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for Session {
         let model_config_json: Option<String> = row.try_get("model_config_json").ok().flatten();
         let model_config = model_config_json.and_then(|json| serde_json::from_str(&json).ok());
 
+        let metadata_json: Option<String> = row.try_get("metadata_json").ok().flatten();
+        let metadata = metadata_json.and_then(|json| serde_json::from_str(&json).ok());
+
         let name: String = {
             let name_val: String = row.try_get("name").unwrap_or_default();
             if !name_val.is_empty() {
@@ -479,6 +492,7 @@ impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for Session {
             message_count: row.try_get("message_count").unwrap_or(0) as usize,
             provider_name: row.try_get("provider_name").ok().flatten(),
             model_config,
+            metadata,
         })
     }
 }
@@ -568,7 +582,8 @@ impl SessionStorage {
                 recipe_json TEXT,
                 user_recipe_values_json TEXT,
                 provider_name TEXT,
-                model_config_json TEXT
+                model_config_json TEXT,
+                metadata_json TEXT
             )
         "#,
         )
@@ -670,6 +685,11 @@ impl SessionStorage {
             None => None,
         };
 
+        let metadata_json = match &session.metadata {
+            Some(metadata) => Some(serde_json::to_string(metadata)?),
+            None => None,
+        };
+
         sqlx::query(
             r#"
         INSERT INTO sessions (
@@ -677,8 +697,8 @@ impl SessionStorage {
             total_tokens, input_tokens, output_tokens,
             accumulated_total_tokens, accumulated_input_tokens, accumulated_output_tokens,
             schedule_id, recipe_json, user_recipe_values_json,
-            provider_name, model_config_json
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            provider_name, model_config_json, metadata_json
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         )
             .bind(&session.id)
@@ -700,6 +720,7 @@ impl SessionStorage {
             .bind(user_recipe_values_json)
             .bind(&session.provider_name)
             .bind(model_config_json)
+            .bind(metadata_json)
             .execute(&mut *tx)
             .await?;
 
@@ -843,6 +864,15 @@ impl SessionStorage {
                 .execute(&self.pool)
                 .await?;
             }
+            7 => {
+                sqlx::query(
+                    r#"
+                    ALTER TABLE sessions ADD COLUMN metadata_json TEXT
+                "#,
+                )
+                .execute(&self.pool)
+                .await?;
+            }
             _ => {
                 anyhow::bail!("Unknown migration version: {}", version);
             }
@@ -898,7 +928,7 @@ impl SessionStorage {
                total_tokens, input_tokens, output_tokens,
                accumulated_total_tokens, accumulated_input_tokens, accumulated_output_tokens,
                schedule_id, recipe_json, user_recipe_values_json,
-               provider_name, model_config_json
+               provider_name, model_config_json, metadata_json
         FROM sessions
         WHERE id = ?
     "#,
@@ -961,6 +991,7 @@ impl SessionStorage {
         add_update!(builder.user_recipe_values, "user_recipe_values_json");
         add_update!(builder.provider_name, "provider_name");
         add_update!(builder.model_config, "model_config_json");
+        add_update!(builder.metadata, "metadata_json");
 
         if updates.is_empty() {
             return Ok(());
@@ -1026,6 +1057,10 @@ impl SessionStorage {
                 .transpose()?;
             q = q.bind(model_config_json);
         }
+        if let Some(metadata) = builder.metadata {
+            let metadata_json = metadata.map(|m| serde_json::to_string(&m)).transpose()?;
+            q = q.bind(metadata_json);
+        }
 
         let mut tx = self.pool.begin().await?;
         q = q.bind(&builder.session_id);
@@ -1141,7 +1176,7 @@ impl SessionStorage {
                    s.total_tokens, s.input_tokens, s.output_tokens,
                    s.accumulated_total_tokens, s.accumulated_input_tokens, s.accumulated_output_tokens,
                    s.schedule_id, s.recipe_json, s.user_recipe_values_json,
-                   s.provider_name, s.model_config_json,
+                   s.provider_name, s.model_config_json, s.metadata_json,
                    COUNT(m.id) as message_count
             FROM sessions s
             INNER JOIN messages m ON s.id = m.session_id
@@ -1235,7 +1270,8 @@ impl SessionStorage {
             .accumulated_output_tokens(import.accumulated_output_tokens)
             .schedule_id(import.schedule_id)
             .recipe(import.recipe)
-            .user_recipe_values(import.user_recipe_values);
+            .user_recipe_values(import.user_recipe_values)
+            .metadata(import.metadata);
 
         if import.user_set_name {
             builder = builder.user_provided_name(import.name.clone());
@@ -1266,7 +1302,8 @@ impl SessionStorage {
             .extension_data(original_session.extension_data)
             .schedule_id(original_session.schedule_id)
             .recipe(original_session.recipe)
-            .user_recipe_values(original_session.user_recipe_values);
+            .user_recipe_values(original_session.user_recipe_values)
+            .metadata(original_session.metadata);
 
         self.apply_update(builder).await?;
 
@@ -1492,6 +1529,41 @@ mod tests {
         assert_eq!(conversation.messages()[1].role, Role::Assistant);
     }
 
+    #[tokio::test]
+    async fn test_metadata_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test_metadata.db");
+        let storage = Arc::new(SessionStorage::create(&db_path).await.unwrap());
+
+        let session = storage
+            .create_session(
+                PathBuf::from("/tmp/test"),
+                "Tagged session".to_string(),
+                SessionType::User,
+            )
+            .await
+            .unwrap();
+        assert_eq!(session.metadata, None);
+
+        let metadata = HashMap::from([
+            ("ticket_id".to_string(), "OPS-42".to_string()),
+            ("experiment_arm".to_string(), "b".to_string()),
+        ]);
+        storage
+            .apply_update(
+                SessionUpdateBuilder::new(session.id.clone()).metadata(Some(metadata.clone())),
+            )
+            .await
+            .unwrap();
+
+        let fetched = storage.get_session(&session.id, false).await.unwrap();
+        assert_eq!(fetched.metadata, Some(metadata.clone()));
+
+        let exported = storage.export_session(&session.id).await.unwrap();
+        let imported = storage.import_session(&exported).await.unwrap();
+        assert_eq!(imported.metadata, Some(metadata));
+    }
+
     #[tokio::test]
     async fn test_import_session_with_description_field() {
         const OLD_FORMAT_JSON: &str = r#"{
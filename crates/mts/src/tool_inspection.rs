@@ -1,6 +1,6 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::config::MtsMode;
 use crate::conversation::message::{Message, ToolRequest};
@@ -132,6 +132,39 @@ impl ToolInspectionManager {
         tracing::warn!("Permission inspector not found for mode update");
     }
 
+    /// Update the modes the permission inspector pins to individual tools
+    pub async fn update_permission_inspector_tool_overrides(
+        &self,
+        overrides: HashMap<String, MtsMode>,
+    ) {
+        for inspector in &self.inspectors {
+            if inspector.name() == "permission" {
+                if let Some(permission_inspector) =
+                    inspector.as_any().downcast_ref::<PermissionInspector>()
+                {
+                    permission_inspector.update_tool_overrides(overrides).await;
+                    return;
+                }
+            }
+        }
+        tracing::warn!("Permission inspector not found for tool override update");
+    }
+
+    /// Update the tools the permission inspector treats as declared read-only
+    pub async fn update_permission_inspector_declared_read_only(&self, tools: HashSet<String>) {
+        for inspector in &self.inspectors {
+            if inspector.name() == "permission" {
+                if let Some(permission_inspector) =
+                    inspector.as_any().downcast_ref::<PermissionInspector>()
+                {
+                    permission_inspector.update_declared_read_only(tools).await;
+                    return;
+                }
+            }
+        }
+        tracing::warn!("Permission inspector not found for read-only tool update");
+    }
+
     /// Update the permission manager for a specific tool
     pub async fn update_permission_manager(
         &self,
@@ -231,6 +231,7 @@ mod tests {
                 on_failure: None,
                 timeout_seconds: Some(30),
                 on_failure_timeout_seconds: Some(60),
+                rate_limit_retries: None,
             };
 
             let success_checks = vec![SuccessCheck::Shell {
@@ -260,6 +261,7 @@ mod tests {
                 on_failure: None,
                 timeout_seconds: Some(0),
                 on_failure_timeout_seconds: None,
+                rate_limit_retries: None,
             };
 
             let validation_result = invalid_retry_config.validate();
@@ -389,6 +391,11 @@ mod tests {
                 schedule_id: None,
                 max_turns: None,
                 retry_config: None,
+                compaction: None,
+                fallback_providers: None,
+                tool_overrides: None,
+                max_total_tokens: None,
+                model_override: None,
             };
 
             let reply_stream = agent.reply(user_message, session_config, None).await?;
@@ -442,6 +449,127 @@ mod tests {
         }
     }
 
+    #[cfg(test)]
+    mod compaction_policy_tests {
+        use super::*;
+        use async_trait::async_trait;
+        use mts::agents::SessionConfig;
+        use mts::context_mgmt::CompactionPolicy;
+        use mts::conversation::message::{Message, MessageContent};
+        use mts::model::ModelConfig;
+        use mts::providers::base::{Provider, ProviderMetadata, ProviderUsage, Usage};
+        use mts::providers::errors::ProviderError;
+        use mts::session::session_manager::SessionType;
+        use mts::session::SessionManager;
+        use rmcp::model::Tool;
+        use std::path::PathBuf;
+
+        /// Answers every request, including the summarization request, with the same text
+        struct MockSummaryProvider {}
+
+        #[async_trait]
+        impl Provider for MockSummaryProvider {
+            async fn complete_with_model(
+                &self,
+                _model_config: &ModelConfig,
+                _system_prompt: &str,
+                _messages: &[Message],
+                _tools: &[Tool],
+            ) -> anyhow::Result<(Message, ProviderUsage), ProviderError> {
+                Ok((
+                    Message::assistant().with_text("<mock summary>"),
+                    ProviderUsage::new(
+                        "mock-model".to_string(),
+                        Usage::new(Some(10), Some(5), Some(15)),
+                    ),
+                ))
+            }
+
+            fn get_model_config(&self) -> ModelConfig {
+                ModelConfig::new("mock-model").unwrap()
+            }
+
+            fn metadata() -> ProviderMetadata {
+                ProviderMetadata {
+                    name: "mock".to_string(),
+                    display_name: "Mock Provider".to_string(),
+                    description: "Mock provider for testing".to_string(),
+                    default_model: "mock-model".to_string(),
+                    known_models: vec![],
+                    model_doc_link: "".to_string(),
+                    config_keys: vec![],
+                }
+            }
+
+            fn get_name(&self) -> &str {
+                "mock-summary"
+            }
+        }
+
+        #[tokio::test]
+        async fn test_message_threshold_triggers_summary() -> Result<()> {
+            let agent = Agent::new();
+            let session = SessionManager::create_session(
+                PathBuf::default(),
+                "compaction-policy-test".to_string(),
+                SessionType::Hidden,
+            )
+            .await?;
+            agent
+                .update_provider(Arc::new(MockSummaryProvider {}), &session.id)
+                .await?;
+
+            for message in [
+                Message::user().with_text("first question"),
+                Message::assistant().with_text("first answer"),
+                Message::user().with_text("second question"),
+                Message::assistant().with_text("second answer"),
+            ] {
+                SessionManager::add_message(&session.id, &message).await?;
+            }
+
+            let session_config = SessionConfig {
+                id: session.id,
+                schedule_id: None,
+                max_turns: None,
+                retry_config: None,
+                compaction: Some(CompactionPolicy {
+                    message_threshold: Some(3),
+                    ..Default::default()
+                }),
+                fallback_providers: None,
+                tool_overrides: None,
+                max_total_tokens: None,
+                model_override: None,
+            };
+
+            let reply_stream = agent
+                .reply(
+                    Message::user().with_text("third question"),
+                    session_config,
+                    None,
+                )
+                .await?;
+            tokio::pin!(reply_stream);
+
+            let mut replaced = None;
+            while let Some(event) = reply_stream.next().await {
+                if let AgentEvent::HistoryReplaced(conversation) = event? {
+                    replaced = Some(conversation);
+                }
+            }
+
+            let conversation = replaced.expect("Expected a HistoryReplaced event");
+            let has_summary = conversation.agent_visible_messages().iter().any(|m| {
+                m.content.iter().any(
+                    |c| matches!(c, MessageContent::Text(t) if t.text.contains("<mock summary>")),
+                )
+            });
+            assert!(has_summary, "Compacted history should contain the summary");
+            Ok(())
+        }
+    }
+
     #[cfg(test)]
     mod extension_manager_tests {
         use super::*;
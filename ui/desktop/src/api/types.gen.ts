@@ -36,6 +36,17 @@ export type Annotations = {
     priority?: number;
 };
 
+/**
+ * Decision on a tool call announced by an ApprovalRequest event
+ */
+export type ApprovalDecision = {
+    approved: boolean;
+    /**
+     * The `request_id` of the ApprovalRequest event
+     */
+    request_id: string;
+};
+
 export type Author = {
     contact?: string | null;
     metadata?: string | null;
@@ -59,10 +70,54 @@ export type CallToolResponse = {
 };
 
 export type ChatRequest = {
+    compaction?: CompactionPolicy | null;
+    /**
+     * Providers to fail over to, in order, if the session's provider errors out
+     */
+    fallback_providers?: Array<FallbackProvider> | null;
+    /**
+     * Client-chosen key that makes the request safe to retry: a later request for
+     * the same session with the same key streams the task this one started, while
+     * it runs or shortly after it finishes, instead of starting another
+     */
+    idempotency_key?: string | null;
+    /**
+     * Token budget for the whole session, at least 1; the reply stops once the
+     * session's accumulated total crosses it
+     */
+    max_total_tokens?: number | null;
+    /**
+     * Maximum agent turns without user input for this reply, at least 1
+     * (default: the global limit)
+     */
+    max_turns?: number | null;
     messages: Array<Message>;
+    /**
+     * Opaque tags stored with the session, merged into any existing metadata
+     */
+    metadata?: {
+        [key: string]: string;
+    } | null;
+    /**
+     * Model to run this reply on. The override is scoped to this single request:
+     * the session keeps its own provider and model for later replies
+     */
+    model_override?: string | null;
+    /**
+     * Provider for `model_override` (default: the session's provider)
+     */
+    provider_override?: string | null;
     recipe_name?: string | null;
     recipe_version?: string | null;
+    retry_config?: RetryConfig | null;
     session_id: string;
+    /**
+     * Modes pinned to individual tools by name, e.g. `{"developer__text_editor": "approve"}`
+     * to require approval for edits even in auto mode
+     */
+    tool_overrides?: {
+        [key: string]: MtsMode;
+    } | null;
 };
 
 export type CheckProviderRequest = {
@@ -71,6 +126,27 @@ export type CheckProviderRequest = {
 
 export type CommandType = 'Builtin' | 'Recipe';
 
+/**
+ * Per-session auto-compaction policy
+ *
+ * Unset thresholds fall back to `MTS_AUTO_COMPACT_THRESHOLD` for tokens and no
+ * limit for messages. Compaction runs when either threshold is crossed.
+ */
+export type CompactionPolicy = {
+    /**
+     * Whether to auto-compact this session at all
+     */
+    enabled?: boolean;
+    /**
+     * Number of agent-visible messages that triggers compaction
+     */
+    message_threshold?: number | null;
+    /**
+     * Fraction of the context limit (0.0-1.0) that triggers compaction
+     */
+    token_threshold?: number | null;
+};
+
 /**
  * Configuration key metadata for provider setup
  */
@@ -120,6 +196,8 @@ export type Content = RawTextContent | RawImageContent | RawEmbeddedResource | R
 
 export type Conversation = Array<Message>;
 
+export type ConversationFormat = 'json' | 'markdown';
+
 export type CreateRecipeRequest = {
     author?: AuthorRequest | null;
     session_id: string;
@@ -205,6 +283,12 @@ export type Envs = {
     [key: string]: string;
 };
 
+/**
+ * Machine-readable cause of an Error event, stable across releases; the event's
+ * `error` text is meant for display
+ */
+export type ErrorCode = 'agent_unavailable' | 'session_unavailable' | 'invalid_request' | 'reply_start_failed' | 'rate_limited' | 'provider_auth_failed' | 'context_length_exceeded' | 'provider_error' | 'agent_error' | 'events_dropped' | 'internal';
+
 export type ErrorResponse = {
     message: string;
 };
@@ -244,6 +328,10 @@ export type ExtensionConfig = {
     bundled?: boolean | null;
     description: string;
     display_name?: string | null;
+    /**
+     * Extra guidance appended to the extension's instructions for this session only
+     */
+    instructions?: string | null;
     /**
      * The name used to identify this extension
      */
@@ -336,9 +424,44 @@ export type ExtensionResponse = {
     extensions: Array<ExtensionEntry>;
 };
 
+/**
+ * A provider and model to switch to when the current provider fails
+ */
+export type FallbackProvider = {
+    /**
+     * Model to use with that provider
+     */
+    model: string;
+    /**
+     * Registered provider name, e.g. "openai"
+     */
+    provider: string;
+};
+
+export type FileDiff = {
+    diff: string;
+    filePath: string;
+};
+
+export type FileMatchGroup = {
+    filePath: string;
+    matches: Array<SearchMatch>;
+};
+
+export type FileSearchStats = {
+    bytesSearched: number;
+    filePath: string;
+    matchedLines: number;
+    matches: number;
+};
+
 export type FilenameMatch = {
     name: string;
     path: string;
+    /**
+     * Fuzzy match score, higher is better; only set for fuzzy searches
+     */
+    score?: number | null;
 };
 
 export type FrontendToolRequest = {
@@ -377,6 +500,21 @@ export type GetToolsQuery = {
     session_id: string;
 };
 
+export type HealthResponse = {
+    status: HealthStatus;
+    /**
+     * Checked subsystems by name; empty for the liveness probe
+     */
+    subsystems?: {
+        [key: string]: SubsystemHealth;
+    };
+};
+
+/**
+ * Health of the server or one of its subsystems
+ */
+export type HealthStatus = 'ok' | 'unavailable';
+
 export type Icon = {
     mimeType?: string;
     sizes?: Array<string>;
@@ -463,10 +601,14 @@ export type MessageContent = (TextContent & {
 
 export type MessageEvent = {
     message: Message;
-    token_state: TokenState;
+    token_state?: TokenState | null;
     type: 'Message';
+} | {
+    token_state: TokenState;
+    type: 'TokenUpdate';
 } | {
     error: string;
+    error_code: ErrorCode;
     type: 'Error';
 } | {
     reason: string;
@@ -485,6 +627,11 @@ export type MessageEvent = {
 } | {
     conversation: Conversation;
     type: 'UpdateConversation';
+} | {
+    arguments: JsonObject;
+    request_id: string;
+    tool_name: string;
+    type: 'ApprovalRequest';
 } | {
     type: 'Ping';
 };
@@ -503,6 +650,31 @@ export type MessageMetadata = {
     userVisible: boolean;
 };
 
+export type MetricsResponse = {
+    /**
+     * Browser commands grouped by command type, then outcome
+     */
+    browserCommands: {
+        [key: string]: {
+            [key: string]: number;
+        };
+    };
+    /**
+     * Browser commands waiting for a connection or consent
+     */
+    queuedBrowserCommands: number;
+    /**
+     * Tracked background tasks grouped by status
+     */
+    tasksByStatus: {
+        [key: string]: number;
+    };
+    /**
+     * Sessions started since the server booted
+     */
+    totalSessions: number;
+};
+
 export type ModelConfig = {
     context_limit?: number | null;
     fast_model?: string | null;
@@ -543,6 +715,8 @@ export type ModelInfo = {
     supports_cache_control?: boolean | null;
 };
 
+export type MtsMode = 'auto' | 'approve' | 'smart_approve' | 'chat' | 'plan';
+
 export type ParseRecipeRequest = {
     content: string;
 };
@@ -567,6 +741,25 @@ export type ProviderDetails = {
 
 export type ProviderEngine = 'openai' | 'ollama' | 'anthropic';
 
+/**
+ * A raw provider request and everything written back for it
+ */
+export type ProviderExchange = {
+    /**
+     * Model config and payload sent to the provider
+     */
+    request: {
+        [key: string]: unknown;
+    };
+    /**
+     * Response entries in the order received; errors appear as `{"error": ...}`
+     */
+    responses: Array<{
+        [key: string]: unknown;
+    }>;
+    timestamp: string;
+};
+
 /**
  * Metadata about a provider's configuration requirements and capabilities
  */
@@ -702,8 +895,20 @@ export type RemoveExtensionRequest = {
 };
 
 export type ReplaceRequest = {
-    caseSensitive?: boolean;
+    /**
+     * Defaults to false for literal queries and to true for regex ones, so a regex
+     * only matches case-insensitively when the caller asks for it
+     */
+    caseSensitive?: boolean | null;
+    /**
+     * Report what would change as unified diffs without writing any files
+     */
+    dryRun?: boolean;
     excludePattern?: string | null;
+    /**
+     * Files to update, relative to `working_dir` or absolute; every one must be inside
+     * `working_dir`
+     */
     filePaths?: Array<string> | null;
     includePattern?: string | null;
     query: string;
@@ -714,6 +919,10 @@ export type ReplaceRequest = {
 };
 
 export type ReplaceResponse = {
+    /**
+     * Per-file unified diffs of the changes, when `dry_run` was requested
+     */
+    diffs?: Array<FileDiff> | null;
     filesModified: number;
     totalReplacements: number;
 };
@@ -763,6 +972,10 @@ export type RetryConfig = {
      * Timeout in seconds for on_failure commands (default: 600 seconds)
      */
     on_failure_timeout_seconds?: number | null;
+    /**
+     * Times a reply backs off and retries after the provider rate-limits it (default: 3)
+     */
+    rate_limit_retries?: number | null;
     /**
      * Timeout in seconds for individual shell commands (default: 300 seconds)
      */
@@ -809,6 +1022,11 @@ export type ScheduledJob = {
 };
 
 export type SearchFilenamesRequest = {
+    /**
+     * Match the query as a fuzzy subsequence of each file's relative path and rank
+     * results by score, instead of as a substring of the file name
+     */
+    fuzzy?: boolean;
     maxResults?: number | null;
     query: string;
     workingDir: string;
@@ -822,29 +1040,164 @@ export type SearchFilenamesResponse = {
 export type SearchFilesRequest = {
     caseSensitive?: boolean;
     contextLines?: number | null;
+    /**
+     * Only count matches: `totalMatches` and `totalFiles` are filled in, while
+     * `matches`, `groups` and `fileStats` stay empty
+     */
+    countOnly?: boolean;
+    /**
+     * Decode files from this encoding (a WHATWG label such as "utf-16le" or
+     * "windows-1252") instead of reading them as UTF-8. Files starting with a
+     * UTF-8 or UTF-16 byte order mark are decoded from that either way.
+     */
+    encoding?: string | null;
     excludePattern?: string | null;
+    /**
+     * Return matches bucketed by file in `groups` instead of the flat `matches` list
+     */
+    groupByFile?: boolean;
     includePattern?: string | null;
     maxResults?: number | null;
+    /**
+     * Matches to skip before the first one returned, for paging through results.
+     * Matches are ordered by file path, then line number. Setting it, even to 0,
+     * makes `totalMatches` count every match rather than stop at `maxResults`.
+     */
+    offset?: number | null;
+    /**
+     * Queries to OR-combine in one search, used instead of `query` when non-empty.
+     * Each match is labeled with the query that matched it; an empty entry is rejected.
+     */
+    queries?: Array<string> | null;
     query: string;
+    /**
+     * Skip files excluded by .gitignore/.ignore files and the usual build and
+     * dependency directories (default: true)
+     */
+    respectGitignore?: boolean;
+    /**
+     * Search files that look binary (they contain NUL bytes) as text instead of
+     * skipping them. Binary files can produce very long, garbled match lines, so
+     * narrow the search with `includePattern` when turning this on.
+     */
+    searchBinary?: boolean;
+    /**
+     * Also search hidden files and directories
+     */
+    searchHidden?: boolean;
+    /**
+     * Stop searching after this many milliseconds and return what was found so far
+     * with `timedOut` set (default: 30000)
+     */
+    timeoutMs?: number | null;
     useRegex?: boolean;
     wholeWord?: boolean;
     workingDir: string;
 };
 
 export type SearchFilesResponse = {
+    /**
+     * Stats for every file with matches, not just those on this page
+     */
+    fileStats: Array<FileSearchStats>;
+    /**
+     * Matches bucketed by file in the order files were found, when `group_by_file` was requested
+     */
+    groups?: Array<FileMatchGroup> | null;
+    /**
+     * Flat list of matches; empty when `group_by_file` was requested
+     */
     matches: Array<SearchMatch>;
+    /**
+     * The search hit its timeout, so the results and totals are partial
+     */
+    timedOut: boolean;
     totalFiles: number;
+    /**
+     * Matched lines in every file when `offset` or `count_only` is set; otherwise
+     * those found before the search stopped at `max_results`
+     */
     totalMatches: number;
+    /**
+     * More matches follow this page
+     */
     truncated: boolean;
 };
 
 export type SearchMatch = {
     column: number;
+    /**
+     * Up to `context_lines` lines right after the match, including nearby matched lines
+     */
     contextAfter: Array<string>;
+    /**
+     * Up to `context_lines` lines right before the match, including nearby matched lines
+     */
     contextBefore: Array<string>;
     filePath: string;
     lineNumber: number;
     lineText: string;
+    /**
+     * Byte offset in the line just past the end of the first match
+     */
+    matchEnd: number;
+    /**
+     * Byte offset in the line where the first match starts; same as `column`
+     */
+    matchStart: number;
+    /**
+     * Which of the request's `queries` matched, when several were given
+     */
+    matchedQuery?: string | null;
+    /**
+     * Byte ranges `[start, end)` of every match in the line, in order
+     */
+    submatches: Array<Array<number & number>>;
+};
+
+/**
+ * Events of `/search/files/stream`: matches file by file, then one `Done`
+ */
+export type SearchStreamEvent = (SearchMatch & {
+    type: 'Match';
+}) | (SearchSummary & {
+    type: 'Done';
+});
+
+/**
+ * Totals sent as the last event of a streamed search
+ */
+export type SearchSummary = {
+    fileStats: Array<FileSearchStats>;
+    timedOut: boolean;
+    totalFiles: number;
+    /**
+     * Counted as in [`SearchFilesResponse::total_matches`]
+     */
+    totalMatches: number;
+    truncated: boolean;
+};
+
+export type SearchSymbolsRequest = {
+    /**
+     * Only return symbols named exactly `query` (case-sensitive)
+     */
+    exact?: boolean;
+    /**
+     * Only return symbols of these kinds (default: all)
+     */
+    kinds?: Array<SymbolKind> | null;
+    maxResults?: number | null;
+    /**
+     * Symbol name to look for, matched case-insensitively as a substring
+     */
+    query: string;
+    workingDir: string;
+};
+
+export type SearchSymbolsResponse = {
+    symbols: Array<SymbolMatch>;
+    truncated: boolean;
 };
 
 export type Session = {
@@ -857,6 +1210,12 @@ export type Session = {
     id: string;
     input_tokens?: number | null;
     message_count: number;
+    /**
+     * Opaque tags supplied by integrators, e.g. external ticket or user ids
+     */
+    metadata?: {
+        [key: string]: string;
+    } | null;
     model_config?: ModelConfig | null;
     name: string;
     output_tokens?: number | null;
@@ -873,6 +1232,14 @@ export type Session = {
     working_dir: string;
 };
 
+export type SessionDebugResponse = {
+    enabled: boolean;
+    /**
+     * Most recent provider exchanges, oldest first, with secrets redacted
+     */
+    exchanges: Array<ProviderExchange>;
+};
+
 export type SessionDisplayInfo = {
     accumulatedInputTokens?: number | null;
     accumulatedOutputTokens?: number | null;
@@ -954,6 +1321,11 @@ export type SubRecipe = {
     } | null;
 };
 
+export type SubsystemHealth = {
+    detail: string;
+    status: HealthStatus;
+};
+
 /**
  * Execute a shell command and check its exit status
  */
@@ -965,6 +1337,22 @@ export type SuccessCheck = {
     type: 'Shell';
 };
 
+/**
+ * What a definition found by a symbol search defines
+ */
+export type SymbolKind = 'fn' | 'struct' | 'enum' | 'union' | 'trait' | 'type' | 'const' | 'static' | 'mod' | 'macro';
+
+export type SymbolMatch = {
+    filePath: string;
+    kind: SymbolKind;
+    /**
+     * The defining line, trimmed
+     */
+    line: string;
+    lineNumber: number;
+    name: string;
+};
+
 export type SystemNotificationContent = {
     msg: string;
     notificationType: SystemNotificationType;
@@ -972,6 +1360,24 @@ export type SystemNotificationContent = {
 
 export type SystemNotificationType = 'thinkingMessage' | 'inlineMessage';
 
+/**
+ * Response for task status queries
+ */
+export type TaskStatusResponse = {
+    has_subscribers: boolean;
+    last_activity_ms: number;
+    /**
+     * Error that ended the task, when status is Error
+     */
+    last_error?: string | null;
+    session_id: string;
+    status: string;
+    /**
+     * Number of clients currently streaming this task's events
+     */
+    subscriber_count: number;
+};
+
 export type TextContent = {
     _meta?: {
         [key: string]: unknown;
@@ -1099,6 +1505,13 @@ export type UpdateScheduleRequest = {
     cron: string;
 };
 
+export type UpdateSessionDebugRequest = {
+    /**
+     * Whether to capture raw provider requests and responses for this session
+     */
+    enabled: boolean;
+};
+
 export type UpdateSessionNameRequest = {
     /**
      * Updated name for the session (max 200 characters)
@@ -2088,6 +2501,22 @@ export type StartTetrateSetupResponses = {
 
 export type StartTetrateSetupResponse = StartTetrateSetupResponses[keyof StartTetrateSetupResponses];
 
+export type HealthzData = {
+    body?: never;
+    path?: never;
+    query?: never;
+    url: '/healthz';
+};
+
+export type HealthzResponses = {
+    /**
+     * The server is running
+     */
+    200: HealthResponse;
+};
+
+export type HealthzResponse = HealthzResponses[keyof HealthzResponses];
+
 export type McpUiProxyData = {
     body?: never;
     path?: never;
@@ -2114,6 +2543,60 @@ export type McpUiProxyResponses = {
     200: unknown;
 };
 
+export type MetricsData = {
+    body?: never;
+    path?: never;
+    query?: never;
+    url: '/metrics';
+};
+
+export type MetricsErrors = {
+    /**
+     * Unauthorized - invalid secret key
+     */
+    401: unknown;
+};
+
+export type MetricsResponses = {
+    /**
+     * Snapshot of in-process counters. Clients accepting text/plain or OpenMetrics, such as Prometheus, get the Prometheus text format instead, which also has tool call, session and token counters.
+     */
+    200: MetricsResponse;
+};
+
+export type MetricsResponse2 = MetricsResponses[keyof MetricsResponses];
+
+export type ReadyzData = {
+    body?: never;
+    path?: never;
+    query?: {
+        /**
+         * Also check that the configured provider can be created and answers a
+         * model listing request. This calls out to the provider, so it is opt-in.
+         */
+        check_provider?: boolean;
+    };
+    url: '/readyz';
+};
+
+export type ReadyzErrors = {
+    /**
+     * At least one checked subsystem is unavailable
+     */
+    503: HealthResponse;
+};
+
+export type ReadyzError = ReadyzErrors[keyof ReadyzErrors];
+
+export type ReadyzResponses = {
+    /**
+     * Every checked subsystem is ready
+     */
+    200: HealthResponse;
+};
+
+export type ReadyzResponse = ReadyzResponses[keyof ReadyzResponses];
+
 export type CreateRecipeData = {
     body: CreateRecipeRequest;
     path?: never;
@@ -2380,11 +2863,25 @@ export type SetRecipeSlashCommandResponses = {
 export type ReplyData = {
     body: ChatRequest;
     path?: never;
-    query?: never;
+    query?: {
+        /**
+         * Also embed the token state in every Message event, for clients that
+         * don't handle TokenUpdate events yet
+         */
+        inline_token_state?: boolean;
+    };
     url: '/reply';
 };
 
 export type ReplyErrors = {
+    /**
+     * Session already has a running reply
+     */
+    409: unknown;
+    /**
+     * Request body is larger than the configured limit
+     */
+    413: ErrorResponse;
     /**
      * Agent not initialized
      */
@@ -2395,9 +2892,11 @@ export type ReplyErrors = {
     500: unknown;
 };
 
+export type ReplyError = ReplyErrors[keyof ReplyErrors];
+
 export type ReplyResponses = {
     /**
-     * Streaming response initiated
+     * Streaming response initiated, or the stream of the task a request with the same idempotency_key started
      */
     200: MessageEvent;
 };
@@ -2739,6 +3238,10 @@ export type SearchFilesData = {
 };
 
 export type SearchFilesErrors = {
+    /**
+     * Bad request - invalid pattern or glob
+     */
+    400: unknown;
     /**
      * Unauthorized - invalid secret key
      */
@@ -2758,6 +3261,33 @@ export type SearchFilesResponses = {
 
 export type SearchFilesResponse2 = SearchFilesResponses[keyof SearchFilesResponses];
 
+export type SearchFilesStreamData = {
+    body: SearchFilesRequest;
+    path?: never;
+    query?: never;
+    url: '/search/files/stream';
+};
+
+export type SearchFilesStreamErrors = {
+    /**
+     * Bad request - invalid pattern or glob
+     */
+    400: unknown;
+    /**
+     * Unauthorized - invalid secret key
+     */
+    401: unknown;
+};
+
+export type SearchFilesStreamResponses = {
+    /**
+     * Streaming search started
+     */
+    200: SearchStreamEvent;
+};
+
+export type SearchFilesStreamResponse = SearchFilesStreamResponses[keyof SearchFilesStreamResponses];
+
 export type ReplaceInFilesData = {
     body: ReplaceRequest;
     path?: never;
@@ -2767,13 +3297,17 @@ export type ReplaceInFilesData = {
 
 export type ReplaceInFilesErrors = {
     /**
-     * Bad request - invalid regex pattern
+     * Bad request - invalid regex pattern, replacement group reference or file path outside working_dir
      */
     400: unknown;
     /**
      * Unauthorized - invalid secret key
      */
     401: unknown;
+    /**
+     * Searching for the files to update timed out
+     */
+    408: unknown;
     /**
      * Internal server error
      */
@@ -2789,6 +3323,37 @@ export type ReplaceInFilesResponses = {
 
 export type ReplaceInFilesResponse = ReplaceInFilesResponses[keyof ReplaceInFilesResponses];
 
+export type SearchSymbolsData = {
+    body: SearchSymbolsRequest;
+    path?: never;
+    query?: never;
+    url: '/search/symbols';
+};
+
+export type SearchSymbolsErrors = {
+    /**
+     * Bad request - working directory not found
+     */
+    400: unknown;
+    /**
+     * Unauthorized - invalid secret key
+     */
+    401: unknown;
+    /**
+     * Internal server error
+     */
+    500: unknown;
+};
+
+export type SearchSymbolsResponses = {
+    /**
+     * Symbol search completed successfully
+     */
+    200: SearchSymbolsResponse;
+};
+
+export type SearchSymbolsResponse2 = SearchSymbolsResponses[keyof SearchSymbolsResponses];
+
 export type ListSessionsData = {
     body?: never;
     path?: never;
@@ -2874,6 +3439,34 @@ export type GetSessionInsightsResponses = {
 
 export type GetSessionInsightsResponse = GetSessionInsightsResponses[keyof GetSessionInsightsResponses];
 
+export type ListRunningTasksData = {
+    body?: never;
+    path?: never;
+    query?: {
+        /**
+         * Only list tasks with this status, e.g. 'running' or 'error'
+         */
+        status?: string | null;
+    };
+    url: '/sessions/running';
+};
+
+export type ListRunningTasksErrors = {
+    /**
+     * Unknown status filter
+     */
+    400: unknown;
+};
+
+export type ListRunningTasksResponses = {
+    /**
+     * Tracked background tasks
+     */
+    200: Array<crate.background_tasks.TaskStatusResponse>;
+};
+
+export type ListRunningTasksResponse = ListRunningTasksResponses[keyof ListRunningTasksResponses];
+
 export type DeleteSessionData = {
     body?: never;
     path: {
@@ -2944,6 +3537,140 @@ export type GetSessionResponses = {
 
 export type GetSessionResponse = GetSessionResponses[keyof GetSessionResponses];
 
+export type ApproveToolCallData = {
+    body: ApprovalDecision;
+    path: {
+        /**
+         * Session whose tool call to decide
+         */
+        session_id: string;
+    };
+    query?: never;
+    url: '/sessions/{session_id}/approve';
+};
+
+export type ApproveToolCallErrors = {
+    /**
+     * No running task for this session, or it never asked about this tool call
+     */
+    404: unknown;
+    /**
+     * The tool call was already decided
+     */
+    409: unknown;
+};
+
+export type ApproveToolCallResponses = {
+    /**
+     * Decision passed to the agent
+     */
+    200: unknown;
+};
+
+export type GetSessionConversationData = {
+    body?: never;
+    path: {
+        /**
+         * Unique identifier for the session
+         */
+        session_id: string;
+    };
+    query?: {
+        /**
+         * `json` (default) returns the `Conversation`; `markdown` returns a
+         * readable transcript of the user-visible messages
+         */
+        format?: ConversationFormat;
+    };
+    url: '/sessions/{session_id}/conversation';
+};
+
+export type GetSessionConversationErrors = {
+    /**
+     * Unauthorized - Invalid or missing API key
+     */
+    401: unknown;
+    /**
+     * Session not found
+     */
+    404: unknown;
+    /**
+     * Internal server error
+     */
+    500: unknown;
+};
+
+export type GetSessionConversationResponses = {
+    /**
+     * Stored conversation, or a transcript when format=markdown
+     */
+    200: Conversation;
+};
+
+export type GetSessionConversationResponse = GetSessionConversationResponses[keyof GetSessionConversationResponses];
+
+export type GetSessionDebugData = {
+    body?: never;
+    path: {
+        /**
+         * Unique identifier for the session
+         */
+        session_id: string;
+    };
+    query?: never;
+    url: '/sessions/{session_id}/debug';
+};
+
+export type GetSessionDebugErrors = {
+    /**
+     * Unauthorized - Invalid or missing API key
+     */
+    401: unknown;
+};
+
+export type GetSessionDebugResponses = {
+    /**
+     * Captured provider exchanges
+     */
+    200: SessionDebugResponse;
+};
+
+export type GetSessionDebugResponse = GetSessionDebugResponses[keyof GetSessionDebugResponses];
+
+export type UpdateSessionDebugData = {
+    body: UpdateSessionDebugRequest;
+    path: {
+        /**
+         * Unique identifier for the session
+         */
+        session_id: string;
+    };
+    query?: never;
+    url: '/sessions/{session_id}/debug';
+};
+
+export type UpdateSessionDebugErrors = {
+    /**
+     * Unauthorized - Invalid or missing API key
+     */
+    401: unknown;
+    /**
+     * Provider debugging is not enabled on this server
+     */
+    403: unknown;
+    /**
+     * Session not found
+     */
+    404: unknown;
+};
+
+export type UpdateSessionDebugResponses = {
+    /**
+     * Debug capture updated
+     */
+    200: unknown;
+};
+
 export type EditMessageData = {
     body: EditMessageRequest;
     path: {
@@ -0,0 +1,477 @@
+// Runs browser agent commands in Electron <webview> tags. Command and result shapes
+// follow BrowserCommand and BrowserEvent in crates/mts-server/src/routes/browser.rs.
+
+export type BrowserErrorCode = 'NoPageLoaded' | 'ElementNotFound' | 'RateLimited';
+export type ScreenshotFormat = 'png' | 'jpeg' | 'webp';
+
+export type WebviewEventType =
+  | 'did-start-loading'
+  | 'did-stop-loading'
+  | 'did-navigate'
+  | 'did-navigate-in-page'
+  | 'did-fail-load'
+  | 'dom-ready'
+  | 'console-message'
+  | 'crashed';
+
+export type WebviewEvent = Event & {
+  url?: string;
+  isMainFrame?: boolean;
+  errorCode?: number;
+  errorDescription?: string;
+  level?: number | string;
+  message?: string;
+};
+
+interface CaptureRect {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+export interface WebviewTag extends HTMLElement {
+  src: string;
+  goBack: () => void;
+  goForward: () => void;
+  reload: () => void;
+  stop: () => void;
+  canGoBack: () => boolean;
+  canGoForward: () => boolean;
+  getURL: () => string;
+  loadURL: (url: string) => Promise<void>;
+  executeJavaScript: (code: string) => Promise<any>;
+  capturePage: (rect?: CaptureRect) => Promise<{ toDataURL: () => string }>;
+  insertCSS: (css: string) => Promise<string>;
+  removeInsertedCSS: (key: string) => Promise<void>;
+  addEventListener(type: WebviewEventType, listener: (event: WebviewEvent) => void): void;
+  removeEventListener(type: WebviewEventType, listener: (event: WebviewEvent) => void): void;
+}
+
+export interface ConsoleEntry {
+  level: 'log' | 'info' | 'warn' | 'error' | 'debug';
+  message: string;
+  timestamp: number;
+}
+
+// The open tabs commands are routed to; a missing tab id means the active tab
+export interface WebviewTabs {
+  get: (tabId?: string) => WebviewTag;
+  open: (tabId: string) => Promise<void>;
+  close: (tabId?: string) => void;
+  consoleLogs: (tabId?: string) => ConsoleEntry[];
+}
+
+// Data and error code of a successful command, sent back in its CommandResult
+export interface CommandOutput {
+  data?: string;
+  error_code?: BrowserErrorCode;
+}
+
+// A failed command whose CommandResult carries a machine-readable error code
+export class CommandError extends Error {
+  code: BrowserErrorCode;
+
+  constructor(message: string, code: BrowserErrorCode) {
+    super(message);
+    this.code = code;
+  }
+}
+
+// Console entries kept per tab, matching what GetConsoleLogs promises
+export const MAX_CONSOLE_ENTRIES = 500;
+
+const WAIT_POLL_MS = 100;
+const SCROLL_POLL_MS = 200;
+const NAVIGATION_TIMEOUT_MS = 30000;
+const VIEWPORT_SETTLE_MS = 1000;
+
+const sleep = (ms: number) => new Promise<void>((resolve) => setTimeout(resolve, ms));
+
+// Electron reports console levels as 0-3 (verbose, info, warning, error) or by name
+export function consoleLevel(level: number | string | undefined): ConsoleEntry['level'] {
+  switch (level) {
+    case 0:
+    case 'verbose':
+    case 'debug':
+      return 'debug';
+    case 2:
+    case 'warning':
+      return 'warn';
+    case 3:
+    case 'error':
+      return 'error';
+    default:
+      return 'info';
+  }
+}
+
+// Run `body` in the page with `el` bound to the first match of `selector`. Errors
+// thrown by `body` are passed back by message, since Electron drops them otherwise.
+async function withElement<T>(webview: WebviewTag, selector: string, body: string): Promise<T> {
+  const result = await webview.executeJavaScript(
+    `(async () => { const el = document.querySelector(${JSON.stringify(selector)}); ` +
+      `if (!el) return { found: false }; ` +
+      `try { return { found: true, value: await (async () => { ${body} })() }; } ` +
+      `catch (e) { return { found: true, error: String((e && e.message) || e) }; } })()`
+  );
+  if (!result?.found) {
+    throw new CommandError(`No element matches '${selector}'`, 'ElementNotFound');
+  }
+  if (result.error !== undefined) {
+    throw new Error(result.error);
+  }
+  return result.value as T;
+}
+
+// Poll until an element matching `selector` is present (or absent), surviving navigations
+async function waitFor(
+  webview: WebviewTag,
+  selector: string,
+  present: boolean,
+  timeoutMs: number
+): Promise<void> {
+  const probe =
+    `(() => { try { return !!document.querySelector(${JSON.stringify(selector)}); } ` +
+    `catch (e) { return String(e.message); } })()`;
+  const deadline = Date.now() + timeoutMs;
+  for (;;) {
+    // A page being replaced can't run scripts; treat it as not settled yet
+    const found = await webview.executeJavaScript(probe).catch(() => undefined);
+    if (typeof found === 'string') {
+      throw new Error(found);
+    }
+    if (found === present) {
+      return;
+    }
+    if (Date.now() >= deadline) {
+      throw new Error(
+        present
+          ? `Timed out after ${timeoutMs}ms waiting for '${selector}'`
+          : `'${selector}' was still present after ${timeoutMs}ms`
+      );
+    }
+    await sleep(WAIT_POLL_MS);
+  }
+}
+
+// Run `trigger` and resolve once the page it starts has loaded or failed to
+function settleAfter(webview: WebviewTag, trigger: () => void): Promise<void> {
+  return new Promise((resolve) => {
+    const events: WebviewEventType[] = [
+      'did-stop-loading',
+      'did-navigate-in-page',
+      'did-fail-load',
+    ];
+    const done = () => {
+      clearTimeout(timer);
+      events.forEach((event) => webview.removeEventListener(event, done));
+      resolve();
+    };
+    events.forEach((event) => webview.addEventListener(event, done));
+    const timer = setTimeout(done, NAVIGATION_TIMEOUT_MS);
+    trigger();
+  });
+}
+
+async function encodeImage(
+  pngDataUrl: string,
+  format: ScreenshotFormat,
+  quality?: number
+): Promise<string> {
+  let dataUrl = pngDataUrl;
+  if (format !== 'png') {
+    const image = new Image();
+    image.src = pngDataUrl;
+    await image.decode();
+    const canvas = document.createElement('canvas');
+    canvas.width = image.naturalWidth;
+    canvas.height = image.naturalHeight;
+    const context = canvas.getContext('2d');
+    if (!context) {
+      throw new Error(`Could not encode the screenshot as ${format}`);
+    }
+    if (format === 'jpeg') {
+      // JPEG has no alpha channel; keep transparent areas white rather than black
+      context.fillStyle = '#fff';
+      context.fillRect(0, 0, canvas.width, canvas.height);
+    }
+    context.drawImage(image, 0, 0);
+    dataUrl = canvas.toDataURL(
+      `image/${format}`,
+      quality === undefined ? undefined : quality / 100
+    );
+  }
+  return dataUrl.slice(dataUrl.indexOf(',') + 1);
+}
+
+// Capture the page or the element matching `selector` as base64. When the selector
+// matches nothing visible the full page is captured and ElementNotFound is reported.
+async function screenshot(
+  webview: WebviewTag,
+  command: Record<string, any>
+): Promise<CommandOutput> {
+  const hidden = (command.hide_selectors ?? [])
+    .map((selector: string) => `${selector} { display: none !important; }`)
+    .join('\n');
+  const css = [hidden, command.css ?? ''].filter(Boolean).join('\n');
+  const cssKey = css ? await webview.insertCSS(css) : undefined;
+  try {
+    // Let injected styles apply before capturing
+    await webview.executeJavaScript(
+      'new Promise((resolve) => requestAnimationFrame(() => requestAnimationFrame(resolve)))'
+    );
+    let rect: CaptureRect | undefined;
+    let errorCode: BrowserErrorCode | undefined;
+    if (command.selector) {
+      rect = await webview.executeJavaScript(
+        `(() => { const el = document.querySelector(${JSON.stringify(command.selector)}); ` +
+          `if (!el) return undefined; el.scrollIntoView({ block: 'nearest' }); ` +
+          `const r = el.getBoundingClientRect(); ` +
+          `return { x: Math.max(0, Math.floor(r.left)), y: Math.max(0, Math.floor(r.top)), ` +
+          `width: Math.ceil(r.width), height: Math.ceil(r.height) }; })()`
+      );
+      if (!rect || rect.width === 0 || rect.height === 0) {
+        rect = undefined;
+        errorCode = 'ElementNotFound';
+      }
+    }
+    const image = await webview.capturePage(rect);
+    const data = await encodeImage(image.toDataURL(), command.format ?? 'png', command.quality);
+    return { data, error_code: errorCode };
+  } finally {
+    if (cssKey) {
+      await webview.removeInsertedCSS(cssKey);
+    }
+  }
+}
+
+// Scroll to the bottom until no new items match, keeping every distinct item seen
+async function scrollAndCollect(
+  webview: WebviewTag,
+  command: Record<string, any>
+): Promise<CommandOutput> {
+  const selector = JSON.stringify(command.item_selector);
+  const seen = new Set<string>();
+  const items: string[] = [];
+  const collect = async () => {
+    const html: string[] = await webview.executeJavaScript(
+      `Array.from(document.querySelectorAll(${selector}), (el) => el.outerHTML)`
+    );
+    let added = 0;
+    for (const item of html) {
+      if (!seen.has(item)) {
+        seen.add(item);
+        items.push(item);
+        added += 1;
+      }
+    }
+    return added;
+  };
+
+  await collect();
+  let iterations = 0;
+  let exhausted = false;
+  while (iterations < command.max_iterations) {
+    await webview.executeJavaScript(
+      'window.scrollTo(0, (document.scrollingElement || document.body).scrollHeight)'
+    );
+    iterations += 1;
+    const deadline = Date.now() + command.wait_ms;
+    let added = 0;
+    do {
+      await sleep(SCROLL_POLL_MS);
+      added = await collect();
+    } while (added === 0 && Date.now() < deadline);
+    if (added === 0) {
+      exhausted = true;
+      break;
+    }
+  }
+  return { data: JSON.stringify({ items, iterations, exhausted }) };
+}
+
+async function dragAndDrop(webview: WebviewTag, command: Record<string, any>): Promise<void> {
+  const source = JSON.stringify(command.source_selector);
+  const target = JSON.stringify(command.target_selector);
+  const missing: string | null = await webview.executeJavaScript(
+    `(() => { const source = document.querySelector(${source}); ` +
+      `const target = document.querySelector(${target}); ` +
+      `if (!source) return ${source}; if (!target) return ${target}; ` +
+      `const dataTransfer = new DataTransfer(); ` +
+      `const fire = (el, type) => el.dispatchEvent(` +
+      `new DragEvent(type, { bubbles: true, cancelable: true, dataTransfer })); ` +
+      `fire(source, 'dragstart'); fire(target, 'dragenter'); fire(target, 'dragover'); ` +
+      `fire(target, 'drop'); fire(source, 'dragend'); return null; })()`
+  );
+  if (missing) {
+    throw new CommandError(`No element matches '${missing}'`, 'ElementNotFound');
+  }
+}
+
+async function setViewport(
+  webview: WebviewTag,
+  command: Record<string, any>
+): Promise<CommandOutput> {
+  webview.style.width = `${command.width}px`;
+  webview.style.height = `${command.height}px`;
+  // Resizing the element reaches the page asynchronously
+  const deadline = Date.now() + VIEWPORT_SETTLE_MS;
+  for (;;) {
+    const size = await webview.executeJavaScript(
+      '({ width: window.innerWidth, height: window.innerHeight })'
+    );
+    const applied = size.width === command.width && size.height === command.height;
+    if (applied || Date.now() >= deadline) {
+      return { data: JSON.stringify(size) };
+    }
+    await sleep(WAIT_POLL_MS);
+  }
+}
+
+const PAGE_INFO_SCRIPT = `(() => {
+  const tags = (prefix) => {
+    const fields = {};
+    const selector = 'meta[property^="' + prefix + '"], meta[name^="' + prefix + '"]';
+    for (const meta of document.querySelectorAll(selector)) {
+      const key = (meta.getAttribute('property') || meta.getAttribute('name')).slice(prefix.length);
+      if (key && meta.content && !(key in fields)) fields[key] = meta.content;
+    }
+    return fields;
+  };
+  const description = document.querySelector('meta[name="description"]');
+  const canonical = document.querySelector('link[rel="canonical"]');
+  return JSON.stringify({
+    title: document.title || undefined,
+    description: (description && description.content) || undefined,
+    canonical_url: (canonical && canonical.href) || undefined,
+    open_graph: tags('og:'),
+    twitter: tags('twitter:'),
+  });
+})()`;
+
+// Run an agent command in the tab it targets, resolving with its result data
+export async function runInWebview(
+  tabs: WebviewTabs,
+  command: Record<string, any>
+): Promise<CommandOutput> {
+  switch (command.type) {
+    case 'OpenTab':
+      await tabs.open(command.tab_id);
+      return {};
+    case 'CloseTab':
+      tabs.close(command.tab_id);
+      return {};
+    case 'GetConsoleLogs': {
+      const since = command.since;
+      const entries = tabs
+        .consoleLogs(command.tab_id)
+        .filter((entry) => since === undefined || since === null || entry.timestamp > since);
+      return { data: JSON.stringify(entries) };
+    }
+  }
+
+  const webview = tabs.get(command.tab_id);
+  switch (command.type) {
+    case 'Navigate':
+      await webview.loadURL(command.url);
+      return {};
+    case 'Click':
+      await withElement(webview, command.selector, 'el.click();');
+      return {};
+    case 'Type':
+      await withElement(
+        webview,
+        command.selector,
+        `el.focus(); el.value = ${JSON.stringify(command.text)}; ` +
+          `el.dispatchEvent(new Event('input', { bubbles: true }));`
+      );
+      return {};
+    case 'ExtractDOM':
+      return {
+        data: command.selector
+          ? await withElement<string>(webview, command.selector, 'return el.outerHTML;')
+          : await webview.executeJavaScript('document.documentElement.outerHTML'),
+      };
+    case 'Screenshot':
+      return screenshot(webview, command);
+    case 'ExecuteScript': {
+      const result = await webview.executeJavaScript(command.script);
+      return { data: result === undefined ? undefined : JSON.stringify(result) };
+    }
+    case 'WaitForSelector':
+      await waitFor(webview, command.selector, true, command.timeout_ms);
+      return {};
+    case 'WaitForAbsence':
+      await waitFor(webview, command.selector, false, command.timeout_ms);
+      return {};
+    case 'ScrollAndCollect':
+      return scrollAndCollect(webview, command);
+    case 'GetComputedStyle': {
+      const properties = JSON.stringify(command.properties);
+      const styles = await withElement<Record<string, string | null>>(
+        webview,
+        command.selector,
+        `const style = getComputedStyle(el); ` +
+          `return Object.fromEntries(${properties}.map((p) => ` +
+          `[p, style.getPropertyValue(p) || style[p] || null]));`
+      );
+      return { data: JSON.stringify(styles) };
+    }
+    case 'GetAttributes': {
+      const names = JSON.stringify(command.attributes);
+      const attributes = await withElement<Record<string, string | null>>(
+        webview,
+        command.selector,
+        // An input's current value lives in the property, not the attribute
+        `return Object.fromEntries(${names}.map((n) => ` +
+          `[n, n === 'value' && 'value' in el ? String(el.value) : el.getAttribute(n)]));`
+      );
+      return { data: JSON.stringify(attributes) };
+    }
+    case 'DragAndDrop':
+      await dragAndDrop(webview, command);
+      return {};
+    case 'SelectOption': {
+      const wanted = JSON.stringify(command.value);
+      const optionKey = command.by_label ? 'o.text.trim()' : 'o.value';
+      const selected = await withElement<string | null>(
+        webview,
+        command.selector,
+        `if (!(el instanceof HTMLSelectElement)) throw new Error('Element is not a <select>'); ` +
+          `const option = Array.from(el.options).find((o) => ${optionKey} === ${wanted}); ` +
+          `if (!option) return null; ` +
+          `el.value = option.value; ` +
+          `el.dispatchEvent(new Event('input', { bubbles: true })); ` +
+          `el.dispatchEvent(new Event('change', { bubbles: true })); ` +
+          `return option.value;`
+      );
+      if (selected === null) {
+        const missing = command.by_label ? 'labeled' : 'with value';
+        throw new CommandError(`No option ${missing} ${wanted}`, 'ElementNotFound');
+      }
+      return { data: selected };
+    }
+    case 'SetViewport':
+      return setViewport(webview, command);
+    case 'PageInfo':
+      return { data: await webview.executeJavaScript(PAGE_INFO_SCRIPT) };
+    case 'GoBack':
+      if (!webview.canGoBack()) {
+        throw new Error('There is no previous page in this tab');
+      }
+      await settleAfter(webview, () => webview.goBack());
+      return { data: webview.getURL() };
+    case 'GoForward':
+      if (!webview.canGoForward()) {
+        throw new Error('There is no next page in this tab');
+      }
+      await settleAfter(webview, () => webview.goForward());
+      return { data: webview.getURL() };
+    case 'Reload':
+      await settleAfter(webview, () => webview.reload());
+      return { data: webview.getURL() };
+    default:
+      throw new Error(`Unsupported command: ${command.type}`);
+  }
+}
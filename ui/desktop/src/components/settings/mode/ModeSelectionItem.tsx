@@ -30,6 +30,11 @@ export const all_mts_modes: MTSMode[] = [
     label: 'Chat only',
     description: 'Engage with the selected provider without using tools or extensions.',
   },
+  {
+    key: 'plan',
+    label: 'Plan only',
+    description: 'Propose the tool calls for a task without running any of them.',
+  },
 ];
 
 interface ModeSelectionItemProps {
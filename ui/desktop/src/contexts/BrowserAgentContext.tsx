@@ -1,5 +1,16 @@
 import { createContext, useContext, useState, useCallback, useEffect, useRef, ReactNode } from 'react';
 import { useLocation } from 'react-router-dom';
+import {
+  BrowserErrorCode,
+  CommandError,
+  ConsoleEntry,
+  MAX_CONSOLE_ENTRIES,
+  WebviewEvent,
+  WebviewTabs,
+  WebviewTag,
+  consoleLevel,
+  runInWebview,
+} from '../components/browser/webviewCommands';
 
 // Types for browser commands and events
 export type BrowserCommandType = 'navigate' | 'click' | 'type' | 'extract' | 'screenshot' | 'execute';
@@ -26,27 +37,12 @@ export interface BrowserEvent {
   success?: boolean;
   data?: any;
   error?: string;
+  error_code?: BrowserErrorCode;
   url?: string;
+  tab_id?: string;
   loading?: boolean;
   allowed_domains?: string[];
-}
-
-interface WebviewTag extends HTMLElement {
-  src: string;
-  goBack: () => void;
-  goForward: () => void;
-  reload: () => void;
-  stop: () => void;
-  canGoBack: () => boolean;
-  canGoForward: () => boolean;
-  addEventListener(
-    type: 'did-start-loading' | 'did-stop-loading' | 'did-navigate' | 'did-fail-load' | 'crashed',
-    listener: (event: Event & { url?: string; errorCode?: number; errorDescription?: string }) => void
-  ): void;
-  removeEventListener(
-    type: 'did-start-loading' | 'did-stop-loading' | 'did-navigate' | 'did-fail-load' | 'crashed',
-    listener: (event: Event & { url?: string; errorCode?: number; errorDescription?: string }) => void
-  ): void;
+  command?: Record<string, any>;
 }
 
 interface BrowserAgentContextValue {
@@ -73,8 +69,12 @@ const defaultState: BrowserAgentState = {
 
 const BrowserAgentContext = createContext<BrowserAgentContextValue | null>(null);
 
+// Tab that exists from the start, as on the server
+const DEFAULT_TAB_ID = 'main';
+const BLANK_PAGE_URL = 'about:blank';
+
 const STORAGE_KEY = 'mts-browser-state';
-const WS_URL = 'ws://localhost:3000/ws/browser';
+const WS_URL = 'ws://localhost:3000/ws/browser?role=frontend';
 const WS_RECONNECT_DELAY = 1000; // Start with 1 second
 const WS_MAX_RECONNECT_DELAY = 30000; // Max 30 seconds
 
@@ -105,6 +105,19 @@ export function BrowserAgentProvider({ children }: { children: ReactNode }) {
   const [canGoBack, setCanGoBack] = useState(false);
   const [canGoForward, setCanGoForward] = useState(false);
 
+  // One webview per tab. The refs mirror the tab state for event handlers, and
+  // webviewRef always points at the active tab's webview.
+  const [initialUrl] = useState(state.currentUrl);
+  const [tabIds, setTabIds] = useState<string[]>([DEFAULT_TAB_ID]);
+  const [activeTab, setActiveTab] = useState(DEFAULT_TAB_ID);
+  const tabIdsRef = useRef<string[]>([DEFAULT_TAB_ID]);
+  const activeTabRef = useRef(DEFAULT_TAB_ID);
+  const webviewsRef = useRef(new Map<string, WebviewTag>());
+  const webviewRefCallbacks = useRef(new Map<string, (webview: WebviewTag | null) => void>());
+  const detachWebviewRef = useRef(new Map<string, () => void>());
+  const consoleLogsRef = useRef(new Map<string, ConsoleEntry[]>());
+  const tabReadyRef = useRef(new Map<string, () => void>());
+
   // Update visibility based on route
   useEffect(() => {
     setState(prev => ({
@@ -123,6 +136,174 @@ export function BrowserAgentProvider({ children }: { children: ReactNode }) {
     }
   }, [state]);
 
+  const sendToBackend = useCallback((event: BrowserEvent) => {
+    if (wsRef.current?.readyState === WebSocket.OPEN) {
+      wsRef.current.send(JSON.stringify(event));
+    }
+  }, []);
+
+  // Show the page of the active tab in the toolbar
+  const syncToolbar = useCallback((tabId: string, webview: WebviewTag) => {
+    if (tabId !== activeTabRef.current) return;
+    try {
+      const url = webview.getURL();
+      if (url && url !== BLANK_PAGE_URL) {
+        setState(prev => ({ ...prev, currentUrl: url }));
+      }
+      setCanGoBack(webview.canGoBack());
+      setCanGoForward(webview.canGoForward());
+    } catch {
+      // The webview has no page until it is attached
+    }
+  }, []);
+
+  const activateTab = useCallback((tabId: string) => {
+    activeTabRef.current = tabId;
+    setActiveTab(tabId);
+    const webview = webviewsRef.current.get(tabId) ?? null;
+    webviewRef.current = webview;
+    if (webview) {
+      syncToolbar(tabId, webview);
+    }
+  }, [syncToolbar]);
+
+  // Report a tab's page events to the backend; returns a function that detaches them
+  const watchWebview = useCallback((tabId: string, webview: WebviewTag) => {
+    const isActive = () => tabId === activeTabRef.current;
+
+    const handleDomReady = () => {
+      tabReadyRef.current.get(tabId)?.();
+      tabReadyRef.current.delete(tabId);
+    };
+
+    const handleDidStartLoading = () => {
+      if (isActive()) setState(prev => ({ ...prev, isLoading: true }));
+      sendToBackend({ type: 'LoadingStateChanged', loading: true, tab_id: tabId });
+    };
+
+    const handleDidStopLoading = () => {
+      if (isActive()) setState(prev => ({ ...prev, isLoading: false }));
+      syncToolbar(tabId, webview);
+      sendToBackend({ type: 'LoadingStateChanged', loading: false, tab_id: tabId });
+    };
+
+    const handleDidNavigate = (e: WebviewEvent) => {
+      if (!e.url || e.isMainFrame === false) return;
+      syncToolbar(tabId, webview);
+      sendToBackend({ type: 'NavigationComplete', url: e.url, tab_id: tabId });
+    };
+
+    const handleDidFailLoad = (e: WebviewEvent) => {
+      console.error('[BrowserAgent] Load failed:', tabId, e.errorCode, e.errorDescription);
+      if (isActive()) setState(prev => ({ ...prev, isLoading: false }));
+    };
+
+    const handleConsoleMessage = (e: WebviewEvent) => {
+      const entries = consoleLogsRef.current.get(tabId) ?? [];
+      entries.push({
+        level: consoleLevel(e.level),
+        message: e.message ?? '',
+        timestamp: Date.now(),
+      });
+      consoleLogsRef.current.set(tabId, entries.slice(-MAX_CONSOLE_ENTRIES));
+    };
+
+    const handleCrashed = () => {
+      console.error('[BrowserAgent] Webview crashed, reloading...', tabId);
+      if (isActive()) setState(prev => ({ ...prev, isLoading: false }));
+      // Auto-reload on crash
+      setTimeout(() => {
+        webview.reload();
+      }, 1000);
+    };
+
+    webview.addEventListener('dom-ready', handleDomReady);
+    webview.addEventListener('did-start-loading', handleDidStartLoading);
+    webview.addEventListener('did-stop-loading', handleDidStopLoading);
+    webview.addEventListener('did-navigate', handleDidNavigate);
+    webview.addEventListener('did-navigate-in-page', handleDidNavigate);
+    webview.addEventListener('did-fail-load', handleDidFailLoad);
+    webview.addEventListener('console-message', handleConsoleMessage);
+    webview.addEventListener('crashed', handleCrashed);
+
+    return () => {
+      webview.removeEventListener('dom-ready', handleDomReady);
+      webview.removeEventListener('did-start-loading', handleDidStartLoading);
+      webview.removeEventListener('did-stop-loading', handleDidStopLoading);
+      webview.removeEventListener('did-navigate', handleDidNavigate);
+      webview.removeEventListener('did-navigate-in-page', handleDidNavigate);
+      webview.removeEventListener('did-fail-load', handleDidFailLoad);
+      webview.removeEventListener('console-message', handleConsoleMessage);
+      webview.removeEventListener('crashed', handleCrashed);
+    };
+  }, [sendToBackend, syncToolbar]);
+
+  // Stable ref callback per tab, so React only calls it when the webview mounts or unmounts
+  const webviewRefFor = useCallback((tabId: string) => {
+    let callback = webviewRefCallbacks.current.get(tabId);
+    if (!callback) {
+      callback = (webview: WebviewTag | null) => {
+        detachWebviewRef.current.get(tabId)?.();
+        detachWebviewRef.current.delete(tabId);
+        if (webview) {
+          webviewsRef.current.set(tabId, webview);
+          detachWebviewRef.current.set(tabId, watchWebview(tabId, webview));
+        } else {
+          webviewsRef.current.delete(tabId);
+        }
+        if (tabId === activeTabRef.current) {
+          webviewRef.current = webview;
+        }
+      };
+      webviewRefCallbacks.current.set(tabId, callback);
+    }
+    return callback;
+  }, [watchWebview]);
+
+  // Tabs as agent commands see them; mirrors the backend's tab bookkeeping
+  const webviewTabs = useRef<WebviewTabs>({
+    get: (tabId) => {
+      const id = tabId ?? activeTabRef.current;
+      const webview = webviewsRef.current.get(id);
+      if (!webview) {
+        throw new Error(`No tab '${id}' is open`);
+      }
+      return webview;
+    },
+    open: (tabId) => {
+      if (tabIdsRef.current.includes(tabId)) {
+        return Promise.reject(new Error(`Tab '${tabId}' is already open`));
+      }
+      const ready = new Promise<void>((resolve) => tabReadyRef.current.set(tabId, resolve));
+      tabIdsRef.current = [...tabIdsRef.current, tabId];
+      setTabIds(tabIdsRef.current);
+      activateTab(tabId);
+      return ready;
+    },
+    close: (tabId) => {
+      const id = tabId ?? activeTabRef.current;
+      if (!tabIdsRef.current.includes(id)) {
+        throw new Error(`No tab '${id}' is open`);
+      }
+      if (tabIdsRef.current.length === 1) {
+        throw new Error(`Tab '${id}' is the last open tab`);
+      }
+      tabIdsRef.current = tabIdsRef.current.filter((openTab) => openTab !== id);
+      setTabIds(tabIdsRef.current);
+      consoleLogsRef.current.delete(id);
+      tabReadyRef.current.delete(id);
+      webviewRefCallbacks.current.delete(id);
+      if (id === activeTabRef.current) {
+        activateTab(
+          tabIdsRef.current.includes(DEFAULT_TAB_ID)
+            ? DEFAULT_TAB_ID
+            : [...tabIdsRef.current].sort()[0]
+        );
+      }
+    },
+    consoleLogs: (tabId) => consoleLogsRef.current.get(tabId ?? activeTabRef.current) ?? [],
+  }).current;
+
   // WebSocket connection management
   const connectWebSocket = useCallback(() => {
     if (wsRef.current?.readyState === WebSocket.OPEN ||
@@ -199,13 +380,15 @@ export function BrowserAgentProvider({ children }: { children: ReactNode }) {
   const handleBrowserEvent = useCallback((event: BrowserEvent) => {
     switch (event.type) {
       case 'NavigationComplete':
-        if (event.url) {
+        if (event.url && (!event.tab_id || event.tab_id === activeTabRef.current)) {
           setState(prev => ({ ...prev, currentUrl: event.url!, isLoading: false }));
         }
         break;
 
       case 'LoadingStateChanged':
-        setState(prev => ({ ...prev, isLoading: event.loading ?? false }));
+        if (!event.tab_id || event.tab_id === activeTabRef.current) {
+          setState(prev => ({ ...prev, isLoading: event.loading ?? false }));
+        }
         break;
 
       case 'CommandResult':
@@ -213,6 +396,24 @@ export function BrowserAgentProvider({ children }: { children: ReactNode }) {
         console.log('[BrowserAgent] Command result:', event);
         break;
 
+      case 'ExecuteCommand': {
+        // Agent command to run in its tab; reply with a CommandResult using its command_id
+        const command = event.command;
+        if (!command) break;
+        const reply = (result: Omit<BrowserEvent, 'type' | 'command_id'>) =>
+          sendToBackend({ type: 'CommandResult', command_id: command.command_id, ...result });
+        runInWebview(webviewTabs, command)
+          .then((output) => reply({ success: true, ...output }))
+          .catch((error) =>
+            reply({
+              success: false,
+              error: String(error?.message ?? error),
+              error_code: error instanceof CommandError ? error.code : undefined,
+            })
+          );
+        break;
+      }
+
       case 'ConsentRequired':
         // Handle consent dialog (will implement in later phase)
         console.log('[BrowserAgent] Consent required for domains:', event.allowed_domains);
@@ -241,66 +442,6 @@ export function BrowserAgentProvider({ children }: { children: ReactNode }) {
     };
   }, [connectWebSocket]);
 
-  // Setup webview event listeners
-  useEffect(() => {
-    const webview = webviewRef.current;
-    if (!webview) return;
-
-    const handleDidStartLoading = () => {
-      setState(prev => ({ ...prev, isLoading: true }));
-    };
-
-    const handleDidStopLoading = () => {
-      setState(prev => ({ ...prev, isLoading: false }));
-      setCanGoBack(webview.canGoBack());
-      setCanGoForward(webview.canGoForward());
-    };
-
-    const handleDidNavigate = (e: Event & { url?: string }) => {
-      if (e.url) {
-        setState(prev => ({ ...prev, currentUrl: e.url! }));
-        setCanGoBack(webview.canGoBack());
-        setCanGoForward(webview.canGoForward());
-
-        // Notify backend of navigation
-        if (wsRef.current?.readyState === WebSocket.OPEN) {
-          wsRef.current.send(JSON.stringify({
-            type: 'NavigationComplete',
-            url: e.url,
-          }));
-        }
-      }
-    };
-
-    const handleDidFailLoad = (e: Event & { errorCode?: number; errorDescription?: string }) => {
-      console.error('[BrowserAgent] Load failed:', e.errorCode, e.errorDescription);
-      setState(prev => ({ ...prev, isLoading: false }));
-    };
-
-    const handleCrashed = () => {
-      console.error('[BrowserAgent] Webview crashed, reloading...');
-      setState(prev => ({ ...prev, isLoading: false }));
-      // Auto-reload on crash
-      setTimeout(() => {
-        webview.reload();
-      }, 1000);
-    };
-
-    webview.addEventListener('did-start-loading', handleDidStartLoading);
-    webview.addEventListener('did-stop-loading', handleDidStopLoading);
-    webview.addEventListener('did-navigate', handleDidNavigate);
-    webview.addEventListener('did-fail-load', handleDidFailLoad);
-    webview.addEventListener('crashed', handleCrashed);
-
-    return () => {
-      webview.removeEventListener('did-start-loading', handleDidStartLoading);
-      webview.removeEventListener('did-stop-loading', handleDidStopLoading);
-      webview.removeEventListener('did-navigate', handleDidNavigate);
-      webview.removeEventListener('did-fail-load', handleDidFailLoad);
-      webview.removeEventListener('crashed', handleCrashed);
-    };
-  }, []);
-
   // Navigation methods
   const navigate = useCallback((url: string) => {
     let target = url;
@@ -308,6 +449,7 @@ export function BrowserAgentProvider({ children }: { children: ReactNode }) {
       target = 'https://' + target;
     }
     setState(prev => ({ ...prev, currentUrl: target }));
+    webviewRef.current?.loadURL(target).catch(console.error);
   }, []);
 
   const goBack = useCallback(() => {
@@ -370,7 +512,7 @@ export function BrowserAgentProvider({ children }: { children: ReactNode }) {
 
   return (
     <BrowserAgentContext.Provider value={value}>
-      {/* Global webview - always mounted, visibility controlled by CSS */}
+      {/* Global webviews, one per tab - always mounted, visibility controlled by CSS */}
       <div
         style={{
           position: 'fixed',
@@ -383,13 +525,23 @@ export function BrowserAgentProvider({ children }: { children: ReactNode }) {
           display: state.isVisible ? 'block' : 'none',
         }}
       >
-        <webview
-          ref={webviewRef}
-          src={state.currentUrl}
-          style={{ width: '100%', height: '100%' }}
-          // @ts-ignore - webpreferences is a valid attribute for webview
-          webpreferences="backgroundThrottling=no"
-        />
+        {tabIds.map((tabId) => (
+          <webview
+            key={tabId}
+            ref={webviewRefFor(tabId)}
+            src={tabId === DEFAULT_TAB_ID ? initialUrl : BLANK_PAGE_URL}
+            style={{
+              position: 'absolute',
+              top: 0,
+              left: 0,
+              width: '100%',
+              height: '100%',
+              visibility: tabId === activeTab ? 'visible' : 'hidden',
+            }}
+            // @ts-ignore - webpreferences is a valid attribute for webview
+            webpreferences="backgroundThrottling=no"
+          />
+        ))}
       </div>
       {children}
     </BrowserAgentContext.Provider>
@@ -21,6 +21,7 @@ import {
 } from '../types/message';
 import { errorMessage } from '../utils/conversionUtils';
 import { useActiveSession } from '../contexts/ActiveSessionContext';
+import { getApiUrl } from '../config';
 
 const resultsCache = new Map<string, { messages: Message[]; session: Session }>();
 
@@ -110,10 +111,16 @@ async function streamFromResponse(
             updateChatState(ChatState.Streaming);
           }
 
-          updateTokenState(event.token_state);
+          if (event.token_state) {
+            updateTokenState(event.token_state);
+          }
           updateMessages(currentMessages);
           break;
         }
+        case 'TokenUpdate': {
+          updateTokenState(event.token_state);
+          break;
+        }
         case 'Error': {
           onFinish('Stream error: ' + event.error);
           return;
@@ -136,6 +143,10 @@ async function streamFromResponse(
           updateNotifications(event as NotificationEvent);
           break;
         }
+        case 'ApprovalRequest': {
+          // The confirmation is rendered from the message sent just before this event
+          break;
+        }
         case 'Ping':
           break;
       }
@@ -459,7 +470,22 @@ export function useChatStream({
   const stopStreaming = useCallback(() => {
     abortControllerRef.current?.abort();
     setChatState(ChatState.Idle);
-  }, []);
+
+    // Aborting only disconnects; the reply keeps running on the server, which
+    // refuses new messages for the session until it is cancelled
+    if (sessionId) {
+      void (async () => {
+        try {
+          await fetch(getApiUrl(`/sessions/${encodeURIComponent(sessionId)}/cancel-task`), {
+            method: 'POST',
+            headers: { 'X-Secret-Key': await window.electron.getSecretKey() },
+          });
+        } catch (error) {
+          console.error('Failed to cancel the running reply:', error);
+        }
+      })();
+    }
+  }, [sessionId]);
 
   const onMessageUpdate = useCallback(
     async (messageId: string, newContent: string, editType: 'fork' | 'edit' = 'fork') => {